@@ -135,7 +135,7 @@ function startLspServer() {
   
   console.log('[DevServer] Starting LSP server:', serverPath);
   
-  lspServer = spawn(serverPath, [], {
+  lspServer = spawn(serverPath, ['--lines'], {
     stdio: ['pipe', 'pipe', 'pipe'],
     env: { ...process.env, LSP_EXTENSION_MODE: '1' }
   });
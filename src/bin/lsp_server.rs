@@ -2,12 +2,25 @@
 //!
 //! This is a JSON-RPC based server for viewing and editing IPC-2581 files.
 //! It communicates over stdio, receiving requests and sending responses.
+//! Messages use LSP Content-Length framing and the initialize/shutdown/exit
+//! lifecycle; `--lines` selects the one-message-per-line protocol of the
+//! VS Code extension, which needs no initialize.
+//!
+//! Stdin is read on its own thread so `$/cancelRequest` can stop Load, Save
+//! and DRC while they run; requests with a `workDoneToken` report `$/progress`.
+//! Client messages and finished async DRC runs arrive on one channel, so
+//! `drcComplete` is sent as soon as a run ends.
 
-use rust_extension::lsp::{Request, Response, ServerState, DrcAsyncResult, error_codes};
+use rust_extension::lsp::{Request, Response, Notification, ServerState, Lifecycle, DrcAsyncResult, document_handle, error_codes};
 use rust_extension::lsp::handlers;
-use rust_extension::lsp::util::{log_to_file, set_cli_mode};
-use std::io::{self, BufRead, Write};
-use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use rust_extension::lsp::transport::{read_message, Framing, MessageWriter};
+use rust_extension::lsp::util::{log_to_file, request_cancelled, set_cli_mode};
+use rust_extension::draw::progress::{CancellationToken, Progress, ProgressReporter};
+use std::collections::HashMap;
+use std::io;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
 use std::time::Instant;
 
 /// Log incoming/outgoing LSP messages to both stderr and file (truncate long payloads)
@@ -22,6 +35,52 @@ fn log_message(direction: &str, msg: &str) {
     log_to_file(&log_line);
 }
 
+/// Methods served besides the LSP lifecycle, advertised by `initialize`
+const METHODS: &[&str] = &[
+    "Load", "GetDocuments", "GetLoadDiagnostics", "Save", "Close",
+    "GetLayers", "GetStackup", "GetBoardOutline", "GetSteps", "UpdateLayerColor", "SetLayerVisibility",
+    "GetTessellation", "GetTessellationBinary",
+    "Select", "BoxSelect", "CheckPointHitsSelection",
+    "HighlightSelectedNets", "HighlightSelectedComponents",
+    "Delete", "Undo", "Redo", "MoveObjects", "UndoMove", "RedoMove", "RotateObjects", "UndoRotate", "RedoRotate", "FlipObjects",
+    "StartTransform", "TransformPreview", "ApplyTransform", "CancelTransform", "UndoTransform", "RedoTransform",
+    "GetComponents", "GetComponent", "GetBom", "GetBomItem",
+    "GetNets", "GetNet",
+    "RunDRC", "GetDRCViolations", "RunDRCWithRegions", "GetDRCRegions",
+    "Validate",
+    "ExportSqlite",
+    "QueryNetAtPoint", "GetObjectBounds", "GetMemory",
+];
+
+/// Message framing from the command line: the LSP base protocol unless `--lines`
+fn parse_framing() -> Framing {
+    let mut framing = Framing::ContentLength;
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--lines" => framing = Framing::Lines,
+            "--stdio" => {}  // Passed by some clients; stdio is the only transport
+            other => eprintln!("[LSP Server] Ignoring unknown argument: {}", other),
+        }
+    }
+    framing
+}
+
+/// Cancellation tokens of the requests read and not yet answered, keyed by their JSON id
+type PendingRequests = Arc<Mutex<HashMap<String, CancellationToken>>>;
+
+/// A message from the stdin reader, with the cancellation token of its request
+struct Incoming {
+    message: String,
+    cancel: CancellationToken,
+}
+
+/// What the server loop handles next
+enum Event {
+    Message(Incoming),
+    DrcComplete(DrcAsyncResult),
+    InputClosed,
+}
+
 fn main() {
     // Detect CLI mode: if LSP_EXTENSION_MODE env var is NOT set, we're running from CLI pipe
     // The extension/dev-server sets this env var, CLI debugging does not
@@ -30,59 +89,85 @@ fn main() {
         set_cli_mode(true);
     }
     
-    eprintln!("[LSP Server] Starting IPC-2581 LSP server...");
-    let mut state = ServerState::new();
-    let stdin = io::stdin();
-    let mut stdout = io::stdout();
-    
-    // Channel for async DRC results
-    let (drc_tx, drc_rx): (Sender<DrcAsyncResult>, Receiver<DrcAsyncResult>) = mpsc::channel();
-    let mut drc_sender: Option<Sender<DrcAsyncResult>> = Some(drc_tx);
-
-    for line in stdin.lock().lines() {
-        // Check for completed DRC results (non-blocking)
-        match drc_rx.try_recv() {
-            Ok(result) => {
-                handle_drc_completion(&mut state, &result, &mut stdout);
-            }
-            Err(TryRecvError::Empty) => {} // No result yet, continue
-            Err(TryRecvError::Disconnected) => {
-                // Channel closed, recreate it
-                let (tx, _rx) = mpsc::channel();
-                drc_sender = Some(tx);
-            }
-        }
-        
-        let line = match line {
-            Ok(l) => l,
-            Err(e) => {
-                eprintln!("[LSP Server] Error reading stdin: {}", e);
+    let framing = parse_framing();
+    eprintln!("[LSP Server] Starting IPC-2581 LSP server ({})...", match framing {
+        Framing::ContentLength => "Content-Length framing",
+        Framing::Lines => "line framing",
+    });
+    let (events_tx, events) = mpsc::channel();
+    let (drc_sender, drc_results) = mpsc::channel();
+    let mut state = ServerState::new(drc_sender);
+    let writer = Arc::new(MessageWriter::new(io::stdout(), framing));
+    let pending: PendingRequests = Arc::default();
+    spawn_reader(framing, pending.clone(), events_tx.clone());
+    forward_drc_results(drc_results, events_tx);
+
+    for event in events {
+        let Incoming { message, cancel } = match event {
+            Event::Message(incoming) => incoming,
+            Event::DrcComplete(result) => {
+                handle_drc_completion(&mut state, &result, &writer, &pending);
                 continue;
             }
+            Event::InputClosed => break,
         };
 
-        if line.trim().is_empty() {
-            continue;
-        }
-
         // Log incoming request (skip GetMemory to reduce noise)
-        if !line.contains("\"method\":\"GetMemory\"") {
-            log_message(">>>", &line);
+        if !message.contains("\"method\":\"GetMemory\"") {
+            log_message(">>>", &message);
         }
 
-        let request: Request = match serde_json::from_str(&line) {
+        let request: Request = match serde_json::from_str(&message) {
             Ok(req) => req,
             Err(e) => {
                 eprintln!("[LSP Server] Failed to parse request: {}", e);
+                if framing == Framing::ContentLength {
+                    let response = Response::error(None, error_codes::PARSE_ERROR, format!("Parse error: {}", e));
+                    writer.send(&serde_json::to_string(&response).unwrap()).unwrap();
+                }
                 continue;
             }
         };
 
+        // Lifecycle notifications
+        match request.method.as_str() {
+            "exit" => {
+                let clean = state.lifecycle == Lifecycle::ShutDown;
+                eprintln!("[LSP Server] Exit{}", if clean { "" } else { " without shutdown" });
+                std::process::exit(if clean { 0 } else { 1 });
+            }
+            "initialized" => {
+                eprintln!("[LSP Server] Client initialized");
+                continue;
+            }
+            _ => {}
+        }
+
+        // LSP notifications get no response; none of the IPC-2581 methods is meaningful without one
+        if request.id.is_none() && framing == Framing::ContentLength {
+            eprintln!("[LSP Server] Ignoring notification: {}", request.method);
+            continue;
+        }
+
         let start = Instant::now();
         let method = request.method.clone();
-        let response_json = dispatch_request(&mut state, request, drc_sender.clone());
+        let id = request.id.clone();
+        let progress = Progress::new(cancel.clone(), work_done_reporter(&writer, request.params.as_ref()));
+        let response_json = match lifecycle_error(&state, &request, framing) {
+            Some(response) => serde_json::to_string(&response).unwrap(),
+            // Cancelled while waiting behind another request
+            None if cancel.is_cancelled() => serde_json::to_string(&request_cancelled(request.id)).unwrap(),
+            None => dispatch_request(&mut state, request, &progress),
+        };
         let elapsed = start.elapsed();
 
+        // A started RunDRCWithRegions stays cancellable until its drcComplete
+        if let Some(id) = &id {
+            if method != "RunDRCWithRegions" || !is_success(&response_json) {
+                pending.lock().unwrap().remove(&id.to_string());
+            }
+        }
+
         // Log outgoing response (skip GetMemory and binary responses to reduce noise)
         if method != "GetMemory" {
             if method == "GetTessellationBinary" {
@@ -94,100 +179,324 @@ fn main() {
             }
         }
 
-        writeln!(stdout, "{}", response_json).unwrap();
-        stdout.flush().unwrap();
+        let response_json = match framing {
+            Framing::ContentLength => binary_to_json(id, response_json),
+            Framing::Lines => response_json,
+        };
+        writer.send(&response_json).unwrap();
     }
 
     eprintln!("[LSP Server] Shutting down...");
 }
 
+/// Read stdin on its own thread, so `$/cancelRequest` reaches a request while it runs
+/// Every request with an id is registered in `pending` before it is queued.
+fn spawn_reader(framing: Framing, pending: PendingRequests, events: Sender<Event>) {
+    thread::spawn(move || {
+        let stdin = io::stdin();
+        let mut reader = stdin.lock();
+        loop {
+            let message = match read_message(&mut reader, framing) {
+                Ok(Some(message)) => message,
+                Ok(None) => break,
+                Err(e) if e.kind() == io::ErrorKind::InvalidData => {
+                    eprintln!("[LSP Server] Skipping malformed message: {}", e);
+                    continue;
+                }
+                Err(e) => {
+                    eprintln!("[LSP Server] Error reading stdin: {}", e);
+                    break;
+                }
+            };
+
+            let cancel = CancellationToken::new();
+            match serde_json::from_str::<Request>(&message) {
+                Ok(request) if request.method == "$/cancelRequest" => {
+                    cancel_request(&pending, request.params);
+                    continue;
+                }
+                Ok(Request { id: Some(id), .. }) => {
+                    pending.lock().unwrap().insert(id.to_string(), cancel.clone());
+                }
+                _ => {}
+            }
+            if events.send(Event::Message(Incoming { message, cancel })).is_err() {
+                return;
+            }
+        }
+        events.send(Event::InputClosed).ok();
+    });
+}
+
+/// Pass finished async DRC runs to the server loop as they arrive, without waiting for a client message
+fn forward_drc_results(results: Receiver<DrcAsyncResult>, events: Sender<Event>) {
+    thread::spawn(move || {
+        for result in results {
+            if events.send(Event::DrcComplete(result)).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+/// Handle $/cancelRequest - flags the request; it answers RequestCancelled once it stops
+fn cancel_request(pending: &PendingRequests, params: Option<serde_json::Value>) {
+    let Some(id) = params.and_then(|p| p.get("id").cloned()) else {
+        return;
+    };
+    match pending.lock().unwrap().get(&id.to_string()) {
+        Some(cancel) => {
+            eprintln!("[LSP Server] Cancelling request {}", id);
+            cancel.cancel();
+        }
+        None => eprintln!("[LSP Server] Ignoring cancel of request {}: not running", id),
+    }
+}
+
+/// `$/progress` reporter for a request whose params carry a `workDoneToken`
+fn work_done_reporter(writer: &Arc<MessageWriter<io::Stdout>>, params: Option<&serde_json::Value>) -> Option<ProgressReporter> {
+    let token = params?.get("workDoneToken")?.clone();
+    let writer = writer.clone();
+    Some(Arc::new(move |event| {
+        let notification = Notification::progress(&token, event);
+        let _ = writer.send(&serde_json::to_string(&notification).unwrap());
+    }))
+}
+
+/// Whether a serialized response carries a result
+fn is_success(response: &str) -> bool {
+    serde_json::from_str::<serde_json::Value>(response)
+        .map(|r| r.get("error").is_none())
+        .unwrap_or(false)
+}
+
+/// Error for a request the lifecycle phase does not accept
+fn lifecycle_error(state: &ServerState, request: &Request, framing: Framing) -> Option<Response> {
+    match state.lifecycle {
+        // The extension's line protocol has no initialize handshake
+        Lifecycle::Uninitialized if framing == Framing::ContentLength && request.method != "initialize" => {
+            Some(Response::error(request.id.clone(), error_codes::SERVER_NOT_INITIALIZED,
+                format!("Server not initialized: '{}' sent before initialize", request.method)))
+        }
+        Lifecycle::ShutDown => {
+            Some(Response::error(request.id.clone(), error_codes::INVALID_REQUEST,
+                format!("Server is shut down: '{}' sent after shutdown", request.method)))
+        }
+        _ => None,
+    }
+}
+
+/// GetTessellationBinary answers `BINARY:<id>:<base64>`, which only the extension reads;
+/// LSP clients get a JSON-RPC response with the same base64 payload as its result
+fn binary_to_json(id: Option<serde_json::Value>, response: String) -> String {
+    match response.strip_prefix("BINARY:").and_then(|rest| rest.rsplit_once(':')) {
+        Some((_, data)) => serde_json::to_string(&Response::success(id, serde_json::json!({
+            "encoding": "base64",
+            "data": data
+        }))).unwrap(),
+        None => response,
+    }
+}
+
 /// Handle completion of async DRC and send notification to client
-fn handle_drc_completion(state: &mut ServerState, result: &DrcAsyncResult, stdout: &mut io::Stdout) {
-    let region_count = result.regions.len();
-    let total_triangles: usize = result.regions.iter().map(|r| r.triangle_count).sum();
-    
-    eprintln!("[LSP Server] Async DRC completed: {} regions, {} triangles in {:.2}ms", 
-        region_count, total_triangles, result.elapsed_ms);
+fn handle_drc_completion(
+    state: &mut ServerState,
+    result: &DrcAsyncResult,
+    writer: &MessageWriter<io::Stdout>,
+    pending: &PendingRequests,
+) {
+    if let Some(id) = &result.request_id {
+        pending.lock().unwrap().remove(&id.to_string());
+    }
     
-    // Store regions in state
-    state.drc_regions = result.regions.clone();
+    // Store regions in the document's state, unless it was closed meanwhile
+    let Some(document) = state.documents.get_mut(&result.document) else {
+        eprintln!("[LSP Server] Dropping DRC result: document {} was closed", result.document);
+        return;
+    };
+    let payload = match &result.regions {
+        Some(regions) => {
+            let region_count = regions.len();
+            let total_triangles: usize = regions.iter().map(|r| r.triangle_count).sum();
+            eprintln!("[LSP Server] Async DRC completed for {}: {} regions, {} triangles in {:.2}ms", 
+                result.document, region_count, total_triangles, result.elapsed_ms);
+            document.drc_regions = regions.clone();
+            serde_json::json!({
+                "status": "ok",
+                "document": result.document,
+                "region_count": region_count,
+                "total_triangles": total_triangles,
+                "elapsed_ms": result.elapsed_ms,
+                "regions": &document.drc_regions
+            })
+        }
+        None => {
+            // Modified regions were consumed by the cancelled run; the next one must be full
+            eprintln!("[LSP Server] Async DRC cancelled for {} after {:.2}ms", result.document, result.elapsed_ms);
+            document.drc_regions.clear();
+            serde_json::json!({
+                "status": "cancelled",
+                "document": result.document,
+                "elapsed_ms": result.elapsed_ms
+            })
+        }
+    };
     
     // Send notification to client
-    let notification = serde_json::json!({
-        "id": null,
-        "method": "drcComplete",
-        "result": {
-            "status": "ok",
-            "region_count": region_count,
-            "total_triangles": total_triangles,
-            "elapsed_ms": result.elapsed_ms,
-            "regions": &state.drc_regions
-        }
-    });
-    writeln!(stdout, "{notification}").unwrap();
-    stdout.flush().unwrap();
+    let notification = match writer.framing() {
+        // The extension reads the payload from `result` of a message with a null id
+        Framing::Lines => serde_json::json!({
+            "id": null,
+            "method": "drcComplete",
+            "result": payload
+        }).to_string(),
+        Framing::ContentLength => serde_json::to_string(&Notification::new("drcComplete", payload)).unwrap(),
+    };
+    writer.send(&notification).unwrap();
+}
+
+/// Handle workspace/executeCommand - runs the method named by `command` with the first argument as params
+fn handle_execute_command(
+    state: &mut ServerState,
+    request: Request,
+    progress: &Progress,
+) -> String {
+    let params = request.params.unwrap_or(serde_json::Value::Null);
+    let command = params["command"].as_str().unwrap_or_default();
+    if !METHODS.contains(&command) {
+        let response = Response::error(request.id, error_codes::INVALID_PARAMS,
+            format!("Unknown command: '{}'", command));
+        return serde_json::to_string(&response).unwrap();
+    }
+    let command_request = Request {
+        id: request.id,
+        method: command.to_string(),
+        params: params["arguments"].get(0).cloned(),
+    };
+    dispatch_request(state, command_request, progress)
 }
 
 /// Dispatch a request to the appropriate handler
 fn dispatch_request(
     state: &mut ServerState, 
     request: Request, 
-    drc_sender: Option<Sender<DrcAsyncResult>>
+    progress: &Progress,
+) -> String {
+    match request.method.as_str() {
+        // Lifecycle
+        "initialize" => serde_json::to_string(&handlers::handle_initialize(state, request.id, request.params, METHODS)).unwrap(),
+        "shutdown" => serde_json::to_string(&handlers::handle_shutdown(state, request.id)).unwrap(),
+        "workspace/executeCommand" => handle_execute_command(state, request, progress),
+
+        // Documents
+        "Load" => serde_json::to_string(&handlers::handle_load(state, request.id, request.params, progress)).unwrap(),
+        "GetDocuments" => serde_json::to_string(&handlers::handle_get_documents(state, request.id)).unwrap(),
+        "Close" => serde_json::to_string(&handlers::handle_close(state, request.id, request.params)).unwrap(),
+
+        // Schema validation and design database export (of `file_path`, or a document's file)
+        "Validate" | "ExportSqlite" => {
+            let handle = document_handle(request.params.as_ref()).map(str::to_string);
+            let document = state.document(handle.as_deref());
+            let response = if request.method == "Validate" {
+                handlers::handle_validate(document, request.id, request.params)
+            } else {
+                handlers::handle_export_sqlite(document, request.id, request.params)
+            };
+            serde_json::to_string(&response).unwrap()
+        }
+
+        // Process
+        "GetMemory" => serde_json::to_string(&handlers::handle_get_memory(request.id)).unwrap(),
+
+        _ => dispatch_document_request(state, request, progress),
+    }
+}
+
+/// Dispatch a request on one document: the `document` param, default the most recently loaded one
+fn dispatch_document_request(
+    state: &mut ServerState, 
+    request: Request, 
+    progress: &Progress,
 ) -> String {
+    let drc_sender = state.drc_sender.clone();
+    let handle = document_handle(request.params.as_ref()).map(str::to_string);
+    let Some(document) = state.document_mut(handle.as_deref()) else {
+        let response = match handle {
+            _ if !METHODS.contains(&request.method.as_str()) => Response::error(request.id, error_codes::METHOD_NOT_FOUND,
+                format!("Method not found: {}", request.method)),
+            Some(handle) => Response::error(request.id, error_codes::DOCUMENT_NOT_FOUND,
+                format!("Document not found: {}", handle)),
+            None => Response::error(request.id, error_codes::NO_FILE_LOADED,
+                "No file loaded. Call Load first.".to_string()),
+        };
+        return serde_json::to_string(&response).unwrap();
+    };
+
     match request.method.as_str() {
         // File operations
-        "Load" => serde_json::to_string(&handlers::handle_load(state, request.id, request.params)).unwrap(),
-        "Save" => serde_json::to_string(&handlers::handle_save(state, request.id, request.params)).unwrap(),
-        "Close" => serde_json::to_string(&handlers::handle_close(state, request.id)).unwrap(),
+        "GetLoadDiagnostics" => serde_json::to_string(&handlers::handle_get_load_diagnostics(document, request.id)).unwrap(),
+        "Save" => serde_json::to_string(&handlers::handle_save(document, request.id, request.params, progress)).unwrap(),
         
         // Layer operations
-        "GetLayers" => serde_json::to_string(&handlers::handle_get_layers(state, request.id)).unwrap(),
-        "UpdateLayerColor" => serde_json::to_string(&handlers::handle_update_layer_color(state, request.id, request.params)).unwrap(),
-        "SetLayerVisibility" => serde_json::to_string(&handlers::handle_set_layer_visibility(state, request.id, request.params)).unwrap(),
+        "GetLayers" => serde_json::to_string(&handlers::handle_get_layers(document, request.id)).unwrap(),
+        "GetStackup" => serde_json::to_string(&handlers::handle_get_stackup(document, request.id)).unwrap(),
+        "GetBoardOutline" => serde_json::to_string(&handlers::handle_get_board_outline(document, request.id)).unwrap(),
+        "GetSteps" => serde_json::to_string(&handlers::handle_get_steps(document, request.id)).unwrap(),
+        "UpdateLayerColor" => serde_json::to_string(&handlers::handle_update_layer_color(document, request.id, request.params)).unwrap(),
+        "SetLayerVisibility" => serde_json::to_string(&handlers::handle_set_layer_visibility(document, request.id, request.params)).unwrap(),
         
         // Tessellation
-        "GetTessellation" => handlers::handle_get_tessellation_json(state, request.id, request.params),
-        "GetTessellationBinary" => handlers::handle_get_tessellation_binary(state, request.id, request.params),
+        "GetTessellation" => handlers::handle_get_tessellation_json(document, request.id, request.params),
+        "GetTessellationBinary" => handlers::handle_get_tessellation_binary(document, request.id, request.params),
         
         // Selection
-        "Select" => serde_json::to_string(&handlers::handle_select(state, request.id, request.params)).unwrap(),
-        "BoxSelect" => serde_json::to_string(&handlers::handle_box_select(state, request.id, request.params)).unwrap(),
-        "CheckPointHitsSelection" => serde_json::to_string(&handlers::handle_check_point_hits_selection(state, request.id, request.params)).unwrap(),
+        "Select" => serde_json::to_string(&handlers::handle_select(document, request.id, request.params)).unwrap(),
+        "BoxSelect" => serde_json::to_string(&handlers::handle_box_select(document, request.id, request.params)).unwrap(),
+        "CheckPointHitsSelection" => serde_json::to_string(&handlers::handle_check_point_hits_selection(document, request.id, request.params)).unwrap(),
         
         // Highlighting
-        "HighlightSelectedNets" => serde_json::to_string(&handlers::handle_highlight_selected_nets(state, request.id, request.params)).unwrap(),
-        "HighlightSelectedComponents" => serde_json::to_string(&handlers::handle_highlight_selected_components(state, request.id, request.params)).unwrap(),
+        "HighlightSelectedNets" => serde_json::to_string(&handlers::handle_highlight_selected_nets(document, request.id, request.params)).unwrap(),
+        "HighlightSelectedComponents" => serde_json::to_string(&handlers::handle_highlight_selected_components(document, request.id, request.params)).unwrap(),
         
         // Edit operations
-        "Delete" => serde_json::to_string(&handlers::handle_delete(state, request.id, request.params)).unwrap(),
-        "Undo" => serde_json::to_string(&handlers::handle_undo(state, request.id, request.params)).unwrap(),
-        "Redo" => serde_json::to_string(&handlers::handle_redo(state, request.id, request.params)).unwrap(),
-        "MoveObjects" => serde_json::to_string(&handlers::handle_move_objects(state, request.id, request.params)).unwrap(),
-        "UndoMove" => serde_json::to_string(&handlers::handle_undo_move(state, request.id, request.params)).unwrap(),
-        "RedoMove" => serde_json::to_string(&handlers::handle_redo_move(state, request.id, request.params)).unwrap(),
-        "RotateObjects" => serde_json::to_string(&handlers::handle_rotate_objects(state, request.id, request.params)).unwrap(),
-        "UndoRotate" => serde_json::to_string(&handlers::handle_undo_rotate(state, request.id, request.params)).unwrap(),
-        "RedoRotate" => serde_json::to_string(&handlers::handle_redo_rotate(state, request.id, request.params)).unwrap(),
-        "FlipObjects" => serde_json::to_string(&handlers::handle_flip_objects(state, request.id, request.params)).unwrap(),
+        "Delete" => serde_json::to_string(&handlers::handle_delete(document, request.id, request.params)).unwrap(),
+        "Undo" => serde_json::to_string(&handlers::handle_undo(document, request.id, request.params)).unwrap(),
+        "Redo" => serde_json::to_string(&handlers::handle_redo(document, request.id, request.params)).unwrap(),
+        "MoveObjects" => serde_json::to_string(&handlers::handle_move_objects(document, request.id, request.params)).unwrap(),
+        "UndoMove" => serde_json::to_string(&handlers::handle_undo_move(document, request.id, request.params)).unwrap(),
+        "RedoMove" => serde_json::to_string(&handlers::handle_redo_move(document, request.id, request.params)).unwrap(),
+        "RotateObjects" => serde_json::to_string(&handlers::handle_rotate_objects(document, request.id, request.params)).unwrap(),
+        "UndoRotate" => serde_json::to_string(&handlers::handle_undo_rotate(document, request.id, request.params)).unwrap(),
+        "RedoRotate" => serde_json::to_string(&handlers::handle_redo_rotate(document, request.id, request.params)).unwrap(),
+        "FlipObjects" => serde_json::to_string(&handlers::handle_flip_objects(document, request.id, request.params)).unwrap(),
         
         // Transform operations (new unified API)
-        "StartTransform" => serde_json::to_string(&handlers::handle_start_transform(state, request.id, request.params)).unwrap(),
-        "TransformPreview" => serde_json::to_string(&handlers::handle_transform_preview(state, request.id, request.params)).unwrap(),
-        "ApplyTransform" => serde_json::to_string(&handlers::handle_apply_transform(state, request.id, request.params)).unwrap(),
-        "CancelTransform" => serde_json::to_string(&handlers::handle_cancel_transform(state, request.id, request.params)).unwrap(),
-        "UndoTransform" => serde_json::to_string(&handlers::handle_undo_transform(state, request.id, request.params)).unwrap(),
-        "RedoTransform" => serde_json::to_string(&handlers::handle_redo_transform(state, request.id, request.params)).unwrap(),
+        "StartTransform" => serde_json::to_string(&handlers::handle_start_transform(document, request.id, request.params)).unwrap(),
+        "TransformPreview" => serde_json::to_string(&handlers::handle_transform_preview(document, request.id, request.params)).unwrap(),
+        "ApplyTransform" => serde_json::to_string(&handlers::handle_apply_transform(document, request.id, request.params)).unwrap(),
+        "CancelTransform" => serde_json::to_string(&handlers::handle_cancel_transform(document, request.id, request.params)).unwrap(),
+        "UndoTransform" => serde_json::to_string(&handlers::handle_undo_transform(document, request.id, request.params)).unwrap(),
+        "RedoTransform" => serde_json::to_string(&handlers::handle_redo_transform(document, request.id, request.params)).unwrap(),
+        
+        // Component queries
+        "GetComponents" => serde_json::to_string(&handlers::handle_get_components(document, request.id, request.params)).unwrap(),
+        "GetComponent" => serde_json::to_string(&handlers::handle_get_component(document, request.id, request.params)).unwrap(),
+        "GetBom" => serde_json::to_string(&handlers::handle_get_bom(document, request.id)).unwrap(),
+        "GetBomItem" => serde_json::to_string(&handlers::handle_get_bom_item(document, request.id, request.params)).unwrap(),
+
+        // Netlist queries
+        "GetNets" => serde_json::to_string(&handlers::handle_get_nets(document, request.id)).unwrap(),
+        "GetNet" => serde_json::to_string(&handlers::handle_get_net(document, request.id, request.params)).unwrap(),
         
         // DRC operations
-        "RunDRC" => serde_json::to_string(&handlers::handle_run_drc(state, request.id, request.params)).unwrap(),
-        "GetDRCViolations" => serde_json::to_string(&handlers::handle_get_drc_violations(state, request.id)).unwrap(),
-        "RunDRCWithRegions" => handlers::handle_run_drc_with_regions_async(state, request.id, request.params, drc_sender),
-        "GetDRCRegions" => serde_json::to_string(&handlers::handle_get_drc_regions(state, request.id)).unwrap(),
+        "RunDRC" => serde_json::to_string(&handlers::handle_run_drc(document, request.id, request.params, progress)).unwrap(),
+        "GetDRCViolations" => serde_json::to_string(&handlers::handle_get_drc_violations(document, request.id)).unwrap(),
+        "RunDRCWithRegions" => handlers::handle_run_drc_with_regions_async(document, request.id, request.params, drc_sender, progress.clone()),
+        "GetDRCRegions" => serde_json::to_string(&handlers::handle_get_drc_regions(document, request.id)).unwrap(),
         
         // Query operations
-        "QueryNetAtPoint" => serde_json::to_string(&handlers::handle_query_net_at_point(state, request.id, request.params)).unwrap(),
-        "GetObjectBounds" => serde_json::to_string(&handlers::handle_get_object_bounds(state, request.id, request.params)).unwrap(),
-        "GetMemory" => serde_json::to_string(&handlers::handle_get_memory(request.id)).unwrap(),
+        "QueryNetAtPoint" => serde_json::to_string(&handlers::handle_query_net_at_point(document, request.id, request.params)).unwrap(),
+        "GetObjectBounds" => serde_json::to_string(&handlers::handle_get_object_bounds(document, request.id, request.params)).unwrap(),
         
         // Unknown method
         _ => {
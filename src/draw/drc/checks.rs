@@ -3,6 +3,7 @@
 //! Contains the core DRC checking algorithms for layer-level clearance analysis.
 
 use crate::draw::geometry::{ObjectRange, LayerJSON, SelectableObject};
+use crate::draw::progress::{Progress, ProgressCounter};
 use super::types::{DrcViolation, TriangleViolation};
 use super::distance::{Triangle, triangle_distance};
 use super::geometry::get_boundary_triangles_for_object;
@@ -27,11 +28,14 @@ pub fn should_check_pair(a: &ObjectRange, b: &ObjectRange) -> bool {
 }
 
 /// Check clearances for objects on a single layer
+/// Each object checked ticks `counter`.
 pub fn check_layer_clearances(
     layer: &LayerJSON,
     objects: &[&SelectableObject],
     spatial_index: &RTree<SelectableObject>,
     clearance: f32,
+    progress: &Progress,
+    counter: &ProgressCounter,
 ) -> Vec<DrcViolation> {
     // Cache: object_id -> boundary triangles
     let boundary_cache: HashMap<u64, Vec<Triangle>> = objects
@@ -48,6 +52,11 @@ pub fn check_layer_clearances(
         .par_iter()
         .flat_map(|obj_a| {
             let mut violations = Vec::new();
+            // Once cancelled, the remaining objects are skipped; the runner discards the result
+            if progress.is_cancelled() {
+                return violations;
+            }
+            counter.tick();
 
             // R-tree query with clearance expansion
             let search_bounds = rstar::AABB::from_corners(
@@ -173,11 +182,14 @@ pub fn check_triangle_clearance_all(
 }
 
 /// Check layer clearances and return all triangle violations
+/// Each object checked ticks `counter`.
 pub fn check_layer_clearances_all(
     layer: &LayerJSON,
     objects: &[&SelectableObject],
     spatial_index: &RTree<SelectableObject>,
     clearance: f32,
+    progress: &Progress,
+    counter: &ProgressCounter,
 ) -> Vec<TriangleViolation> {
     // Cache: object_id -> boundary triangles
     let boundary_cache: HashMap<u64, Vec<Triangle>> = objects
@@ -194,6 +206,11 @@ pub fn check_layer_clearances_all(
         .par_iter()
         .flat_map(|obj_a| {
             let mut violations = Vec::new();
+            // Once cancelled, the remaining objects are skipped; the runner discards the result
+            if progress.is_cancelled() {
+                return violations;
+            }
+            counter.tick();
 
             // R-tree query with clearance expansion
             let search_bounds = rstar::AABB::from_corners(
@@ -5,6 +5,7 @@
 //! - Targeted DRC on specific objects
 
 use crate::draw::geometry::{LayerJSON, SelectableObject};
+use crate::draw::progress::{Cancelled, Progress};
 use rayon::prelude::*;
 use rstar::{RTree, AABB};
 use std::collections::{HashMap, HashSet};
@@ -13,12 +14,13 @@ use super::types::{DrcViolation, DesignRules, is_copper_layer};
 use super::{checks, geometry};
 
 /// Run full DRC on all conductor layers
-/// Returns list of violations found
+/// Returns list of violations found, or Cancelled when `progress` is cancelled
 pub fn run_full_drc(
     layers: &[LayerJSON],
     spatial_index: &RTree<SelectableObject>,
     rules: &DesignRules,
-) -> Vec<DrcViolation> {
+    progress: &Progress,
+) -> Result<Vec<DrcViolation>, Cancelled> {
     let start = std::time::Instant::now();
     let clearance = rules.conductor_clearance_mm;
 
@@ -56,16 +58,18 @@ pub fn run_full_drc(
         .collect();
 
     // Parallel per-layer DRC
+    let counter = progress.counter("Checking clearances", objects_by_layer.values().map(Vec::len).sum());
     let violations: Vec<DrcViolation> = objects_by_layer
         .par_iter()
         .flat_map(|(layer_id, layer_objects)| {
             if let Some(layer) = layer_lookup.get(layer_id) {
-                checks::check_layer_clearances(layer, layer_objects, spatial_index, clearance)
+                checks::check_layer_clearances(layer, layer_objects, spatial_index, clearance, progress, &counter)
             } else {
                 vec![]
             }
         })
         .collect();
+    progress.check()?;
 
     eprintln!(
         "[DRC] Full check completed: {} objects checked, {} violations found in {:?}",
@@ -74,7 +78,7 @@ pub fn run_full_drc(
         start.elapsed()
     );
 
-    violations
+    Ok(violations)
 }
 
 /// Run targeted DRC on specific objects (after edit)
@@ -5,6 +5,7 @@
 //! - Incremental DRC for modified regions
 
 use crate::draw::geometry::{LayerJSON, SelectableObject};
+use crate::draw::progress::{Cancelled, Progress};
 use rayon::prelude::*;
 use rstar::RTree;
 use std::collections::{HashMap, HashSet};
@@ -16,12 +17,14 @@ use super::types::{
 use super::{checks, regions};
 
 /// Run full DRC and return fused regions for visualization
+/// Cancelled when `progress` is cancelled before the check completes
 pub fn run_full_drc_with_regions(
     layers: &[LayerJSON],
     spatial_index: &RTree<SelectableObject>,
     rules: &DesignRules,
     deleted_object_ids: &HashSet<u64>,
-) -> Vec<DrcRegion> {
+    progress: &Progress,
+) -> Result<Vec<DrcRegion>, Cancelled> {
     let start = std::time::Instant::now();
     let clearance = rules.conductor_clearance_mm;
 
@@ -65,16 +68,18 @@ pub fn run_full_drc_with_regions(
         .collect();
 
     // Collect all triangle violations
+    let counter = progress.counter("Checking clearances", objects_by_layer.values().map(Vec::len).sum());
     let all_violations: Vec<TriangleViolation> = objects_by_layer
         .par_iter()
         .flat_map(|(layer_id, layer_objects)| {
             if let Some(layer) = layer_lookup.get(layer_id) {
-                checks::check_layer_clearances_all(layer, layer_objects, spatial_index, clearance)
+                checks::check_layer_clearances_all(layer, layer_objects, spatial_index, clearance, progress, &counter)
             } else {
                 vec![]
             }
         })
         .collect();
+    progress.check()?;
 
     eprintln!(
         "[DRC Regions] Found {} triangle violations in {:?}",
@@ -91,7 +96,7 @@ pub fn run_full_drc_with_regions(
         start.elapsed()
     );
 
-    fused_regions
+    Ok(fused_regions)
 }
 
 /// Run incremental DRC only on regions that have been modified
@@ -103,13 +108,14 @@ pub fn run_incremental_drc_with_regions(
     deleted_object_ids: &HashSet<u64>,
     modified_regions: &[ModifiedRegionInfo],
     existing_regions: &[DrcRegion],
-) -> Vec<DrcRegion> {
+    progress: &Progress,
+) -> Result<Vec<DrcRegion>, Cancelled> {
     let start = std::time::Instant::now();
     let clearance = rules.conductor_clearance_mm;
     
     if modified_regions.is_empty() {
         eprintln!("[DRC Incremental] No modified regions, returning existing {} regions", existing_regions.len());
-        return existing_regions.to_vec();
+        return Ok(existing_regions.to_vec());
     }
     
     // Expand modified region bounds by clearance distance to catch nearby objects
@@ -198,16 +204,18 @@ pub fn run_incremental_drc_with_regions(
         .collect();
     
     // Run DRC on affected regions
+    let counter = progress.counter("Checking modified regions", objects_by_layer.values().map(Vec::len).sum());
     let new_violations: Vec<TriangleViolation> = objects_by_layer
         .par_iter()
         .flat_map(|(layer_id, layer_objects)| {
             if let Some(layer) = layer_lookup.get(layer_id) {
-                checks::check_layer_clearances_all(layer, layer_objects, spatial_index, clearance)
+                checks::check_layer_clearances_all(layer, layer_objects, spatial_index, clearance, progress, &counter)
             } else {
                 vec![]
             }
         })
         .collect();
+    progress.check()?;
     
     eprintln!(
         "[DRC Incremental] Found {} new violations in affected regions",
@@ -263,5 +271,5 @@ pub fn run_incremental_drc_with_regions(
         start.elapsed()
     );
     
-    all_regions
+    Ok(all_regions)
 }
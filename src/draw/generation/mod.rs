@@ -8,14 +8,19 @@
 //! - `polygons` - Polygon geometry generation
 //! - `pads` - Instanced pad geometry generation
 //! - `vias` - Instanced via geometry generation
+//! - `text` - Text layout into stroke polylines
+//! - `stroke_font` - Built-in single-stroke font
 
 mod polylines;
 mod polygons;
 mod pads;
 mod vias;
+mod text;
+mod stroke_font;
 
 use crate::draw::geometry::*;
 use crate::draw::tessellation::tessellate_polyline;
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::env;
 
@@ -23,6 +28,7 @@ pub use polylines::generate_polyline_geometry;
 pub use polygons::generate_polygon_geometry;
 pub use pads::generate_pad_geometry;
 pub use vias::generate_via_geometry;
+pub use text::layout_text;
 
 fn should_debug_layer(layer_id: &str) -> bool {
     match env::var("DEBUG_TESSELLATION_LAYER") {
@@ -75,6 +81,24 @@ pub(crate) fn debug_print_polyline(
     }
 }
 
+/// Axis-aligned bounds of an instanced shape placed at (x, y) and rotated by `rotation` radians
+pub(crate) fn instance_bounds(shape_verts: &[f32], x: f32, y: f32, rotation: f32) -> [f32; 4] {
+    let (sin, cos) = rotation.sin_cos();
+    let mut bounds = [f32::MAX, f32::MAX, f32::MIN, f32::MIN];
+    for v in shape_verts.chunks_exact(2) {
+        let px = x + v[0] * cos - v[1] * sin;
+        let py = y + v[0] * sin + v[1] * cos;
+        bounds[0] = bounds[0].min(px);
+        bounds[1] = bounds[1].min(py);
+        bounds[2] = bounds[2].max(px);
+        bounds[3] = bounds[3].max(py);
+    }
+    if shape_verts.is_empty() {
+        return [x, y, x, y];
+    }
+    bounds
+}
+
 /// Generate LayerJSON for all geometry types (polylines, polygons, pads, vias) in a layer
 #[allow(clippy::too_many_arguments)]
 pub fn generate_layer_json(
@@ -91,12 +115,23 @@ pub fn generate_layer_json(
     let layer_start = std::time::Instant::now();
     let mut object_ranges = Vec::new();
     
+    // Text is laid out into stroke polylines and batched after the layer's own polylines
+    let polylines: Cow<[Polyline]> = if geometries.texts.is_empty() {
+        Cow::Borrowed(&geometries.polylines)
+    } else {
+        let mut all = geometries.polylines.clone();
+        all.extend(geometries.texts.iter().flat_map(layout_text));
+        Cow::Owned(all)
+    };
+    
     // Generate polyline geometry (opaque, no alpha) - for batch.wgsl
-    let polyline_lods = if !geometries.polylines.is_empty() {
-        generate_polyline_geometry(layer_id, layer_index, layer_name, &geometries.polylines, culling_stats, &mut object_ranges)?
+    let polyline_lods = if !polylines.is_empty() {
+        generate_polyline_geometry(layer_id, layer_index, layer_name, &polylines, culling_stats, &mut object_ranges)?
     } else {
         Vec::new()
     };
+    // Text strokes are drawn but not selectable: Save maps polyline ids to Polyline/Line/Arc elements
+    object_ranges.truncate(geometries.polylines.len());
     
     // Generate polygon geometry (with alpha) - for batch_colored.wgsl
     let polygon_lods = if !geometries.polygons.is_empty() {
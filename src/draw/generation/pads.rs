@@ -6,6 +6,16 @@
 use std::collections::HashMap;
 use crate::draw::geometry::*;
 use crate::draw::tessellation::*;
+use super::instance_bounds;
+
+/// Pads sharing a primitive are instanced together only if their Xform mirror and scale match,
+/// since those are baked into the shape while rotation and position are per instance
+#[derive(Debug, Hash, Eq, PartialEq)]
+struct PadGroupKey {
+    shape_id: String,
+    mirror: bool,
+    scale_bits: u32,
+}
 
 /// Generate instanced_rot geometry for pads (shapes with rotation)
 /// Creates 3 LOD levels, each containing multiple geometries for different pad shapes
@@ -24,10 +34,15 @@ pub fn generate_pad_geometry(
         eprintln!("  Generating pad geometry for {} pads", pads.len());
     }
     
-    // Group pads by shape_id for efficient instancing
-    let mut shape_groups: HashMap<String, Vec<(usize, &PadInstance)>> = HashMap::new();
+    // Group pads by shape_id (and baked Xform mirror/scale) for efficient instancing
+    let mut shape_groups: HashMap<PadGroupKey, Vec<(usize, &PadInstance)>> = HashMap::new();
     for (i, pad) in pads.iter().enumerate() {
-        shape_groups.entry(pad.shape_id.clone())
+        let key = PadGroupKey {
+            shape_id: pad.shape_id.clone(),
+            mirror: pad.mirror,
+            scale_bits: pad.scale.to_bits(),
+        };
+        shape_groups.entry(key)
             .or_default()
             .push((i, pad));
     }
@@ -41,31 +56,21 @@ pub fn generate_pad_geometry(
     let mut lod2_entries = Vec::new();
     let mut shape_index_counter: u32 = 0;
     
-    for (shape_id, instances) in shape_groups {
-        if let Some(primitive) = primitives.get(&shape_id) {
+    for (group_key, instances) in shape_groups {
+        let shape_id = &group_key.shape_id;
+        if let Some(primitive) = primitives.get(shape_id) {
             if std::env::var("DEBUG_PADS").is_ok() {
                 eprintln!("    Shape {}: {} instances, primitive: {:?}", shape_id, instances.len(), primitive);
             }
             
-            // Tessellate the base shape once
-            let (shape_verts, shape_indices) = tessellate_primitive(primitive);
-            
-            // Calculate primitive bounds
-            let mut prim_min_x = f32::MAX;
-            let mut prim_min_y = f32::MAX;
-            let mut prim_max_x = f32::MIN;
-            let mut prim_max_y = f32::MIN;
-            for i in 0..(shape_verts.len()/2) {
-                let x = shape_verts[i*2];
-                let y = shape_verts[i*2+1];
-                prim_min_x = prim_min_x.min(x);
-                prim_min_y = prim_min_y.min(y);
-                prim_max_x = prim_max_x.max(x);
-                prim_max_y = prim_max_y.max(y);
-            }
-            let prim_w = prim_max_x - prim_min_x;
-            let prim_h = prim_max_y - prim_min_y;
-            let radius = (prim_w.max(prim_h) / 2.0) * 1.414; // Rough bounding circle radius
+            // Tessellate the base shape once, then bake in the Xform mirror and scale
+            let (mut shape_verts, shape_indices) = tessellate_primitive(primitive);
+            let local_xform = Xform {
+                mirror: group_key.mirror,
+                scale: f32::from_bits(group_key.scale_bits),
+                ..Xform::default()
+            };
+            local_xform.to_transform().apply_to_vertices(&mut shape_verts);
 
             // Create instance data (x, y, rotation) for each pad
             let mut instance_data = Vec::new();
@@ -81,11 +86,8 @@ pub fn generate_pad_geometry(
                 
                 let id = ((layer_index as u64) << 40) | ((3u64) << 36) | (*original_idx as u64);
                 
-                // Calculate bounds (approximate with bounding circle)
-                let min_x = inst.x - radius;
-                let min_y = inst.y - radius;
-                let max_x = inst.x + radius;
-                let max_y = inst.y + radius;
+                // Calculate bounds from the rotated shape
+                let bounds = instance_bounds(&shape_verts, inst.x, inst.y, inst.rotation.to_radians());
                 
                 object_ranges.push(ObjectRange {
                     id,
@@ -94,7 +96,7 @@ pub fn generate_pad_geometry(
                     vertex_ranges: Vec::new(), // Not used for instanced
                     instance_index: Some(local_idx as u32), // Index within this shape group
                     shape_index: Some(current_shape_index), // Which shape/LOD entry group
-                    bounds: [min_x, min_y, max_x, max_y],
+                    bounds,
                     net_name: inst.net_name.clone(),
                     component_ref: inst.component_ref.clone(),
                     pin_ref: inst.pin_ref.clone(),
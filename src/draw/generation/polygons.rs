@@ -1,36 +1,88 @@
 //! Polygon geometry generation using earcut triangulation
 //!
 //! Generates polygon geometry from polygon outlines with holes using tessellation.
+//! Non-solid fills (FillDesc HOLLOW, HATCH, MESH) are tessellated as their boundary and lattice.
 //! Polygons are rendered as filled triangles with per-vertex alpha support.
 
 use crate::draw::geometry::*;
-use crate::draw::tessellation::tessellate_polygon;
+use crate::draw::tessellation::{polygon_with_arc_tolerance, tessellate_polygon_fill, ARC_CHORD_TOLERANCE_LOD};
 use rayon::prelude::*;
 
 /// Generate polygon LOD geometry using earcut triangulation
+/// Layers with true arcs get one LOD per arc chord tolerance, re-flattening only the curved polygons;
+/// other layers get a single LOD.
 pub fn generate_polygon_geometry(
     layer_id: &str,
     layer_index: u32,
     polygons: &[Polygon],
     object_ranges: &mut Vec<ObjectRange>,
 ) -> Result<Vec<GeometryLOD>, anyhow::Error> {
+    let lod_count = if polygons.iter().any(|p| p.curved_rings.is_some()) { ARC_CHORD_TOLERANCE_LOD.len() } else { 1 };
+
     // Use rayon to tessellate polygons in parallel
-    let results: Vec<(Vec<f32>, Vec<u32>)> = polygons.par_iter()
-        .map(|polygon| tessellate_polygon(polygon, 0.0)) // LOD0: no simplification
+    let results: Vec<Vec<(Vec<f32>, Vec<u32>)>> = polygons.par_iter()
+        .map(|polygon| {
+            // LOD0: no simplification; hatched fills become their lattice
+            let lod0 = tessellate_polygon_fill(polygon);
+            let mut lods = Vec::with_capacity(lod_count);
+            for &tolerance in &ARC_CHORD_TOLERANCE_LOD[1..lod_count] {
+                lods.push(match polygon.curved_rings {
+                    Some(_) => tessellate_polygon_fill(&polygon_with_arc_tolerance(polygon, tolerance)),
+                    None => lod0.clone(),
+                });
+            }
+            lods.insert(0, lod0);
+            lods
+        })
         .collect();
-        
-    let mut all_verts = Vec::new();
-    let mut all_indices = Vec::new();
-    let mut alpha_values = Vec::new();
-    let mut visibility_values = Vec::new();
 
-    let _start_obj_idx = object_ranges.len();
+    let mut lod_geometries = Vec::with_capacity(lod_count);
+    let mut vertex_ranges = vec![Vec::with_capacity(lod_count); polygons.len()];
+    for lod_idx in 0..lod_count {
+        let mut all_verts = Vec::new();
+        let mut all_indices = Vec::new();
+        let mut alpha_values = Vec::new();
+        let mut visibility_values = Vec::new();
 
-    // Combine results sequentially
-    for (i, (verts, indices)) in results.into_iter().enumerate() {
-        let polygon = &polygons[i];
-        let vert_count = verts.len() / 2;
-        
+        // Combine results sequentially
+        for (i, lods) in results.iter().enumerate() {
+            let (verts, indices) = &lods[lod_idx];
+            let vert_count = verts.len() / 2;
+
+            // Offset indices by current vertex count
+            let vert_offset = (all_verts.len() / 2) as u32;
+            vertex_ranges[i].push((vert_offset, vert_count as u32));
+            all_verts.extend_from_slice(verts);
+            all_indices.extend(indices.iter().map(|&idx| idx + vert_offset));
+
+            // Add alpha values
+            let alpha = polygons[i].fill_color[3];
+            alpha_values.extend(std::iter::repeat_n(alpha, vert_count));
+
+            // Add visibility values
+            visibility_values.extend(std::iter::repeat_n(1.0, vert_count));
+        }
+
+        if all_verts.is_empty() || all_indices.is_empty() {
+            lod_geometries.clear();
+            break;
+        }
+
+        let vert_count = all_verts.len() / 2;
+        let index_count = all_indices.len();
+        lod_geometries.push(GeometryLOD {
+            vertex_data: all_verts,
+            vertex_count: vert_count,
+            index_data: Some(all_indices),
+            index_count: Some(index_count),
+            alpha_data: Some(alpha_values),
+            visibility_data: Some(visibility_values),
+            instance_data: None,
+            instance_count: None,
+        });
+    }
+
+    for (i, (polygon, mut ranges)) in polygons.iter().zip(vertex_ranges).enumerate() {
         // Generate ID and bounds
         let id = ((layer_index as u64) << 40) | ((1u64) << 36) | (i as u64);
         
@@ -45,14 +97,15 @@ pub fn generate_polygon_geometry(
             max_x = max_x.max(p.x);
             max_y = max_y.max(p.y);
         }
-        
-        let current_vert_start = (all_verts.len() / 2) as u32;
+
+        // A single LOD serves every zoom level
+        ranges.resize(ARC_CHORD_TOLERANCE_LOD.len(), ranges[0]);
         
         object_ranges.push(ObjectRange {
             id,
             layer_id: layer_id.to_string(),
             obj_type: 1, // Polygon
-            vertex_ranges: vec![(current_vert_start, vert_count as u32); 5], // Same for all LODs (simplified)
+            vertex_ranges: ranges,
             instance_index: None,
             shape_index: None, // Not used for batched geometry
             bounds: [min_x, min_y, max_x, max_y],
@@ -63,37 +116,60 @@ pub fn generate_polygon_geometry(
             polar_radius: None,
             polar_angle: None,
         });
-
-        // Offset indices by current vertex count
-        let vert_offset = (all_verts.len() / 2) as u32;
-        all_verts.extend(verts);
-        all_indices.extend(indices.iter().map(|&idx| idx + vert_offset));
-        
-        // Add alpha values
-        let alpha = polygon.fill_color[3];
-        alpha_values.extend(std::iter::repeat_n(alpha, vert_count));
-        
-        // Add visibility values
-        visibility_values.extend(std::iter::repeat_n(1.0, vert_count));
     }
     
-    if all_verts.is_empty() || all_indices.is_empty() {
-        return Ok(Vec::new());
+    Ok(lod_geometries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::draw::tessellation::flatten_path;
+
+    fn polygon(outer_ring: Vec<Point>, curved_rings: Option<Vec<CurvedPath>>) -> Polygon {
+        Polygon {
+            outer_ring,
+            holes: Vec::new(),
+            curved_rings,
+            fill_color: [1.0; 4],
+            net_name: None,
+            component_ref: None,
+            polarity: Polarity::Positive,
+            fill: FillDescriptor::default(),
+            line_width: None,
+        }
+    }
+
+    #[test]
+    fn test_curved_polygon_lods_reflatten_arcs() {
+        let disc = CurvedPath {
+            start: Point { x: 5.0, y: 0.0 },
+            steps: vec![PathStep::Curve { end: Point { x: 5.0, y: 0.0 }, center: Point { x: 0.0, y: 0.0 }, clockwise: false }],
+        };
+        let square = vec![
+            Point { x: 10.0, y: 0.0 },
+            Point { x: 11.0, y: 0.0 },
+            Point { x: 11.0, y: 1.0 },
+            Point { x: 10.0, y: 1.0 },
+        ];
+        let polygons = vec![
+            polygon(flatten_path(&disc, ARC_CHORD_TOLERANCE_LOD[0]), Some(vec![disc])),
+            polygon(square.clone(), None),
+        ];
+
+        let mut ranges = Vec::new();
+        let lods = generate_polygon_geometry("TOP", 0, &polygons, &mut ranges).unwrap();
+        assert_eq!(lods.len(), ARC_CHORD_TOLERANCE_LOD.len());
+        let disc_counts: Vec<u32> = ranges[0].vertex_ranges.iter().map(|r| r.1).collect();
+        assert!(disc_counts.windows(2).all(|pair| pair[1] <= pair[0]));
+        assert!(disc_counts[4] < disc_counts[0]);
+        // Straight polygons keep their LOD0 triangles at every LOD
+        assert!(ranges[1].vertex_ranges.iter().all(|r| r.1 == 4));
+
+        // Without arcs one LOD serves every zoom level
+        let mut ranges = Vec::new();
+        let lods = generate_polygon_geometry("TOP", 0, &[polygon(square, None)], &mut ranges).unwrap();
+        assert_eq!(lods.len(), 1);
+        assert_eq!(ranges[0].vertex_ranges, vec![(0, 4); 5]);
     }
-    
-    let vert_count = all_verts.len() / 2;
-    let index_count = all_indices.len();
-    
-    let geometry_lod = GeometryLOD {
-        vertex_data: all_verts,
-        vertex_count: vert_count,
-        index_data: Some(all_indices),
-        index_count: Some(index_count),
-        alpha_data: Some(alpha_values),
-        visibility_data: Some(visibility_values),
-        instance_data: None,
-        instance_count: None,
-    };
-    
-    Ok(vec![geometry_lod])
 }
@@ -0,0 +1,139 @@
+//! Built-in single-stroke font
+//!
+//! Glyphs are drawn on a grid 4 units wide with the baseline at y = 0, cap
+//! height 6, x-height 4 and descenders down to -2. Each glyph is a list of
+//! strokes separated by `;`, each stroke a space-separated list of `x,y` points.
+
+/// Cap height of the glyph grid
+pub const CAP_HEIGHT: f32 = 6.0;
+/// Width of a glyph cell
+pub const GLYPH_WIDTH: f32 = 4.0;
+/// Horizontal distance between glyph origins
+pub const ADVANCE: f32 = 5.5;
+/// Vertical distance between baselines of consecutive lines
+pub const LINE_SPACING: f32 = 10.0;
+
+/// Drawn for characters the font has no glyph for
+const MISSING_GLYPH: &str = "0,0 4,0 4,6 0,6 0,0";
+
+/// Stroke data for a character
+fn glyph_strokes(c: char) -> &'static str {
+    match c {
+        ' ' => "",
+        '!' => "2,6 2,2; 2,0.5 2,0",
+        '"' => "1,6 1,4.5; 3,6 3,4.5",
+        '#' => "1,0 1.5,6; 2.5,0 3,6; 0,2 4,2; 0,4 4,4",
+        '$' => "4,5 1,5 0,4 1,3 3,3 4,2 3,1 0,1; 2,6 2,0",
+        '%' => "0,0 4,6; 0,6 0,5 1,5 1,6 0,6; 3,0 3,1 4,1 4,0 3,0",
+        '&' => "4,0 0,4 0,5 1,6 2,5 2,4 0,2 0,1 1,0 2,0 4,2",
+        '\'' => "2,6 2,4.5",
+        '(' => "3,6 1.5,4.5 1.5,1.5 3,0",
+        ')' => "1,6 2.5,4.5 2.5,1.5 1,0",
+        '*' => "2,5 2,1; 0.5,4 3.5,2; 0.5,2 3.5,4",
+        '+' => "2,5 2,1; 0,3 4,3",
+        ',' => "2,0.5 2,0 1,-1",
+        '-' => "0.5,3 3.5,3",
+        '.' => "2,0.5 2,0",
+        '/' => "0,0 4,6",
+        '0' => "1,0 0,1 0,5 1,6 3,6 4,5 4,1 3,0 1,0; 0,1 4,5",
+        '1' => "1,5 2,6 2,0; 1,0 3,0",
+        '2' => "0,5 1,6 3,6 4,5 4,4 0,0 4,0",
+        '3' => "0,5 1,6 3,6 4,5 4,4 3,3 4,2 4,1 3,0 1,0 0,1; 1,3 3,3",
+        '4' => "3,0 3,6 0,2 4,2",
+        '5' => "4,6 0,6 0,3 3,3 4,2 4,1 3,0 1,0 0,1",
+        '6' => "4,5 3,6 1,6 0,5 0,1 1,0 3,0 4,1 4,2 3,3 0,3",
+        '7' => "0,6 4,6 1,0",
+        '8' => "1,3 0,4 0,5 1,6 3,6 4,5 4,4 3,3 1,3 0,2 0,1 1,0 3,0 4,1 4,2 3,3",
+        '9' => "0,1 1,0 3,0 4,1 4,5 3,6 1,6 0,5 0,4 1,3 4,3",
+        ':' => "2,4 2,3.5; 2,0.5 2,0",
+        ';' => "2,4 2,3.5; 2,0.5 2,0 1,-1",
+        '<' => "4,5 0,3 4,1",
+        '=' => "0,4 4,4; 0,2 4,2",
+        '>' => "0,5 4,3 0,1",
+        '?' => "0,5 1,6 3,6 4,5 4,4 2,3 2,2; 2,0.5 2,0",
+        '@' => "3,2 3,4 1,4 1,2 3,2 4,2 4,5 3,6 1,6 0,5 0,1 1,0 4,0",
+        'A' => "0,0 2,6 4,0; 0.7,2 3.3,2",
+        'B' => "0,0 0,6 3,6 4,5 4,4 3,3 0,3; 3,3 4,2 4,1 3,0 0,0",
+        'C' => "4,5 3,6 1,6 0,5 0,1 1,0 3,0 4,1",
+        'D' => "0,0 0,6 3,6 4,5 4,1 3,0 0,0",
+        'E' => "4,6 0,6 0,0 4,0; 0,3 3,3",
+        'F' => "4,6 0,6 0,0; 0,3 3,3",
+        'G' => "4,5 3,6 1,6 0,5 0,1 1,0 3,0 4,1 4,3 2,3",
+        'H' => "0,0 0,6; 4,0 4,6; 0,3 4,3",
+        'I' => "1,6 3,6; 2,6 2,0; 1,0 3,0",
+        'J' => "4,6 4,1 3,0 1,0 0,1",
+        'K' => "0,0 0,6; 4,6 0,2; 1,3 4,0",
+        'L' => "0,6 0,0 4,0",
+        'M' => "0,0 0,6 2,3 4,6 4,0",
+        'N' => "0,0 0,6 4,0 4,6",
+        'O' => "1,0 0,1 0,5 1,6 3,6 4,5 4,1 3,0 1,0",
+        'P' => "0,0 0,6 3,6 4,5 4,4 3,3 0,3",
+        'Q' => "1,0 0,1 0,5 1,6 3,6 4,5 4,1 3,0 1,0; 2,2 4,0",
+        'R' => "0,0 0,6 3,6 4,5 4,4 3,3 0,3; 2,3 4,0",
+        'S' => "4,5 3,6 1,6 0,5 0,4 1,3 3,3 4,2 4,1 3,0 1,0 0,1",
+        'T' => "0,6 4,6; 2,6 2,0",
+        'U' => "0,6 0,1 1,0 3,0 4,1 4,6",
+        'V' => "0,6 2,0 4,6",
+        'W' => "0,6 1,0 2,4 3,0 4,6",
+        'X' => "0,0 4,6; 0,6 4,0",
+        'Y' => "0,6 2,3 4,6; 2,3 2,0",
+        'Z' => "0,6 4,6 0,0 4,0",
+        '[' => "3,6 1,6 1,0 3,0",
+        '\\' => "0,6 4,0",
+        ']' => "1,6 3,6 3,0 1,0",
+        '^' => "0,4 2,6 4,4",
+        '_' => "0,-1 4,-1",
+        '`' => "1,6 2,5",
+        'a' => "4,4 4,0; 4,3 3,4 1,4 0,3 0,1 1,0 3,0 4,1",
+        'b' => "0,6 0,0; 0,3 1,4 3,4 4,3 4,1 3,0 1,0 0,1",
+        'c' => "4,3 3,4 1,4 0,3 0,1 1,0 3,0 4,1",
+        'd' => "4,6 4,0; 4,3 3,4 1,4 0,3 0,1 1,0 3,0 4,1",
+        'e' => "0,2 4,2 4,3 3,4 1,4 0,3 0,1 1,0 3,0 4,1",
+        'f' => "3,6 2,6 1,5 1,0; 0,4 3,4",
+        'g' => "4,4 4,-1 3,-2 1,-2 0,-1; 4,3 3,4 1,4 0,3 0,1 1,0 3,0 4,1",
+        'h' => "0,6 0,0; 0,3 1,4 3,4 4,3 4,0",
+        'i' => "2,4 2,0; 2,5.5 2,6",
+        'j' => "3,4 3,-1 2,-2 1,-2; 3,5.5 3,6",
+        'k' => "0,6 0,0; 4,4 0,1.5; 1.5,2.5 4,0",
+        'l' => "2,6 2,1 3,0",
+        'm' => "0,4 0,0; 0,3 1,4 2,3 2,0; 2,3 3,4 4,3 4,0",
+        'n' => "0,4 0,0; 0,3 1,4 3,4 4,3 4,0",
+        'o' => "1,0 0,1 0,3 1,4 3,4 4,3 4,1 3,0 1,0",
+        'p' => "0,4 0,-2; 0,3 1,4 3,4 4,3 4,1 3,0 1,0 0,1",
+        'q' => "4,4 4,-2; 4,3 3,4 1,4 0,3 0,1 1,0 3,0 4,1",
+        'r' => "0,4 0,0; 0,2.5 1.5,4 3,4 4,3",
+        's' => "4,3 3,4 1,4 0,3 1,2 3,2 4,1 3,0 1,0 0,1",
+        't' => "1,6 1,1 2,0 3,0; 0,4 3,4",
+        'u' => "0,4 0,1 1,0 3,0 4,1; 4,4 4,0",
+        'v' => "0,4 2,0 4,4",
+        'w' => "0,4 1,0 2,3 3,0 4,4",
+        'x' => "0,0 4,4; 0,4 4,0",
+        'y' => "0,4 2,0; 4,4 1.5,-1 0.5,-2",
+        'z' => "0,4 4,4 0,0 4,0",
+        '{' => "3,6 2,5 2,3.5 1,3 2,2.5 2,1 3,0",
+        '|' => "2,6 2,-1",
+        '}' => "1,6 2,5 2,3.5 3,3 2,2.5 2,1 1,0",
+        '~' => "0,3 1,4 3,2 4,3",
+        '°' => "1,6 2,6 2,5 1,5 1,6",
+        'µ' => "0,-2 0,4; 0,1 1,0 3,0 4,1; 4,4 4,0",
+        'Ω' => "0,0 1.5,0 1.5,1 0,3 0,5 1,6 3,6 4,5 4,3 2.5,1 2.5,0 4,0",
+        '±' => "2,5 2,2; 0,3.5 4,3.5; 0,0.5 4,0.5",
+        _ => MISSING_GLYPH,
+    }
+}
+
+/// Strokes of a glyph in grid units (empty for whitespace)
+pub fn glyph(c: char) -> Vec<Vec<(f32, f32)>> {
+    glyph_strokes(c)
+        .split(';')
+        .map(|stroke| {
+            stroke.split_whitespace()
+                .filter_map(|pt| {
+                    let (x, y) = pt.split_once(',')?;
+                    Some((x.parse().ok()?, y.parse().ok()?))
+                })
+                .collect::<Vec<_>>()
+        })
+        .filter(|stroke| stroke.len() >= 2)
+        .collect()
+}
@@ -0,0 +1,121 @@
+//! Text layout with the built-in stroke font
+//!
+//! Turns Text features into stroke polylines that are batched with the layer's
+//! other polylines. IPC-2581 has no justification attribute: the BoundingBox is
+//! the text extent in the source CAD tool, so text is centred in that box and
+//! condensed horizontally if it would not fit.
+
+use super::stroke_font::{self, ADVANCE, CAP_HEIGHT, GLYPH_WIDTH, LINE_SPACING};
+use crate::draw::geometry::*;
+
+/// Default stroke width as a fraction of the cap height, for fonts without a LineDesc
+const DEFAULT_STROKE_RATIO: f32 = 0.12;
+
+/// Lay out a Text feature into board-space stroke polylines
+pub fn layout_text(text: &Text) -> Vec<Polyline> {
+    let lines: Vec<Vec<char>> = text.text.lines().map(|l| l.chars().collect()).collect();
+    let max_chars = lines.iter().map(|l| l.len()).max().unwrap_or(0);
+    if max_chars == 0 {
+        return Vec::new();
+    }
+
+    // Cap height: fontSize, but never taller than the bounding box
+    let cap_height = match text.bounding_box {
+        Some(bbox) if text.font_size > 0.0 => text.font_size.min(bbox[3] - bbox[1]),
+        Some(bbox) => bbox[3] - bbox[1],
+        None => text.font_size,
+    };
+    let scale_y = cap_height / CAP_HEIGHT;
+    let line_count = lines.len() as f32;
+    let natural_width = ((max_chars - 1) as f32 * ADVANCE + GLYPH_WIDTH) * scale_y;
+    let natural_height = ((line_count - 1.0) * LINE_SPACING + CAP_HEIGHT) * scale_y;
+
+    // Place the block in the bounding box, or with its first baseline at the origin
+    let (scale_x, origin_x, top_baseline_y) = match text.bounding_box {
+        Some([min_x, min_y, max_x, max_y]) => {
+            let width = natural_width.min(max_x - min_x);
+            let scale_x = scale_y * width / natural_width;
+            let origin_x = min_x + ((max_x - min_x) - width) / 2.0;
+            let bottom = min_y + ((max_y - min_y) - natural_height) / 2.0;
+            (scale_x, origin_x, bottom + (line_count - 1.0) * LINE_SPACING * scale_y)
+        }
+        None => (scale_y, 0.0, 0.0),
+    };
+
+    let width = text.stroke_width.unwrap_or(cap_height * DEFAULT_STROKE_RATIO);
+    let mut polylines = Vec::new();
+
+    for (line_idx, line) in lines.iter().enumerate() {
+        let baseline = top_baseline_y - line_idx as f32 * LINE_SPACING * scale_y;
+        for (char_idx, &c) in line.iter().enumerate() {
+            let cell_x = origin_x + char_idx as f32 * ADVANCE * scale_x;
+            for stroke in stroke_font::glyph(c) {
+                let mut polyline = Polyline {
+                    points: stroke.iter()
+                        .map(|&(x, y)| Point { x: cell_x + x * scale_x, y: baseline + y * scale_y })
+                        .collect(),
+                    width,
+                    color: text.color,
+                    line_end: LineEnd::Round,
+                    net_name: text.net_name.clone(),
+                    component_ref: text.component_ref.clone(),
+                    curved_path: None,
+                    polarity: text.polarity,
+                };
+                text.transform.apply_to_polyline(&mut polyline);
+                polylines.push(polyline);
+            }
+        }
+    }
+
+    polylines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(s: &str, bounding_box: Option<[f32; 4]>, transform: Transform2D) -> Text {
+        Text {
+            text: s.to_string(),
+            bounding_box,
+            font_size: 1.2,
+            stroke_width: None,
+            color: [1.0; 4],
+            transform,
+            net_name: None,
+            component_ref: Some("R1".to_string()),
+            polarity: Polarity::Positive,
+        }
+    }
+
+    fn bounds(polylines: &[Polyline]) -> [f32; 4] {
+        let mut b = [f32::MAX, f32::MAX, f32::MIN, f32::MIN];
+        for p in polylines.iter().flat_map(|l| l.points.iter()) {
+            b = [b[0].min(p.x), b[1].min(p.y), b[2].max(p.x), b[3].max(p.y)];
+        }
+        b
+    }
+
+    #[test]
+    fn test_text_centred_in_bounding_box() {
+        // "HH" at 1.2mm cap height is 1.9mm wide, centred in a 4 x 2mm box
+        let strokes = layout_text(&text("HH", Some([0.0, 0.0, 4.0, 2.0]), Transform2D::identity()));
+        assert!(!strokes.is_empty());
+        assert!((strokes[0].width - 0.144).abs() < 1e-5);
+        let b = bounds(&strokes);
+        assert!((b[1] - 0.4).abs() < 1e-4 && (b[3] - 1.6).abs() < 1e-4, "{:?}", b);
+        assert!(((b[0] + b[2]) / 2.0 - 2.0).abs() < 1e-4, "{:?}", b);
+    }
+
+    #[test]
+    fn test_text_condensed_and_transformed() {
+        // Too wide for a 1mm box: condensed to fit, then rotated 90 degrees and moved
+        let xform = Xform { x_offset: 10.0, rotation: 90.0, ..Xform::default() };
+        let strokes = layout_text(&text("HELLO", Some([0.0, 0.0, 1.0, 1.2]), xform.to_transform()));
+        let b = bounds(&strokes);
+        assert!((b[3] - b[1] - 1.0).abs() < 1e-4, "{:?}", b);
+        assert!((b[0] - 8.8).abs() < 1e-4 && (b[2] - 10.0).abs() < 1e-4, "{:?}", b);
+        assert!(strokes.iter().all(|s| s.component_ref.as_deref() == Some("R1")));
+    }
+}
@@ -6,6 +6,7 @@
 use std::collections::HashMap;
 use crate::draw::geometry::*;
 use crate::draw::tessellation::*;
+use super::instance_bounds;
 
 /// Via shape key for grouping vias by shape type and dimensions
 #[derive(Debug, Hash, Eq, PartialEq)]
@@ -13,6 +14,8 @@ enum ShapeKey {
     Circle { diameter_key: String, hole_key: String },
     Rectangle { width_key: String, height_key: String, hole_key: String },
     Oval { width_key: String, height_key: String, hole_key: String },
+    /// Remaining standard primitives, keyed by their full description
+    Other { shape_key: String, hole_key: String },
 }
 
 /// Generate instanced geometry for vias with shape and size-based LOD
@@ -26,9 +29,11 @@ pub fn generate_via_geometry(
         return Ok(Vec::new());
     }
     
-    // Group vias by shape type and size
-    let mut shape_groups: HashMap<ShapeKey, Vec<(usize, &ViaInstance)>> = HashMap::new();
-    for (i, via) in vias.iter().enumerate() {
+    // Group vias by shape type, size and rotation
+    // The instanced via shader has no per-instance rotation, so Xform rotation is baked into each group's shape
+    let mut shape_groups: HashMap<(ShapeKey, u32), Vec<(usize, &ViaInstance)>> = HashMap::new();
+    // Vias whose drill doesn't reach this layer keep their index but are not drawn or selectable
+    for (i, via) in vias.iter().enumerate().filter(|(_, via)| !via.outside_span) {
         let hole_key = format!("{:.4}", via.hole_diameter);
         let key = match &via.shape {
             StandardPrimitive::Circle { diameter } => {
@@ -76,8 +81,14 @@ pub fn generate_via_geometry(
                     hole_key,
                 }
             }
+            other => ShapeKey::Other {
+                shape_key: format!("{:?}", other),
+                hole_key,
+            },
         };
-        shape_groups.entry(key)
+        // Rotation does not change a circle, so don't split circle groups by it
+        let rotation_key = if matches!(via.shape, StandardPrimitive::Circle { .. }) { 0 } else { via.rotation.to_bits() };
+        shape_groups.entry((key, rotation_key))
             .or_default()
             .push((i, via));
     }
@@ -87,7 +98,7 @@ pub fn generate_via_geometry(
     let mut lod2_entries = Vec::new();
     let mut shape_index_counter: u32 = 0;
     
-    for (shape_key, instances) in shape_groups {
+    for ((shape_key, _), instances) in shape_groups {
         if let Some((_, first_via)) = instances.first() {
             let hole_radius = first_via.hole_diameter / 2.0;
             
@@ -98,6 +109,16 @@ pub fn generate_via_geometry(
             // Track the shape index for this group
             let current_shape_index = shape_index_counter;
             
+            // Tessellate geometry based on shape, rotated by the group's Xform rotation
+            let (mut with_hole_verts, with_hole_indices, mut without_hole_verts, without_hole_indices, max_dimension) = 
+                tessellate_via_shape(first_via, hole_radius);
+            let rotation = match first_via.shape {
+                StandardPrimitive::Circle { .. } => Transform2D::identity(),
+                _ => Xform { rotation: first_via.rotation, ..Xform::default() }.to_transform(),
+            };
+            rotation.apply_to_vertices(&mut with_hole_verts);
+            rotation.apply_to_vertices(&mut without_hole_verts);
+            
             // Create instance data (x, y) for this shape group
             let mut instance_data = Vec::new();
             for (local_idx, (original_idx, inst)) in instances.iter().enumerate() {
@@ -108,12 +129,8 @@ pub fn generate_via_geometry(
                 
                 let id = ((layer_index as u64) << 40) | ((2u64) << 36) | (*original_idx as u64);
                 
-                // Calculate bounds
-                let radius = first_via.diameter / 2.0; // Approx
-                let min_x = inst.x - radius;
-                let min_y = inst.y - radius;
-                let max_x = inst.x + radius;
-                let max_y = inst.y + radius;
+                // Calculate bounds from the (already rotated) solid shape
+                let bounds = instance_bounds(&without_hole_verts, inst.x, inst.y, 0.0);
                 
                 object_ranges.push(ObjectRange {
                     id,
@@ -122,7 +139,7 @@ pub fn generate_via_geometry(
                     vertex_ranges: Vec::new(),
                     instance_index: Some(local_idx as u32),
                     shape_index: Some(current_shape_index),
-                    bounds: [min_x, min_y, max_x, max_y],
+                    bounds,
                     net_name: inst.net_name.clone(),
                     component_ref: inst.component_ref.clone(),
                     pin_ref: inst.pin_ref.clone(),
@@ -135,10 +152,6 @@ pub fn generate_via_geometry(
             shape_index_counter += 1;
             let inst_count = instances.len();
             
-            // Tessellate geometry based on shape
-            let (with_hole_verts, with_hole_indices, without_hole_verts, without_hole_indices, max_dimension) = 
-                tessellate_via_shape(first_via, hole_radius);
-            
             let with_hole_vert_count = with_hole_verts.len() / 2;
             let with_hole_idx_count = with_hole_indices.len();
             let without_hole_vert_count = without_hole_verts.len() / 2;
@@ -245,12 +258,6 @@ fn tessellate_via_shape(
             let ring = tessellate_rectangular_ring(*width, *height, effective_hole_radius);
             (ring.0, ring.1, rect.0, rect.1, width.max(*height))
         }
-        StandardPrimitive::Oval { width, height } => {
-            // For ovals, use simplified approach: oval shape with circular hole
-            // TODO: Proper oval ring tessellation
-            let oval = tessellate_oval(*width, *height);
-            (oval.0.clone(), oval.1.clone(), oval.0, oval.1, width.max(*height))
-        }
         StandardPrimitive::RoundRect { width, height, corner_radius } => {
             let roundrect = tessellate_roundrect(*width, *height, *corner_radius);
             let min_dim = width.min(*height);
@@ -268,6 +275,31 @@ fn tessellate_via_shape(
             }
             (poly.0.clone(), poly.1.clone(), poly.0, poly.1, max_dim * 2.0)
         }
+        StandardPrimitive::Donut { .. }
+        | StandardPrimitive::Thermal { .. }
+        | StandardPrimitive::Butterfly { .. }
+        | StandardPrimitive::Moire { .. } => {
+            // These shapes already have their own openings; draw them as-is at every LOD
+            let shape = tessellate_primitive(&via.shape);
+            (shape.0.clone(), shape.1.clone(), shape.0, shape.1, via.shape.outer_diameter())
+        }
+        _ => {
+            // Single-outline shapes (ovals, ellipses, polygons): cut the drill as a circular hole
+            let solid = tessellate_primitive(&via.shape);
+            let outline = primitive_outline(&via.shape).unwrap_or_default();
+            let with_hole = if hole_radius > 0.0 && outline.len() >= 3 {
+                let hole: Vec<Point> = (0..32)
+                    .map(|i| {
+                        let angle = i as f32 / 32.0 * std::f32::consts::TAU;
+                        Point { x: angle.cos() * hole_radius, y: angle.sin() * hole_radius }
+                    })
+                    .collect();
+                tessellate_outline_with_holes(&outline, &[hole])
+            } else {
+                solid.clone()
+            };
+            (with_hole.0, with_hole.1, solid.0, solid.1, via.shape.outer_diameter())
+        }
     }
 }
 
@@ -310,3 +342,49 @@ fn create_empty_lod_entry() -> GeometryLOD {
         instance_count: Some(0),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Total triangle area of an indexed mesh
+    fn mesh_area(verts: &[f32], indices: &[u32]) -> f32 {
+        indices
+            .chunks_exact(3)
+            .map(|t| {
+                let p = |i: u32| (verts[i as usize * 2], verts[i as usize * 2 + 1]);
+                let ((x0, y0), (x1, y1), (x2, y2)) = (p(t[0]), p(t[1]), p(t[2]));
+                ((x1 - x0) * (y2 - y0) - (x2 - x0) * (y1 - y0)).abs() / 2.0
+            })
+            .sum()
+    }
+
+    fn via(shape: StandardPrimitive) -> ViaInstance {
+        ViaInstance {
+            x: 0.0,
+            y: 0.0,
+            rotation: 0.0,
+            diameter: shape.outer_diameter(),
+            hole_diameter: 0.4,
+            shape,
+            net_name: None,
+            component_ref: None,
+            pin_ref: None,
+            outside_span: false,
+        }
+    }
+
+    #[test]
+    fn test_oval_and_ellipse_vias_have_drill_hole() {
+        let hole_area = std::f32::consts::PI * 0.2 * 0.2;
+        for shape in [
+            StandardPrimitive::Oval { width: 1.2, height: 0.8 },
+            StandardPrimitive::Ellipse { width: 1.2, height: 0.8 },
+        ] {
+            let (ring_verts, ring_indices, solid_verts, solid_indices, _) = tessellate_via_shape(&via(shape.clone()), 0.2);
+            let ring = mesh_area(&ring_verts, &ring_indices);
+            let solid = mesh_area(&solid_verts, &solid_indices);
+            assert!((solid - ring - hole_area).abs() < 0.01, "{:?}: solid {} ring {}", shape, solid, ring);
+        }
+    }
+}
@@ -2,9 +2,10 @@
 //! 
 //! This module contains the serializable geometry structures used for
 //! transferring geometry data to the WebGPU renderer, including LOD support
-//! and base64 encoding for efficient JSON transmission.
+//! and base64 encoding for efficient JSON transmission. Binary formats (the
+//! on-disk Load cache) get the raw bytes instead of base64.
 
-use serde::{Serialize, Serializer};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use base64::{Engine as _, engine::general_purpose};
 use std::f32::consts::PI;
 
@@ -21,8 +22,7 @@ where
                     vec.len() * std::mem::size_of::<f32>(),
                 )
             };
-            let encoded = general_purpose::STANDARD.encode(bytes);
-            serializer.serialize_some(&encoded)
+            serializer.serialize_some(&EncodedBytes(bytes))
         }
         None => serializer.serialize_none(),
     }
@@ -39,8 +39,7 @@ where
             std::mem::size_of_val(data),
         )
     };
-    let encoded = general_purpose::STANDARD.encode(bytes);
-    serializer.serialize_str(&encoded)
+    EncodedBytes(bytes).serialize(serializer)
 }
 
 /// Serialize Vec<u32> as base64-encoded string for compact JSON transmission
@@ -56,13 +55,90 @@ where
                     vec.len() * std::mem::size_of::<u32>(),
                 )
             };
-            let encoded = general_purpose::STANDARD.encode(bytes);
-            serializer.serialize_some(&encoded)
+            serializer.serialize_some(&EncodedBytes(bytes))
         }
         None => serializer.serialize_none(),
     }
 }
 
+/// Buffer bytes: base64 for JSON, raw bytes for binary formats
+struct EncodedBytes<'a>(&'a [u8]);
+
+impl Serialize for EncodedBytes<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&general_purpose::STANDARD.encode(self.0))
+        } else {
+            serializer.serialize_bytes(self.0)
+        }
+    }
+}
+
+/// Bytes written by [`EncodedBytes`], from either representation
+struct DecodedBytes(Vec<u8>);
+
+impl<'de> Deserialize<'de> for DecodedBytes {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct BytesVisitor;
+
+        impl serde::de::Visitor<'_> for BytesVisitor {
+            type Value = DecodedBytes;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("a base64 string or a byte array")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<DecodedBytes, E> {
+                general_purpose::STANDARD.decode(value).map(DecodedBytes).map_err(E::custom)
+            }
+
+            fn visit_bytes<E: serde::de::Error>(self, value: &[u8]) -> Result<DecodedBytes, E> {
+                Ok(DecodedBytes(value.to_vec()))
+            }
+
+            fn visit_byte_buf<E: serde::de::Error>(self, value: Vec<u8>) -> Result<DecodedBytes, E> {
+                Ok(DecodedBytes(value))
+            }
+        }
+
+        deserializer.deserialize_any(BytesVisitor)
+    }
+}
+
+impl DecodedBytes {
+    fn into_f32s(self) -> Vec<f32> {
+        self.0.chunks_exact(4).map(|c| f32::from_ne_bytes([c[0], c[1], c[2], c[3]])).collect()
+    }
+
+    fn into_u32s(self) -> Vec<u32> {
+        self.0.chunks_exact(4).map(|c| u32::from_ne_bytes([c[0], c[1], c[2], c[3]])).collect()
+    }
+}
+
+/// Deserialize Vec<f32> written by `serialize_f32_vec_base64`
+pub fn deserialize_f32_vec_base64<'de, D>(deserializer: D) -> Result<Vec<f32>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(DecodedBytes::deserialize(deserializer)?.into_f32s())
+}
+
+/// Deserialize Option<Vec<f32>> written by `serialize_f32_vec_as_base64`
+pub fn deserialize_f32_vec_as_base64<'de, D>(deserializer: D) -> Result<Option<Vec<f32>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(Option::<DecodedBytes>::deserialize(deserializer)?.map(DecodedBytes::into_f32s))
+}
+
+/// Deserialize Option<Vec<u32>> written by `serialize_u32_vec_as_base64`
+pub fn deserialize_u32_vec_as_base64<'de, D>(deserializer: D) -> Result<Option<Vec<u32>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(Option::<DecodedBytes>::deserialize(deserializer)?.map(DecodedBytes::into_u32s))
+}
+
 /// Helper to pack rotation angle (radians) and visibility into a single f32
 /// Format: [16-bit quantized angle][15-bit unused][1-bit visibility]
 pub fn pack_rotation_visibility(angle: f32, visible: bool) -> f32 {
@@ -81,10 +157,10 @@ pub fn pack_rotation_visibility(angle: f32, visible: bool) -> f32 {
 }
 
 /// Serializable geometry LOD for JSON
-#[derive(Serialize, Clone)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct GeometryLOD {
     /// Base64-encoded Float32 vertex data (x, y, x, y, ...)
-    #[serde(rename = "vertexData", serialize_with = "serialize_f32_vec_base64")]
+    #[serde(rename = "vertexData", serialize_with = "serialize_f32_vec_base64", deserialize_with = "deserialize_f32_vec_base64")]
     pub vertex_data: Vec<f32>,
     
     /// Number of vertices (not bytes)
@@ -92,7 +168,8 @@ pub struct GeometryLOD {
     pub vertex_count: usize,
     
     /// Base64-encoded Uint32 indices
-    #[serde(rename = "indexData", skip_serializing_if = "Option::is_none", serialize_with = "serialize_u32_vec_as_base64")]
+    #[serde(rename = "indexData", default, skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_u32_vec_as_base64", deserialize_with = "deserialize_u32_vec_as_base64")]
     pub index_data: Option<Vec<u32>>,
     
     /// Optional number of indices
@@ -100,15 +177,18 @@ pub struct GeometryLOD {
     pub index_count: Option<usize>,
     
     /// Base64-encoded per-vertex alpha values (1 float per vertex)
-    #[serde(rename = "alphaData", skip_serializing_if = "Option::is_none", serialize_with = "serialize_f32_vec_as_base64")]
+    #[serde(rename = "alphaData", default, skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_f32_vec_as_base64", deserialize_with = "deserialize_f32_vec_as_base64")]
     pub alpha_data: Option<Vec<f32>>,
 
     /// Base64-encoded per-vertex visibility values (1 float per vertex) - for batched geometry
-    #[serde(rename = "visibilityData", skip_serializing_if = "Option::is_none", serialize_with = "serialize_f32_vec_as_base64")]
+    #[serde(rename = "visibilityData", default, skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_f32_vec_as_base64", deserialize_with = "deserialize_f32_vec_as_base64")]
     pub visibility_data: Option<Vec<f32>>,
     
     /// Base64-encoded instance data for instanced rendering (x, y, rotation for instanced_rot; x, y for instanced)
-    #[serde(rename = "instanceData", skip_serializing_if = "Option::is_none", serialize_with = "serialize_f32_vec_as_base64")]
+    #[serde(rename = "instanceData", default, skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_f32_vec_as_base64", deserialize_with = "deserialize_f32_vec_as_base64")]
     pub instance_data: Option<Vec<f32>>,
     
     /// Optional number of instances
@@ -124,7 +204,7 @@ pub struct CullingStats {
 }
 
 /// Shader geometry organized by type
-#[derive(Serialize, Default, Clone)]
+#[derive(Serialize, Deserialize, Default, Clone)]
 pub struct ShaderGeometry {
     /// For batch.wgsl - polylines without alpha (opaque, alpha=1.0 implicit)
     pub batch: Option<Vec<GeometryLOD>>,
@@ -143,7 +223,7 @@ pub struct ShaderGeometry {
 }
 
 /// Complete layer JSON structure matching main.ts
-#[derive(Serialize, Clone)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct LayerJSON {
     #[serde(rename = "layerId")]
     pub layer_id: String,
@@ -185,5 +265,16 @@ mod tests {
         let json = serde_json::to_string(&lod).unwrap();
         assert!(json.contains("vertexData"));
         assert!(json.contains("vertexCount"));
+
+        // Both the base64 (JSON) and raw byte (MessagePack) forms read back
+        let from_json: GeometryLOD = serde_json::from_str(&json).unwrap();
+        let packed = rmp_serde::to_vec_named(&lod).unwrap();
+        let from_packed: GeometryLOD = rmp_serde::from_slice(&packed).unwrap();
+        for decoded in [from_json, from_packed] {
+            assert_eq!(decoded.vertex_data, data);
+            assert_eq!(decoded.index_data, Some(vec![0, 1, 2]));
+            assert!(decoded.alpha_data.is_none());
+        }
+        assert!(packed.len() < json.len());
     }
 }
@@ -4,28 +4,39 @@
 //! and serializing PCB geometry data including polylines, polygons, pads, and vias.
 //!
 //! # Submodules
-//! - `types` - Core geometric primitives (Point, Polyline, Polygon, etc.)
+//! - `types` - Core geometric primitives (Point, Polyline, Polygon, Text, etc.)
 //! - `spatial` - Spatial indexing for efficient object selection
 //! - `lod` - Level of Detail geometry for GPU rendering
 //! - `binary` - Binary serialization for zero-copy transfer
+//! - `xform` - IPC-2581 Xform and 2D affine transforms
 
 mod types;
 mod spatial;
 mod lod;
 mod binary;
+mod xform;
 
 // Re-export all public types for backward compatibility
 pub use types::{
     Point,
     LineEnd,
+    Polarity,
     LineDescriptor,
+    FillProperty,
+    FillDescriptor,
+    PathStep,
+    CurvedPath,
     Polyline,
     Polygon,
+    Text,
     PadStackHole,
     StandardPrimitive,
+    RingShape,
     PadInstance,
     PadStackDef,
     ViaInstance,
+    FeatureRef,
+    NegativeFeature,
     LayerGeometries,
 };
 
@@ -46,6 +57,11 @@ pub use lod::{
     LayerJSON,
 };
 
+pub use xform::{
+    Xform,
+    Transform2D,
+};
+
 pub use binary::{
     LayerBinary,
     serialize_geometry_binary,
@@ -3,10 +3,11 @@
 //! This module contains the fundamental geometric primitives used throughout
 //! the application: points, polylines, polygons, pads, and vias.
 
-use serde::Serialize;
+use super::xform::Transform2D;
+use serde::{Deserialize, Serialize};
 
 /// A 2D point
-#[derive(Debug, Clone, Copy, Serialize)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct Point {
     pub x: f32,
     pub y: f32,
@@ -21,6 +22,33 @@ pub enum LineEnd {
     Butt,
 }
 
+/// Feature or layer polarity: POSITIVE draws copper, NEGATIVE erases it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+pub enum Polarity {
+    #[default]
+    Positive,
+    Negative,
+}
+
+impl Polarity {
+    /// Parse a `polarity` attribute value (POSITIVE | NEGATIVE)
+    pub fn parse(value: &str) -> Option<Polarity> {
+        match value {
+            "POSITIVE" => Some(Polarity::Positive),
+            "NEGATIVE" => Some(Polarity::Negative),
+            _ => None,
+        }
+    }
+
+    /// The opposite polarity (features on a NEGATIVE layer erase the plane)
+    pub fn flipped(self) -> Polarity {
+        match self {
+            Polarity::Positive => Polarity::Negative,
+            Polarity::Negative => Polarity::Positive,
+        }
+    }
+}
+
 /// Line descriptor from DictionaryLineDesc
 #[derive(Debug, Clone)]
 pub struct LineDescriptor {
@@ -28,6 +56,55 @@ pub struct LineDescriptor {
     pub line_end: LineEnd,
 }
 
+/// Fill texture of a closed shape (FillDesc fillProperty)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FillProperty {
+    #[default]
+    Fill,
+    Hollow,
+    Hatch,
+    Mesh,
+    Void,  // Reserved for cutouts: the area is cleared
+}
+
+/// Fill descriptor from FillDesc or DictionaryFillDesc
+/// Unset values fall back to the spec defaults when the fill is generated.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct FillDescriptor {
+    pub fill_property: FillProperty,
+    pub line_width: Option<f32>,  // HATCH and MESH line width
+    pub pitch1: Option<f32>,      // Default 4 x line width
+    pub pitch2: Option<f32>,      // MESH only
+    pub angle1: Option<f32>,      // degrees, default 45
+    pub angle2: Option<f32>,      // degrees, MESH only, default 135
+}
+
+/// A single step along an IPC-2581 path, starting from the previous point
+#[derive(Debug, Clone, Copy)]
+pub enum PathStep {
+    /// Straight segment to the given point (PolyStepSegment)
+    Segment(Point),
+    /// Circular arc to `end` around `center` (PolyStepCurve, Arc)
+    Curve { end: Point, center: Point, clockwise: bool },
+}
+
+impl PathStep {
+    /// Point this step ends at
+    pub fn end_point(&self) -> Point {
+        match *self {
+            PathStep::Segment(end) => end,
+            PathStep::Curve { end, .. } => end,
+        }
+    }
+}
+
+/// A path that contains true arcs (PolyBegin followed by steps)
+#[derive(Debug, Clone)]
+pub struct CurvedPath {
+    pub start: Point,
+    pub steps: Vec<PathStep>,
+}
+
 /// Represents a single polyline
 #[derive(Debug, Clone)]
 pub struct Polyline {
@@ -37,6 +114,9 @@ pub struct Polyline {
     pub line_end: LineEnd,
     pub net_name: Option<String>,
     pub component_ref: Option<String>,
+    /// Source path when the polyline contains arcs; `points` holds its LOD0 flattening
+    pub curved_path: Option<CurvedPath>,
+    pub polarity: Polarity,  // Effect on the layer image, after the layer polarity is applied
 }
 
 /// Represents a filled polygon (with optional holes)
@@ -44,9 +124,29 @@ pub struct Polyline {
 pub struct Polygon {
     pub outer_ring: Vec<Point>,
     pub holes: Vec<Vec<Point>>,
+    /// Source paths of the outer ring then the holes when any contains arcs; the rings hold their LOD0 flattening
+    pub curved_rings: Option<Vec<CurvedPath>>,
     pub fill_color: [f32; 4],  // Supports alpha for transparency
     pub net_name: Option<String>,
     pub component_ref: Option<String>,
+    pub polarity: Polarity,
+    pub fill: FillDescriptor,
+    pub line_width: Option<f32>,  // Boundary width from LineDesc, drawn for non-solid fills
+}
+
+/// A Text feature, drawn with the built-in stroke font
+#[derive(Debug, Clone)]
+pub struct Text {
+    pub text: String,
+    /// Text extent before `transform` ([min_x, min_y, max_x, max_y] of BoundingBox), if non-empty
+    pub bounding_box: Option<[f32; 4]>,
+    pub font_size: f32,
+    pub stroke_width: Option<f32>,  // From the referenced font's LineDesc, if any
+    pub color: [f32; 4],
+    pub transform: Transform2D,     // Text Xform, then the enclosing Features placement
+    pub net_name: Option<String>,
+    pub component_ref: Option<String>,
+    pub polarity: Polarity,
 }
 
 /// Represents a pad stack hole with optional annular ring
@@ -59,22 +159,149 @@ pub struct PadStackHole {
 }
 
 /// Standard primitive shape definition
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum StandardPrimitive {
     Circle { diameter: f32 },
     Rectangle { width: f32, height: f32 },
     Oval { width: f32, height: f32 },
     RoundRect { width: f32, height: f32, corner_radius: f32 },
     CustomPolygon { points: Vec<Point> },
+    Diamond { width: f32, height: f32 },
+    Ellipse { width: f32, height: f32 },
+    Hexagon { length: f32 },   // point-to-point, vertices on the X axis
+    Octagon { length: f32 },   // flat-to-flat
+    Triangle { base: f32, height: f32 },  // isosceles, apex up
+    /// Rectangle with 45° chamfers; corners ordered [upper_right, upper_left, lower_left, lower_right]
+    RectCham { width: f32, height: f32, chamfer: f32, corners: [bool; 4] },
+    /// Rectangle given by its corners (not centred on the origin)
+    RectCorner { lower_left: Point, upper_right: Point },
+    Donut { shape: RingShape, outer_diameter: f32, inner_diameter: f32 },
+    /// Ring broken by `spoke_count` gaps of `spoke_width` (thermal relief)
+    Thermal {
+        shape: RingShape,
+        outer_diameter: f32,
+        inner_diameter: f32,
+        spoke_count: u32,
+        spoke_width: f32,
+        spoke_start_angle: f32,  // degrees
+    },
+    /// First and third quadrants of a circle (size = diameter) or square (size = side)
+    Butterfly { shape: RingShape, size: f32 },
+    /// Concentric rings with a crosshair
+    Moire {
+        diameter: f32,
+        ring_width: f32,
+        ring_gap: f32,
+        ring_number: u32,
+        line_width: f32,
+        line_length: f32,
+        line_angle: f32,  // degrees
+    },
+}
+
+/// Outline of Donut, Thermal and Butterfly primitives
+/// Sizes are flat-to-flat for SQUARE and OCTAGON and point-to-point for HEXAGON
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum RingShape {
+    Round,
+    Square,
+    Hexagon,
+    Octagon,
+}
+
+impl StandardPrimitive {
+    /// Copy of this primitive with every dimension multiplied by `factor`
+    pub fn scaled(&self, factor: f32) -> StandardPrimitive {
+        let scale_point = |p: &Point| Point { x: p.x * factor, y: p.y * factor };
+        match self {
+            StandardPrimitive::Circle { diameter } => StandardPrimitive::Circle { diameter: diameter * factor },
+            StandardPrimitive::Rectangle { width, height } => StandardPrimitive::Rectangle { width: width * factor, height: height * factor },
+            StandardPrimitive::Oval { width, height } => StandardPrimitive::Oval { width: width * factor, height: height * factor },
+            StandardPrimitive::RoundRect { width, height, corner_radius } => StandardPrimitive::RoundRect {
+                width: width * factor,
+                height: height * factor,
+                corner_radius: corner_radius * factor,
+            },
+            StandardPrimitive::CustomPolygon { points } => StandardPrimitive::CustomPolygon {
+                points: points.iter().map(scale_point).collect(),
+            },
+            StandardPrimitive::Diamond { width, height } => StandardPrimitive::Diamond { width: width * factor, height: height * factor },
+            StandardPrimitive::Ellipse { width, height } => StandardPrimitive::Ellipse { width: width * factor, height: height * factor },
+            StandardPrimitive::Hexagon { length } => StandardPrimitive::Hexagon { length: length * factor },
+            StandardPrimitive::Octagon { length } => StandardPrimitive::Octagon { length: length * factor },
+            StandardPrimitive::Triangle { base, height } => StandardPrimitive::Triangle { base: base * factor, height: height * factor },
+            StandardPrimitive::RectCham { width, height, chamfer, corners } => StandardPrimitive::RectCham {
+                width: width * factor,
+                height: height * factor,
+                chamfer: chamfer * factor,
+                corners: *corners,
+            },
+            StandardPrimitive::RectCorner { lower_left, upper_right } => StandardPrimitive::RectCorner {
+                lower_left: scale_point(lower_left),
+                upper_right: scale_point(upper_right),
+            },
+            StandardPrimitive::Donut { shape, outer_diameter, inner_diameter } => StandardPrimitive::Donut {
+                shape: *shape,
+                outer_diameter: outer_diameter * factor,
+                inner_diameter: inner_diameter * factor,
+            },
+            StandardPrimitive::Thermal { shape, outer_diameter, inner_diameter, spoke_count, spoke_width, spoke_start_angle } => StandardPrimitive::Thermal {
+                shape: *shape,
+                outer_diameter: outer_diameter * factor,
+                inner_diameter: inner_diameter * factor,
+                spoke_count: *spoke_count,
+                spoke_width: spoke_width * factor,
+                spoke_start_angle: *spoke_start_angle,
+            },
+            StandardPrimitive::Butterfly { shape, size } => StandardPrimitive::Butterfly { shape: *shape, size: size * factor },
+            StandardPrimitive::Moire { diameter, ring_width, ring_gap, ring_number, line_width, line_length, line_angle } => StandardPrimitive::Moire {
+                diameter: diameter * factor,
+                ring_width: ring_width * factor,
+                ring_gap: ring_gap * factor,
+                ring_number: *ring_number,
+                line_width: line_width * factor,
+                line_length: line_length * factor,
+                line_angle: *line_angle,
+            },
+        }
+    }
+
+    /// Largest extent of the shape, used as the "outer diameter" of pads and vias
+    /// Shapes not centred on the origin report twice their furthest coordinate
+    pub fn outer_diameter(&self) -> f32 {
+        let max_abs = |points: &[Point]| points.iter().fold(0.0f32, |m, p| m.max(p.x.abs()).max(p.y.abs())) * 2.0;
+        match self {
+            StandardPrimitive::Circle { diameter } => *diameter,
+            StandardPrimitive::Rectangle { width, height }
+            | StandardPrimitive::Oval { width, height }
+            | StandardPrimitive::RoundRect { width, height, .. }
+            | StandardPrimitive::Diamond { width, height }
+            | StandardPrimitive::Ellipse { width, height }
+            | StandardPrimitive::RectCham { width, height, .. } => width.max(*height),
+            StandardPrimitive::Triangle { base, height } => base.max(*height),
+            StandardPrimitive::Hexagon { length } => *length,
+            StandardPrimitive::Octagon { length } => *length,
+            StandardPrimitive::CustomPolygon { points } => max_abs(points),
+            StandardPrimitive::RectCorner { lower_left, upper_right } => max_abs(&[*lower_left, *upper_right]),
+            StandardPrimitive::Donut { outer_diameter, .. }
+            | StandardPrimitive::Thermal { outer_diameter, .. } => *outer_diameter,
+            StandardPrimitive::Butterfly { size, .. } => *size,
+            StandardPrimitive::Moire { diameter, line_length, .. } => diameter.max(*line_length),
+        }
+    }
 }
 
 /// Pad instance with shape reference, position, and rotation
+/// Position already includes the Xform offset; mirror and scale are baked into the shape at generation
 #[derive(Debug, Clone, Serialize)]
 pub struct PadInstance {
     pub shape_id: String,
     pub x: f32,
     pub y: f32,
     pub rotation: f32,  // degrees
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub mirror: bool,   // Xform mirror about the pad's Y axis
+    pub scale: f32,     // Xform scale (1.0 if absent)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub net_name: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -84,7 +311,7 @@ pub struct PadInstance {
 }
 
 /// Padstack definition (for vias and component pads)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PadStackDef {
     pub hole_diameter: f32,
     pub outer_diameter: f32,  // From pad definition circle (deprecated for non-circles)
@@ -96,6 +323,7 @@ pub struct PadStackDef {
 pub struct ViaInstance {
     pub x: f32,
     pub y: f32,
+    pub rotation: f32,  // degrees
     pub diameter: f32,  // For circles, or max dimension for other shapes
     pub hole_diameter: f32,
     pub shape: StandardPrimitive,
@@ -105,10 +333,29 @@ pub struct ViaInstance {
     pub component_ref: Option<String>,  // Component reference for PTH pads
     #[serde(skip_serializing_if = "Option::is_none")]
     pub pin_ref: Option<String>,        // Pin reference for PTH component pads (e.g., "PIN:1")
+    #[serde(skip)]
+    pub outside_span: bool,             // Drilled span doesn't reach this copper layer: not drawn
+}
+
+/// A feature in one of the per-type lists of `LayerGeometries`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeatureRef {
+    Polyline(usize),
+    Polygon(usize),
+    Text(usize),
+}
+
+/// A negative feature and how many positive features preceded it in document order
+/// A negative feature only erases what was drawn before it, so later positives stay intact.
+#[derive(Debug, Clone, Copy)]
+pub struct NegativeFeature {
+    pub feature: FeatureRef,
+    pub polylines_before: usize,
+    pub polygons_before: usize,
 }
 
 /// Represents all geometries organized by layer
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct LayerGeometries {
     pub layer_ref: String,
     pub polylines: Vec<Polyline>,
@@ -116,4 +363,28 @@ pub struct LayerGeometries {
     pub padstack_holes: Vec<PadStackHole>,
     pub pads: Vec<PadInstance>,
     pub vias: Vec<ViaInstance>,
+    pub texts: Vec<Text>,
+    pub negatives: Vec<NegativeFeature>,  // In document order; emptied once polarity is resolved
+}
+
+impl LayerGeometries {
+    /// Empty geometry lists for `layer_ref`
+    pub fn new(layer_ref: &str) -> Self {
+        LayerGeometries {
+            layer_ref: layer_ref.to_string(),
+            polylines: Vec::new(),
+            polygons: Vec::new(),
+            padstack_holes: Vec::new(),
+            pads: Vec::new(),
+            vias: Vec::new(),
+            texts: Vec::new(),
+            negatives: Vec::new(),
+        }
+    }
+
+    /// True when the layer has nothing to draw
+    pub fn is_empty(&self) -> bool {
+        self.polylines.is_empty() && self.polygons.is_empty() && self.padstack_holes.is_empty()
+            && self.pads.is_empty() && self.vias.is_empty() && self.texts.is_empty()
+    }
 }
@@ -0,0 +1,293 @@
+//! IPC-2581 Xform and 2D affine transforms
+//!
+//! An `Xform` (spec section 3.3) is applied to local geometry in this order:
+//! mirror about the Y axis, uniform scale, counter-clockwise rotation, then
+//! translation by xOffset/yOffset. `Transform2D` is the equivalent affine
+//! matrix, used to compose nested Features transforms with their Location.
+
+use super::types::{CurvedPath, Point, PathStep, Polyline, Polygon, PadInstance, ViaInstance};
+use serde::Serialize;
+
+/// Parsed IPC-2581 Xform element
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Xform {
+    pub x_offset: f32,
+    pub y_offset: f32,
+    pub rotation: f32,  // degrees, counter-clockwise
+    pub mirror: bool,   // mirror about the Y axis (x -> -x)
+    pub scale: f32,
+}
+
+impl Default for Xform {
+    fn default() -> Self {
+        Self {
+            x_offset: 0.0,
+            y_offset: 0.0,
+            rotation: 0.0,
+            mirror: false,
+            scale: 1.0,
+        }
+    }
+}
+
+impl Xform {
+    /// True if applying this Xform leaves geometry unchanged
+    pub fn is_identity(&self) -> bool {
+        *self == Xform::default()
+    }
+
+    /// Affine matrix equivalent of this Xform
+    pub fn to_transform(&self) -> Transform2D {
+        let (sin, cos) = self.rotation.to_radians().sin_cos();
+        let sx = if self.mirror { -self.scale } else { self.scale };
+        let sy = self.scale;
+        Transform2D {
+            a: cos * sx,
+            b: sin * sx,
+            c: -sin * sy,
+            d: cos * sy,
+            tx: self.x_offset,
+            ty: self.y_offset,
+        }
+    }
+}
+
+/// 2D affine transform: x' = a*x + c*y + tx, y' = b*x + d*y + ty
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform2D {
+    pub a: f32,
+    pub b: f32,
+    pub c: f32,
+    pub d: f32,
+    pub tx: f32,
+    pub ty: f32,
+}
+
+impl Default for Transform2D {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl Transform2D {
+    pub const fn identity() -> Self {
+        Self { a: 1.0, b: 0.0, c: 0.0, d: 1.0, tx: 0.0, ty: 0.0 }
+    }
+
+    pub const fn translation(x: f32, y: f32) -> Self {
+        Self { a: 1.0, b: 0.0, c: 0.0, d: 1.0, tx: x, ty: y }
+    }
+
+    pub fn is_identity(&self) -> bool {
+        *self == Self::identity()
+    }
+
+    /// Compose transforms: the result applies `inner` first, then `self`
+    pub fn then_apply(&self, inner: &Transform2D) -> Transform2D {
+        Transform2D {
+            a: self.a * inner.a + self.c * inner.b,
+            b: self.b * inner.a + self.d * inner.b,
+            c: self.a * inner.c + self.c * inner.d,
+            d: self.b * inner.c + self.d * inner.d,
+            tx: self.a * inner.tx + self.c * inner.ty + self.tx,
+            ty: self.b * inner.tx + self.d * inner.ty + self.ty,
+        }
+    }
+
+    pub fn apply(&self, p: Point) -> Point {
+        Point {
+            x: self.a * p.x + self.c * p.y + self.tx,
+            y: self.b * p.x + self.d * p.y + self.ty,
+        }
+    }
+
+    /// Apply only the linear part (no translation), e.g. to a displacement
+    pub fn apply_vector(&self, dx: f32, dy: f32) -> (f32, f32) {
+        (self.a * dx + self.c * dy, self.b * dx + self.d * dy)
+    }
+
+    /// Inverse transform, or None if the transform is degenerate (scale 0)
+    pub fn inverse(&self) -> Option<Transform2D> {
+        let det = self.a * self.d - self.b * self.c;
+        if det.abs() < 1e-12 {
+            return None;
+        }
+        let (a, b, c, d) = (self.d / det, -self.b / det, -self.c / det, self.a / det);
+        Some(Transform2D {
+            a,
+            b,
+            c,
+            d,
+            tx: -(a * self.tx + c * self.ty),
+            ty: -(b * self.tx + d * self.ty),
+        })
+    }
+
+    /// True if the transform flips orientation (odd number of mirrors)
+    pub fn is_mirrored(&self) -> bool {
+        self.a * self.d - self.b * self.c < 0.0
+    }
+
+    /// Length scale factor (Xform scale is uniform, so this is exact for IPC-2581 transforms)
+    pub fn length_scale(&self) -> f32 {
+        (self.a * self.d - self.b * self.c).abs().sqrt()
+    }
+
+    /// Transform a polyline in place, including its width and any true arcs
+    pub fn apply_to_polyline(&self, polyline: &mut Polyline) {
+        if self.is_identity() {
+            return;
+        }
+        for p in &mut polyline.points {
+            *p = self.apply(*p);
+        }
+        polyline.width *= self.length_scale();
+
+        if let Some(path) = &mut polyline.curved_path {
+            self.apply_to_path(path);
+        }
+    }
+
+    /// Transform a polygon (outer ring, holes and any true arcs) in place
+    pub fn apply_to_polygon(&self, polygon: &mut Polygon) {
+        if self.is_identity() {
+            return;
+        }
+        for p in polygon.outer_ring.iter_mut().chain(polygon.holes.iter_mut().flatten()) {
+            *p = self.apply(*p);
+        }
+        for path in polygon.curved_rings.iter_mut().flatten() {
+            self.apply_to_path(path);
+        }
+    }
+
+    fn apply_to_path(&self, path: &mut CurvedPath) {
+        let mirrored = self.is_mirrored();
+        path.start = self.apply(path.start);
+        for step in &mut path.steps {
+            *step = match *step {
+                PathStep::Segment(end) => PathStep::Segment(self.apply(end)),
+                PathStep::Curve { end, center, clockwise } => PathStep::Curve {
+                    end: self.apply(end),
+                    center: self.apply(center),
+                    // Mirroring reverses the winding direction of arcs
+                    clockwise: clockwise != mirrored,
+                },
+            };
+        }
+    }
+
+    /// Counter-clockwise rotation in degrees (applied after any mirror)
+    pub fn rotation_degrees(&self) -> f32 {
+        (-self.c).atan2(self.d).to_degrees()
+    }
+
+    /// Rotation of a placed instance whose own rotation is `rotation` degrees
+    /// A mirrored transform reverses the direction of the instance rotation.
+    fn placed_rotation(&self, rotation: f32) -> f32 {
+        let placed = if self.is_mirrored() { self.rotation_degrees() - rotation } else { self.rotation_degrees() + rotation };
+        placed.rem_euclid(360.0)
+    }
+
+    /// Transform a pad instance in place (position, rotation, mirror and scale)
+    pub fn apply_to_pad(&self, pad: &mut PadInstance) {
+        if self.is_identity() {
+            return;
+        }
+        let p = self.apply(Point { x: pad.x, y: pad.y });
+        pad.x = p.x;
+        pad.y = p.y;
+        pad.rotation = self.placed_rotation(pad.rotation);
+        pad.mirror ^= self.is_mirrored();
+        pad.scale *= self.length_scale();
+    }
+
+    /// Transform a via instance in place; via shapes are symmetric about their Y axis, so mirror only affects rotation
+    pub fn apply_to_via(&self, via: &mut ViaInstance) {
+        if self.is_identity() {
+            return;
+        }
+        let p = self.apply(Point { x: via.x, y: via.y });
+        via.x = p.x;
+        via.y = p.y;
+        via.rotation = self.placed_rotation(via.rotation);
+        let scale = self.length_scale();
+        if (scale - 1.0).abs() > f32::EPSILON {
+            via.diameter *= scale;
+            via.hole_diameter *= scale;
+            via.shape = via.shape.scaled(scale);
+        }
+    }
+
+    /// Transform interleaved [x, y, x, y, ...] vertex data in place
+    pub fn apply_to_vertices(&self, verts: &mut [f32]) {
+        if self.is_identity() {
+            return;
+        }
+        for v in verts.chunks_exact_mut(2) {
+            let p = self.apply(Point { x: v[0], y: v[1] });
+            v[0] = p.x;
+            v[1] = p.y;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(p: Point, x: f32, y: f32) {
+        assert!((p.x - x).abs() < 1e-5 && (p.y - y).abs() < 1e-5, "got ({}, {}), expected ({}, {})", p.x, p.y, x, y);
+    }
+
+    #[test]
+    fn test_xform_order_mirror_scale_rotate_offset() {
+        let xform = Xform { x_offset: 10.0, y_offset: 5.0, rotation: 90.0, mirror: true, scale: 2.0 };
+        let t = xform.to_transform();
+
+        // (1, 0) -> mirror (-1, 0) -> scale (-2, 0) -> rotate 90 (0, -2) -> offset (10, 3)
+        assert_close(t.apply(Point { x: 1.0, y: 0.0 }), 10.0, 3.0);
+        // (0, 1) -> mirror (0, 1) -> scale (0, 2) -> rotate 90 (-2, 0) -> offset (8, 5)
+        assert_close(t.apply(Point { x: 0.0, y: 1.0 }), 8.0, 5.0);
+
+        assert!(t.is_mirrored());
+        assert!((t.length_scale() - 2.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_compose_with_location() {
+        let local = Xform { rotation: 180.0, ..Xform::default() }.to_transform();
+        let placed = Transform2D::translation(3.0, 4.0).then_apply(&local);
+        assert_close(placed.apply(Point { x: 1.0, y: 1.0 }), 2.0, 3.0);
+        let inverse = placed.inverse().unwrap();
+        assert_close(inverse.apply(Point { x: 2.0, y: 3.0 }), 1.0, 1.0);
+        assert!(Xform::default().is_identity());
+        assert!(Xform::default().to_transform().is_identity());
+    }
+
+    #[test]
+    fn test_placed_pad_rotation_and_mirror() {
+        let mut pad = PadInstance {
+            shape_id: "R1".to_string(),
+            x: 1.0,
+            y: 0.0,
+            rotation: 30.0,
+            mirror: false,
+            scale: 1.0,
+            net_name: None,
+            component_ref: None,
+            pin_ref: None,
+        };
+        let turned = Xform { rotation: 90.0, ..Xform::default() }.to_transform();
+        assert!((turned.rotation_degrees() - 90.0).abs() < 1e-4);
+        turned.apply_to_pad(&mut pad);
+        assert_close(Point { x: pad.x, y: pad.y }, 0.0, 1.0);
+        assert!((pad.rotation - 120.0).abs() < 1e-4 && !pad.mirror);
+
+        // Mirroring reverses the pad's own rotation
+        let mirrored = Xform { mirror: true, ..Xform::default() }.to_transform();
+        mirrored.apply_to_pad(&mut pad);
+        assert!((pad.rotation - 240.0).abs() < 1e-4 && pad.mirror);
+    }
+
+}
@@ -3,3 +3,4 @@ pub mod tessellation;
 pub mod generation;
 pub mod parsing;
 pub mod drc;
+pub mod progress;
@@ -0,0 +1,219 @@
+//! BOM and AVL parsing
+//!
+//! Reads `Bom/BomItem` (part number, quantity, refdes list, characteristics)
+//! and joins each item with its `Avl/AvlItem` approved manufacturer parts.
+//! Vendor names are resolved through `Content/Enterprise`.
+
+use crate::parse_xml::XmlNode;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A reference designator listed on a BOM item
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BomRefDes {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub package_ref: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub layer_ref: Option<String>,
+    pub populate: bool,
+    pub placed: bool,  // A Component with this refdes exists in the design
+}
+
+/// A Textual or Measured characteristic of a BOM item
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BomCharacteristic {
+    pub name: String,
+    pub value: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>,
+}
+
+/// An approved manufacturer part from the AVL
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovedVendor {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vendor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mpn: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rank: Option<u32>,
+    pub qualified: bool,
+    pub chosen: bool,
+}
+
+/// One BomItem joined with its AVL entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BomItem {
+    pub part_number: String,  // OEMDesignNumberRef
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub internal_part_number: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    pub quantity: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pin_count: Option<u32>,
+    pub refdes: Vec<BomRefDes>,
+    pub characteristics: Vec<BomCharacteristic>,
+    pub approved_vendors: Vec<ApprovedVendor>,
+}
+
+/// Parse the first Bom and all Avl sections
+/// `placed_refdes` marks which BOM refdes have a placed Component.
+pub fn parse_bom<F: Fn(&str) -> bool>(root: &XmlNode, placed_refdes: F) -> Vec<BomItem> {
+    let Some(bom) = root.children.iter().find(|n| n.name == "Bom") else {
+        return Vec::new();
+    };
+    let avl = parse_avl(root);
+
+    bom.children.iter()
+        .filter(|n| n.name == "BomItem")
+        .map(|item| {
+            let part_number = item.attributes.get("OEMDesignNumberRef").cloned().unwrap_or_default();
+            let refdes: Vec<BomRefDes> = item.children.iter()
+                .filter(|c| c.name == "RefDes")
+                .filter_map(|c| {
+                    let name = c.attributes.get("name")?.clone();
+                    Some(BomRefDes {
+                        package_ref: c.attributes.get("packageRef").cloned(),
+                        layer_ref: c.attributes.get("layerRef").cloned(),
+                        populate: c.attributes.get("populate").is_none_or(|v| !v.eq_ignore_ascii_case("false")),
+                        placed: placed_refdes(&name),
+                        name,
+                    })
+                })
+                .collect();
+
+            BomItem {
+                internal_part_number: item.attributes.get("internalPartNumber").cloned(),
+                description: item.attributes.get("description").cloned(),
+                category: item.attributes.get("category").cloned(),
+                quantity: item.attributes.get("quantity")
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(refdes.len() as u32),
+                pin_count: item.attributes.get("pinCount").and_then(|v| v.parse().ok()),
+                characteristics: item.children.iter()
+                    .filter(|c| c.name == "Characteristics")
+                    .flat_map(|c| c.children.iter().filter_map(parse_characteristic))
+                    .collect(),
+                approved_vendors: avl.get(&part_number).cloned().unwrap_or_default(),
+                refdes,
+                part_number,
+            }
+        })
+        .collect()
+}
+
+fn parse_characteristic(node: &XmlNode) -> Option<BomCharacteristic> {
+    let attr = |name: &str| node.attributes.get(name).cloned();
+    match node.name.as_str() {
+        "Textual" => Some(BomCharacteristic {
+            name: attr("textualCharacteristicName")?,
+            value: attr("textualCharacteristicValue").unwrap_or_default(),
+            unit: None,
+        }),
+        "Measured" => Some(BomCharacteristic {
+            name: attr("measuredCharacteristicName")?,
+            value: attr("measuredCharacteristicValue").unwrap_or_default(),
+            unit: attr("engineeringUnitOfMeasure"),
+        }),
+        _ => None,
+    }
+}
+
+/// Approved vendor parts by OEMDesignNumber
+fn parse_avl(root: &XmlNode) -> IndexMap<String, Vec<ApprovedVendor>> {
+    let enterprises = parse_enterprises(root);
+    let mut avl: IndexMap<String, Vec<ApprovedVendor>> = IndexMap::new();
+
+    for item in root.children.iter()
+        .filter(|n| n.name == "Avl")
+        .flat_map(|n| n.children.iter().filter(|c| c.name == "AvlItem"))
+    {
+        let Some(part_number) = item.attributes.get("OEMDesignNumber") else { continue };
+        let vendors = avl.entry(part_number.clone()).or_default();
+
+        for vmpn in item.children.iter().filter(|c| c.name == "AvlVmpn") {
+            let flag = |name: &str| vmpn.attributes.get(name).is_some_and(|v| v.eq_ignore_ascii_case("true"));
+            let vendor = vmpn.children.iter()
+                .find(|c| c.name == "AvlVendor")
+                .and_then(|c| c.attributes.get("enterpriseRef"))
+                .map(|id| enterprises.get(id).cloned().unwrap_or_else(|| id.clone()));
+            let mpns: Vec<&XmlNode> = vmpn.children.iter().filter(|c| c.name == "AvlMpn").collect();
+
+            let entry = |mpn: Option<&XmlNode>| ApprovedVendor {
+                vendor: vendor.clone(),
+                mpn: mpn.and_then(|m| m.attributes.get("name")).cloned(),
+                rank: mpn.and_then(|m| m.attributes.get("rank")).and_then(|v| v.parse().ok()),
+                qualified: flag("qualified"),
+                chosen: flag("chosen"),
+            };
+            if mpns.is_empty() {
+                vendors.push(entry(None));
+            } else {
+                vendors.extend(mpns.into_iter().map(|m| entry(Some(m))));
+            }
+        }
+    }
+
+    avl
+}
+
+/// Enterprise display names by id (name, falling back to code)
+fn parse_enterprises(root: &XmlNode) -> HashMap<String, String> {
+    root.children.iter()
+        .filter(|n| n.name == "Content")
+        .flat_map(|n| n.children.iter().filter(|c| c.name == "Enterprise"))
+        .filter_map(|e| {
+            let id = e.attributes.get("id")?.clone();
+            let name = e.attributes.get("name").or_else(|| e.attributes.get("code")).cloned()?;
+            Some((id, name))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bom_with_avl() {
+        let root = XmlNode::new("IPC-2581", &[], vec![
+            XmlNode::new("Content", &[], vec![XmlNode::new("Enterprise", &[("id", "E1"), ("name", "Yageo"), ("code", "DUNS")], vec![])]),
+            XmlNode::new("Bom", &[("name", "BOM")], vec![
+                XmlNode::new("BomItem", &[("OEMDesignNumberRef", "RES_10K"), ("quantity", "2"), ("description", "10k 0805")], vec![
+                    XmlNode::new("RefDes", &[("name", "R1"), ("populate", "true")], vec![]),
+                    XmlNode::new("RefDes", &[("name", "R9"), ("populate", "false")], vec![]),
+                    XmlNode::new("Characteristics", &[], vec![
+                        XmlNode::new("Textual", &[("textualCharacteristicName", "Value"), ("textualCharacteristicValue", "10k")], vec![]),
+                        XmlNode::new("Measured", &[("measuredCharacteristicName", "Tolerance"), ("measuredCharacteristicValue", "1"), ("engineeringUnitOfMeasure", "PERCENT")], vec![]),
+                    ]),
+                ]),
+            ]),
+            XmlNode::new("Avl", &[], vec![
+                XmlNode::new("AvlItem", &[("OEMDesignNumber", "RES_10K")], vec![
+                    XmlNode::new("AvlVmpn", &[("qualified", "true"), ("chosen", "true")], vec![
+                        XmlNode::new("AvlMpn", &[("name", "RC0805FR-0710KL"), ("rank", "1")], vec![]),
+                        XmlNode::new("AvlVendor", &[("enterpriseRef", "E1")], vec![]),
+                    ]),
+                ]),
+            ]),
+        ]);
+
+        let items = parse_bom(&root, |refdes| refdes == "R1");
+        assert_eq!(items.len(), 1);
+        let item = &items[0];
+        assert_eq!(item.quantity, 2);
+        assert_eq!(item.refdes.iter().map(|r| (r.name.as_str(), r.populate, r.placed)).collect::<Vec<_>>(),
+            vec![("R1", true, true), ("R9", false, false)]);
+        assert_eq!(item.characteristics[1].unit.as_deref(), Some("PERCENT"));
+
+        let vendor = &item.approved_vendors[0];
+        assert_eq!(vendor.vendor.as_deref(), Some("Yageo"));
+        assert_eq!(vendor.mpn.as_deref(), Some("RC0805FR-0710KL"));
+        assert!(vendor.qualified && vendor.chosen);
+    }
+}
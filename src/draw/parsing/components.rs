@@ -0,0 +1,262 @@
+//! Component and Package parsing
+//!
+//! Builds the placed-component model from `Step/Component` elements and the
+//! `Step/Package` footprints they reference. Pin and pickup point locations are
+//! package-local in the file; here they are transformed to board coordinates
+//! with the component's Xform and Location.
+
+use super::descriptors::LayerMeta;
+use super::xform::{parse_child_location, parse_child_xform};
+use crate::draw::geometry::*;
+use crate::parse_xml::XmlNode;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A pin of a placed component, in board coordinates
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComponentPin {
+    pub number: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pin_type: Option<String>,         // THRU, SURFACE, ...
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub electrical_type: Option<String>,  // ELECTRICAL, MECHANICAL, ...
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mount_type: Option<String>,       // SURFACE_MOUNT_PIN, THROUGH_HOLE_PIN, ...
+    pub x: f32,
+    pub y: f32,
+}
+
+/// A placed component (Component element joined with its Package)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Component {
+    pub refdes: String,
+    pub package_ref: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub part: Option<String>,
+    pub layer_ref: String,
+    pub side: String,        // TOP or BOTTOM
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mount_type: Option<String>,  // SMT, THMT, OTHER
+    pub x: f32,
+    pub y: f32,
+    pub rotation: f32,       // degrees, counter-clockwise
+    pub mirror: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pickup_point: Option<Point>,
+    pub bounds: [f32; 4],    // [min_x, min_y, max_x, max_y] of pins, pickup point and package outline
+    pub pins: Vec<ComponentPin>,
+}
+
+/// Package-local footprint data needed to place a component
+struct PackageDef {
+    height: Option<f32>,
+    pickup_point: Option<Point>,
+    outline: Vec<Point>,
+    pins: Vec<ComponentPin>,
+}
+
+/// Parse all placed components, keyed by refdes in document order
+pub fn parse_components(root: &XmlNode, layer_meta: &HashMap<String, LayerMeta>) -> IndexMap<String, Component> {
+    let mut packages = HashMap::new();
+    let mut component_nodes = Vec::new();
+
+    for step in find_steps(root) {
+        for child in &step.children {
+            match child.name.as_str() {
+                "Package" => {
+                    if let Some(name) = child.attributes.get("name") {
+                        packages.insert(name.clone(), parse_package(child));
+                    }
+                }
+                "Component" => component_nodes.push(child),
+                _ => {}
+            }
+        }
+    }
+
+    let mut components = IndexMap::new();
+    for node in component_nodes {
+        let Some(refdes) = node.attributes.get("refDes") else { continue };
+        let package_ref = node.attributes.get("packageRef").cloned().unwrap_or_default();
+        let layer_ref = node.attributes.get("layerRef").cloned().unwrap_or_default();
+
+        let xform = parse_child_xform(node);
+        let location = parse_child_location(node);
+        let placement = Transform2D::translation(location.x, location.y).then_apply(&xform.to_transform());
+
+        // Prefer the placement layer's declared side; a mirrored Xform means bottom otherwise
+        let side = match layer_meta.get(&layer_ref).map(|m| m.side.as_str()) {
+            Some("BOTTOM") => "BOTTOM",
+            Some("TOP") => "TOP",
+            _ if xform.mirror => "BOTTOM",
+            _ => "TOP",
+        };
+
+        let package = packages.get(&package_ref);
+        if package.is_none() && !package_ref.is_empty() {
+            eprintln!("[Components] {} references unknown package '{}'", refdes, package_ref);
+        }
+
+        let pins: Vec<ComponentPin> = package
+            .map(|p| p.pins.iter().map(|pin| {
+                let board = placement.apply(Point { x: pin.x, y: pin.y });
+                ComponentPin { x: board.x, y: board.y, ..pin.clone() }
+            }).collect())
+            .unwrap_or_default();
+        let pickup_point = package.and_then(|p| p.pickup_point).map(|p| placement.apply(p));
+
+        let mut bounds = [location.x, location.y, location.x, location.y];
+        let outline = package.map(|p| p.outline.as_slice()).unwrap_or_default();
+        let placed_points = outline.iter()
+            .map(|p| placement.apply(*p))
+            .chain(pins.iter().map(|pin| Point { x: pin.x, y: pin.y }))
+            .chain(pickup_point);
+        for p in placed_points {
+            bounds[0] = bounds[0].min(p.x);
+            bounds[1] = bounds[1].min(p.y);
+            bounds[2] = bounds[2].max(p.x);
+            bounds[3] = bounds[3].max(p.y);
+        }
+
+        components.insert(refdes.clone(), Component {
+            refdes: refdes.clone(),
+            package_ref,
+            part: node.attributes.get("part").cloned(),
+            layer_ref,
+            side: side.to_string(),
+            mount_type: node.attributes.get("mountType").cloned(),
+            x: location.x,
+            y: location.y,
+            rotation: xform.rotation,
+            mirror: xform.mirror,
+            height: node.attributes.get("height")
+                .and_then(|v| v.parse().ok())
+                .or(package.and_then(|p| p.height)),
+            pickup_point,
+            bounds,
+            pins,
+        });
+    }
+
+    components
+}
+
+/// Collect Step elements under Ecad/CadData
+pub(super) fn find_steps(root: &XmlNode) -> Vec<&XmlNode> {
+    root.children.iter()
+        .filter(|n| n.name == "Ecad")
+        .flat_map(|ecad| ecad.children.iter().filter(|n| n.name == "CadData"))
+        .flat_map(|cad_data| cad_data.children.iter().filter(|n| n.name == "Step"))
+        .collect()
+}
+
+fn parse_package(node: &XmlNode) -> PackageDef {
+    let mut package = PackageDef {
+        height: node.attributes.get("height").and_then(|v| v.parse().ok()),
+        pickup_point: None,
+        outline: Vec::new(),
+        pins: Vec::new(),
+    };
+
+    for child in &node.children {
+        match child.name.as_str() {
+            "PickupPoint" => {
+                let coord = |name: &str| child.attributes.get(name).and_then(|v| v.parse::<f32>().ok());
+                if let (Some(x), Some(y)) = (coord("x"), coord("y")) {
+                    package.pickup_point = Some(Point { x, y });
+                }
+            }
+            "Outline" => collect_outline_points(child, &mut package.outline),
+            "Pin" => {
+                let Some(number) = child.attributes.get("number") else { continue };
+                // Pin locations are package-local; like pads, a pin Xform offset adds to its Location
+                let offset = parse_child_xform(child);
+                let location = parse_child_location(child);
+                package.pins.push(ComponentPin {
+                    number: number.clone(),
+                    name: child.attributes.get("name").cloned(),
+                    pin_type: child.attributes.get("type").cloned(),
+                    electrical_type: child.attributes.get("electricalType").cloned(),
+                    mount_type: child.attributes.get("mountType").cloned(),
+                    x: location.x + offset.x_offset,
+                    y: location.y + offset.y_offset,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    package
+}
+
+/// Gather outline vertices (PolyBegin/PolyStep* x, y) for the component bounds
+fn collect_outline_points(node: &XmlNode, points: &mut Vec<Point>) {
+    if node.name.starts_with("Poly") {
+        let coord = |name: &str| node.attributes.get(name).and_then(|v| v.parse::<f32>().ok());
+        if let (Some(x), Some(y)) = (coord("x"), coord("y")) {
+            points.push(Point { x, y });
+        }
+    }
+    for child in &node.children {
+        collect_outline_points(child, points);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pin(number: &str, x: &str, y: &str) -> XmlNode {
+        XmlNode::new("Pin", &[("number", number), ("type", "SURFACE")], vec![
+            XmlNode::new("Location", &[("x", x), ("y", y)], vec![]),
+        ])
+    }
+
+    fn board() -> XmlNode {
+        let package = XmlNode::new("Package", &[("name", "R0805"), ("height", "0.6")], vec![
+            XmlNode::new("Outline", &[], vec![XmlNode::new("Polygon", &[], vec![
+                XmlNode::new("PolyBegin", &[("x", "-1.5"), ("y", "-0.8")], vec![]),
+                XmlNode::new("PolyStepSegment", &[("x", "1.5"), ("y", "0.8")], vec![]),
+            ])]),
+            XmlNode::new("PickupPoint", &[("x", "0"), ("y", "0")], vec![]),
+            pin("1", "-1", "0"),
+            pin("2", "1", "0"),
+        ]);
+        let top = XmlNode::new("Component", &[("refDes", "R1"), ("packageRef", "R0805"), ("layerRef", "TOP"), ("mountType", "SMT")], vec![
+            XmlNode::new("Xform", &[("rotation", "90")], vec![]),
+            XmlNode::new("Location", &[("x", "10"), ("y", "5")], vec![]),
+        ]);
+        let bottom = XmlNode::new("Component", &[("refDes", "R2"), ("packageRef", "R0805"), ("layerRef", "BOTTOM")], vec![
+            XmlNode::new("Xform", &[("mirror", "true")], vec![]),
+            XmlNode::new("Location", &[("x", "20"), ("y", "0")], vec![]),
+        ]);
+        XmlNode::new("IPC-2581", &[], vec![XmlNode::new("Ecad", &[], vec![XmlNode::new("CadData", &[], vec![
+            XmlNode::new("Step", &[("name", "board")], vec![package, top, bottom]),
+        ])])])
+    }
+
+    #[test]
+    fn test_component_placement() {
+        let components = parse_components(&board(), &HashMap::new());
+        assert_eq!(components.keys().collect::<Vec<_>>(), vec!["R1", "R2"]);
+
+        let r1 = &components["R1"];
+        assert_eq!(r1.side, "TOP");
+        assert_eq!(r1.rotation, 90.0);
+        assert_eq!(r1.height, Some(0.6));
+        // Pin 1 at (-1, 0) rotated 90 degrees CCW lands below the origin
+        assert!((r1.pins[0].x - 10.0).abs() < 1e-5 && (r1.pins[0].y - 4.0).abs() < 1e-5);
+        assert!((r1.bounds[0] - 9.2).abs() < 1e-5 && (r1.bounds[3] - 6.5).abs() < 1e-5);
+
+        // Mirrored placement is reported as bottom side with pins swapped in X
+        let r2 = &components["R2"];
+        assert_eq!(r2.side, "BOTTOM");
+        assert!((r2.pins[0].x - 21.0).abs() < 1e-5);
+        assert_eq!(r2.pickup_point.map(|p| (p.x, p.y)), Some((20.0, 0.0)));
+    }
+}
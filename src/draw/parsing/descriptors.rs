@@ -1,6 +1,6 @@
-//! Line descriptor and layer function parsing
+//! Line descriptor, fill descriptor and layer function parsing
 //!
-//! Handles parsing DictionaryLineDesc entries and layer function attributes.
+//! Handles parsing DictionaryLineDesc and DictionaryFillDesc entries and layer function attributes.
 
 use crate::draw::geometry::*;
 use crate::parse_xml::XmlNode;
@@ -65,14 +65,51 @@ pub fn parse_line_descriptors(root: &XmlNode) -> IndexMap<String, LineDescriptor
     line_descriptors
 }
 
-/// Layer metadata including function and side
+/// Parse a FillDesc element (fillProperty, lineWidth, pitch1/2, angle1/2)
+pub fn parse_fill_desc(node: &XmlNode) -> FillDescriptor {
+    let value = |name: &str| node.attributes.get(name).and_then(|v| v.parse::<f32>().ok());
+    let fill_property = match node.attributes.get("fillProperty").map(|s| s.as_str()) {
+        Some("HOLLOW") => FillProperty::Hollow,
+        Some("HATCH") => FillProperty::Hatch,
+        Some("MESH") => FillProperty::Mesh,
+        Some("VOID") => FillProperty::Void,
+        _ => FillProperty::Fill,
+    };
+
+    FillDescriptor {
+        fill_property,
+        line_width: value("lineWidth"),
+        pitch1: value("pitch1"),
+        pitch2: value("pitch2"),
+        angle1: value("angle1"),
+        angle2: value("angle2"),
+    }
+}
+
+/// Parse DictionaryFillDesc to extract the fill descriptor for each fill ID
+pub fn parse_fill_descriptors(root: &XmlNode) -> IndexMap<String, FillDescriptor> {
+    root.children.iter()
+        .filter(|n| n.name == "Content")
+        .flat_map(|n| n.children.iter().filter(|c| c.name == "DictionaryFillDesc"))
+        .flat_map(|n| n.children.iter().filter(|c| c.name == "EntryFillDesc"))
+        .filter_map(|entry| {
+            let id = entry.attributes.get("id")?;
+            let fill_desc = entry.children.iter().find(|c| c.name == "FillDesc")?;
+            Some((id.clone(), parse_fill_desc(fill_desc)))
+        })
+        .collect()
+}
+
+/// Layer metadata including function, side and polarity
 #[derive(Clone, Debug)]
 pub struct LayerMeta {
     pub function: String,  // CONDUCTOR, SOLDERMASK, SILKSCREEN, etc.
     pub side: String,      // TOP, BOTTOM, INTERNAL, NONE, ALL
+    pub polarity: Polarity,  // NEGATIVE layers describe laminate: features are clearances in a plane
+    pub span: Option<(String, String)>,  // Span fromLayer/toLayer of DRILL layers
 }
 
-/// Parse layer function and side attributes from Layer elements in the StackupGroup
+/// Parse layer function, side and polarity attributes from Layer elements in the StackupGroup
 /// Returns a map from layer name to LayerMeta
 pub fn parse_layer_metadata(root: &XmlNode) -> HashMap<String, LayerMeta> {
     let mut layer_meta = HashMap::new();
@@ -87,10 +124,16 @@ pub fn parse_layer_metadata(root: &XmlNode) -> HashMap<String, LayerMeta> {
                 let side = node.attributes.get("side")
                     .cloned()
                     .unwrap_or_else(|| "NONE".to_string());
+                let polarity = node.attributes.get("polarity")
+                    .and_then(|p| Polarity::parse(p))
+                    .unwrap_or_default();
+                let span = parse_span(node);
                 
                 let layer_meta_entry = LayerMeta { 
                     function: function.clone(), 
-                    side: side.clone() 
+                    side: side.clone(),
+                    polarity,
+                    span,
                 };
                 
                 meta.insert(name.clone(), layer_meta_entry.clone());
@@ -110,6 +153,12 @@ pub fn parse_layer_metadata(root: &XmlNode) -> HashMap<String, LayerMeta> {
     layer_meta
 }
 
+/// fromLayer/toLayer of a `Span` child (drill Layer or LayerHole)
+pub(super) fn parse_span(node: &XmlNode) -> Option<(String, String)> {
+    let span = node.children.iter().find(|c| c.name == "Span")?;
+    Some((span.attributes.get("fromLayer")?.clone(), span.attributes.get("toLayer")?.clone()))
+}
+
 /// Parse layer function attribute from Layer elements in the StackupGroup
 /// Returns a map from layer name to function (SIGNAL, CONDUCTOR, PLANE, MIXED, etc.)
 /// (Legacy function - use parse_layer_metadata for full info)
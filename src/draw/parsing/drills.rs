@@ -0,0 +1,414 @@
+//! Drill model
+//!
+//! Collects the board's holes: `Hole` and `SlotCavity` features on drill
+//! layers, and the `LayerHole` of Step-level `PadStack`s. Each drill keeps its
+//! shape, plating status, tolerance and the copper layers it spans, taken from
+//! the `Span` of its drill layer (or of the LayerHole). Spans are classified
+//! against the copper stack as through, blind, buried or backdrilled.
+//!
+//! Vias are drawn on a copper layer only when a drill at their location spans
+//! it. Drills without a span are assumed to go through the whole board, and a
+//! backdrill removes the plated barrel from the layers it cuts.
+
+use super::descriptors::{parse_span, LayerMeta};
+use super::primitives::parse_standard_primitive;
+use crate::draw::drc::is_copper_layer;
+use crate::draw::geometry::{StandardPrimitive, ViaInstance};
+use crate::parse_xml::XmlNode;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Via and hole locations closer than this (mm) are treated as the same drill site
+const LOCATION_GRID: f32 = 0.01;
+
+/// Hole shape: `Hole type` CIRCLE or SQUARE, or a SlotCavity
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum HoleShape {
+    Circle,
+    Square,
+    Slot { width: f32, length: f32 },
+}
+
+/// `platingStatus` of a Hole, SlotCavity, LayerHole or PadstackHoleDef
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+pub enum PlatingStatus {
+    #[default]
+    Plated,
+    NonPlated,
+    Via,
+    ViaCapped,
+}
+
+impl PlatingStatus {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_uppercase().as_str() {
+            "PLATED" => Some(PlatingStatus::Plated),
+            "NONPLATED" => Some(PlatingStatus::NonPlated),
+            "VIA" => Some(PlatingStatus::Via),
+            "VIA_CAPPED" => Some(PlatingStatus::ViaCapped),
+            _ => None,
+        }
+    }
+
+    pub fn is_plated(self) -> bool {
+        self != PlatingStatus::NonPlated
+    }
+}
+
+/// How a span sits in the copper stack
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum SpanKind {
+    /// First to last copper layer
+    Through,
+    /// From one outer layer to an inner layer
+    Blind,
+    /// Between inner layers only
+    Buried,
+    /// Non-plated drill removing the stub of a plated drill at the same location
+    Backdrilled,
+}
+
+/// Copper layers a drill passes through, top to bottom
+#[derive(Debug, Clone, Serialize)]
+pub struct DrillSpan {
+    pub from_layer: String,
+    pub to_layer: String,
+    pub kind: SpanKind,
+    #[serde(skip)]
+    copper_range: (usize, usize),
+}
+
+/// A drilled or routed hole
+#[derive(Debug, Clone, Serialize)]
+pub struct Drill {
+    pub name: String,
+    /// Drill layer of Hole and SlotCavity features; None for a PadStack LayerHole
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub layer_ref: Option<String>,
+    pub shape: HoleShape,
+    pub diameter: f32,
+    pub plating: PlatingStatus,
+    pub plus_tol: f32,
+    pub minus_tol: f32,
+    pub x: f32,
+    pub y: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub net_name: Option<String>,
+    /// PadStackDef named by the enclosing Set `geometry`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub padstack_ref: Option<String>,
+    /// None when no Span is given or its layers are not in the copper stack
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub span: Option<DrillSpan>,
+}
+
+/// All drills of the design and the copper layers each drill site reaches
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DrillModel {
+    pub drills: Vec<Drill>,
+    #[serde(skip)]
+    copper_layers: Vec<String>,
+    /// Drill site -> per copper layer, whether a plated barrel reaches it
+    #[serde(skip)]
+    coverage: HashMap<(i64, i64), Vec<bool>>,
+}
+
+impl DrillModel {
+    /// True when a via at (x, y) on `layer_ref` is outside the span of every drill there
+    /// Layers outside the copper stack and vias without a drill are never hidden.
+    pub fn hides_via(&self, layer_ref: &str, x: f32, y: f32) -> bool {
+        let Some(index) = self.copper_layers.iter().position(|l| l == layer_ref) else { return false };
+        self.coverage.get(&site(x, y)).is_some_and(|layers| !layers[index])
+    }
+
+    /// Mark the vias of a layer that its drill spans don't reach
+    pub fn restrict_vias(&self, layer_ref: &str, vias: &mut [ViaInstance]) {
+        for via in vias {
+            via.outside_span = self.hides_via(layer_ref, via.x, via.y);
+        }
+    }
+}
+
+/// Parse drills and classify their spans against `stack_order` (top to bottom)
+pub fn parse_drills(
+    root: &XmlNode,
+    layer_meta: &HashMap<String, LayerMeta>,
+    stack_order: &[String],
+    primitives: &HashMap<String, StandardPrimitive>,
+) -> DrillModel {
+    let mut drills = Vec::new();
+    collect_drill_features(root, layer_meta, stack_order, primitives, &mut drills);
+    drill_model(drills, layer_meta, stack_order)
+}
+
+/// Append the drills (Hole, SlotCavity, PadStack LayerHole) under `root` to `drills`
+/// Parts of a document can be collected separately and combined with [`drill_model`].
+pub fn collect_drill_features(
+    root: &XmlNode,
+    layer_meta: &HashMap<String, LayerMeta>,
+    stack_order: &[String],
+    primitives: &HashMap<String, StandardPrimitive>,
+    drills: &mut Vec<Drill>,
+) {
+    let copper_layers = copper_layers(layer_meta, stack_order);
+    let scope = DrillScope { layer_meta, copper_layers: &copper_layers, primitives };
+    collect_drills(root, &scope, None, &SetContext::default(), drills);
+}
+
+/// Classify backdrills and compute the copper coverage of collected drills
+pub fn drill_model(mut drills: Vec<Drill>, layer_meta: &HashMap<String, LayerMeta>, stack_order: &[String]) -> DrillModel {
+    let copper_layers = copper_layers(layer_meta, stack_order);
+    classify_backdrills(&mut drills);
+    let coverage = drill_coverage(&drills, copper_layers.len());
+
+    DrillModel { drills, copper_layers, coverage }
+}
+
+/// Copper layers of the stack, top to bottom
+fn copper_layers(layer_meta: &HashMap<String, LayerMeta>, stack_order: &[String]) -> Vec<String> {
+    stack_order.iter()
+        .filter(|l| layer_meta.get(*l).is_some_and(|m| is_copper_layer(&m.function)))
+        .cloned()
+        .collect()
+}
+
+/// Lookups shared by the whole drill walk
+struct DrillScope<'a> {
+    layer_meta: &'a HashMap<String, LayerMeta>,
+    copper_layers: &'a [String],
+    primitives: &'a HashMap<String, StandardPrimitive>,
+}
+
+/// Net and padstack of the enclosing Set elements
+#[derive(Default, Clone)]
+struct SetContext {
+    net: Option<String>,
+    padstack: Option<String>,
+}
+
+fn collect_drills(
+    node: &XmlNode,
+    scope: &DrillScope,
+    layer_ref: Option<&str>,
+    context: &SetContext,
+    drills: &mut Vec<Drill>,
+) {
+    let layer_ref = match node.name.as_str() {
+        "LayerFeature" => node.attributes.get("layerRef").map(|s| s.as_str()),
+        _ => layer_ref,
+    };
+    let context = match node.name.as_str() {
+        "Set" => SetContext {
+            net: node.attributes.get("net").cloned().or_else(|| context.net.clone()),
+            padstack: node.attributes.get("geometry").cloned().or_else(|| context.padstack.clone()),
+        },
+        "PadStack" => SetContext { net: node.attributes.get("net").cloned(), padstack: None },
+        _ => context.clone(),
+    };
+    let layer_span = || layer_ref
+        .and_then(|l| scope.layer_meta.get(l))
+        .and_then(|m| m.span.clone());
+
+    let drill = match node.name.as_str() {
+        "Hole" if layer_ref.is_some() => {
+            let shape = match node.attributes.get("type").map(|t| t.as_str()) {
+                Some("SQUARE") => HoleShape::Square,
+                _ => HoleShape::Circle,
+            };
+            Some(parse_drill(node, shape, attr(node, "x"), attr(node, "y"), layer_span()))
+        }
+        "SlotCavity" if layer_ref.is_some() => {
+            let location = node.children.iter().find(|c| c.name == "Location");
+            let x = location.map(|l| attr(l, "x")).unwrap_or(0.0);
+            let y = location.map(|l| attr(l, "y")).unwrap_or(0.0);
+            let (width, length) = node.children.iter()
+                .find(|c| c.name == "Feature")
+                .and_then(|f| f.children.iter().find_map(|c| match c.name.as_str() {
+                    "StandardPrimitiveRef" => c.attributes.get("id").and_then(|id| scope.primitives.get(id)).cloned(),
+                    _ => parse_standard_primitive(c),
+                }))
+                .map(|p| slot_size(&p))
+                .unwrap_or((0.0, 0.0));
+            Some(Drill { diameter: width, ..parse_drill(node, HoleShape::Slot { width, length }, x, y, layer_span()) })
+        }
+        "LayerHole" => Some(parse_drill(node, HoleShape::Circle, attr(node, "x"), attr(node, "y"), parse_span(node))),
+        _ => None,
+    };
+
+    if let Some(mut drill) = drill {
+        if node.name != "LayerHole" {
+            drill.layer_ref = layer_ref.map(|l| l.to_string());
+        }
+        drill.net_name = context.net.clone();
+        drill.padstack_ref = context.padstack.clone();
+        drill.span = drill.span.and_then(|span| classify_span(span, scope.copper_layers));
+        drills.push(drill);
+        return;
+    }
+
+    for child in &node.children {
+        collect_drills(child, scope, layer_ref, &context, drills);
+    }
+}
+
+fn attr(node: &XmlNode, name: &str) -> f32 {
+    node.attributes.get(name).and_then(|v| v.parse().ok()).unwrap_or(0.0)
+}
+
+/// Common attributes of Hole, SlotCavity and LayerHole; the span is classified by the caller
+fn parse_drill(node: &XmlNode, shape: HoleShape, x: f32, y: f32, span: Option<(String, String)>) -> Drill {
+    Drill {
+        name: node.attributes.get("name").cloned().unwrap_or_default(),
+        layer_ref: None,
+        shape,
+        diameter: attr(node, "diameter"),
+        plating: node.attributes.get("platingStatus")
+            .and_then(|p| PlatingStatus::parse(p))
+            .unwrap_or_default(),
+        plus_tol: attr(node, "plusTol"),
+        minus_tol: attr(node, "minusTol"),
+        x,
+        y,
+        net_name: None,
+        padstack_ref: None,
+        span: span.map(|(from_layer, to_layer)| DrillSpan {
+            from_layer,
+            to_layer,
+            kind: SpanKind::Through,
+            copper_range: (0, 0),
+        }),
+    }
+}
+
+/// Slot width and length from the SlotCavity feature's primitive
+fn slot_size(primitive: &StandardPrimitive) -> (f32, f32) {
+    match primitive {
+        StandardPrimitive::Oval { width, height }
+        | StandardPrimitive::Rectangle { width, height }
+        | StandardPrimitive::RoundRect { width, height, .. } => (width.min(*height), width.max(*height)),
+        other => (other.outer_diameter(), other.outer_diameter()),
+    }
+}
+
+/// Place a span in the copper stack; None when either end is not a copper layer
+/// Backdrills are classified later, once every drill site is known.
+fn classify_span(mut span: DrillSpan, copper_layers: &[String]) -> Option<DrillSpan> {
+    let from = copper_layers.iter().position(|l| *l == span.from_layer)?;
+    let to = copper_layers.iter().position(|l| *l == span.to_layer)?;
+    let (top, bottom) = (from.min(to), from.max(to));
+    let last = copper_layers.len() - 1;
+
+    span.copper_range = (top, bottom);
+    span.kind = match (top == 0, bottom == last) {
+        (true, true) => SpanKind::Through,
+        (true, false) | (false, true) => SpanKind::Blind,
+        (false, false) => SpanKind::Buried,
+    };
+    Some(span)
+}
+
+/// Non-plated partial-depth drills at the site of a plated drill are backdrills
+fn classify_backdrills(drills: &mut [Drill]) {
+    let plated_sites: std::collections::HashSet<(i64, i64)> = drills.iter()
+        .filter(|d| d.plating.is_plated())
+        .map(|d| site(d.x, d.y))
+        .collect();
+
+    for drill in drills.iter_mut().filter(|d| !d.plating.is_plated()) {
+        let at_plated_site = plated_sites.contains(&site(drill.x, drill.y));
+        if let Some(span) = drill.span.as_mut().filter(|s| s.kind != SpanKind::Through && at_plated_site) {
+            span.kind = SpanKind::Backdrilled;
+        }
+    }
+}
+
+/// Copper layers reached by the plated barrels at each drill site
+fn drill_coverage(drills: &[Drill], copper_count: usize) -> HashMap<(i64, i64), Vec<bool>> {
+    let mut coverage: HashMap<(i64, i64), Vec<bool>> = HashMap::new();
+    if copper_count == 0 {
+        return coverage;
+    }
+
+    for drill in drills.iter().filter(|d| d.plating.is_plated()) {
+        let layers = coverage.entry(site(drill.x, drill.y)).or_insert_with(|| vec![false; copper_count]);
+        let (top, bottom) = drill.span.as_ref().map_or((0, copper_count - 1), |s| s.copper_range);
+        layers[top..=bottom].iter_mut().for_each(|l| *l = true);
+    }
+    for drill in drills {
+        let Some(span) = drill.span.as_ref().filter(|s| s.kind == SpanKind::Backdrilled) else { continue };
+        if let Some(layers) = coverage.get_mut(&site(drill.x, drill.y)) {
+            let (top, bottom) = span.copper_range;
+            layers[top..=bottom].iter_mut().for_each(|l| *l = false);
+        }
+    }
+    coverage
+}
+
+fn site(x: f32, y: f32) -> (i64, i64) {
+    ((x / LOCATION_GRID).round() as i64, (y / LOCATION_GRID).round() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::descriptors::parse_layer_metadata;
+
+    fn drill_layer(name: &str, from: &str, to: &str) -> XmlNode {
+        XmlNode::new("Layer", &[("name", name), ("layerFunction", "DRILL"), ("side", "ALL"), ("polarity", "POSITIVE")], vec![
+            XmlNode::new("Span", &[("fromLayer", from), ("toLayer", to)], vec![]),
+        ])
+    }
+
+    fn hole(layer: &str, name: &str, plating: &str, x: &str, y: &str) -> XmlNode {
+        XmlNode::new("LayerFeature", &[("layerRef", layer)], vec![
+            XmlNode::new("Set", &[("net", "GND"), ("geometry", "VIA_STACK")], vec![
+                XmlNode::new("Hole", &[("name", name), ("diameter", "0.3"), ("platingStatus", plating),
+                    ("plusTol", "0.05"), ("minusTol", "0.02"), ("x", x), ("y", y)], vec![]),
+            ]),
+        ])
+    }
+
+    #[test]
+    fn test_drill_spans_restrict_vias() {
+        let copper = ["L1", "L2", "L3", "L4"];
+        let mut cad_data: Vec<XmlNode> = copper.iter()
+            .map(|l| XmlNode::new("Layer", &[("name", l), ("layerFunction", "SIGNAL"), ("side", "INTERNAL"), ("polarity", "POSITIVE")], vec![]))
+            .collect();
+        cad_data.push(drill_layer("D12", "L1", "L2"));
+        cad_data.push(drill_layer("D14", "L1", "L4"));
+        cad_data.push(drill_layer("D23", "L2", "L3"));
+        cad_data.push(drill_layer("BD43", "L4", "L3"));
+        cad_data.push(XmlNode::new("Step", &[("name", "board")], vec![
+            hole("D12", "blind", "VIA", "1.0", "1.0"),
+            hole("D23", "buried", "VIA", "2.0", "2.0"),
+            hole("D14", "through", "PLATED", "5.0", "5.0"),
+            hole("BD43", "backdrill", "NONPLATED", "5.0", "5.0"),
+        ]));
+        let root = XmlNode::new("IPC-2581", &[], vec![XmlNode::new("Ecad", &[], vec![XmlNode::new("CadData", &[], cad_data)])]);
+        let order: Vec<String> = copper.iter().map(|l| l.to_string()).collect();
+        let model = parse_drills(&root, &parse_layer_metadata(&root), &order, &HashMap::new());
+
+        let kinds: Vec<_> = model.drills.iter().map(|d| d.span.as_ref().map(|s| s.kind)).collect();
+        assert_eq!(kinds, [Some(SpanKind::Blind), Some(SpanKind::Buried), Some(SpanKind::Through), Some(SpanKind::Backdrilled)]);
+        let blind = &model.drills[0];
+        assert_eq!(blind.plating, PlatingStatus::Via);
+        assert_eq!((blind.plus_tol, blind.minus_tol), (0.05, 0.02));
+        assert_eq!(blind.net_name.as_deref(), Some("GND"));
+        assert_eq!(blind.padstack_ref.as_deref(), Some("VIA_STACK"));
+
+        // Blind L1-L2 via
+        assert!(!model.hides_via("L1", 1.0, 1.0));
+        assert!(!model.hides_via("L2", 1.0, 1.0));
+        assert!(model.hides_via("L3", 1.0, 1.0));
+        // Buried L2-L3 via
+        assert!(model.hides_via("L1", 2.0, 2.0));
+        assert!(!model.hides_via("L3", 2.0, 2.0));
+        // Through via backdrilled from L4 up to L3
+        assert!(!model.hides_via("L2", 5.0, 5.0));
+        assert!(model.hides_via("L3", 5.0, 5.0));
+        assert!(model.hides_via("L4", 5.0, 5.0));
+        // Non-copper layers and vias without a drill are left alone
+        assert!(!model.hides_via("D12", 1.0, 1.0));
+        assert!(!model.hides_via("L4", 9.0, 9.0));
+    }
+}
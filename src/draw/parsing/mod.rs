@@ -3,60 +3,91 @@
 //! This module parses IPC-2581 XML format and extracts geometry data for rendering.
 //!
 //! # Submodules
+//! - `bom` - BOM items joined with AVL approved vendors
 //! - `colors` - Color parsing and layer color assignment
-//! - `descriptors` - Line descriptor and layer function parsing  
+//! - `components` - Component, Package and Pin parsing into placed components
+//! - `descriptors` - Line descriptor, fill descriptor and layer function parsing  
+//! - `drills` - Drill model: holes, slots, plating and copper spans
 //! - `primitives` - Standard primitive and padstack definition parsing
 //! - `polylines` - Polyline and line node parsing
 //! - `polygons` - Polygon and contour parsing
+//! - `nets` - LogicalNet and PhyNetGroup parsing into the netlist
+//! - `outline` - Board outline from Profile and the synthetic outline layer
 //! - `padstacks` - Pad and via collection from layers
+//! - `polarity` - Negative feature and layer polarity resolved into copper
+//! - `references` - Dangling references, duplicate ids and unused dictionary entries
+//! - `stackup` - Stackup model and physical layer order
+//! - `steps` - Step selection and StepRepeat panel expansion
+//! - `streaming` - Single-pass Load that collects LayerFeatures without keeping them in the DOM
+//! - `text` - Text features and DictionaryFont stroke widths
+//! - `units` - CadHeader unit detection and normalization to millimetres
+//! - `xform` - Xform and Location parsing for placed features
 
+mod bom;
 mod colors;
+mod components;
 mod descriptors;
+mod drills;
+mod nets;
+mod outline;
 mod primitives;
 mod polylines;
 mod polygons;
 mod padstacks;
+mod polarity;
+mod references;
+mod stackup;
+mod steps;
+mod streaming;
+mod text;
+mod units;
+mod xform;
 
 use crate::draw::geometry::*;
 use crate::draw::generation::*;
+use crate::draw::progress::Progress;
 use crate::draw::tessellation::MIN_VISIBLE_WIDTH_LOD;
 use crate::parse_xml::XmlNode;
 use indexmap::IndexMap;
 use rayon::prelude::*;
-use std::collections::HashSet;
+use std::collections::HashMap;
 
 // Re-export key parsing functions
+pub use bom::{parse_bom, BomItem, BomRefDes, BomCharacteristic, ApprovedVendor};
 pub use colors::get_layer_color;
+pub use components::{parse_components, Component, ComponentPin};
+pub use nets::{parse_nets, Net, NetPin, NetPoint};
+pub use outline::{parse_board_outline, parse_step_outline, BoardOutline, OUTLINE_LAYER_ID};
+pub use drills::{parse_drills, Drill, DrillModel, DrillSpan, HoleShape, PlatingStatus, SpanKind};
 pub use descriptors::{parse_line_descriptors, parse_layer_functions, parse_layer_metadata, build_layer_pairs, LayerMeta};
 pub use primitives::{parse_standard_primitives, parse_padstack_definitions};
+pub use references::{check_references, LoadDiagnostic, LoadDiagnosticKind, ReferenceCollector};
+pub use stackup::{parse_stackup, physical_layer_order, Stackup, StackupGroup, StackupLayer};
+pub use steps::{parse_steps, find_step, default_step, select_step, step_name, StepInfo, StepRepeat};
+pub use streaming::{stream_document, parse_document_parallel, StreamedDocument};
+pub use units::{LengthUnit, parse_cad_units, normalize_units, normalize_subtree};
+pub use xform::{parse_xform, features_transform};
 
-/// Extract all LayerFeatures from XML root and generate LayerJSON for each
+/// Extract all LayerFeatures of the default Step and generate LayerJSON for each
+/// All output geometry is in millimetres regardless of the CadHeader unit
 pub fn extract_and_generate_layers(root: &XmlNode) -> Result<(Vec<LayerJSON>, Vec<ObjectRange>), anyhow::Error> {
-    let total_start = std::time::Instant::now();
-    let mut layers_seen = HashSet::new();
+    extract_and_generate_step_layers(root, None)
+}
 
-    // Parse line descriptors from DictionaryLineDesc
-    let parse_start = std::time::Instant::now();
-    let line_descriptors = descriptors::parse_line_descriptors(root);
-    let parse_time = parse_start.elapsed();
-    
-    // Parse standard primitive definitions (circles, rectangles, etc.)
-    let primitives = primitives::parse_standard_primitives(root);
-    
-    // Parse padstack definitions (for vias)
-    let padstack_defs = primitives::parse_padstack_definitions(root);
-    
-    // Parse layer functions from Layer elements (SIGNAL, CONDUCTOR, PLANE, etc.)
-    let layer_metadata = descriptors::parse_layer_metadata(root);
-    
-    if std::env::var("PROFILE_TIMING").is_ok() {
-        eprintln!("\n=== Detailed Timing Profile ===");
-        eprintln!("Line descriptor parsing: {:.2}ms", parse_time.as_secs_f64() * 1000.0);
-        eprintln!("Parsed {} standard primitives", primitives.len());
-        eprintln!("Parsed {} padstack definitions", padstack_defs.len());
-        eprintln!("Parsed {} layer metadata entries", layer_metadata.len());
+/// Extract the LayerFeatures of one Step (the default step when `step` is None) and generate LayerJSON for each
+/// StepRepeat instances are drawn as transformed copies; only the step's own features are selectable.
+pub fn extract_and_generate_step_layers(root: &XmlNode, step: Option<&str>) -> Result<(Vec<LayerJSON>, Vec<ObjectRange>), anyhow::Error> {
+    // Documents not yet normalized are converted on a copy (Load normalizes in place up front)
+    if units::parse_cad_units(root) != LengthUnit::Millimeter {
+        let mut normalized = root.clone();
+        units::normalize_units(&mut normalized);
+        return extract_and_generate_step_layers(&normalized, step);
     }
 
+    let total_start = std::time::Instant::now();
+    let dictionaries = Dictionaries::parse(root);
+    let scope = dictionaries.scope();
+
     // Find Ecad node which contains all the CAD data
     let ecad_node = root
         .children
@@ -71,16 +102,109 @@ pub fn extract_and_generate_layers(root: &XmlNode) -> Result<(Vec<LayerJSON>, Ve
         .find(|n| n.name == "CadData")
         .ok_or_else(|| anyhow::anyhow!("No CadData node found"))?;
 
+    // Geometry comes from the active Step only (the whole CadData if the file has no Step)
+    let active_step = steps::select_step(root, step)?;
+
     // 1. Collect all LayerFeature nodes and their geometries (Sequential)
     let collect_start = std::time::Instant::now();
-    let mut layer_contexts = IndexMap::new();
-    collect_layer_features(cad_data, &mut layer_contexts, &mut layers_seen, &line_descriptors, &padstack_defs)?;
-    
-    // Also collect PadStack instances from Step (vias defined at Step level)
-    padstacks::collect_padstacks_from_step(cad_data, &mut layer_contexts, &primitives);
+    let layer_contexts = collect_step_geometries(active_step.unwrap_or(cad_data), &scope)?;
+    generate_step_layers(root, active_step, layer_contexts, HashMap::new(), &scope, collect_start, total_start, &Progress::default())
+}
+
+/// Dictionaries and layer tables the geometry of every step is resolved against
+struct Dictionaries {
+    line_descriptors: IndexMap<String, LineDescriptor>,
+    fill_descriptors: IndexMap<String, FillDescriptor>,
+    padstack_defs: IndexMap<String, PadStackDef>,
+    font_stroke_widths: HashMap<String, f32>,
+    layer_metadata: HashMap<String, LayerMeta>,
+    primitives: HashMap<String, StandardPrimitive>,
+    stack_order: Vec<String>,
+}
+
+impl Dictionaries {
+    fn parse(root: &XmlNode) -> Self {
+        // Parse line descriptors from DictionaryLineDesc
+        let parse_start = std::time::Instant::now();
+        let line_descriptors = descriptors::parse_line_descriptors(root);
+        let parse_time = parse_start.elapsed();
+        
+        // Parse fill descriptors from DictionaryFillDesc
+        let fill_descriptors = descriptors::parse_fill_descriptors(root);
+        
+        // Parse standard primitive definitions (circles, rectangles, etc.)
+        let primitives = primitives::parse_standard_primitives(root);
+        
+        // Parse padstack definitions (for vias)
+        let padstack_defs = primitives::parse_padstack_definitions(root);
+        
+        // Parse font stroke widths from DictionaryFont (for Text features)
+        let font_stroke_widths = text::parse_font_stroke_widths(root, &line_descriptors);
+        
+        // Parse layer functions from Layer elements (SIGNAL, CONDUCTOR, PLANE, etc.)
+        let layer_metadata = descriptors::parse_layer_metadata(root);
+
+        // Physical stack sequence (top to bottom), for layer order and drill spans
+        let stackup = stackup::parse_stackup(root, &layer_metadata);
+        let stack_order = stackup::physical_layer_order(root, stackup.as_ref());
+        
+        if std::env::var("PROFILE_TIMING").is_ok() {
+            eprintln!("\n=== Detailed Timing Profile ===");
+            eprintln!("Line descriptor parsing: {:.2}ms", parse_time.as_secs_f64() * 1000.0);
+            eprintln!("Parsed {} standard primitives", primitives.len());
+            eprintln!("Parsed {} padstack definitions", padstack_defs.len());
+            eprintln!("Parsed {} layer metadata entries", layer_metadata.len());
+        }
+
+        Dictionaries { line_descriptors, fill_descriptors, padstack_defs, font_stroke_widths, layer_metadata, primitives, stack_order }
+    }
+
+    fn scope(&self) -> StepScope<'_> {
+        StepScope {
+            line_descriptors: &self.line_descriptors,
+            fill_descriptors: &self.fill_descriptors,
+            padstack_defs: &self.padstack_defs,
+            font_stroke_widths: &self.font_stroke_widths,
+            layer_metadata: &self.layer_metadata,
+            primitives: &self.primitives,
+            stack_order: &self.stack_order,
+        }
+    }
+}
+
+/// Expand StepRepeats around the active step's own geometry, then tessellate every layer
+/// `cache` may hold the geometry of repeated steps collected up front; missing steps are collected from `root`.
+/// Each layer counts as one unit of `progress`; cancellation stops the layers not yet started.
+#[allow(clippy::too_many_arguments)]
+fn generate_step_layers(
+    root: &XmlNode,
+    active_step: Option<&XmlNode>,
+    mut layer_contexts: IndexMap<String, LayerGeometries>,
+    mut cache: HashMap<String, IndexMap<String, LayerGeometries>>,
+    scope: &StepScope,
+    collect_start: std::time::Instant,
+    total_start: std::time::Instant,
+    progress: &Progress,
+) -> Result<(Vec<LayerJSON>, Vec<ObjectRange>), anyhow::Error> {
+    let layer_metadata = scope.layer_metadata;
+    let primitives = scope.primitives;
+
+    // Objects of the step itself map to XML elements on Save; StepRepeat copies appended after them don't
+    let selectable: HashMap<String, [usize; 4]> = layer_contexts.iter()
+        .map(|(layer_ref, g)| (layer_ref.clone(), [g.polylines.len(), g.polygons.len(), g.vias.len(), g.pads.len()]))
+        .collect();
+    if let Some(active_step) = active_step {
+        expand_step_repeats(root, active_step, &Transform2D::identity(), 0, &mut layer_contexts, &mut cache, scope)?;
+    }
+
+    // Order layers by physical stack sequence (top to bottom) rather than LayerFeature order
+    sort_by_stack_order(&mut layer_contexts, scope.stack_order);
     
     let collect_time = collect_start.elapsed();
 
+    // Board outline bounds the plane of NEGATIVE layers and is drawn as its own layer
+    let board_outline = active_step.and_then(outline::parse_step_outline);
+
     // 2. Process layers in parallel (Parallel)
     let process_start = std::time::Instant::now();
     
@@ -88,14 +212,19 @@ pub fn extract_and_generate_layers(root: &XmlNode) -> Result<(Vec<LayerJSON>, Ve
     type LayerResult = Result<(LayerJSON, Vec<ObjectRange>, CullingStats), anyhow::Error>;
     
     // Use rayon to process layers in parallel
+    let counter = progress.counter("Tessellating layers", layer_contexts.len());
     let results: Vec<LayerResult> = layer_contexts
         .into_iter()
         .collect::<Vec<_>>()
         .into_par_iter()
         .enumerate()
-        .map(|(idx, (layer_ref, geometries))| {
+        .map(|(idx, (layer_ref, mut geometries))| {
+            progress.check()?;
             let mut local_culling_stats = CullingStats::default();
             
+            // Subtract negative features from the copper drawn before them
+            polarity::resolve_polarity(&mut geometries, layer_polarity(layer_metadata, &layer_ref), board_outline.as_ref());
+            
             // Extract layer name from layerRef (e.g., "LAYER:Design" -> "Design")
             let layer_name = layer_ref
                 .split(':')
@@ -112,7 +241,7 @@ pub fn extract_and_generate_layers(root: &XmlNode) -> Result<(Vec<LayerJSON>, Ve
                 .map(|m| (m.function.as_str(), m.side.as_str()))
                 .unwrap_or(("", "NONE"));
             
-            let (layer_json, object_ranges) = generate_layer_json(
+            let (layer_json, mut object_ranges) = generate_layer_json(
                 &layer_ref,
                 idx as u32,
                 &layer_name,
@@ -121,9 +250,14 @@ pub fn extract_and_generate_layers(root: &XmlNode) -> Result<(Vec<LayerJSON>, Ve
                 color,
                 &geometries,
                 &mut local_culling_stats,
-                &primitives,
+                primitives,
             )?;
             
+            // Only the step's own objects are selectable: pieces appended after them have no XML element of their own
+            let own = selectable.get(&layer_ref).copied().unwrap_or_default();
+            object_ranges.retain(|r| ((r.id & 0xFFFFFFFFF) as usize) < own[r.obj_type as usize]);
+            counter.tick();
+            
             Ok((layer_json, object_ranges, local_culling_stats))
         })
         .collect();
@@ -145,6 +279,22 @@ pub fn extract_and_generate_layers(root: &XmlNode) -> Result<(Vec<LayerJSON>, Ve
         }
     }
     
+    // 4. Synthetic board outline layer from Step/Profile (not selectable, so its ranges are dropped)
+    if let Some(outline) = &board_outline {
+        let (layer_json, _) = generate_layer_json(
+            OUTLINE_LAYER_ID,
+            layer_jsons.len() as u32,
+            outline::OUTLINE_LAYER_NAME,
+            "BOARD_OUTLINE",
+            "ALL",
+            outline::OUTLINE_COLOR,
+            &outline.to_layer_geometries(),
+            &mut total_culling_stats,
+            primitives,
+        )?;
+        layer_jsons.push(layer_json);
+    }
+    
     if std::env::var("PROFILE_TIMING").is_ok() {
         eprintln!("\nTotal collection time: {:.2}ms", collect_time.as_secs_f64() * 1000.0);
         eprintln!("Parallel processing time: {:.2}ms", process_start.elapsed().as_secs_f64() * 1000.0);
@@ -169,42 +319,123 @@ pub fn extract_and_generate_layers(root: &XmlNode) -> Result<(Vec<LayerJSON>, Ve
     Ok((layer_jsons, all_object_ranges))
 }
 
-/// Recursively find LayerFeature nodes and collect geometries for each unique layer
+/// Stable-sort layers by their position in `stack_order`
+/// Layers missing from the stack (e.g. drill or document layers) keep their relative order after it.
+fn sort_by_stack_order(layer_contexts: &mut IndexMap<String, LayerGeometries>, stack_order: &[String]) {
+    let positions: HashMap<&str, usize> = stack_order.iter()
+        .enumerate()
+        .map(|(i, name)| (name.as_str(), i))
+        .collect();
+    let position = |layer_ref: &str| {
+        positions.get(layer_ref)
+            .or_else(|| positions.get(layer_ref.split(':').next_back().unwrap_or(layer_ref)))
+            .copied()
+            .unwrap_or(usize::MAX)
+    };
+    layer_contexts.sort_by(|a, _, b, _| position(a).cmp(&position(b)));
+}
+
+/// Lookups shared by the geometry collection of every step
+struct StepScope<'a> {
+    line_descriptors: &'a IndexMap<String, LineDescriptor>,
+    fill_descriptors: &'a IndexMap<String, FillDescriptor>,
+    padstack_defs: &'a IndexMap<String, PadStackDef>,
+    font_stroke_widths: &'a HashMap<String, f32>,
+    layer_metadata: &'a HashMap<String, LayerMeta>,
+    primitives: &'a HashMap<String, StandardPrimitive>,
+    stack_order: &'a [String],
+}
+
+/// Geometry of a Step's own LayerFeatures and PadStacks, with vias limited to their drill spans
+fn collect_step_geometries(step: &XmlNode, scope: &StepScope) -> Result<IndexMap<String, LayerGeometries>, anyhow::Error> {
+    let mut layer_contexts = IndexMap::new();
+    collect_layer_features(step, &mut layer_contexts, scope.line_descriptors, scope.fill_descriptors, scope.padstack_defs, scope.font_stroke_widths, scope.layer_metadata)?;
+    let mut drills = Vec::new();
+    drills::collect_drill_features(step, scope.layer_metadata, scope.stack_order, scope.primitives, &mut drills);
+    Ok(finish_step_geometries(step, layer_contexts, drills, scope))
+}
+
+/// Add a step's PadStacks to its LayerFeature geometry and limit vias to the spans of `drills`
+fn finish_step_geometries(
+    step: &XmlNode,
+    mut layer_contexts: IndexMap<String, LayerGeometries>,
+    drills: Vec<Drill>,
+    scope: &StepScope,
+) -> IndexMap<String, LayerGeometries> {
+    layer_contexts.retain(|_, geometries| !geometries.is_empty());
+
+    // Also collect PadStack instances from Step (vias defined at Step level)
+    padstacks::collect_padstacks_from_step(step, &mut layer_contexts, scope.primitives);
+
+    // Drill spans decide which copper layers show each via
+    let drill_model = drills::drill_model(drills, scope.layer_metadata, scope.stack_order);
+    for (layer_ref, geometries) in layer_contexts.iter_mut() {
+        drill_model.restrict_vias(layer_ref, &mut geometries.vias);
+    }
+    layer_contexts
+}
+
+/// Append the geometry of every StepRepeat instance of `step`, placed by `transform`, recursing into nested repeats
+/// `cache` holds the collected geometry of each repeated step, so a step is only walked once.
+fn expand_step_repeats(
+    root: &XmlNode,
+    step: &XmlNode,
+    transform: &Transform2D,
+    depth: usize,
+    layer_contexts: &mut IndexMap<String, LayerGeometries>,
+    cache: &mut HashMap<String, IndexMap<String, LayerGeometries>>,
+    scope: &StepScope,
+) -> Result<(), anyhow::Error> {
+    for repeat in steps::step_repeats(step) {
+        let Some(repeated) = steps::find_step(root, &repeat.step_ref) else {
+            eprintln!("[Steps] StepRepeat references unknown step '{}', skipping", repeat.step_ref);
+            continue;
+        };
+        if depth >= steps::MAX_STEP_DEPTH {
+            return Err(anyhow::anyhow!("StepRepeat of '{}' nested more than {} deep (recursive stepRef?)", repeat.step_ref, steps::MAX_STEP_DEPTH));
+        }
+        if !cache.contains_key(&repeat.step_ref) {
+            cache.insert(repeat.step_ref.clone(), collect_step_geometries(repeated, scope)?);
+        }
+        let repeated_geometries = cache[&repeat.step_ref].clone();
+
+        for placement in repeat.placements(steps::step_datum(repeated)) {
+            let placement = transform.then_apply(&placement);
+            for (layer_ref, geometries) in &repeated_geometries {
+                let target = layer_contexts.entry(layer_ref.clone())
+                    .or_insert_with(|| LayerGeometries::new(layer_ref));
+                steps::append_placed(target, geometries.clone(), &placement);
+            }
+            expand_step_repeats(root, repeated, &placement, depth + 1, layer_contexts, cache, scope)?;
+        }
+    }
+    Ok(())
+}
+
+/// Recursively find LayerFeature nodes and collect geometries per layer
+/// LayerFeatures sharing a layerRef are merged in document order, matching the element order Save counts in.
 fn collect_layer_features(
     node: &XmlNode,
     layer_contexts: &mut IndexMap<String, LayerGeometries>,
-    layers_seen: &mut HashSet<String>,
     line_descriptors: &IndexMap<String, LineDescriptor>,
+    fill_descriptors: &IndexMap<String, FillDescriptor>,
     padstack_defs: &IndexMap<String, PadStackDef>,
+    font_stroke_widths: &HashMap<String, f32>,
+    layer_metadata: &HashMap<String, LayerMeta>,
 ) -> Result<(), anyhow::Error> {
     // If this is a LayerFeature node, process it
     if node.name == "LayerFeature" {
         if let Some(layer_ref) = node.attributes.get("layerRef") {
-            if !layers_seen.contains(layer_ref) {
-                layers_seen.insert(layer_ref.clone());
-                
-                // Collect all geometries from this LayerFeature
-                let mut geometries = LayerGeometries {
-                    layer_ref: layer_ref.clone(),
-                    polylines: Vec::new(),
-                    polygons: Vec::new(),
-                    padstack_holes: Vec::new(),
-                    pads: Vec::new(),
-                    vias: Vec::new(),
-                };
-                collect_geometries_from_node(node, &mut geometries, line_descriptors, padstack_defs);
-                
-                // Only add layer if it has any geometry
-                if !geometries.polylines.is_empty() || !geometries.polygons.is_empty() || !geometries.padstack_holes.is_empty() || !geometries.pads.is_empty() || !geometries.vias.is_empty() {
-                    layer_contexts.insert(layer_ref.clone(), geometries);
-                }
-            }
+            let geometries = layer_contexts.entry(layer_ref.clone())
+                .or_insert_with(|| LayerGeometries::new(layer_ref));
+            let polarity = layer_polarity(layer_metadata, layer_ref);
+            collect_geometries_from_node(node, geometries, line_descriptors, fill_descriptors, padstack_defs, font_stroke_widths, polarity);
         }
     }
 
     // Recursively search all children
     for child in &node.children {
-        collect_layer_features(child, layer_contexts, layers_seen, line_descriptors, padstack_defs)?;
+        collect_layer_features(child, layer_contexts, line_descriptors, fill_descriptors, padstack_defs, font_stroke_widths, layer_metadata)?;
     }
 
     Ok(())
@@ -215,20 +446,38 @@ fn collect_geometries_from_node(
     node: &XmlNode,
     geometries: &mut LayerGeometries,
     line_descriptors: &IndexMap<String, LineDescriptor>,
+    fill_descriptors: &IndexMap<String, FillDescriptor>,
     padstack_defs: &IndexMap<String, PadStackDef>,
+    font_stroke_widths: &HashMap<String, f32>,
+    layer_polarity: Polarity,
 ) {
-    // Start with no net or component context
-    collect_geometries_with_context(node, geometries, line_descriptors, padstack_defs, None, None);
+    // Start with no net or component context, in board coordinates; features on a NEGATIVE layer are clearances
+    collect_geometries_with_context(node, geometries, line_descriptors, fill_descriptors, padstack_defs, font_stroke_widths, None, None, &Transform2D::identity(), layer_polarity);
+}
+
+/// Polarity of a layer from its Layer element (POSITIVE when not declared)
+fn layer_polarity(layer_metadata: &HashMap<String, LayerMeta>, layer_ref: &str) -> Polarity {
+    layer_metadata.get(layer_ref)
+        .or_else(|| layer_metadata.get(layer_ref.split(':').next_back().unwrap_or(layer_ref)))
+        .map(|m| m.polarity)
+        .unwrap_or_default()
 }
 
-/// Recursively collect all geometry elements, tracking the current net and component context from Set nodes
+/// Recursively collect all geometry elements, tracking the current net, component and polarity context from Set nodes
+/// and the placement transform accumulated from enclosing Features nodes
+/// `current_polarity` is the effect on the layer image: a NEGATIVE Set on a NEGATIVE layer draws copper.
+#[allow(clippy::too_many_arguments)]
 fn collect_geometries_with_context(
     node: &XmlNode,
     geometries: &mut LayerGeometries,
     line_descriptors: &IndexMap<String, LineDescriptor>,
+    fill_descriptors: &IndexMap<String, FillDescriptor>,
     padstack_defs: &IndexMap<String, PadStackDef>,
+    font_stroke_widths: &HashMap<String, f32>,
     current_net: Option<&str>,
     current_component: Option<&str>,
+    current_transform: &Transform2D,
+    current_polarity: Polarity,
 ) {
     // Check if this node is a Set with a net or componentRef attribute
     let net_context = if node.name == "Set" {
@@ -242,35 +491,74 @@ fn collect_geometries_with_context(
     } else {
         current_component
     };
+
+    // A NEGATIVE Set inverts the polarity it is placed in (clear on a positive layer, draw on a negative one)
+    let polarity = if node.name == "Set" && node.attributes.get("polarity").map(|s| s.as_str()) == Some("NEGATIVE") {
+        current_polarity.flipped()
+    } else {
+        current_polarity
+    };
+
+    // Features nodes place their children with Location + Xform
+    let transform = if node.name == "Features" {
+        current_transform.then_apply(&xform::features_transform(node))
+    } else {
+        *current_transform
+    };
     
     // If this is a Polyline node, parse it
     if node.name == "Polyline" {
         if let Ok(mut polyline) = polylines::parse_polyline_node(node, line_descriptors) {
             polyline.net_name = net_context.map(|s| s.to_string());
             polyline.component_ref = component_context.map(|s| s.to_string());
-            geometries.polylines.push(polyline);
+            polyline.polarity = polarity;
+            transform.apply_to_polyline(&mut polyline);
+            push_polyline(geometries, polyline);
         }
     } else if node.name == "Line" {
         if let Ok(mut line_polyline) = polylines::parse_line_node(node, line_descriptors) {
             line_polyline.net_name = net_context.map(|s| s.to_string());
             line_polyline.component_ref = component_context.map(|s| s.to_string());
-            geometries.polylines.push(line_polyline);
+            line_polyline.polarity = polarity;
+            transform.apply_to_polyline(&mut line_polyline);
+            push_polyline(geometries, line_polyline);
+        }
+    } else if node.name == "Arc" {
+        if let Ok(mut arc_polyline) = polylines::parse_arc_node(node, line_descriptors) {
+            arc_polyline.net_name = net_context.map(|s| s.to_string());
+            arc_polyline.component_ref = component_context.map(|s| s.to_string());
+            arc_polyline.polarity = polarity;
+            transform.apply_to_polyline(&mut arc_polyline);
+            push_polyline(geometries, arc_polyline);
         }
     } else if node.name == "Polygon" {
         // Parse filled polygon shapes
-        if let Ok(mut polygon) = polygons::parse_polygon_node(node) {
+        if let Ok(mut polygon) = polygons::parse_polygon_node(node, line_descriptors, fill_descriptors) {
             polygon.net_name = net_context.map(|s| s.to_string());
             polygon.component_ref = component_context.map(|s| s.to_string());
-            geometries.polygons.push(polygon);
+            polygon.polarity = polygon_polarity(&polygon, polarity);
+            transform.apply_to_polygon(&mut polygon);
+            push_polygon(geometries, polygon);
         }
     } else if node.name == "Contour" {
         // Parse Contour elements (polygon with cutouts for copper pours)
-        if let Ok(mut polygon) = polygons::parse_contour_node(node) {
+        if let Ok(mut polygon) = polygons::parse_contour_node(node, line_descriptors, fill_descriptors) {
             polygon.net_name = net_context.map(|s| s.to_string());
             polygon.component_ref = component_context.map(|s| s.to_string());
-            geometries.polygons.push(polygon);
+            polygon.polarity = polygon_polarity(&polygon, polarity);
+            transform.apply_to_polygon(&mut polygon);
+            push_polygon(geometries, polygon);
         }
         return; // Don't recurse - we've already processed Polygon and Cutout children
+    } else if node.name == "Text" {
+        // Text is laid out with the stroke font at generation time
+        if let Ok(mut text) = text::parse_text_node(node, font_stroke_widths, &transform) {
+            text.net_name = net_context.map(|s| s.to_string());
+            text.component_ref = component_context.map(|s| s.to_string());
+            text.polarity = polarity;
+            push_text(geometries, text);
+        }
+        return;
     } else if node.name == "LayerFeature" {
         // Collect pads and vias from this layer (they handle their own net context)
         let pads = padstacks::collect_pads_from_layer(node, padstack_defs);
@@ -280,8 +568,55 @@ fn collect_geometries_with_context(
         geometries.vias.extend(vias);
     }
 
-    // Recursively search all children, passing down the net and component context
+    // Recursively search all children, passing down the net, component and placement context
     for child in &node.children {
-        collect_geometries_with_context(child, geometries, line_descriptors, padstack_defs, net_context, component_context);
+        collect_geometries_with_context(child, geometries, line_descriptors, fill_descriptors, padstack_defs, font_stroke_widths, net_context, component_context, &transform, polarity);
+    }
+}
+
+/// A VOID fill clears its area whatever the polarity it is drawn in
+fn polygon_polarity(polygon: &Polygon, polarity: Polarity) -> Polarity {
+    if polygon.fill.fill_property == FillProperty::Void {
+        Polarity::Negative
+    } else {
+        polarity
+    }
+}
+
+/// Record where a negative feature falls in document order, before it is pushed at `feature`
+fn note_negative(geometries: &mut LayerGeometries, feature: FeatureRef) {
+    geometries.negatives.push(NegativeFeature {
+        feature,
+        polylines_before: geometries.polylines.len(),
+        polygons_before: geometries.polygons.len(),
+    });
+}
+
+// Negative features stay in their lists so object indices keep matching XML element order on Save
+
+fn push_polyline(geometries: &mut LayerGeometries, polyline: Polyline) {
+    if polyline.polarity == Polarity::Negative {
+        note_negative(geometries, FeatureRef::Polyline(geometries.polylines.len()));
+    }
+    geometries.polylines.push(polyline);
+}
+
+fn push_polygon(geometries: &mut LayerGeometries, polygon: Polygon) {
+    if polygon.polarity == Polarity::Negative {
+        note_negative(geometries, FeatureRef::Polygon(geometries.polygons.len()));
+    }
+    geometries.polygons.push(polygon);
+}
+
+fn push_text(geometries: &mut LayerGeometries, text: Text) {
+    if text.polarity == Polarity::Negative {
+        note_negative(geometries, FeatureRef::Text(geometries.texts.len()));
     }
+    geometries.texts.push(text);
+}
+
+/// Value of an xsd:boolean attribute ("true" or "1")
+fn xsd_bool(value: &str) -> bool {
+    let value = value.trim();
+    value.eq_ignore_ascii_case("true") || value == "1"
 }
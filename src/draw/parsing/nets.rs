@@ -0,0 +1,188 @@
+//! Netlist parsing
+//!
+//! Builds the intended netlist from `Step/LogicalNet` (component pins per net)
+//! and `Step/PhyNetGroup/PhyNet` (physical net points per layer). Pin
+//! locations are resolved from the placed component model.
+
+use super::components::{find_steps, Component};
+use crate::parse_xml::XmlNode;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+/// A component pin on a logical net
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetPin {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refdes: Option<String>,  // None for pins that are not on a component (e.g. mounting holes)
+    pub pin: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub x: Option<f32>,  // Board position, when the component and pin are placed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub y: Option<f32>,
+}
+
+/// A PhyNetPoint: a physical access point of a net on one layer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetPoint {
+    pub x: f32,
+    pub y: f32,
+    pub layer_ref: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secondary_layer_ref: Option<String>,
+    pub net_node: String,  // END or MIDDLE
+    pub exposure: String,  // EXPOSED, COVERED_PRIMARY, COVERED_SECONDARY, COVERED
+    pub via: bool,
+    pub test: bool,
+}
+
+/// A net joined from its LogicalNet and PhyNet entries
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Net {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub net_class: Option<String>,  // POWER, GROUND, SIGNAL, ...
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub net_pair: Option<String>,
+    pub logical: bool,  // Declared by a LogicalNet
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub phy_net_group: Option<String>,
+    pub pins: Vec<NetPin>,
+    pub points: Vec<NetPoint>,
+}
+
+impl Net {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            net_class: None,
+            net_pair: None,
+            logical: false,
+            phy_net_group: None,
+            pins: Vec::new(),
+            points: Vec::new(),
+        }
+    }
+
+    /// Layers referenced by the physical net points, in first-seen order
+    pub fn point_layers(&self) -> Vec<&str> {
+        let mut layers: Vec<&str> = Vec::new();
+        for layer in self.points.iter()
+            .flat_map(|p| std::iter::once(&p.layer_ref).chain(p.secondary_layer_ref.as_ref()))
+        {
+            if !layers.contains(&layer.as_str()) {
+                layers.push(layer);
+            }
+        }
+        layers
+    }
+}
+
+/// Parse LogicalNet and PhyNetGroup elements of all Steps, keyed by net name in document order
+/// LogicalNets come first; nets that only appear as a PhyNet are appended.
+pub fn parse_nets(root: &XmlNode, components: &IndexMap<String, Component>) -> IndexMap<String, Net> {
+    let mut nets: IndexMap<String, Net> = IndexMap::new();
+    let steps = find_steps(root);
+
+    for node in steps.iter().flat_map(|s| s.children.iter().filter(|c| c.name == "LogicalNet")) {
+        let Some(name) = node.attributes.get("name") else { continue };
+        let net = nets.entry(name.clone()).or_insert_with(|| Net::new(name));
+        net.logical = true;
+        net.net_class = net.net_class.take().or_else(|| node.attributes.get("netClass").cloned());
+        net.net_pair = net.net_pair.take().or_else(|| node.attributes.get("netPair").cloned());
+
+        for pin_ref in node.children.iter().filter(|c| c.name == "PinRef") {
+            let Some(pin) = pin_ref.attributes.get("pin") else { continue };
+            let refdes = pin_ref.attributes.get("componentRef").cloned();
+            let location = refdes.as_ref()
+                .and_then(|r| components.get(r))
+                .and_then(|c| c.pins.iter().find(|p| &p.number == pin));
+            net.pins.push(NetPin {
+                pin: pin.clone(),
+                title: pin_ref.attributes.get("title").cloned(),
+                x: location.map(|p| p.x),
+                y: location.map(|p| p.y),
+                refdes,
+            });
+        }
+    }
+
+    for group in steps.iter().flat_map(|s| s.children.iter().filter(|c| c.name == "PhyNetGroup")) {
+        for phy_net in group.children.iter().filter(|c| c.name == "PhyNet") {
+            let Some(name) = phy_net.attributes.get("name") else { continue };
+            let net = nets.entry(name.clone()).or_insert_with(|| Net::new(name));
+            if net.phy_net_group.is_none() {
+                net.phy_net_group = group.attributes.get("name").cloned();
+            }
+            net.points.extend(phy_net.children.iter()
+                .filter(|c| c.name == "PhyNetPoint")
+                .filter_map(parse_net_point));
+        }
+    }
+
+    nets
+}
+
+fn parse_net_point(node: &XmlNode) -> Option<NetPoint> {
+    let attr = |name: &str| node.attributes.get(name).cloned();
+    let flag = |name: &str| node.attributes.get(name).is_some_and(|v| v.eq_ignore_ascii_case("true"));
+    Some(NetPoint {
+        x: node.attributes.get("x")?.parse().ok()?,
+        y: node.attributes.get("y")?.parse().ok()?,
+        layer_ref: attr("layerRef")?,
+        secondary_layer_ref: attr("secondaryLayerRef"),
+        net_node: attr("netNode").unwrap_or_default(),
+        exposure: attr("exposure").unwrap_or_default(),
+        via: flag("via"),
+        test: flag("test"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::draw::parsing::ComponentPin;
+
+    #[test]
+    fn test_logical_and_physical_nets() {
+        let step = XmlNode::new("Step", &[("name", "board")], vec![
+            XmlNode::new("LogicalNet", &[("name", "GND"), ("netClass", "GROUND")], vec![
+                XmlNode::new("PinRef", &[("componentRef", "R1"), ("pin", "2")], vec![]),
+                XmlNode::new("PinRef", &[("componentRef", "U9"), ("pin", "4")], vec![]),
+            ]),
+            XmlNode::new("PhyNetGroup", &[("name", "PHY")], vec![
+                XmlNode::new("PhyNet", &[("name", "GND")], vec![
+                    XmlNode::new("PhyNetPoint", &[("x", "1"), ("y", "2"), ("layerRef", "TOP"), ("netNode", "END"), ("exposure", "EXPOSED")], vec![]),
+                    XmlNode::new("PhyNetPoint", &[("x", "3"), ("y", "2"), ("layerRef", "TOP"), ("secondaryLayerRef", "BOTTOM"),
+                        ("netNode", "MIDDLE"), ("exposure", "COVERED"), ("via", "true")], vec![]),
+                ]),
+                XmlNode::new("PhyNet", &[("name", "TP1")], vec![]),
+            ]),
+        ]);
+        let root = XmlNode::new("IPC-2581", &[], vec![XmlNode::new("Ecad", &[], vec![XmlNode::new("CadData", &[], vec![step])])]);
+
+        let mut components = IndexMap::new();
+        components.insert("R1".to_string(), Component {
+            refdes: "R1".to_string(), package_ref: String::new(), part: None, layer_ref: "TOP".to_string(),
+            side: "TOP".to_string(), mount_type: None, x: 0.0, y: 0.0, rotation: 0.0, mirror: false,
+            height: None, pickup_point: None, bounds: [0.0; 4],
+            pins: vec![ComponentPin { number: "2".to_string(), name: None, pin_type: None,
+                electrical_type: None, mount_type: None, x: 5.0, y: 6.0 }],
+        });
+
+        let nets = parse_nets(&root, &components);
+        assert_eq!(nets.keys().collect::<Vec<_>>(), vec!["GND", "TP1"]);
+
+        let gnd = &nets["GND"];
+        assert!(gnd.logical);
+        assert_eq!(gnd.net_class.as_deref(), Some("GROUND"));
+        assert_eq!((gnd.pins[0].x, gnd.pins[0].y), (Some(5.0), Some(6.0)));
+        assert_eq!(gnd.pins[1].x, None);  // U9 is not placed
+        assert_eq!(gnd.phy_net_group.as_deref(), Some("PHY"));
+        assert!(gnd.points[1].via);
+        assert_eq!(gnd.point_layers(), vec!["TOP", "BOTTOM"]);
+
+        assert!(!nets["TP1"].logical);
+    }
+}
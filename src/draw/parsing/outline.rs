@@ -0,0 +1,174 @@
+//! Board outline parsing
+//!
+//! Reads `Step/Profile` (an outer Polygon plus Cutout holes) into the board
+//! outline. Arcs are kept as curved paths so the synthetic outline layer is
+//! tessellated per LOD like any other curved polyline.
+
+use super::components::find_steps;
+use super::polygons::parse_path_step;
+use crate::draw::geometry::*;
+use crate::draw::tessellation::{flatten_path, ARC_CHORD_TOLERANCE_LOD};
+use crate::parse_xml::XmlNode;
+use serde::{Deserialize, Serialize};
+
+/// Layer id of the synthetic outline layer (not a layerRef used by the file)
+pub const OUTLINE_LAYER_ID: &str = "PROFILE";
+pub const OUTLINE_LAYER_NAME: &str = "Board Outline";
+pub const OUTLINE_COLOR: [f32; 4] = [1.0, 1.0, 0.0, 1.0];
+/// Stroke width of the rendered outline; wide enough to survive LOD culling up to LOD3
+const OUTLINE_LINE_WIDTH: f32 = 0.25;
+
+/// Board outline from Profile: outer boundary with cutouts, in millimetres
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoardOutline {
+    pub outline: Vec<Point>,        // Outer boundary, arcs flattened at LOD0 tolerance
+    pub cutouts: Vec<Vec<Point>>,
+    pub bounds: [f32; 4],           // [min_x, min_y, max_x, max_y] of the outer boundary
+    pub area: f32,                  // Outer area minus cutouts
+    #[serde(skip)]
+    paths: Vec<CurvedPath>,         // Outer boundary followed by cutouts, with true arcs
+}
+
+impl BoardOutline {
+    /// Geometry for the synthetic outline layer: one closed polyline per boundary
+    pub fn to_layer_geometries(&self) -> LayerGeometries {
+        let polylines = self.paths.iter()
+            .map(|path| Polyline {
+                points: flatten_path(path, ARC_CHORD_TOLERANCE_LOD[0]),
+                width: OUTLINE_LINE_WIDTH,
+                color: OUTLINE_COLOR,
+                line_end: LineEnd::Round,
+                net_name: None,
+                component_ref: None,
+                curved_path: path.steps.iter()
+                    .any(|s| matches!(s, PathStep::Curve { .. }))
+                    .then(|| path.clone()),
+                polarity: Polarity::Positive,
+            })
+            .collect();
+
+        LayerGeometries {
+            polylines,
+            ..LayerGeometries::new(OUTLINE_LAYER_ID)
+        }
+    }
+}
+
+/// Parse the Profile of the first Step that has one
+pub fn parse_board_outline(root: &XmlNode) -> Option<BoardOutline> {
+    find_steps(root).into_iter()
+        .find(|step| step.children.iter().any(|c| c.name == "Profile"))
+        .and_then(parse_step_outline)
+}
+
+/// Parse the Profile of one Step
+pub fn parse_step_outline(step: &XmlNode) -> Option<BoardOutline> {
+    let profile = step.children.iter().find(|c| c.name == "Profile")?;
+
+    let outer = profile.children.iter()
+        .find(|c| c.name == "Polygon")
+        .and_then(parse_closed_path)?;
+    let cutouts: Vec<CurvedPath> = profile.children.iter()
+        .filter(|c| c.name == "Cutout")
+        .filter_map(parse_closed_path)
+        .collect();
+
+    let outline = flatten_path(&outer, ARC_CHORD_TOLERANCE_LOD[0]);
+    if outline.len() < 3 {
+        eprintln!("[Outline] Profile polygon has fewer than 3 points, ignoring");
+        return None;
+    }
+    let cutout_rings: Vec<Vec<Point>> = cutouts.iter()
+        .map(|c| flatten_path(c, ARC_CHORD_TOLERANCE_LOD[0]))
+        .collect();
+
+    let mut bounds = [f32::MAX, f32::MAX, f32::MIN, f32::MIN];
+    for p in &outline {
+        bounds[0] = bounds[0].min(p.x);
+        bounds[1] = bounds[1].min(p.y);
+        bounds[2] = bounds[2].max(p.x);
+        bounds[3] = bounds[3].max(p.y);
+    }
+    let area = ring_area(&outline) - cutout_rings.iter().map(|r| ring_area(r)).sum::<f32>();
+
+    let mut paths = vec![outer];
+    paths.extend(cutouts);
+
+    Some(BoardOutline {
+        outline,
+        cutouts: cutout_rings,
+        bounds,
+        area: area.max(0.0),
+        paths,
+    })
+}
+
+/// Parse PolyBegin/PolyStep* children into a path closed back to its start
+fn parse_closed_path(node: &XmlNode) -> Option<CurvedPath> {
+    let mut steps = node.children.iter().filter_map(|c| parse_path_step(c).map(|s| (c, s)));
+    let (first, start) = steps.next()?;
+    if first.name != "PolyBegin" {
+        return None;
+    }
+    let start = start.end_point();
+    let mut path = CurvedPath { start, steps: steps.map(|(_, s)| s).collect() };
+
+    let end = path.steps.last()?.end_point();
+    if (end.x - start.x).abs() > 1e-6 || (end.y - start.y).abs() > 1e-6 {
+        path.steps.push(PathStep::Segment(start));
+    }
+    Some(path)
+}
+
+/// Unsigned shoelace area of a ring
+fn ring_area(ring: &[Point]) -> f32 {
+    let twice: f32 = ring.iter()
+        .zip(ring.iter().cycle().skip(1))
+        .map(|(a, b)| a.x * b.y - b.x * a.y)
+        .sum();
+    twice.abs() / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pt(name: &str, x: &str, y: &str) -> XmlNode {
+        XmlNode::new(name, &[("x", x), ("y", y)], vec![])
+    }
+
+    #[test]
+    fn test_profile_with_arc_and_cutout() {
+        // 10 x 10 square whose top edge is replaced by a half circle of radius 5
+        let polygon = XmlNode::new("Polygon", &[], vec![
+            pt("PolyBegin", "0", "0"),
+            pt("PolyStepSegment", "10", "0"),
+            pt("PolyStepSegment", "10", "10"),
+            XmlNode::new("PolyStepCurve", &[("x", "0"), ("y", "10"), ("centerX", "5"), ("centerY", "10"), ("clockwise", "false")], vec![]),
+        ]);
+        let cutout = XmlNode::new("Cutout", &[], vec![
+            pt("PolyBegin", "2", "2"),
+            pt("PolyStepSegment", "4", "2"),
+            pt("PolyStepSegment", "4", "4"),
+            pt("PolyStepSegment", "2", "4"),
+            pt("PolyStepSegment", "2", "2"),
+        ]);
+        let step = XmlNode::new("Step", &[("name", "board")], vec![XmlNode::new("Profile", &[], vec![polygon, cutout])]);
+        let root = XmlNode::new("IPC-2581", &[], vec![XmlNode::new("Ecad", &[], vec![XmlNode::new("CadData", &[], vec![step])])]);
+
+        let outline = parse_board_outline(&root).expect("outline");
+        assert_eq!(outline.cutouts.len(), 1);
+        assert!((outline.bounds[3] - 15.0).abs() < 1e-3);
+
+        let expected = 100.0 + std::f32::consts::PI * 12.5 - 4.0;
+        assert!((outline.area - expected).abs() < 0.1, "area {}", outline.area);
+
+        // The open outer path is closed back to its start; the arc is kept for LOD tessellation
+        let geometries = outline.to_layer_geometries();
+        assert_eq!(geometries.polylines.len(), 2);
+        let outer = &geometries.polylines[0];
+        assert!(outer.curved_path.is_some());
+        let last = outer.points.last().unwrap();
+        assert!(last.x.abs() < 1e-5 && last.y.abs() < 1e-5);
+    }
+}
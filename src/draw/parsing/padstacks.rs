@@ -6,6 +6,7 @@ use crate::draw::geometry::*;
 use crate::parse_xml::XmlNode;
 use indexmap::IndexMap;
 use std::collections::HashMap;
+use super::xform::parse_xform;
 
 /// Collect pad instances from LayerFeature nodes
 pub fn collect_pads_from_layer(layer_node: &XmlNode, padstack_defs: &IndexMap<String, PadStackDef>) -> Vec<PadInstance> {
@@ -46,7 +47,7 @@ pub fn collect_pads_from_layer(layer_node: &XmlNode, padstack_defs: &IndexMap<St
             
             let mut x = 0.0;
             let mut y = 0.0;
-            let mut rotation = 0.0;
+            let mut xform = Xform::default();
             let mut shape_id = String::new();
             let mut component_ref = component_context.map(|s| s.to_string());
             let mut pin_ref: Option<String> = None;
@@ -62,9 +63,7 @@ pub fn collect_pads_from_layer(layer_node: &XmlNode, padstack_defs: &IndexMap<St
                             .unwrap_or(0.0);
                     }
                     "Xform" => {
-                        rotation = child.attributes.get("rotation")
-                            .and_then(|v| v.parse().ok())
-                            .unwrap_or(0.0);
+                        xform = parse_xform(child);
                     }
                     "StandardPrimitiveRef" => {
                         shape_id = child.attributes.get("id")
@@ -87,9 +86,11 @@ pub fn collect_pads_from_layer(layer_node: &XmlNode, padstack_defs: &IndexMap<St
             if !shape_id.is_empty() {
                 pads.push(PadInstance {
                     shape_id,
-                    x,
-                    y,
-                    rotation,
+                    x: x + xform.x_offset,
+                    y: y + xform.y_offset,
+                    rotation: xform.rotation,
+                    mirror: xform.mirror,
+                    scale: xform.scale,
                     net_name: net_context.map(|s| s.to_string()),
                     component_ref,
                     pin_ref,
@@ -144,6 +145,7 @@ pub fn collect_vias_from_layer(layer_node: &XmlNode, padstack_defs: &IndexMap<St
                     if in_via_set || def.hole_diameter > 0.01 {
                         let mut x = 0.0;
                         let mut y = 0.0;
+                        let mut xform = Xform::default();
                         let mut component_ref = component_context.map(|s| s.to_string());
                         let mut pin_ref: Option<String> = None;
                         
@@ -157,6 +159,9 @@ pub fn collect_vias_from_layer(layer_node: &XmlNode, padstack_defs: &IndexMap<St
                                         .and_then(|v| v.parse().ok())
                                         .unwrap_or(0.0);
                                 }
+                                "Xform" => {
+                                    xform = parse_xform(child);
+                                }
                                 "PinRef" => {
                                     // Get componentRef and pin from PinRef child element
                                     if let Some(comp_ref) = child.attributes.get("componentRef") {
@@ -170,15 +175,18 @@ pub fn collect_vias_from_layer(layer_node: &XmlNode, padstack_defs: &IndexMap<St
                             }
                         }
                         
+                        // Via shapes are symmetric about their Y axis, so mirror has no visible effect
                         vias.push(ViaInstance {
-                            x,
-                            y,
-                            diameter: def.outer_diameter,
-                            hole_diameter: def.hole_diameter,
-                            shape: def.shape.clone(),
+                            x: x + xform.x_offset,
+                            y: y + xform.y_offset,
+                            rotation: xform.rotation,
+                            diameter: def.outer_diameter * xform.scale,
+                            hole_diameter: def.hole_diameter * xform.scale,
+                            shape: def.shape.scaled(xform.scale),
                             net_name: net_context.map(|s| s.to_string()),
                             component_ref,
                             pin_ref,
+                            outside_span: false,
                         });
                     }
                 }
@@ -234,7 +242,6 @@ pub fn collect_padstacks_from_step(
                             // Parse location
                             let mut x = 0.0;
                             let mut y = 0.0;
-                            let mut rotation = 0.0;
                             
                             // Find Location node
                             if let Some(loc_node) = subchild.children.iter().find(|n| n.name == "Location") {
@@ -242,10 +249,13 @@ pub fn collect_padstacks_from_step(
                                 y = loc_node.attributes.get("y").and_then(|s| s.parse::<f32>().ok()).unwrap_or(0.0);
                             }
                             
-                            // Find Xform for rotation
-                            if let Some(xform_node) = subchild.children.iter().find(|n| n.name == "Xform") {
-                                rotation = xform_node.attributes.get("rotation").and_then(|s| s.parse::<f32>().ok()).unwrap_or(0.0);
-                            }
+                            // Find Xform (rotation, mirror, scale and offset)
+                            let xform = subchild.children.iter()
+                                .find(|n| n.name == "Xform")
+                                .map(parse_xform)
+                                .unwrap_or_default();
+                            x += xform.x_offset;
+                            y += xform.y_offset;
                             
                             // Find StandardPrimitiveRef
                             if let Some(prim_ref) = subchild.children.iter().find(|n| n.name == "StandardPrimitiveRef") {
@@ -259,35 +269,24 @@ pub fn collect_padstacks_from_step(
                                     }
                                     
                                     let layer_geom = layer_contexts.entry(layer_ref.clone())
-                                        .or_insert_with(|| LayerGeometries {
-                                            layer_ref: layer_ref.clone(),
-                                            polylines: Vec::new(),
-                                            polygons: Vec::new(),
-                                            padstack_holes: Vec::new(),
-                                            pads: Vec::new(),
-                                            vias: Vec::new(),
-                                        });
+                                        .or_insert_with(|| LayerGeometries::new(layer_ref));
                                     
                                     if is_via {
                                         // Has hole - treat as via
                                         if let Some(primitive) = primitives.get(prim_id) {
-                                            let outer_diameter = match primitive {
-                                                StandardPrimitive::Circle { diameter } => *diameter,
-                                                StandardPrimitive::Rectangle { width, height } => width.max(*height),
-                                                StandardPrimitive::Oval { width, height } => width.max(*height),
-                                                StandardPrimitive::RoundRect { width, height, .. } => width.max(*height),
-                                                StandardPrimitive::CustomPolygon { .. } => 0.0,
-                                            };
+                                            let outer_diameter = primitive.outer_diameter();
                                             
                                             layer_geom.vias.push(ViaInstance {
                                                 x,
                                                 y,
-                                                diameter: outer_diameter,
-                                                hole_diameter,
-                                                shape: primitive.clone(),
+                                                rotation: xform.rotation,
+                                                diameter: outer_diameter * xform.scale,
+                                                hole_diameter: hole_diameter * xform.scale,
+                                                shape: primitive.scaled(xform.scale),
                                                 net_name: net_name.clone(),
                                                 component_ref,
                                                 pin_ref,
+                                                outside_span: false,
                                             });
                                         }
                                     } else {
@@ -296,7 +295,9 @@ pub fn collect_padstacks_from_step(
                                             shape_id: prim_id.clone(),
                                             x,
                                             y,
-                                            rotation,
+                                            rotation: xform.rotation,
+                                            mirror: xform.mirror,
+                                            scale: xform.scale,
                                             net_name: net_name.clone(),
                                             component_ref,
                                             pin_ref,
@@ -316,3 +317,58 @@ pub fn collect_padstacks_from_step(
         collect_padstacks_from_step(child, layer_contexts, primitives);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scaled_via_pad(children: Vec<XmlNode>) -> Vec<XmlNode> {
+        let mut pad = vec![
+            XmlNode::new("Location", &[("x", "1"), ("y", "2")], vec![]),
+            XmlNode::new("Xform", &[("scale", "2")], vec![]),
+        ];
+        pad.extend(children);
+        pad
+    }
+
+    #[test]
+    fn test_layer_via_scaled_by_xform() {
+        let defs = IndexMap::from([("VIA1".to_string(), PadStackDef {
+            hole_diameter: 0.3,
+            outer_diameter: 0.6,
+            shape: StandardPrimitive::Circle { diameter: 0.6 },
+        })]);
+        let layer = XmlNode::new("LayerFeature", &[("layerRef", "TOP")], vec![
+            XmlNode::new("Set", &[("padUsage", "VIA"), ("net", "GND")], vec![
+                XmlNode::new("Pad", &[("padstackDefRef", "VIA1")], scaled_via_pad(vec![])),
+            ]),
+        ]);
+
+        let vias = collect_vias_from_layer(&layer, &defs);
+        assert_eq!(vias.len(), 1);
+        assert_eq!((vias[0].x, vias[0].y), (1.0, 2.0));
+        assert!((vias[0].diameter - 1.2).abs() < 1e-6);
+        assert!((vias[0].hole_diameter - 0.6).abs() < 1e-6);
+        assert!(matches!(vias[0].shape, StandardPrimitive::Circle { diameter } if (diameter - 1.2).abs() < 1e-6));
+    }
+
+    #[test]
+    fn test_step_padstack_via_scaled_by_xform() {
+        let primitives = HashMap::from([("C06".to_string(), StandardPrimitive::Circle { diameter: 0.6 })]);
+        let step = XmlNode::new("Step", &[("name", "board")], vec![
+            XmlNode::new("PadStack", &[("net", "GND")], vec![
+                XmlNode::new("LayerHole", &[("name", "DRILL"), ("diameter", "0.3")], vec![]),
+                XmlNode::new("LayerPad", &[("layerRef", "TOP")], scaled_via_pad(vec![
+                    XmlNode::new("StandardPrimitiveRef", &[("id", "C06")], vec![]),
+                ])),
+            ]),
+        ]);
+
+        let mut layers = IndexMap::new();
+        collect_padstacks_from_step(&step, &mut layers, &primitives);
+        let vias = &layers["TOP"].vias;
+        assert_eq!(vias.len(), 1);
+        assert!((vias[0].diameter - 1.2).abs() < 1e-6);
+        assert!((vias[0].hole_diameter - 0.6).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,314 @@
+//! Feature polarity resolution
+//!
+//! Negative features (in a NEGATIVE Set, or any feature on a NEGATIVE layer) erase
+//! the copper drawn before them. They are subtracted from earlier positive polygons
+//! and polylines before tessellation, so the renderer and DRC both see resolved
+//! copper. A NEGATIVE layer starts as a plane covering the board outline.
+//!
+//! Objects keep their index because Save maps indices to XML elements: negative
+//! features and cut traces are left empty, and the copper that remains of a cut
+//! trace (or of a pour split into islands) is appended as extra polygons.
+//! Pads, vias and text are drawn as they are and never cut.
+
+use super::outline::BoardOutline;
+use crate::draw::generation::layout_text;
+use crate::draw::geometry::*;
+use crate::draw::tessellation::{contours_bounds, polygon_contours, ring_area, shapes_area, stroke_contours};
+use i_overlay::core::fill_rule::FillRule;
+use i_overlay::core::overlay_rule::OverlayRule;
+use i_overlay::float::single::SingleFloatOverlay;
+
+/// Fill of the plane a NEGATIVE layer starts from (same default as unstyled polygons)
+const PLANE_FILL_COLOR: [f32; 4] = [0.5, 0.5, 0.5, 0.5];
+/// Overlap area (mm²) below which a clearance is treated as only touching a feature
+const MIN_OVERLAP_AREA: f32 = 1e-6;
+
+/// Area erased by a negative feature, as contours filled with the non-zero rule
+struct Clearance {
+    contours: Vec<Vec<Point>>,
+    bounds: [f32; 4],
+    polylines_before: usize,
+    polygons_before: usize,
+}
+
+/// Subtract negative features from the positive copper drawn before them
+/// `outline` bounds the plane of a NEGATIVE layer; without one the extent of the layer's features is used.
+pub fn resolve_polarity(geometries: &mut LayerGeometries, layer_polarity: Polarity, outline: Option<&BoardOutline>) {
+    if geometries.negatives.is_empty() && layer_polarity == Polarity::Positive {
+        return;
+    }
+
+    let clearances: Vec<Clearance> = geometries.negatives.iter()
+        .filter_map(|negative| {
+            let contours = match negative.feature {
+                FeatureRef::Polyline(i) => polyline_contours(&geometries.polylines[i]),
+                FeatureRef::Polygon(i) => polygon_contours(&geometries.polygons[i]),
+                FeatureRef::Text(i) => layout_text(&geometries.texts[i]).iter().flat_map(polyline_contours).collect(),
+            };
+            Some(Clearance {
+                bounds: contours_bounds(&contours)?,
+                contours,
+                polylines_before: negative.polylines_before,
+                polygons_before: negative.polygons_before,
+            })
+        })
+        .collect();
+
+    let mut pieces = Vec::new();
+
+    // A NEGATIVE layer is a plane drawn before every feature, so every clearance cuts it
+    if layer_polarity == Polarity::Negative {
+        let plane = plane_polygon(geometries, outline);
+        let all: Vec<&Clearance> = clearances.iter().collect();
+        match cut(&polygon_contours(&plane), &all) {
+            Some(shapes) => pieces.extend(shapes.into_iter().map(|shape| shape_polygon(shape, &plane))),
+            None => pieces.push(plane),
+        }
+    }
+
+    for (i, polygon) in geometries.polygons.iter_mut().enumerate() {
+        if polygon.polarity == Polarity::Negative {
+            continue;
+        }
+        let contours = polygon_contours(polygon);
+        let Some(bounds) = contours_bounds(&contours) else { continue };
+        let later = later_clearances(&clearances, bounds, |c| c.polygons_before > i);
+        let Some(mut shapes) = cut(&contours, &later) else { continue };
+
+        // The largest island keeps the polygon's index; any others are appended
+        shapes.sort_by(|a, b| ring_area(&b[0]).total_cmp(&ring_area(&a[0])));
+        let mut shapes = shapes.into_iter();
+        let template = polygon.clone();
+        match shapes.next() {
+            Some(mut shape) => {
+                polygon.outer_ring = shape.remove(0);
+                polygon.holes = shape;
+                polygon.curved_rings = None;
+            }
+            None => clear_polygon(polygon),
+        }
+        pieces.extend(shapes.map(|shape| shape_polygon(shape, &template)));
+    }
+
+    for (i, polyline) in geometries.polylines.iter_mut().enumerate() {
+        if polyline.polarity == Polarity::Negative {
+            continue;
+        }
+        let contours = polyline_contours(polyline);
+        let Some(bounds) = contours_bounds(&contours) else { continue };
+        let later = later_clearances(&clearances, bounds, |c| c.polylines_before > i);
+        let Some(shapes) = cut(&contours, &later) else { continue };
+
+        // What is left of a cut trace is filled copper, not a stroke
+        let template = Polygon {
+            outer_ring: Vec::new(),
+            holes: Vec::new(),
+            curved_rings: None,
+            fill_color: polyline.color,
+            net_name: polyline.net_name.clone(),
+            component_ref: polyline.component_ref.clone(),
+            polarity: Polarity::Positive,
+            fill: FillDescriptor::default(),
+            line_width: None,
+        };
+        pieces.extend(shapes.into_iter().map(|shape| shape_polygon(shape, &template)));
+        clear_polyline(polyline);
+    }
+
+    // Negative features have done their work; they draw nothing themselves
+    for negative in std::mem::take(&mut geometries.negatives) {
+        match negative.feature {
+            FeatureRef::Polyline(i) => clear_polyline(&mut geometries.polylines[i]),
+            FeatureRef::Polygon(i) => clear_polygon(&mut geometries.polygons[i]),
+            FeatureRef::Text(_) => {}
+        }
+    }
+    geometries.texts.retain(|t| t.polarity == Polarity::Positive);
+    geometries.polygons.extend(pieces);
+}
+
+/// Clearances drawn after a feature whose bounds overlap it
+fn later_clearances(clearances: &[Clearance], bounds: [f32; 4], is_later: impl Fn(&Clearance) -> bool) -> Vec<&Clearance> {
+    clearances.iter()
+        .filter(|c| is_later(c))
+        .filter(|c| c.bounds[0] <= bounds[2] && c.bounds[2] >= bounds[0] && c.bounds[1] <= bounds[3] && c.bounds[3] >= bounds[1])
+        .collect()
+}
+
+/// Subtract clearances from a shape; None when they do not overlap it, so the feature is kept as is
+fn cut(contours: &[Vec<Point>], clearances: &[&Clearance]) -> Option<Vec<Vec<Vec<Point>>>> {
+    if clearances.is_empty() {
+        return None;
+    }
+    let clip: Vec<Vec<Point>> = clearances.iter().flat_map(|c| c.contours.iter().cloned()).collect();
+    let subject = contours.to_vec();
+
+    let overlap = subject.overlay(&clip, OverlayRule::Intersect, FillRule::NonZero);
+    if shapes_area(&overlap) < MIN_OVERLAP_AREA {
+        return None;
+    }
+    Some(subject.overlay(&clip, OverlayRule::Difference, FillRule::NonZero))
+}
+
+/// Polygon for one overlay result shape (outer contour then holes), styled like `template`
+fn shape_polygon(mut shape: Vec<Vec<Point>>, template: &Polygon) -> Polygon {
+    let outer_ring = shape.remove(0);
+    Polygon {
+        outer_ring,
+        holes: shape,
+        curved_rings: None,
+        ..template.clone()
+    }
+}
+
+/// Plane of a NEGATIVE layer: the board outline with its cutouts, or the extent of the layer's features
+fn plane_polygon(geometries: &LayerGeometries, outline: Option<&BoardOutline>) -> Polygon {
+    let (outer_ring, holes) = match outline {
+        Some(outline) => (outline.outline.clone(), outline.cutouts.clone()),
+        None => {
+            let contours: Vec<Vec<Point>> = geometries.polylines.iter().flat_map(polyline_contours)
+                .chain(geometries.polygons.iter().flat_map(polygon_contours))
+                .collect();
+            let [min_x, min_y, max_x, max_y] = contours_bounds(&contours).unwrap_or([0.0; 4]);
+            eprintln!("[Polarity] Negative layer {} without a board outline, using its feature extent", geometries.layer_ref);
+            let corners = vec![
+                Point { x: min_x, y: min_y },
+                Point { x: max_x, y: min_y },
+                Point { x: max_x, y: max_y },
+                Point { x: min_x, y: max_y },
+            ];
+            (corners, Vec::new())
+        }
+    };
+    Polygon {
+        outer_ring,
+        holes,
+        curved_rings: None,
+        fill_color: PLANE_FILL_COLOR,
+        net_name: None,
+        component_ref: None,
+        polarity: Polarity::Positive,
+        fill: FillDescriptor::default(),
+        line_width: None,
+    }
+}
+
+fn polyline_contours(polyline: &Polyline) -> Vec<Vec<Point>> {
+    stroke_contours(&polyline.points, polyline.width, polyline.line_end)
+}
+
+fn clear_polyline(polyline: &mut Polyline) {
+    polyline.points.clear();
+    polyline.curved_path = None;
+}
+
+fn clear_polygon(polygon: &mut Polygon) {
+    polygon.outer_ring.clear();
+    polygon.holes.clear();
+    polygon.curved_rings = None;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(x: f32, y: f32, size: f32, polarity: Polarity) -> Polygon {
+        Polygon {
+            outer_ring: vec![
+                Point { x, y },
+                Point { x: x + size, y },
+                Point { x: x + size, y: y + size },
+                Point { x, y: y + size },
+            ],
+            holes: Vec::new(),
+            curved_rings: None,
+            fill_color: [1.0; 4],
+            net_name: Some("GND".to_string()),
+            component_ref: None,
+            polarity,
+            fill: FillDescriptor::default(),
+            line_width: None,
+        }
+    }
+
+    fn trace(from: (f32, f32), to: (f32, f32), polarity: Polarity) -> Polyline {
+        Polyline {
+            points: vec![Point { x: from.0, y: from.1 }, Point { x: to.0, y: to.1 }],
+            width: 0.2,
+            color: [1.0; 4],
+            line_end: LineEnd::Butt,
+            net_name: Some("SIG".to_string()),
+            component_ref: None,
+            curved_path: None,
+            polarity,
+        }
+    }
+
+    fn layer(polylines: Vec<Polyline>, polygons: Vec<Polygon>, negatives: Vec<NegativeFeature>) -> LayerGeometries {
+        LayerGeometries {
+            layer_ref: "L2".to_string(),
+            polylines,
+            polygons,
+            padstack_holes: Vec::new(),
+            pads: Vec::new(),
+            vias: Vec::new(),
+            texts: Vec::new(),
+            negatives,
+        }
+    }
+
+    fn polygon_area(polygon: &Polygon) -> f32 {
+        ring_area(&polygon.outer_ring) - polygon.holes.iter().map(|h| ring_area(h)).sum::<f32>()
+    }
+
+    #[test]
+    fn test_negative_feature_erases_only_earlier_copper() {
+        // Pour, then a trace across it, then a negative void, then an island inside the void
+        let polygons = vec![
+            square(0.0, 0.0, 10.0, Polarity::Positive),
+            square(3.0, 3.0, 4.0, Polarity::Negative),
+            square(4.0, 4.0, 2.0, Polarity::Positive),
+        ];
+        let polylines = vec![trace((0.0, 5.0), (10.0, 5.0), Polarity::Positive)];
+        let negatives = vec![NegativeFeature { feature: FeatureRef::Polygon(1), polylines_before: 1, polygons_before: 1 }];
+        let mut geometries = layer(polylines, polygons, negatives);
+
+        resolve_polarity(&mut geometries, Polarity::Positive, None);
+
+        // Indices are kept: pour has a hole, the void and the cut trace draw nothing, the island is intact
+        assert!((polygon_area(&geometries.polygons[0]) - 84.0).abs() < 1e-3);
+        assert!(geometries.polygons[1].outer_ring.is_empty());
+        assert!((polygon_area(&geometries.polygons[2]) - 4.0).abs() < 1e-4);
+        assert!(geometries.polylines[0].points.is_empty());
+        assert!(geometries.negatives.is_empty());
+
+        // The two ends of the trace remain as filled copper on its net
+        let trace_pieces = &geometries.polygons[3..];
+        assert_eq!(trace_pieces.len(), 2);
+        assert!(trace_pieces.iter().all(|p| p.net_name.as_deref() == Some("SIG")));
+        let area: f32 = trace_pieces.iter().map(polygon_area).sum();
+        assert!((area - 6.0 * 0.2).abs() < 1e-3, "area {}", area);
+    }
+
+    #[test]
+    fn test_negative_layer_is_a_plane_minus_its_features() {
+        // On a NEGATIVE layer drawn features are clearances and a NEGATIVE Set draws copper;
+        // the walker has already flipped both, and without an outline the plane spans the features
+        let polygons = vec![
+            square(0.0, 0.0, 5.0, Polarity::Positive),
+            square(2.0, 2.0, 1.0, Polarity::Negative),
+        ];
+        let negatives = vec![NegativeFeature { feature: FeatureRef::Polygon(1), polylines_before: 0, polygons_before: 1 }];
+        let mut geometries = layer(Vec::new(), polygons, negatives);
+
+        resolve_polarity(&mut geometries, Polarity::Negative, None);
+
+        assert_eq!(geometries.polygons.len(), 3);
+        assert!(geometries.polygons[1].outer_ring.is_empty());
+        for copper in [&geometries.polygons[0], &geometries.polygons[2]] {
+            assert_eq!(copper.holes.len(), 1);
+            assert!((polygon_area(copper) - 24.0).abs() < 1e-3);
+        }
+        assert_eq!(geometries.polygons[2].net_name, None);
+    }
+}
@@ -5,67 +5,24 @@
 use crate::draw::geometry::*;
 use crate::parse_xml::XmlNode;
 use super::colors::parse_color;
+use super::descriptors::parse_fill_desc;
+use super::polylines::parse_line_style;
+use indexmap::IndexMap;
+use crate::draw::tessellation::{flatten_path, tessellate_arc, ARC_CHORD_TOLERANCE_LOD};
 
 /// Parse a Polygon node (filled shape with optional holes)
-/// Expects <Polygon> with PolyBegin/PolyStepSegment children
-pub fn parse_polygon_node(node: &XmlNode) -> Result<Polygon, anyhow::Error> {
-    let mut outer_ring: Vec<Point> = Vec::new();
-    let mut current_ring: Vec<Point> = Vec::new();
-    let mut holes: Vec<Vec<Point>> = Vec::new();
-    let mut is_first_contour = true;
-    
+/// Expects <Polygon> with PolyBegin/PolyStepSegment/PolyStepCurve children and an optional LineDesc and FillDesc
+/// Each PolyBegin starts a contour: the first is the outer ring, later ones are holes.
+pub fn parse_polygon_node(
+    node: &XmlNode,
+    line_descriptors: &IndexMap<String, LineDescriptor>,
+    fill_descriptors: &IndexMap<String, FillDescriptor>,
+) -> Result<Polygon, anyhow::Error> {
     // Extract fill color from attributes or use default with alpha
     let fill_color = parse_color(&node.attributes).unwrap_or([0.5, 0.5, 0.5, 0.5]);
     
     // Parse polygon contours (outer ring + holes)
-    for child in &node.children {
-        match child.name.as_str() {
-            "PolyBegin" => {
-                // Save previous contour if exists
-                if !current_ring.is_empty() {
-                    if is_first_contour {
-                        outer_ring = current_ring.clone();
-                        is_first_contour = false;
-                    } else {
-                        holes.push(current_ring.clone());
-                    }
-                    current_ring.clear();
-                }
-                
-                // Start new contour
-                if let (Some(x_str), Some(y_str)) = (
-                    child.attributes.get("x"),
-                    child.attributes.get("y"),
-                ) {
-                    if let (Ok(x), Ok(y)) = (x_str.parse::<f32>(), y_str.parse::<f32>()) {
-                        current_ring.push(Point { x, y });
-                    }
-                }
-            }
-            "PolyStepSegment" | "PolyStepCurve" => {
-                // Add point to current contour
-                if let (Some(x_str), Some(y_str)) = (
-                    child.attributes.get("x"),
-                    child.attributes.get("y"),
-                ) {
-                    if let (Ok(x), Ok(y)) = (x_str.parse::<f32>(), y_str.parse::<f32>()) {
-                        current_ring.push(Point { x, y });
-                    }
-                }
-            }
-            _ => {}
-        }
-    }
-    
-    // Save last contour
-    if !current_ring.is_empty() {
-        if is_first_contour {
-            outer_ring = current_ring;
-        } else {
-            holes.push(current_ring);
-        }
-    }
-    
+    let (outer_ring, holes, curved_rings) = flatten_rings(parse_contour_paths(node));
     if outer_ring.len() < 3 {
         return Err(anyhow::anyhow!("Polygon must have at least 3 points"));
     }
@@ -73,36 +30,49 @@ pub fn parse_polygon_node(node: &XmlNode) -> Result<Polygon, anyhow::Error> {
     Ok(Polygon {
         outer_ring,
         holes,
+        curved_rings,
         fill_color,
         net_name: None, // Will be set by caller with net context
         component_ref: None, // Will be set by caller with component context
+        polarity: Polarity::Positive, // Will be set by caller with Set and layer polarity
+        fill: parse_polygon_fill(node, fill_descriptors),
+        line_width: parse_polygon_line_width(node, line_descriptors),
     })
 }
 
 /// Parse a Contour node (copper pour with cutouts)
 /// Expects <Contour> with <Polygon> (outer boundary) and <Cutout> children (holes)
-pub fn parse_contour_node(node: &XmlNode) -> Result<Polygon, anyhow::Error> {
-    let mut outer_ring: Vec<Point> = Vec::new();
-    let mut holes: Vec<Vec<Point>> = Vec::new();
+/// Fill and line style come from the outer Polygon
+pub fn parse_contour_node(
+    node: &XmlNode,
+    line_descriptors: &IndexMap<String, LineDescriptor>,
+    fill_descriptors: &IndexMap<String, FillDescriptor>,
+) -> Result<Polygon, anyhow::Error> {
+    let mut paths: Vec<CurvedPath> = Vec::new();
+    let mut fill = FillDescriptor::default();
+    let mut line_width = None;
     
     // Default fill color with alpha
     let fill_color = [0.5, 0.5, 0.5, 0.5];
     
     // Parse the outer Polygon
     if let Some(polygon_node) = node.children.iter().find(|c| c.name == "Polygon") {
-        outer_ring = parse_poly_points(polygon_node);
+        paths.extend(parse_contour_paths(polygon_node).into_iter().next());
+        fill = parse_polygon_fill(polygon_node, fill_descriptors);
+        line_width = parse_polygon_line_width(polygon_node, line_descriptors);
+    }
+    if paths.is_empty() {
+        return Err(anyhow::anyhow!("Contour must have a Polygon with at least 3 points"));
     }
     
     // Parse all Cutout elements as holes
     for child in &node.children {
         if child.name == "Cutout" {
-            let hole_ring = parse_poly_points(child);
-            if hole_ring.len() >= 3 {
-                holes.push(hole_ring);
-            }
+            paths.extend(parse_contour_paths(child).into_iter().next());
         }
     }
     
+    let (outer_ring, holes, curved_rings) = flatten_rings(paths);
     if outer_ring.len() < 3 {
         return Err(anyhow::anyhow!("Contour must have a Polygon with at least 3 points"));
     }
@@ -110,31 +80,116 @@ pub fn parse_contour_node(node: &XmlNode) -> Result<Polygon, anyhow::Error> {
     Ok(Polygon {
         outer_ring,
         holes,
+        curved_rings,
         fill_color,
         net_name: None,
         component_ref: None,
+        polarity: Polarity::Positive,
+        fill,
+        line_width,
     })
 }
 
-/// Helper to parse PolyBegin/PolyStepSegment points from a node
+/// One path per contour of a node: each PolyBegin starts a path, the steps after it extend it
+fn parse_contour_paths(node: &XmlNode) -> Vec<CurvedPath> {
+    let mut paths: Vec<CurvedPath> = Vec::new();
+    for child in &node.children {
+        let Some(step) = parse_path_step(child) else { continue };
+        match paths.last_mut() {
+            Some(path) if child.name != "PolyBegin" => path.steps.push(step),
+            _ => paths.push(CurvedPath { start: step.end_point(), steps: Vec::new() }),
+        }
+    }
+    paths
+}
+
+/// Outer ring and holes flattened at LOD0 arc tolerance, plus the source paths if any contains an arc
+/// The first path is the outer ring; holes with fewer than 3 points are dropped.
+fn flatten_rings(paths: Vec<CurvedPath>) -> (Vec<Point>, Vec<Vec<Point>>, Option<Vec<CurvedPath>>) {
+    let mut outer_ring = Vec::new();
+    let mut holes = Vec::new();
+    let mut kept = Vec::new();
+    let mut curved = false;
+    for (i, path) in paths.into_iter().enumerate() {
+        let ring = flatten_path(&path, ARC_CHORD_TOLERANCE_LOD[0]);
+        if i == 0 {
+            outer_ring = ring;
+        } else if ring.len() >= 3 {
+            holes.push(ring);
+        } else {
+            continue;
+        }
+        curved |= path.steps.iter().any(|step| matches!(step, PathStep::Curve { .. }));
+        kept.push(path);
+    }
+    (outer_ring, holes, curved.then_some(kept))
+}
+
+/// Fill from an inline FillDesc or a FillDescRef into DictionaryFillDesc (solid when absent)
+fn parse_polygon_fill(node: &XmlNode, fill_descriptors: &IndexMap<String, FillDescriptor>) -> FillDescriptor {
+    node.children.iter()
+        .find_map(|c| match c.name.as_str() {
+            "FillDesc" => Some(parse_fill_desc(c)),
+            "FillDescRef" => c.attributes.get("id").and_then(|id| fill_descriptors.get(id)).copied(),
+            _ => None,
+        })
+        .unwrap_or_default()
+}
+
+/// Boundary width from an inline LineDesc or a LineDescRef, if the polygon has one
+fn parse_polygon_line_width(node: &XmlNode, line_descriptors: &IndexMap<String, LineDescriptor>) -> Option<f32> {
+    node.children.iter()
+        .any(|c| c.name == "LineDesc" || c.name == "LineDescRef")
+        .then(|| parse_line_style(node, line_descriptors).0)
+}
+
+/// Helper to parse PolyBegin/PolyStepSegment/PolyStepCurve points from a node
+/// Curves are flattened at LOD0 arc tolerance
 pub fn parse_poly_points(node: &XmlNode) -> Vec<Point> {
     let mut points = Vec::new();
     
     for child in &node.children {
-        match child.name.as_str() {
-            "PolyBegin" | "PolyStepSegment" | "PolyStepCurve" => {
-                if let (Some(x_str), Some(y_str)) = (
-                    child.attributes.get("x"),
-                    child.attributes.get("y"),
-                ) {
-                    if let (Ok(x), Ok(y)) = (x_str.parse::<f32>(), y_str.parse::<f32>()) {
-                        points.push(Point { x, y });
-                    }
-                }
+        if let Some(step) = parse_path_step(child) {
+            if child.name == "PolyBegin" {
+                points.push(step.end_point());
+            } else {
+                push_path_step(&mut points, step);
             }
-            _ => {}
         }
     }
     
     points
 }
+
+/// Parse a single path vertex (PolyBegin, PolyStepSegment, PolyStepCurve or Pt)
+/// PolyStepCurve becomes a curve step using its centerX/centerY/clockwise attributes,
+/// every other vertex becomes a straight segment ending at x/y
+pub fn parse_path_step(node: &XmlNode) -> Option<PathStep> {
+    let coord = |name: &str| node.attributes.get(name).and_then(|v| v.parse::<f32>().ok());
+
+    let end = Point { x: coord("x")?, y: coord("y")? };
+
+    match node.name.as_str() {
+        "PolyStepCurve" => Some(PathStep::Curve {
+            end,
+            center: Point { x: coord("centerX")?, y: coord("centerY")? },
+            clockwise: node
+                .attributes
+                .get("clockwise")
+                .map(|v| v.eq_ignore_ascii_case("true"))
+                .unwrap_or(false),
+        }),
+        "PolyBegin" | "PolyStepSegment" | "Pt" => Some(PathStep::Segment(end)),
+        _ => None,
+    }
+}
+
+/// Append a step to a point ring, flattening curves at LOD0 tolerance
+fn push_path_step(ring: &mut Vec<Point>, step: PathStep) {
+    match (step, ring.last().copied()) {
+        (PathStep::Curve { end, center, clockwise }, Some(current)) => {
+            ring.extend(tessellate_arc(current, end, center, clockwise, ARC_CHORD_TOLERANCE_LOD[0]));
+        }
+        _ => ring.push(step.end_point()),
+    }
+}
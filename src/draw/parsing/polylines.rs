@@ -1,19 +1,23 @@
 //! Polyline and line node parsing
 //!
-//! Handles parsing Polyline and Line XML elements into geometry.
+//! Handles parsing Polyline, Line and Arc XML elements into geometry.
 
 use crate::draw::geometry::*;
 use crate::parse_xml::XmlNode;
 use indexmap::IndexMap;
 use super::colors::parse_color;
 use super::descriptors::parse_line_end;
+use super::polygons::parse_path_step;
+use crate::draw::tessellation::{flatten_path, ARC_CHORD_TOLERANCE_LOD};
 
 /// Parse a single Polyline XML node
 pub fn parse_polyline_node(
     node: &XmlNode,
     line_descriptors: &IndexMap<String, LineDescriptor>,
 ) -> Result<Polyline, anyhow::Error> {
-    let mut points = Vec::new();
+    let mut start: Option<Point> = None;
+    let mut steps: Vec<PathStep> = Vec::new();
+    let mut has_curve = false;
     let mut width: f32 = node
         .attributes
         .get("width")
@@ -30,36 +34,15 @@ pub fn parse_polyline_node(
     // Extract points from various child node types
     for child in &node.children {
         match child.name.as_str() {
-            // Standard point format
-            "Pt" => {
-                if let (Some(x_str), Some(y_str)) = (
-                    child.attributes.get("x"),
-                    child.attributes.get("y"),
-                ) {
-                    if let (Ok(x), Ok(y)) = (x_str.parse::<f32>(), y_str.parse::<f32>()) {
-                        points.push(Point { x, y });
-                    }
-                }
-            }
-            // IPC-2581 polyline format: PolyBegin + PolyStepSegment
-            "PolyBegin" => {
-                if let (Some(x_str), Some(y_str)) = (
-                    child.attributes.get("x"),
-                    child.attributes.get("y"),
-                ) {
-                    if let (Ok(x), Ok(y)) = (x_str.parse::<f32>(), y_str.parse::<f32>()) {
-                        points.push(Point { x, y });
-                    }
-                }
-            }
-            "PolyStepSegment" => {
-                if let (Some(x_str), Some(y_str)) = (
-                    child.attributes.get("x"),
-                    child.attributes.get("y"),
-                ) {
-                    if let (Ok(x), Ok(y)) = (x_str.parse::<f32>(), y_str.parse::<f32>()) {
-                        points.push(Point { x, y });
+            // Standard point format and IPC-2581 polyline format: PolyBegin + PolyStepSegment/PolyStepCurve
+            "Pt" | "PolyBegin" | "PolyStepSegment" | "PolyStepCurve" => {
+                if let Some(step) = parse_path_step(child) {
+                    if start.is_none() {
+                        start = Some(step.end_point());
+                        continue;
                     }
+                    has_curve |= matches!(step, PathStep::Curve { .. });
+                    steps.push(step);
                 }
             }
             "LineDescRef" => {
@@ -79,6 +62,12 @@ pub fn parse_polyline_node(
         }
     }
 
+    let path = start.map(|start| CurvedPath { start, steps });
+    let points = path
+        .as_ref()
+        .map(|p| flatten_path(p, ARC_CHORD_TOLERANCE_LOD[0]))
+        .unwrap_or_default();
+
     Ok(Polyline {
         points,
         width,
@@ -86,35 +75,17 @@ pub fn parse_polyline_node(
         line_end,
         net_name: None, // Will be set by caller with net context
         component_ref: None, // Will be set by caller with component context
+        curved_path: if has_curve { path } else { None },
+        polarity: Polarity::Positive, // Will be set by caller with Set and layer polarity
     })
 }
 
-/// Parse a Line XML node by converting it into a two-point polyline
-pub fn parse_line_node(
+/// Resolve line width and end style from an inline LineDesc or a LineDescRef child
+/// A LineDescRef takes precedence over an inline LineDesc
+pub(super) fn parse_line_style(
     node: &XmlNode,
     line_descriptors: &IndexMap<String, LineDescriptor>,
-) -> Result<Polyline, anyhow::Error> {
-    let start_x = node
-        .attributes
-        .get("startX")
-        .and_then(|v| v.parse::<f32>().ok())
-        .ok_or_else(|| anyhow::anyhow!("Line missing startX attribute"))?;
-    let start_y = node
-        .attributes
-        .get("startY")
-        .and_then(|v| v.parse::<f32>().ok())
-        .ok_or_else(|| anyhow::anyhow!("Line missing startY attribute"))?;
-    let end_x = node
-        .attributes
-        .get("endX")
-        .and_then(|v| v.parse::<f32>().ok())
-        .ok_or_else(|| anyhow::anyhow!("Line missing endX attribute"))?;
-    let end_y = node
-        .attributes
-        .get("endY")
-        .and_then(|v| v.parse::<f32>().ok())
-        .ok_or_else(|| anyhow::anyhow!("Line missing endY attribute"))?;
-
+) -> (f32, LineEnd) {
     let mut width: f32 = node
         .attributes
         .get("width")
@@ -122,8 +93,6 @@ pub fn parse_line_node(
         .unwrap_or(0.1);
     let mut line_end = LineEnd::Round;
 
-    let color = parse_color(&node.attributes).unwrap_or([0.5, 0.5, 0.5, 1.0]);
-
     let mut line_desc_ref: Option<String> = None;
 
     for child in &node.children {
@@ -154,6 +123,30 @@ pub fn parse_line_node(
         }
     }
 
+    (width, line_end)
+}
+
+/// Read a required f32 attribute, naming the element in the error
+fn required_f32(node: &XmlNode, attr: &str) -> Result<f32, anyhow::Error> {
+    node.attributes
+        .get(attr)
+        .and_then(|v| v.parse::<f32>().ok())
+        .ok_or_else(|| anyhow::anyhow!("{} missing {} attribute", node.name, attr))
+}
+
+/// Parse a Line XML node by converting it into a two-point polyline
+pub fn parse_line_node(
+    node: &XmlNode,
+    line_descriptors: &IndexMap<String, LineDescriptor>,
+) -> Result<Polyline, anyhow::Error> {
+    let start_x = required_f32(node, "startX")?;
+    let start_y = required_f32(node, "startY")?;
+    let end_x = required_f32(node, "endX")?;
+    let end_y = required_f32(node, "endY")?;
+
+    let (width, line_end) = parse_line_style(node, line_descriptors);
+    let color = parse_color(&node.attributes).unwrap_or([0.5, 0.5, 0.5, 1.0]);
+
     Ok(Polyline {
         points: vec![
             Point { x: start_x, y: start_y },
@@ -164,5 +157,51 @@ pub fn parse_line_node(
         line_end,
         net_name: None, // Will be set by caller with net context
         component_ref: None, // Will be set by caller with component context
+        curved_path: None,
+        polarity: Polarity::Positive, // Will be set by caller with Set and layer polarity
+    })
+}
+
+/// Parse an Arc XML node into a curved polyline
+/// Identical start and end points describe a full circle
+pub fn parse_arc_node(
+    node: &XmlNode,
+    line_descriptors: &IndexMap<String, LineDescriptor>,
+) -> Result<Polyline, anyhow::Error> {
+    let start = Point {
+        x: required_f32(node, "startX")?,
+        y: required_f32(node, "startY")?,
+    };
+    let end = Point {
+        x: required_f32(node, "endX")?,
+        y: required_f32(node, "endY")?,
+    };
+    let center = Point {
+        x: required_f32(node, "centerX")?,
+        y: required_f32(node, "centerY")?,
+    };
+    let clockwise = node
+        .attributes
+        .get("clockwise")
+        .map(|v| v.eq_ignore_ascii_case("true"))
+        .unwrap_or(false);
+
+    let (width, line_end) = parse_line_style(node, line_descriptors);
+    let color = parse_color(&node.attributes).unwrap_or([0.5, 0.5, 0.5, 1.0]);
+
+    let path = CurvedPath {
+        start,
+        steps: vec![PathStep::Curve { end, center, clockwise }],
+    };
+
+    Ok(Polyline {
+        points: flatten_path(&path, ARC_CHORD_TOLERANCE_LOD[0]),
+        width,
+        color,
+        line_end,
+        net_name: None, // Will be set by caller with net context
+        component_ref: None, // Will be set by caller with component context
+        curved_path: Some(path),
+        polarity: Polarity::Positive, // Will be set by caller with Set and layer polarity
     })
 }
@@ -6,6 +6,7 @@ use crate::draw::geometry::*;
 use crate::parse_xml::XmlNode;
 use indexmap::IndexMap;
 use std::collections::HashMap;
+use super::polygons::parse_poly_points;
 
 /// Parse StandardPrimitive definitions from DictionaryStandard
 pub fn parse_standard_primitives(root: &XmlNode) -> HashMap<String, StandardPrimitive> {
@@ -16,45 +17,7 @@ pub fn parse_standard_primitives(root: &XmlNode) -> HashMap<String, StandardPrim
         if node.name == "EntryStandard" {
             if let Some(id) = node.attributes.get("id") {
                 for child in &node.children {
-                    let mut shape = match child.name.as_str() {
-                        "Circle" => {
-                            let diameter = child.attributes.get("diameter")
-                                .and_then(|v| v.parse::<f32>().ok())
-                                .unwrap_or(0.0);
-                            Some(StandardPrimitive::Circle { diameter })
-                        }
-                        "RectCenter" => {
-                            let width = child.attributes.get("width")
-                                .and_then(|v| v.parse::<f32>().ok())
-                                .unwrap_or(0.0);
-                            let height = child.attributes.get("height")
-                                .and_then(|v| v.parse::<f32>().ok())
-                                .unwrap_or(0.0);
-                            Some(StandardPrimitive::Rectangle { width, height })
-                        }
-                        "Oval" => {
-                            let width = child.attributes.get("width")
-                                .and_then(|v| v.parse::<f32>().ok())
-                                .unwrap_or(0.0);
-                            let height = child.attributes.get("height")
-                                .and_then(|v| v.parse::<f32>().ok())
-                                .unwrap_or(0.0);
-                            Some(StandardPrimitive::Oval { width, height })
-                        }
-                        "RectRound" => {
-                            let width = child.attributes.get("width")
-                                .and_then(|v| v.parse::<f32>().ok())
-                                .unwrap_or(0.0);
-                            let height = child.attributes.get("height")
-                                .and_then(|v| v.parse::<f32>().ok())
-                                .unwrap_or(0.0);
-                            let corner_radius = child.attributes.get("radius")
-                                .and_then(|v| v.parse::<f32>().ok())
-                                .unwrap_or(0.0);
-                            Some(StandardPrimitive::RoundRect { width, height, corner_radius })
-                        }
-                        _ => None,
-                    };
+                    let mut shape = parse_standard_primitive(child);
                     
                     // If no primitive found, check for <Contour><Polygon> (CUSTOM shapes)
                     if shape.is_none() {
@@ -62,17 +25,8 @@ pub fn parse_standard_primitives(root: &XmlNode) -> HashMap<String, StandardPrim
                             .find(|c| c.name == "Contour") {
                             if let Some(polygon_node) = contour_node.children.iter()
                                 .find(|c| c.name == "Polygon") {
-                                // Parse polygon points from PolyBegin + PolyStepSegment
-                                let mut points = Vec::new();
-                                for poly_child in &polygon_node.children {
-                                    if poly_child.name == "PolyBegin" || poly_child.name == "PolyStepSegment" {
-                                        if let (Some(x_str), Some(y_str)) = (poly_child.attributes.get("x"), poly_child.attributes.get("y")) {
-                                            if let (Ok(x), Ok(y)) = (x_str.parse::<f32>(), y_str.parse::<f32>()) {
-                                                points.push(Point { x, y });
-                                            }
-                                        }
-                                    }
-                                }
+                                // Parse polygon points from PolyBegin + PolyStepSegment/PolyStepCurve
+                                let points = parse_poly_points(polygon_node);
                                 if !points.is_empty() {
                                     shape = Some(StandardPrimitive::CustomPolygon { points });
                                 }
@@ -98,6 +52,85 @@ pub fn parse_standard_primitives(root: &XmlNode) -> HashMap<String, StandardPrim
     primitives
 }
 
+/// Parse a single StandardPrimitive element (Circle, RectCenter, Thermal, ...)
+/// Returns None for elements outside the StandardPrimitive substitution group
+pub fn parse_standard_primitive(node: &XmlNode) -> Option<StandardPrimitive> {
+    let attr = |name: &str| node.attributes.get(name).and_then(|v| v.parse::<f32>().ok());
+    let dim = |name: &str| attr(name).unwrap_or(0.0);
+    let ring_shape = || match node.attributes.get("shape").map(|s| s.to_uppercase()).as_deref() {
+        Some("SQUARE") => RingShape::Square,
+        Some("HEXAGON") => RingShape::Hexagon,
+        Some("OCTAGON") => RingShape::Octagon,
+        _ => RingShape::Round,
+    };
+
+    let shape = match node.name.as_str() {
+        "Circle" => StandardPrimitive::Circle { diameter: dim("diameter") },
+        "RectCenter" => StandardPrimitive::Rectangle { width: dim("width"), height: dim("height") },
+        "Oval" => StandardPrimitive::Oval { width: dim("width"), height: dim("height") },
+        "RectRound" => StandardPrimitive::RoundRect {
+            width: dim("width"),
+            height: dim("height"),
+            corner_radius: dim("radius"),
+        },
+        "Diamond" => StandardPrimitive::Diamond { width: dim("width"), height: dim("height") },
+        "Ellipse" => StandardPrimitive::Ellipse { width: dim("width"), height: dim("height") },
+        "Hexagon" => StandardPrimitive::Hexagon { length: dim("length") },
+        "Octagon" => StandardPrimitive::Octagon { length: dim("length") },
+        "Triangle" => StandardPrimitive::Triangle { base: dim("base"), height: dim("height") },
+        "RectCham" => {
+            // Corner flags are optional; a RectCham without any is chamfered on all four corners
+            let flags = ["upperRight", "upperLeft", "lowerLeft", "lowerRight"]
+                .map(|name| node.attributes.get(name).map(|v| v.eq_ignore_ascii_case("true")));
+            let corners = if flags.iter().all(|f| f.is_none()) {
+                [true; 4]
+            } else {
+                flags.map(|f| f.unwrap_or(false))
+            };
+            StandardPrimitive::RectCham {
+                width: dim("width"),
+                height: dim("height"),
+                chamfer: dim("chamfer"),
+                corners,
+            }
+        }
+        "RectCorner" => StandardPrimitive::RectCorner {
+            lower_left: Point { x: dim("lowerLeftX"), y: dim("lowerLeftY") },
+            upper_right: Point { x: dim("upperRightX"), y: dim("upperRightY") },
+        },
+        "Donut" => StandardPrimitive::Donut {
+            shape: ring_shape(),
+            outer_diameter: dim("outerDiameter"),
+            inner_diameter: dim("innerDiameter"),
+        },
+        "Thermal" => StandardPrimitive::Thermal {
+            shape: ring_shape(),
+            outer_diameter: dim("outerDiameter"),
+            inner_diameter: dim("innerDiameter"),
+            spoke_count: attr("spokeCount").map(|n| n.max(0.0) as u32).unwrap_or(4),
+            spoke_width: dim("spokeWidth"),
+            spoke_start_angle: attr("spokeStartAngle").unwrap_or(0.0),
+        },
+        "Butterfly" => {
+            let shape = ring_shape();
+            let size = if shape == RingShape::Square { dim("side") } else { dim("diameter") };
+            StandardPrimitive::Butterfly { shape, size }
+        }
+        "Moire" => StandardPrimitive::Moire {
+            diameter: dim("diameter"),
+            ring_width: dim("ringWidth"),
+            ring_gap: dim("ringGap"),
+            ring_number: attr("ringNumber").map(|n| n.max(0.0) as u32).unwrap_or(1),
+            line_width: dim("lineWidth"),
+            line_length: dim("lineLength"),
+            line_angle: attr("lineAngle").unwrap_or(0.0),
+        },
+        _ => return None,
+    };
+
+    Some(shape)
+}
+
 /// Parse pad stack definitions and extract hole + outer diameter information
 /// Returns a map of pad stack name -> definition (hole dia + outer dia)
 pub fn parse_padstack_definitions(root: &XmlNode) -> IndexMap<String, PadStackDef> {
@@ -202,20 +235,7 @@ pub fn parse_padstack_definitions(root: &XmlNode) -> IndexMap<String, PadStackDe
                                             if let Some(prim) = primitives.get(std_id) {
                                                 shape = Some(prim.clone());
                                                 // Set outer_diameter based on shape type
-                                                outer_diameter = match prim {
-                                                    StandardPrimitive::Circle { diameter } => *diameter,
-                                                    StandardPrimitive::Rectangle { width, height } => width.max(*height),
-                                                    StandardPrimitive::Oval { width, height } => width.max(*height),
-                                                    StandardPrimitive::RoundRect { width, height, .. } => width.max(*height),
-                                                    StandardPrimitive::CustomPolygon { points } => {
-                                                        // Find bounding box of polygon
-                                                        let mut max_dim = 0.0f32;
-                                                        for p in points {
-                                                            max_dim = max_dim.max(p.x.abs()).max(p.y.abs());
-                                                        }
-                                                        max_dim * 2.0
-                                                    }
-                                                };
+                                                outer_diameter = prim.outer_diameter();
                                             }
                                         }
                                     }
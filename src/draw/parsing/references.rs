@@ -0,0 +1,323 @@
+//! Referential integrity of dictionary and definition references
+//!
+//! Parsing resolves references by lookup and skips the ones that do not
+//! resolve, so a pad whose StandardPrimitiveRef names no dictionary entry is
+//! simply not drawn. This pass reports those dangling references, ids defined
+//! more than once, and dictionary entries nothing refers to. Padstacks, packages
+//! and components belong to their Step, so those are checked per step.
+
+use super::steps::step_name;
+use crate::parse_xml::{child_paths, XmlNode};
+use crate::schema::Severity;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+/// Occurrence paths kept per diagnostic
+const MAX_PATHS: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LoadDiagnosticKind {
+    DanglingReference,
+    DuplicateId,
+    UnusedEntry,
+}
+
+/// One unresolved, duplicated or unused id, with the element paths where it occurs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoadDiagnostic {
+    pub severity: Severity,
+    pub kind: LoadDiagnosticKind,
+    pub element: String,  // Defining element: EntryStandard, PadStackDef, Component, ...
+    pub id: String,
+    pub count: usize,           // Uses (dangling, unused) or definitions (duplicate)
+    pub paths: Vec<String>,     // First occurrences
+    pub message: String,
+}
+
+/// How a reference names its target
+#[derive(Clone, Copy)]
+enum Reference {
+    Element(&'static str),    // `<LineDescRef id="..."/>`
+    Attribute(&'static str, &'static [&'static str]),  // `padstackDefRef="..."` on the listed elements (any when empty)
+}
+
+/// A kind of definition and the references that resolve against it
+struct Rule {
+    definers: &'static [&'static str],
+    key: &'static str,
+    reference: Reference,
+    dictionary: bool,
+    per_step: bool,        // Ids are local to the enclosing Step
+    effect: &'static str,  // What parsing does with a dangling reference
+}
+
+const RULES: &[Rule] = &[
+    Rule { definers: &["EntryLineDesc"], key: "id", reference: Reference::Element("LineDescRef"), dictionary: true, per_step: false,
+        effect: "lines fall back to their inline or default width" },
+    Rule { definers: &["EntryFillDesc"], key: "id", reference: Reference::Element("FillDescRef"), dictionary: true, per_step: false,
+        effect: "areas are drawn solid" },
+    Rule { definers: &["EntryStandard"], key: "id", reference: Reference::Element("StandardPrimitiveRef"), dictionary: true, per_step: false,
+        effect: "pads and features using it are not drawn" },
+    Rule { definers: &["EntryUser"], key: "id", reference: Reference::Element("UserPrimitiveRef"), dictionary: true, per_step: false,
+        effect: "shapes using it are not drawn" },
+    Rule { definers: &["EntryColor"], key: "id", reference: Reference::Element("ColorRef"), dictionary: true, per_step: false,
+        effect: "the default color is used" },
+    Rule { definers: &["EntryFont"], key: "id", reference: Reference::Element("FontRef"), dictionary: true, per_step: false,
+        effect: "text uses the default stroke width" },
+    Rule { definers: &["PadStackDef"], key: "name", reference: Reference::Attribute("padstackDefRef", &[]), dictionary: false, per_step: true,
+        effect: "pads are not recognized as plated holes" },
+    Rule { definers: &["Package"], key: "name", reference: Reference::Attribute("packageRef", &["Component"]), dictionary: false, per_step: true,
+        effect: "components have no package outline or pins" },
+    Rule { definers: &["Component"], key: "refDes", reference: Reference::Attribute("componentRef", &[]), dictionary: false, per_step: true,
+        effect: "objects are not linked to a placed component" },
+    Rule { definers: &["Layer"], key: "name", reference: Reference::Attribute("layerRef", &[]), dictionary: false, per_step: false,
+        effect: "features have no layer function, side or polarity" },
+    Rule { definers: &["LogicalNet", "PhyNet"], key: "name", reference: Reference::Attribute("net", &[]), dictionary: false, per_step: false,
+        effect: "objects carry a net that is not in the netlist" },
+];
+
+/// Rule defined by `element`
+fn rule_of(element: &str) -> usize {
+    RULES.iter().position(|r| r.definers[0] == element).unwrap_or(0)
+}
+
+#[derive(Default)]
+struct Occurrences {
+    count: usize,
+    paths: Vec<String>,
+}
+
+impl Occurrences {
+    fn add(&mut self, path: &str) {
+        self.count += 1;
+        if self.paths.len() < MAX_PATHS {
+            self.paths.push(path.to_string());
+        }
+    }
+}
+
+/// Step name (empty for document-wide rules) and id
+type Key = (String, String);
+
+/// Definitions and references per rule, in document order
+struct Index {
+    defined: Vec<IndexMap<Key, Occurrences>>,
+    referenced: Vec<IndexMap<Key, Occurrences>>,
+}
+
+/// Collect dangling references, duplicate ids and unused dictionary entries of a document
+pub fn check_references(root: &XmlNode) -> Vec<LoadDiagnostic> {
+    let mut collector = ReferenceCollector::new();
+    collector.collect(root, &format!("/{}", root.name));
+    collector.finish()
+}
+
+/// Definitions and references gathered from parts of a document visited one at a time
+pub struct ReferenceCollector {
+    index: Index,
+}
+
+impl Default for ReferenceCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ReferenceCollector {
+    pub fn new() -> Self {
+        ReferenceCollector {
+            index: Index {
+                defined: RULES.iter().map(|_| IndexMap::new()).collect(),
+                referenced: RULES.iter().map(|_| IndexMap::new()).collect(),
+            },
+        }
+    }
+
+    /// Add `node`, found at element path `path`, and its descendants
+    pub fn collect(&mut self, node: &XmlNode, path: &str) {
+        collect(node, path, "", &mut self.index);
+    }
+
+    /// Add the descendants of `node`, inside the Step named `step`, only (its own attributes are collected elsewhere)
+    pub fn collect_children(&mut self, node: &XmlNode, path: &str, step: &str) {
+        for (child, child_path) in node.children.iter().zip(child_paths(node, path)) {
+            collect(child, &child_path, step, &mut self.index);
+        }
+    }
+
+    /// Diagnostics for everything collected
+    pub fn finish(self) -> Vec<LoadDiagnostic> {
+        report(&self.index)
+    }
+}
+
+fn report(index: &Index) -> Vec<LoadDiagnostic> {
+    let mut diagnostics = Vec::new();
+    for (rule_index, rule) in RULES.iter().enumerate() {
+        let defined = &index.defined[rule_index];
+        let referenced = &index.referenced[rule_index];
+        let element = rule.definers[0];
+        let reference = match rule.reference {
+            Reference::Element(name) | Reference::Attribute(name, _) => name,
+        };
+        // Files without any LogicalNet/PhyNet have no netlist to check against,
+        // and a net legitimately appears in both LogicalNet and PhyNet
+        let is_net = element == "LogicalNet";
+        let in_step = |step: &str| if step.is_empty() { String::new() } else { format!(" in step '{}'", step) };
+
+        if !is_net || !defined.is_empty() {
+            for ((step, id), uses) in referenced.iter().filter(|(key, _)| !defined.contains_key(*key)) {
+                diagnostics.push(LoadDiagnostic {
+                    severity: Severity::Error,
+                    kind: LoadDiagnosticKind::DanglingReference,
+                    element: element.to_string(),
+                    id: id.clone(),
+                    count: uses.count,
+                    paths: uses.paths.clone(),
+                    message: format!("{} '{}' does not match any {}{} ({} uses): {}",
+                        reference, id, rule.definers.join("/"), in_step(step), uses.count, rule.effect),
+                });
+            }
+        }
+        if !is_net {
+            for ((step, id), definitions) in defined.iter().filter(|(_, d)| d.count > 1) {
+                diagnostics.push(LoadDiagnostic {
+                    severity: Severity::Error,
+                    kind: LoadDiagnosticKind::DuplicateId,
+                    element: element.to_string(),
+                    id: id.clone(),
+                    count: definitions.count,
+                    paths: definitions.paths.clone(),
+                    message: format!("{} '{}' is defined {} times{}; only one definition is used",
+                        element, id, definitions.count, in_step(step)),
+                });
+            }
+        }
+        if rule.dictionary {
+            // Layer colors are looked up by id (`LAYER_COLOR_<layer>` or the layer name), not referenced
+            let layers = &index.defined[rule_of("Layer")];
+            let is_layer = |name: &str| layers.contains_key(&(String::new(), name.to_string()));
+            let layer_color = |id: &str| element == "EntryColor"
+                && (is_layer(id) || id.strip_prefix("LAYER_COLOR_").is_some_and(is_layer));
+            for ((_, id), definitions) in defined.iter().filter(|(key, _)| !referenced.contains_key(*key) && !layer_color(&key.1)) {
+                diagnostics.push(LoadDiagnostic {
+                    severity: Severity::Warning,
+                    kind: LoadDiagnosticKind::UnusedEntry,
+                    element: element.to_string(),
+                    id: id.clone(),
+                    count: 0,
+                    paths: definitions.paths.clone(),
+                    message: format!("{} '{}' is never referenced", element, id),
+                });
+            }
+        }
+    }
+    diagnostics
+}
+
+fn collect(node: &XmlNode, path: &str, step: &str, index: &mut Index) {
+    let step = if node.name == "Step" { step_name(node) } else { step };
+    for (rule_index, rule) in RULES.iter().enumerate() {
+        let key = |id: &String| (if rule.per_step { step.to_string() } else { String::new() }, id.clone());
+        if rule.definers.contains(&node.name.as_str()) {
+            if let Some(id) = node.attributes.get(rule.key) {
+                index.defined[rule_index].entry(key(id)).or_default().add(path);
+            }
+        }
+        let id = match rule.reference {
+            Reference::Element(name) if node.name == name => node.attributes.get("id"),
+            Reference::Attribute(name, elements) if elements.is_empty() || elements.contains(&node.name.as_str()) => {
+                node.attributes.get(name)
+            }
+            _ => None,
+        };
+        if let Some(id) = id {
+            index.referenced[rule_index].entry(key(id)).or_default().add(path);
+        }
+    }
+
+    for (child, child_path) in node.children.iter().zip(child_paths(node, path)) {
+        collect(child, &child_path, step, index);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pad(primitive: &str) -> XmlNode {
+        XmlNode::new("Pad", &[("padstackDefRef", "PTH1")], vec![
+            XmlNode::new("Location", &[("x", "0"), ("y", "0")], vec![]),
+            XmlNode::new("StandardPrimitiveRef", &[("id", primitive)], vec![]),
+        ])
+    }
+
+    #[test]
+    fn test_dangling_duplicate_and_unused() {
+        let root = XmlNode::new("IPC-2581", &[], vec![
+            XmlNode::new("Content", &[], vec![XmlNode::new("DictionaryStandard", &[], vec![
+                XmlNode::new("EntryStandard", &[("id", "RECT_1")], vec![]),
+                XmlNode::new("EntryStandard", &[("id", "CIRCLE_1")], vec![]),
+                XmlNode::new("EntryStandard", &[("id", "CIRCLE_1")], vec![]),
+                XmlNode::new("EntryStandard", &[("id", "SPARE")], vec![]),
+            ])]),
+            XmlNode::new("Ecad", &[], vec![XmlNode::new("CadData", &[], vec![
+                XmlNode::new("Layer", &[("name", "TOP")], vec![]),
+                XmlNode::new("Step", &[("name", "board")], vec![
+                    XmlNode::new("PadStackDef", &[("name", "PTH1")], vec![]),
+                    XmlNode::new("LayerFeature", &[("layerRef", "TOP")], vec![
+                        XmlNode::new("Set", &[("net", "GND"), ("componentRef", "R1")], vec![pad("RECT_1"), pad("RECT_9"), pad("RECT_9")]),
+                        XmlNode::new("Set", &[], vec![pad("CIRCLE_1")]),
+                    ]),
+                ]),
+            ])]),
+        ]);
+        let diagnostics = check_references(&root);
+        let find = |kind: LoadDiagnosticKind, id: &str| diagnostics.iter().find(|d| d.kind == kind && d.id == id);
+
+        // Two pads name a primitive that is not in the dictionary
+        let missing = find(LoadDiagnosticKind::DanglingReference, "RECT_9").unwrap();
+        assert_eq!((missing.element.as_str(), missing.count, missing.severity), ("EntryStandard", 2, Severity::Error));
+        assert_eq!(missing.paths[0], "/IPC-2581/Ecad/CadData/Step/LayerFeature/Set[1]/Pad[2]/StandardPrimitiveRef");
+
+        // No Component R1 is placed; without any LogicalNet the net is not checked
+        assert!(find(LoadDiagnosticKind::DanglingReference, "R1").is_some());
+        assert!(find(LoadDiagnosticKind::DanglingReference, "GND").is_none());
+
+        assert_eq!(find(LoadDiagnosticKind::DuplicateId, "CIRCLE_1").unwrap().count, 2);
+        assert_eq!(find(LoadDiagnosticKind::UnusedEntry, "SPARE").unwrap().severity, Severity::Warning);
+        assert_eq!(diagnostics.len(), 4);
+    }
+
+    #[test]
+    fn test_step_local_definitions() {
+        let step = |name: &str, refdes: &str| XmlNode::new("Step", &[("name", name)], vec![
+            XmlNode::new("PadStackDef", &[("name", "PTH1")], vec![]),
+            XmlNode::new("Package", &[("name", "R0603")], vec![]),
+            XmlNode::new("Component", &[("refDes", "R1"), ("packageRef", "R0603")], vec![]),
+            XmlNode::new("LayerFeature", &[("layerRef", "TOP")], vec![
+                XmlNode::new("Set", &[("componentRef", refdes)], vec![pad("RECT_1")]),
+            ]),
+        ]);
+        let root = XmlNode::new("IPC-2581", &[], vec![
+            XmlNode::new("Content", &[], vec![XmlNode::new("DictionaryStandard", &[], vec![
+                XmlNode::new("EntryStandard", &[("id", "RECT_1")], vec![]),
+            ])]),
+            XmlNode::new("Ecad", &[], vec![XmlNode::new("CadData", &[], vec![
+                XmlNode::new("Layer", &[("name", "TOP")], vec![]),
+                step("board", "R1"),
+                step("coupon", "R2"),
+            ])]),
+        ]);
+        let diagnostics = check_references(&root);
+
+        // Each step defines the same padstack, package and component once
+        assert!(diagnostics.iter().all(|d| d.kind != LoadDiagnosticKind::DuplicateId), "{:?}", diagnostics);
+
+        // R2 is only missing from the coupon step
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!((diagnostics[0].kind, diagnostics[0].id.as_str()), (LoadDiagnosticKind::DanglingReference, "R2"));
+        assert!(diagnostics[0].message.contains("in step 'coupon'"));
+    }
+}
@@ -0,0 +1,261 @@
+//! Stackup parsing
+//!
+//! Builds the physical layer build-up from `CadData/Stackup`, joining each
+//! `StackupLayer` with its `Layer` (function, side) and the material and
+//! dielectric properties of its `CadHeader/Spec`. The stack sequence is also
+//! used to order rendered layers top to bottom.
+
+use super::descriptors::LayerMeta;
+use crate::parse_xml::XmlNode;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// One layer of the physical stack
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StackupLayer {
+    pub layer_ref: String,
+    /// Position in the build (nonNegativeDouble, so fractional values insert between layers)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sequence: Option<f64>,
+    pub thickness: f32,
+    pub tol_plus: f32,
+    pub tol_minus: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub layer_function: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub side: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub material: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dielectric_constant: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub loss_tangent: Option<f32>,
+}
+
+/// A StackupGroup and its layers, sorted by sequence
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StackupGroup {
+    pub name: String,
+    pub thickness: f32,
+    pub layers: Vec<StackupLayer>,
+}
+
+/// Parsed Stackup element
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Stackup {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub overall_thickness: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub where_measured: Option<String>,
+    pub groups: Vec<StackupGroup>,
+}
+
+impl Stackup {
+    /// All stackup layers across groups, in physical (sequence) order
+    pub fn layers_in_sequence(&self) -> Vec<&StackupLayer> {
+        let mut layers: Vec<&StackupLayer> = self.groups.iter().flat_map(|g| g.layers.iter()).collect();
+        layers.sort_by(|a, b| sequence_order(a, b));
+        layers
+    }
+}
+
+/// Order stackup layers by sequence, layers without one last
+fn sequence_order(a: &StackupLayer, b: &StackupLayer) -> Ordering {
+    match (a.sequence, b.sequence) {
+        (Some(a), Some(b)) => a.total_cmp(&b),
+        (a, b) => a.is_none().cmp(&b.is_none()),
+    }
+}
+
+/// Material and dielectric properties of a Spec element
+#[derive(Default)]
+struct SpecProperties {
+    material: Option<String>,
+    dielectric_constant: Option<f32>,
+    loss_tangent: Option<f32>,
+}
+
+fn parse_f32(node: &XmlNode, name: &str) -> Option<f32> {
+    node.attributes.get(name).and_then(|v| v.parse().ok())
+}
+
+fn find_cad_child<'a>(root: &'a XmlNode, name: &str) -> Option<&'a XmlNode> {
+    root.children
+        .iter()
+        .find(|n| n.name == "Ecad")?
+        .children
+        .iter()
+        .find(|n| n.name == name)
+}
+
+/// Parse the first Stackup in CadData, or None if the file has no stackup
+pub fn parse_stackup(root: &XmlNode, layer_meta: &HashMap<String, LayerMeta>) -> Option<Stackup> {
+    let stackup_node = find_cad_child(root, "CadData")?
+        .children
+        .iter()
+        .find(|n| n.name == "Stackup")?;
+    let specs = parse_specs(root);
+
+    let groups = stackup_node.children.iter()
+        .filter(|n| n.name == "StackupGroup")
+        .map(|group| {
+            let mut layers: Vec<StackupLayer> = group.children.iter()
+                .filter(|n| n.name == "StackupLayer")
+                .filter_map(|node| {
+                    let layer_ref = node.attributes.get("layerOrGroupRef")?.clone();
+                    let meta = layer_meta.get(&layer_ref);
+                    let spec = node.children.iter()
+                        .find(|c| c.name == "SpecRef")
+                        .and_then(|c| c.attributes.get("id"))
+                        .and_then(|id| specs.get(id));
+
+                    Some(StackupLayer {
+                        sequence: node.attributes.get("sequence").and_then(|v| v.trim().parse().ok()),
+                        thickness: parse_f32(node, "thickness").unwrap_or(0.0),
+                        tol_plus: parse_f32(node, "tolPlus").unwrap_or(0.0),
+                        tol_minus: parse_f32(node, "tolMinus").unwrap_or(0.0),
+                        layer_function: meta.map(|m| m.function.clone()),
+                        side: meta.map(|m| m.side.clone()),
+                        material: spec.and_then(|s| s.material.clone()),
+                        dielectric_constant: spec.and_then(|s| s.dielectric_constant),
+                        loss_tangent: spec.and_then(|s| s.loss_tangent),
+                        layer_ref,
+                    })
+                })
+                .collect();
+            layers.sort_by(sequence_order);
+
+            StackupGroup {
+                name: group.attributes.get("name").cloned().unwrap_or_default(),
+                thickness: parse_f32(group, "thickness").unwrap_or(0.0),
+                layers,
+            }
+        })
+        .collect();
+
+    Some(Stackup {
+        name: stackup_node.attributes.get("name").cloned().unwrap_or_default(),
+        overall_thickness: parse_f32(stackup_node, "overallThickness"),
+        where_measured: stackup_node.attributes.get("whereMeasured").cloned(),
+        groups,
+    })
+}
+
+/// Parse CadHeader Spec elements by name
+fn parse_specs(root: &XmlNode) -> HashMap<String, SpecProperties> {
+    let mut specs = HashMap::new();
+    let Some(header) = find_cad_child(root, "CadHeader") else { return specs };
+
+    for spec in header.children.iter().filter(|n| n.name == "Spec") {
+        let Some(name) = spec.attributes.get("name") else { continue };
+        let mut props = SpecProperties::default();
+
+        for child in &spec.children {
+            let property = child.children.iter().find(|c| c.name == "Property");
+            let value = property.and_then(|p| parse_f32(p, "value"));
+            match (child.name.as_str(), child.attributes.get("type").map(|t| t.as_str())) {
+                ("General", Some("MATERIAL")) => {
+                    props.material = property.and_then(|p| p.attributes.get("text")).cloned();
+                }
+                ("Dielectric", Some("DIELECTRIC_CONSTANT")) => props.dielectric_constant = value,
+                ("Dielectric", Some("LOSS_TANGENT")) => props.loss_tangent = value,
+                _ => {}
+            }
+        }
+
+        specs.insert(name.clone(), props);
+    }
+
+    specs
+}
+
+/// Layer names from top to bottom of the physical build
+/// Uses the stackup sequence when present, otherwise CadData Layer element order.
+pub fn physical_layer_order(root: &XmlNode, stackup: Option<&Stackup>) -> Vec<String> {
+    if let Some(stackup) = stackup {
+        let order: Vec<String> = stackup.layers_in_sequence().iter().map(|l| l.layer_ref.clone()).collect();
+        if !order.is_empty() {
+            return order;
+        }
+    }
+
+    find_cad_child(root, "CadData")
+        .map(|cad_data| cad_data.children.iter()
+            .filter(|n| n.name == "Layer")
+            .filter_map(|n| n.attributes.get("name").cloned())
+            .collect())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stackup_layer(layer: &str, sequence: &str, thickness: &str) -> XmlNode {
+        XmlNode::new("StackupLayer", &[("layerOrGroupRef", layer), ("sequence", sequence), ("thickness", thickness)], vec![
+            XmlNode::new("SpecRef", &[("id", layer)], vec![]),
+        ])
+    }
+
+    #[test]
+    fn test_stackup_sequence_and_specs() {
+        let root = XmlNode::new("IPC-2581", &[], vec![XmlNode::new("Ecad", &[], vec![
+            XmlNode::new("CadHeader", &[("units", "MILLIMETER")], vec![
+                XmlNode::new("Spec", &[("name", "Core")], vec![
+                    XmlNode::new("General", &[("type", "MATERIAL")], vec![XmlNode::new("Property", &[("text", "FR-4")], vec![])]),
+                    XmlNode::new("Dielectric", &[("type", "DIELECTRIC_CONSTANT")], vec![XmlNode::new("Property", &[("value", "4.3")], vec![])]),
+                ]),
+            ]),
+            XmlNode::new("CadData", &[], vec![
+                XmlNode::new("Layer", &[("name", "BOT"), ("layerFunction", "SIGNAL"), ("side", "BOTTOM")], vec![]),
+                XmlNode::new("Layer", &[("name", "Core"), ("layerFunction", "DIELCORE"), ("side", "NONE")], vec![]),
+                XmlNode::new("Layer", &[("name", "TOP"), ("layerFunction", "SIGNAL"), ("side", "TOP")], vec![]),
+                XmlNode::new("Stackup", &[("name", "S"), ("overallThickness", "1.6")], vec![
+                    XmlNode::new("StackupGroup", &[("name", "G"), ("thickness", "1.6")], vec![
+                        stackup_layer("BOT", "3", "0.035"),
+                        stackup_layer("TOP", "1", "0.035"),
+                        stackup_layer("Core", "2", "1.53"),
+                    ]),
+                ]),
+            ]),
+        ])]);
+
+        let meta = super::super::descriptors::parse_layer_metadata(&root);
+        let stackup = parse_stackup(&root, &meta).unwrap();
+        assert_eq!(stackup.overall_thickness, Some(1.6));
+
+        let layers = stackup.layers_in_sequence();
+        assert_eq!(layers.iter().map(|l| l.layer_ref.as_str()).collect::<Vec<_>>(), vec!["TOP", "Core", "BOT"]);
+        assert_eq!(layers[1].material.as_deref(), Some("FR-4"));
+        assert_eq!(layers[1].dielectric_constant, Some(4.3));
+        assert_eq!(layers[2].side.as_deref(), Some("BOTTOM"));
+
+        // Stack sequence wins over Layer element order
+        assert_eq!(physical_layer_order(&root, Some(&stackup)), vec!["TOP", "Core", "BOT"]);
+        assert_eq!(physical_layer_order(&root, None), vec!["BOT", "Core", "TOP"]);
+    }
+
+    #[test]
+    fn test_fractional_and_missing_sequence() {
+        let root = XmlNode::new("IPC-2581", &[], vec![XmlNode::new("Ecad", &[], vec![
+            XmlNode::new("CadData", &[], vec![
+                XmlNode::new("Stackup", &[("name", "S")], vec![
+                    XmlNode::new("StackupGroup", &[("name", "G")], vec![
+                        XmlNode::new("StackupLayer", &[("layerOrGroupRef", "MASK")], vec![]),
+                        stackup_layer("BOT", "10", "0.035"),
+                        stackup_layer("PREPREG", "1.5", "0.1"),
+                        stackup_layer("TOP", "1", "0.035"),
+                    ]),
+                ]),
+            ]),
+        ])]);
+
+        let stackup = parse_stackup(&root, &HashMap::new()).unwrap();
+        let order: Vec<&str> = stackup.layers_in_sequence().iter().map(|l| l.layer_ref.as_str()).collect();
+        assert_eq!(order, vec!["TOP", "PREPREG", "BOT", "MASK"]);
+        assert_eq!(stackup.groups[0].layers[1].sequence, Some(1.5));
+        assert_eq!(stackup.groups[0].layers[3].sequence, None);
+    }
+}
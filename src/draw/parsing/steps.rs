@@ -0,0 +1,274 @@
+//! Step selection and StepRepeat panel expansion
+//!
+//! A file can hold several `Step`s (boards, coupons, panels); one of them is
+//! drawn at a time. By default that is the first step no `StepRepeat` places:
+//! the panel of a panelized file, or the board of a single-step file.
+//! `StepRepeat` (Appendix A) places an nx × ny array of another step. The
+//! repeated step's geometry, measured from its `Datum`, is copied into the
+//! active step once per instance.
+
+use super::components::find_steps;
+use crate::draw::geometry::*;
+use crate::parse_xml::XmlNode;
+use serde::{Deserialize, Serialize};
+
+/// Nesting depth of StepRepeat references above which the file is treated as recursive
+pub const MAX_STEP_DEPTH: usize = 8;
+
+/// A StepRepeat: an nx × ny array of `step_ref` instances
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepRepeat {
+    pub step_ref: String,
+    pub x: f32,
+    pub y: f32,
+    pub nx: u32,
+    pub ny: u32,
+    pub dx: f32,
+    pub dy: f32,
+    pub angle: f32,  // degrees, counter-clockwise
+    pub mirror: bool,
+}
+
+impl StepRepeat {
+    /// Placement of every instance, taking the repeated step's `datum` to the instance origin
+    pub fn placements(&self, datum: Point) -> Vec<Transform2D> {
+        let orientation = Xform { rotation: self.angle, mirror: self.mirror, ..Xform::default() }.to_transform();
+        let to_datum = Transform2D::translation(-datum.x, -datum.y);
+        (0..self.ny)
+            .flat_map(|j| (0..self.nx).map(move |i| (i, j)))
+            .map(|(i, j)| {
+                Transform2D::translation(self.x + i as f32 * self.dx, self.y + j as f32 * self.dy)
+                    .then_apply(&orientation)
+                    .then_apply(&to_datum)
+            })
+            .collect()
+    }
+}
+
+/// Summary of a Step for step selection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepInfo {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub step_type: Option<String>,   // BOARD, PALLET, COUPON, ...
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stackup_ref: Option<String>,
+    pub datum: Point,
+    pub has_profile: bool,
+    pub layer_refs: Vec<String>,     // Layers with LayerFeatures in this step
+    pub step_repeats: Vec<StepRepeat>,
+    pub placed: bool,                // Placed by another step's StepRepeat
+}
+
+/// List every Step with its layers and StepRepeats
+pub fn parse_steps(root: &XmlNode) -> Vec<StepInfo> {
+    let steps = find_steps(root);
+    steps.iter()
+        .map(|step| {
+            let name = step_name(step).to_string();
+            let mut layer_refs: Vec<String> = Vec::new();
+            for layer_ref in step.children.iter()
+                .filter(|c| c.name == "LayerFeature")
+                .filter_map(|c| c.attributes.get("layerRef"))
+            {
+                if !layer_refs.contains(layer_ref) {
+                    layer_refs.push(layer_ref.clone());
+                }
+            }
+
+            StepInfo {
+                placed: steps.iter().any(|s| step_repeats(s).iter().any(|r| r.step_ref == name)),
+                step_type: step.attributes.get("type").cloned(),
+                stackup_ref: step.attributes.get("stackupRef").cloned(),
+                datum: step_datum(step),
+                has_profile: step.children.iter().any(|c| c.name == "Profile"),
+                layer_refs,
+                step_repeats: step_repeats(step),
+                name,
+            }
+        })
+        .collect()
+}
+
+/// Step named `name`
+pub fn find_step<'a>(root: &'a XmlNode, name: &str) -> Option<&'a XmlNode> {
+    find_steps(root).into_iter().find(|s| step_name(s) == name)
+}
+
+/// Step drawn when none is chosen: the first one not placed by a StepRepeat
+pub fn default_step(root: &XmlNode) -> Option<&XmlNode> {
+    let steps = find_steps(root);
+    let placed: Vec<String> = steps.iter()
+        .flat_map(|s| step_repeats(s))
+        .map(|r| r.step_ref)
+        .collect();
+    steps.iter()
+        .find(|s| !placed.iter().any(|p| p == step_name(s)))
+        .or(steps.first())
+        .copied()
+}
+
+/// The step named `name`, or the default step when `name` is None
+/// Ok(None) only when the file has no Step at all.
+pub fn select_step<'a>(root: &'a XmlNode, name: Option<&str>) -> Result<Option<&'a XmlNode>, anyhow::Error> {
+    match name {
+        Some(name) => find_step(root, name)
+            .map(Some)
+            .ok_or_else(|| anyhow::anyhow!("Step '{}' not found", name)),
+        None => Ok(default_step(root)),
+    }
+}
+
+pub fn step_name(step: &XmlNode) -> &str {
+    step.attributes.get("name").map(|s| s.as_str()).unwrap_or("")
+}
+
+/// Datum of a step (origin if absent)
+pub fn step_datum(step: &XmlNode) -> Point {
+    let datum = step.children.iter().find(|c| c.name == "Datum");
+    let coord = |name: &str| datum
+        .and_then(|d| d.attributes.get(name))
+        .and_then(|v| v.parse::<f32>().ok())
+        .unwrap_or(0.0);
+    Point { x: coord("x"), y: coord("y") }
+}
+
+/// StepRepeat children of a step
+pub fn step_repeats(step: &XmlNode) -> Vec<StepRepeat> {
+    step.children.iter()
+        .filter(|c| c.name == "StepRepeat")
+        .filter_map(|node| {
+            let attr = |name: &str| node.attributes.get(name).and_then(|v| v.parse::<f32>().ok());
+            let count = |name: &str| node.attributes.get(name).and_then(|v| v.parse::<u32>().ok()).unwrap_or(1);
+            Some(StepRepeat {
+                step_ref: node.attributes.get("stepRef")?.clone(),
+                x: attr("x").unwrap_or(0.0),
+                y: attr("y").unwrap_or(0.0),
+                nx: count("nx"),
+                ny: count("ny"),
+                dx: attr("dx").unwrap_or(0.0),
+                dy: attr("dy").unwrap_or(0.0),
+                angle: attr("angle").unwrap_or(0.0),
+                mirror: node.attributes.get("mirror").is_some_and(|v| super::xsd_bool(v)),
+            })
+        })
+        .collect()
+}
+
+/// Append `source` placed by `transform` to `target`, keeping negative features pointing at their own copies
+pub fn append_placed(target: &mut LayerGeometries, mut source: LayerGeometries, transform: &Transform2D) {
+    let (polylines, polygons, texts) = (target.polylines.len(), target.polygons.len(), target.texts.len());
+
+    source.polylines.iter_mut().for_each(|p| transform.apply_to_polyline(p));
+    source.polygons.iter_mut().for_each(|p| transform.apply_to_polygon(p));
+    source.pads.iter_mut().for_each(|p| transform.apply_to_pad(p));
+    source.vias.iter_mut().for_each(|v| transform.apply_to_via(v));
+    for text in &mut source.texts {
+        text.transform = transform.then_apply(&text.transform);
+    }
+    for hole in &mut source.padstack_holes {
+        let p = transform.apply(Point { x: hole.x, y: hole.y });
+        hole.x = p.x;
+        hole.y = p.y;
+    }
+    for negative in &mut source.negatives {
+        negative.feature = match negative.feature {
+            FeatureRef::Polyline(i) => FeatureRef::Polyline(i + polylines),
+            FeatureRef::Polygon(i) => FeatureRef::Polygon(i + polygons),
+            FeatureRef::Text(i) => FeatureRef::Text(i + texts),
+        };
+        negative.polylines_before += polylines;
+        negative.polygons_before += polygons;
+    }
+
+    target.polylines.append(&mut source.polylines);
+    target.polygons.append(&mut source.polygons);
+    target.padstack_holes.append(&mut source.padstack_holes);
+    target.pads.append(&mut source.pads);
+    target.vias.append(&mut source.vias);
+    target.texts.append(&mut source.texts);
+    target.negatives.append(&mut source.negatives);
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::extract_and_generate_step_layers;
+
+    fn line(x1: &str, x2: &str) -> XmlNode {
+        XmlNode::new("Set", &[], vec![XmlNode::new("Line", &[("startX", x1), ("startY", "0"), ("endX", x2), ("endY", "0")], vec![
+            XmlNode::new("LineDesc", &[("lineWidth", "0.2"), ("lineEnd", "ROUND")], vec![]),
+        ])])
+    }
+
+    fn panel() -> XmlNode {
+        let board = XmlNode::new("Step", &[("name", "board")], vec![
+            XmlNode::new("Datum", &[("x", "1"), ("y", "0")], vec![]),
+            XmlNode::new("LayerFeature", &[("layerRef", "TOP")], vec![line("1", "3")]),
+        ]);
+        let panel = XmlNode::new("Step", &[("name", "panel"), ("type", "PALLET")], vec![
+            XmlNode::new("Datum", &[("x", "0"), ("y", "0")], vec![]),
+            XmlNode::new("StepRepeat", &[("stepRef", "board"), ("x", "10"), ("y", "5"), ("nx", "2"), ("ny", "1"),
+                ("dx", "20"), ("dy", "0"), ("angle", "90"), ("mirror", "false")], vec![]),
+            XmlNode::new("LayerFeature", &[("layerRef", "TOP")], vec![line("0", "50")]),
+        ]);
+        XmlNode::new("IPC-2581", &[], vec![XmlNode::new("Ecad", &[], vec![
+            XmlNode::new("CadHeader", &[("units", "MILLIMETER")], vec![]),
+            XmlNode::new("CadData", &[], vec![
+                XmlNode::new("Layer", &[("name", "TOP"), ("layerFunction", "SIGNAL"), ("side", "TOP"), ("polarity", "POSITIVE")], vec![]),
+                board,
+                panel,
+            ]),
+        ])])
+    }
+
+    #[test]
+    fn test_step_repeat_placements_and_default_step() {
+        let root = panel();
+        let steps = parse_steps(&root);
+        assert_eq!(steps.len(), 2);
+        assert!(steps[0].placed && !steps[1].placed);
+        assert_eq!(steps[1].step_repeats[0].nx, 2);
+        assert_eq!(step_name(default_step(&root).unwrap()), "panel");
+        assert!(select_step(&root, Some("coupon")).is_err());
+
+        // Board datum (1, 0) lands on the instance origin, then the board is turned 90 degrees
+        let placements = steps[1].step_repeats[0].placements(steps[0].datum);
+        let end = |t: &Transform2D| t.apply(Point { x: 3.0, y: 0.0 });
+        assert_eq!(placements.len(), 2);
+        assert!((end(&placements[0]).x - 10.0).abs() < 1e-5 && (end(&placements[0]).y - 7.0).abs() < 1e-5);
+        assert!((end(&placements[1]).x - 30.0).abs() < 1e-5 && (end(&placements[1]).y - 7.0).abs() < 1e-5);
+
+        // In an INCH file the pitch is converted with the coordinates, and mirror="1" is an xsd:boolean true
+        let mut root = panel();
+        let ecad = &mut root.children[0];
+        ecad.children[0].attributes.insert("units".to_string(), "INCH".to_string());
+        ecad.children[1].children[2].children[1].attributes.insert("mirror".to_string(), "1".to_string());
+        super::super::normalize_units(&mut root);
+        let steps = parse_steps(&root);
+        let repeat = &steps[1].step_repeats[0];
+        assert!(repeat.mirror);
+        let origin = repeat.placements(steps[0].datum)[1].apply(steps[0].datum);
+        assert!((origin.x - 762.0).abs() < 1e-3 && (origin.y - 127.0).abs() < 1e-3, "{:?}", origin);
+    }
+
+    #[test]
+    fn test_panel_instances_drawn_but_not_selectable() {
+        let root = panel();
+        let (layers, ranges) = extract_and_generate_step_layers(&root, None).unwrap();
+        let top = layers.iter().find(|l| l.layer_id == "TOP").unwrap();
+        let lod0 = &top.geometry.batch.as_ref().unwrap()[0];
+        // The panel rail plus two board copies are drawn; only the rail maps to an XML element
+        let ranges: Vec<_> = ranges.iter().filter(|r| r.layer_id == "TOP").collect();
+        assert_eq!(ranges.len(), 1);
+        assert!(ranges[0].bounds[2] > 49.0);
+        let rail_only = extract_and_generate_step_layers(&root, Some("board")).unwrap();
+        assert!(lod0.vertex_count > rail_only.0[0].geometry.batch.as_ref().unwrap()[0].vertex_count);
+
+        // Choosing the board step draws it alone, at its own coordinates
+        let (_, board_ranges) = rail_only;
+        assert_eq!(board_ranges.len(), 1);
+        assert!((board_ranges[0].bounds[0] - 0.9).abs() < 1e-3);
+    }
+}
@@ -0,0 +1,238 @@
+//! Single-pass Load without the full DOM
+//!
+//! LayerFeature elements hold nearly all of a board's data. The parser hands them out one at a
+//! time; each is turned into layer geometry against the dictionaries read before it and dropped.
+//! The rest of the document stays as a skeleton tree, with LayerFeatures as empty placeholders,
+//! for the component, BOM, netlist, stackup and step parsers.
+//!
+//! Dictionaries must precede the features that use them, which the IPC-2581 schema order
+//! guarantees: Content before Ecad, Layer and Stackup before Step, PadStackDef before LayerFeature.
+
+use super::*;
+use crate::draw::progress::Progress;
+use crate::parse_xml::{parse_xml_file_detaching, parse_xml_file_parallel, PartialDocument};
+
+/// A document parsed for Load, with its LayerFeatures collected into geometry while streaming
+/// (or kept in `root` by [`parse_document_parallel`])
+pub struct StreamedDocument {
+    /// Document without LayerFeature contents when streamed, normalized to millimetres
+    pub root: XmlNode,
+    /// CadHeader unit the file was written in
+    pub source_unit: LengthUnit,
+    /// Reference problems of the whole document, LayerFeature contents included
+    pub load_diagnostics: Vec<LoadDiagnostic>,
+    /// Collected features per step name ("" for LayerFeatures outside any Step)
+    steps: IndexMap<String, StreamedStep>,
+}
+
+/// Geometry and drills of one step's LayerFeatures
+struct StreamedStep {
+    unit: LengthUnit,
+    dictionaries: Dictionaries,  // As of the step's first LayerFeature, in millimetres
+    layer_contexts: IndexMap<String, LayerGeometries>,
+    drills: Vec<Drill>,
+}
+
+/// Parse an IPC-2581 file, collecting the geometry of every LayerFeature as it is read
+/// Memory holds the skeleton, the collected geometry and one LayerFeature subtree at a time.
+/// Cancelling `progress` stops parsing at the next LayerFeature.
+pub fn stream_document<P: AsRef<std::path::Path>>(path: P, progress: &Progress) -> Result<StreamedDocument, anyhow::Error> {
+    let mut steps: IndexMap<String, StreamedStep> = IndexMap::new();
+    let mut references = ReferenceCollector::new();
+
+    let mut root = parse_xml_file_detaching(path, "LayerFeature", |document, mut feature| {
+        progress.check()?;
+        let step = document.ancestors().iter().rev()
+            .find(|n| n.name == "Step")
+            .map(|s| steps::step_name(s).to_string())
+            .unwrap_or_default();
+        if !steps.contains_key(&step) {
+            steps.insert(step.clone(), start_step(document));
+        }
+        let streamed = steps.get_mut(&step).expect("step inserted above");
+
+        // The placeholder kept in the skeleton carries the LayerFeature's own attributes
+        references.collect_children(&feature, &feature_path(document.ancestors(), &feature.name), &step);
+
+        units::normalize_subtree(&mut feature, streamed.unit);
+        let scope = streamed.dictionaries.scope();
+        collect_layer_features(&feature, &mut streamed.layer_contexts, scope.line_descriptors, scope.fill_descriptors,
+            scope.padstack_defs, scope.font_stroke_widths, scope.layer_metadata)?;
+        drills::collect_drill_features(&feature, scope.layer_metadata, scope.stack_order, scope.primitives, &mut streamed.drills);
+        Ok(())
+    })?;
+
+    let source_unit = units::normalize_units(&mut root);
+    references.collect(&root, &format!("/{}", root.name));
+    let mut load_diagnostics = references.finish();
+    for diagnostic in &mut load_diagnostics {
+        for path in &mut diagnostic.paths {
+            *path = canonical_path(&root, path);
+        }
+    }
+
+    Ok(StreamedDocument { root, source_unit, load_diagnostics, steps })
+}
+
+/// Parse an IPC-2581 file into the full DOM with its LayerFeatures parsed on all cores
+/// Faster than [`stream_document`] on multi-core machines, but holds the file and the whole tree in memory.
+pub fn parse_document_parallel<P: AsRef<std::path::Path>>(path: P) -> Result<StreamedDocument, anyhow::Error> {
+    let mut root = parse_xml_file_parallel(path, "LayerFeature")?;
+    let source_unit = units::normalize_units(&mut root);
+    let load_diagnostics = check_references(&root);
+    // No streamed steps: step_layers collects every step from the DOM
+    Ok(StreamedDocument { root, source_unit, load_diagnostics, steps: IndexMap::new() })
+}
+
+impl StreamedDocument {
+    /// Layers of one Step (the default step when `step` is None), like [`extract_and_generate_step_layers`]
+    /// The collected geometry is moved into the layers, so this is called once per document.
+    pub fn step_layers(&mut self, step: Option<&str>, progress: &Progress) -> Result<(Vec<LayerJSON>, Vec<ObjectRange>), anyhow::Error> {
+        let total_start = std::time::Instant::now();
+        let root = &self.root;
+        let dictionaries = Dictionaries::parse(root);
+        let scope = dictionaries.scope();
+
+        let cad_data = root.children.iter()
+            .find(|n| n.name == "Ecad")
+            .ok_or_else(|| anyhow::anyhow!("No Ecad node found"))?
+            .children.iter()
+            .find(|n| n.name == "CadData")
+            .ok_or_else(|| anyhow::anyhow!("No CadData node found"))?;
+        let active_step = steps::select_step(root, step)?;
+
+        // Complete each step's features with its PadStacks and drill spans
+        let collect_start = std::time::Instant::now();
+        let mut cache = HashMap::new();
+        for (name, streamed) in std::mem::take(&mut self.steps) {
+            let step_node = if name.is_empty() { Some(cad_data) } else { steps::find_step(root, &name) };
+            if let Some(step_node) = step_node {
+                cache.insert(name, finish_step_geometries(step_node, streamed.layer_contexts, streamed.drills, &scope));
+            }
+        }
+        let active_name = active_step.map(steps::step_name).unwrap_or("");
+        let layer_contexts = match cache.remove(active_name) {
+            Some(layer_contexts) => layer_contexts,
+            None => collect_step_geometries(active_step.unwrap_or(cad_data), &scope)?,
+        };
+        generate_step_layers(root, active_step, layer_contexts, cache, &scope, collect_start, total_start, progress)
+    }
+}
+
+/// Unit and dictionaries for the features of a step, from everything parsed before its first LayerFeature
+fn start_step(document: &mut PartialDocument) -> StreamedStep {
+    let (unit, dictionaries) = document.with_root(|root| {
+        let unit = units::parse_cad_units(root);
+        if unit == LengthUnit::Millimeter {
+            (unit, Dictionaries::parse(root))
+        } else {
+            // The skeleton is normalized once complete; convert a copy of the part read so far
+            let mut normalized = root.clone();
+            units::normalize_units(&mut normalized);
+            (unit, Dictionaries::parse(&normalized))
+        }
+    });
+    StreamedStep { unit, dictionaries, layer_contexts: IndexMap::new(), drills: Vec::new() }
+}
+
+/// Element path of a handed-out element, indexed at every level since later siblings are unknown yet
+fn feature_path(ancestors: &[XmlNode], name: &str) -> String {
+    let mut path = String::new();
+    for (depth, node) in ancestors.iter().enumerate() {
+        match depth.checked_sub(1).map(|parent| &ancestors[parent]) {
+            Some(parent) => {
+                let index = parent.children.iter().filter(|c| c.name == node.name).count() + 1;
+                path.push_str(&format!("/{}[{}]", node.name, index));
+            }
+            None => path.push_str(&format!("/{}", node.name)),
+        }
+    }
+    let index = ancestors.last().map_or(0, |parent| parent.children.iter().filter(|c| c.name == name).count()) + 1;
+    format!("{}/{}[{}]", path, name, index)
+}
+
+/// Rewrite a path from [`feature_path`] the way `child_paths` writes it: indexed only where siblings share a name
+/// Segments below the skeleton (inside a LayerFeature) are already written that way.
+fn canonical_path(root: &XmlNode, path: &str) -> String {
+    let mut segments = path.trim_start_matches('/').split('/');
+    let mut canonical = format!("/{}", segments.next().unwrap_or_default());
+    let mut node = Some(root);
+    for segment in segments {
+        let (name, index) = match segment.strip_suffix(']').and_then(|s| s.split_once('[')) {
+            Some((name, index)) => (name, index.parse::<usize>().unwrap_or(1)),
+            None => (segment, 1),
+        };
+        let siblings: Vec<&XmlNode> = node
+            .map(|n| n.children.iter().filter(|c| c.name == name).collect())
+            .unwrap_or_default();
+        if siblings.len() == 1 {
+            canonical.push_str(&format!("/{}", name));
+        } else {
+            canonical.push_str(&format!("/{}", segment));
+        }
+        node = siblings.get(index.saturating_sub(1)).copied();
+    }
+    canonical
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Vertices per shader kind, as instanced shapes are not emitted in a fixed order
+    fn vertex_counts(layer: &LayerJSON) -> Vec<(String, u64)> {
+        let geometry = serde_json::to_value(&layer.geometry).unwrap();
+        let mut counts: Vec<(String, u64)> = geometry.as_object().unwrap().iter()
+            .map(|(kind, lods)| {
+                let count = lods.as_array().map_or(0, |lods| lods.iter().filter_map(|l| l["vertexCount"].as_u64()).sum());
+                (kind.clone(), count)
+            })
+            .collect();
+        counts.sort();
+        counts
+    }
+
+    fn range_keys(ranges: &[ObjectRange]) -> Vec<String> {
+        let mut keys: Vec<String> = ranges.iter()
+            .map(|r| format!("{} {} {:?} {:?} {:?} {:?}", r.layer_id, r.obj_type, r.bounds, r.net_name, r.component_ref, r.pin_ref))
+            .collect();
+        keys.sort();
+        keys
+    }
+
+    #[test]
+    fn test_streamed_layers_match_dom() {
+        let path = "tests/pic_programmerC.xml";
+        let mut root = crate::parse_xml::parse_xml_file(path).unwrap();
+        let unit = normalize_units(&mut root);
+        let (dom_layers, dom_ranges) = extract_and_generate_step_layers(&root, None).unwrap();
+
+        let mut document = stream_document(path, &Progress::default()).unwrap();
+        let (layers, ranges) = document.step_layers(None, &Progress::default()).unwrap();
+        assert_eq!(document.source_unit, unit);
+        assert_eq!(layers.len(), dom_layers.len());
+        for (streamed, dom) in layers.iter().zip(&dom_layers) {
+            assert_eq!(streamed.layer_id, dom.layer_id);
+            assert_eq!(vertex_counts(streamed), vertex_counts(dom), "{}", dom.layer_id);
+        }
+        assert_eq!(range_keys(&ranges), range_keys(&dom_ranges));
+
+        let mut parallel = parse_document_parallel(path).unwrap();
+        let (parallel_layers, parallel_ranges) = parallel.step_layers(None, &Progress::default()).unwrap();
+        assert_eq!(parallel_layers.iter().map(vertex_counts).collect::<Vec<_>>(), dom_layers.iter().map(vertex_counts).collect::<Vec<_>>());
+        assert_eq!(range_keys(&parallel_ranges), range_keys(&dom_ranges));
+
+        // The skeleton keeps every LayerFeature as an empty placeholder
+        let features = parse_steps(&document.root).iter().map(|s| s.layer_refs.len()).sum::<usize>();
+        assert_eq!(features, parse_steps(&root).iter().map(|s| s.layer_refs.len()).sum::<usize>());
+
+        let diagnostics = check_references(&root);
+        assert_eq!(document.load_diagnostics.len(), diagnostics.len());
+        for diagnostic in &diagnostics {
+            let streamed = document.load_diagnostics.iter()
+                .find(|d| d.kind == diagnostic.kind && d.id == diagnostic.id)
+                .unwrap();
+            assert_eq!((streamed.count, &streamed.paths), (diagnostic.count, &diagnostic.paths));
+        }
+    }
+}
@@ -0,0 +1,97 @@
+//! Text and DictionaryFont parsing
+//!
+//! Handles `Text` features (textString, fontSize, BoundingBox, Xform) and the
+//! stroke width of the fonts they reference. Glyph shapes come from the
+//! built-in stroke font at generation time; embedded glyphs are not used.
+
+use super::colors::parse_color;
+use super::xform::parse_child_xform;
+use crate::draw::geometry::*;
+use crate::parse_xml::XmlNode;
+use indexmap::IndexMap;
+use std::collections::HashMap;
+
+/// Stroke width of each font in DictionaryFont, by EntryFont id
+/// Only FontDefEmbedded carries a LineDesc; external fonts fall back to the default width.
+pub fn parse_font_stroke_widths(
+    root: &XmlNode,
+    line_descriptors: &IndexMap<String, LineDescriptor>,
+) -> HashMap<String, f32> {
+    let mut widths = HashMap::new();
+
+    let entries = root.children.iter()
+        .filter(|n| n.name == "Content")
+        .flat_map(|n| n.children.iter().filter(|c| c.name == "DictionaryFont"))
+        .flat_map(|n| n.children.iter().filter(|c| c.name == "EntryFont"));
+
+    for entry in entries {
+        let Some(id) = entry.attributes.get("id") else { continue };
+        let width = entry.children.iter()
+            .filter(|c| c.name == "FontDefEmbedded")
+            .flat_map(|font| font.children.iter())
+            .find_map(|c| match c.name.as_str() {
+                "LineDesc" => c.attributes.get("lineWidth").and_then(|w| w.parse::<f32>().ok()),
+                "LineDescRef" => c.attributes.get("id")
+                    .and_then(|ref_id| line_descriptors.get(ref_id))
+                    .map(|d| d.line_width),
+                _ => None,
+            });
+        if let Some(width) = width.filter(|w| *w > 0.0) {
+            widths.insert(id.clone(), width);
+        }
+    }
+
+    widths
+}
+
+/// Parse a Text node; `transform` is the placement of the enclosing Features
+pub fn parse_text_node(
+    node: &XmlNode,
+    font_stroke_widths: &HashMap<String, f32>,
+    transform: &Transform2D,
+) -> Result<Text, anyhow::Error> {
+    let text = node.attributes.get("textString")
+        .filter(|s| !s.trim().is_empty())
+        .ok_or_else(|| anyhow::anyhow!("Text has no textString"))?
+        .clone();
+    let font_size = node.attributes.get("fontSize")
+        .and_then(|v| v.parse::<f32>().ok())
+        .unwrap_or(0.0);
+
+    let bounding_box = node.children.iter()
+        .find(|c| c.name == "BoundingBox")
+        .and_then(|b| {
+            let coord = |name: &str| b.attributes.get(name).and_then(|v| v.parse::<f32>().ok());
+            let [x0, y0, x1, y1] = [coord("lowerLeftX")?, coord("lowerLeftY")?, coord("upperRightX")?, coord("upperRightY")?];
+            let bbox = [x0.min(x1), y0.min(y1), x0.max(x1), y0.max(y1)];
+            (bbox[2] > bbox[0] && bbox[3] > bbox[1]).then_some(bbox)
+        });
+
+    if bounding_box.is_none() && font_size <= 0.0 {
+        return Err(anyhow::anyhow!("Text '{}' has neither a BoundingBox nor a fontSize", text));
+    }
+
+    let stroke_width = node.children.iter()
+        .find(|c| c.name == "FontRef")
+        .and_then(|f| f.attributes.get("id"))
+        .and_then(|id| font_stroke_widths.get(id))
+        .copied();
+
+    let color = node.children.iter()
+        .find(|c| c.name == "ColorGroup")
+        .and_then(|g| g.children.iter().find(|c| c.name == "Color"))
+        .and_then(|c| parse_color(&c.attributes))
+        .unwrap_or([0.5, 0.5, 0.5, 1.0]);
+
+    Ok(Text {
+        text,
+        bounding_box,
+        font_size,
+        stroke_width,
+        color,
+        transform: transform.then_apply(&parse_child_xform(node).to_transform()),
+        net_name: None, // Will be set by caller with net context
+        component_ref: None, // Will be set by caller with component context
+        polarity: Polarity::Positive, // Will be set by caller with Set and layer polarity
+    })
+}
@@ -0,0 +1,270 @@
+//! CadHeader unit handling
+//!
+//! IPC-2581 files declare their length unit once in `Ecad/CadHeader units`.
+//! Everything downstream (tessellation, LOD widths, DRC clearance) works in
+//! millimetres, so the DOM is normalized to mm before geometry is extracted.
+
+use crate::parse_xml::XmlNode;
+use serde::{Deserialize, Serialize};
+
+/// Attributes whose values are lengths in the CadHeader unit on every element
+/// Counts, angles, scales and Dfx property values are deliberately absent
+const LENGTH_ATTRIBUTES: &[&str] = &[
+    // Coordinates
+    "x", "y",
+    "startX", "startY", "endX", "endY", "centerX", "centerY",
+    "xOffset", "yOffset",
+    "lowerLeftX", "lowerLeftY", "upperRightX", "upperRightY",
+    // Line widths
+    "lineWidth", "width",
+    // Primitive dimensions
+    "height", "diameter", "radius", "chamfer", "base", "length", "side",
+    "outerDiameter", "innerDiameter", "gap", "spokeWidth",
+    "ringWidth", "ringGap", "lineLength",
+    "outerWidth", "outerHeight", "innerWidth", "innerHeight",
+    "hSize", "vSize",
+    // Fill hatch pitch
+    "pitch1", "pitch2",
+    // Stackup, drill and text sizes
+    "thickness", "overallThickness", "plusTol", "minusTol",
+    "characterHeight", "strokeWidth",
+];
+
+/// Attributes that are lengths only on the listed elements
+/// `tolPlus`/`tolMinus` also appear on impedances and properties, and are percentages when `tolPercent` is true.
+const ELEMENT_LENGTH_ATTRIBUTES: &[(&str, &[&str])] = &[
+    ("Stackup", &["tolPlus", "tolMinus"]),
+    ("StackupGroup", &["tolPlus", "tolMinus"]),
+    ("StackupLayer", &["tolPlus", "tolMinus"]),
+    // Panel pitch
+    ("StepRepeat", &["dx", "dy"]),
+];
+
+/// Whether `attribute` of `node` holds a length
+fn is_length_attribute(node: &XmlNode, attribute: &str) -> bool {
+    if LENGTH_ATTRIBUTES.contains(&attribute) {
+        return true;
+    }
+    let scoped = ELEMENT_LENGTH_ATTRIBUTES
+        .iter()
+        .any(|(element, attributes)| *element == node.name && attributes.contains(&attribute));
+    let percent = matches!(attribute, "tolPlus" | "tolMinus")
+        && node.attributes.get("tolPercent").is_some_and(|v| super::xsd_bool(v));
+    scoped && !percent
+}
+
+/// Length unit declared by a file's CadHeader
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum LengthUnit {
+    #[default]
+    Millimeter,
+    Micron,
+    Inch,
+    Mils,
+}
+
+impl LengthUnit {
+    /// Parse an IPC-2581 unit name (case-insensitive, common abbreviations accepted)
+    pub fn from_ipc(value: &str) -> Option<Self> {
+        match value.trim().to_uppercase().as_str() {
+            "MILLIMETER" | "MM" => Some(LengthUnit::Millimeter),
+            "MICRON" | "UM" => Some(LengthUnit::Micron),
+            "INCH" | "IN" => Some(LengthUnit::Inch),
+            "MILS" | "MIL" => Some(LengthUnit::Mils),
+            _ => None,
+        }
+    }
+
+    /// IPC-2581 name for this unit, as written in CadHeader
+    pub fn as_ipc_str(&self) -> &'static str {
+        match self {
+            LengthUnit::Millimeter => "MILLIMETER",
+            LengthUnit::Micron => "MICRON",
+            LengthUnit::Inch => "INCH",
+            LengthUnit::Mils => "MILS",
+        }
+    }
+
+    /// Millimetres per one unit
+    pub fn mm_per_unit(&self) -> f32 {
+        match self {
+            LengthUnit::Millimeter => 1.0,
+            LengthUnit::Micron => 0.001,
+            LengthUnit::Inch => 25.4,
+            LengthUnit::Mils => 0.0254,
+        }
+    }
+
+    /// Convert a value in this unit to millimetres
+    pub fn to_mm(&self, value: f32) -> f32 {
+        value * self.mm_per_unit()
+    }
+
+    /// Convert a value in millimetres to this unit
+    pub fn from_mm(&self, value_mm: f32) -> f32 {
+        value_mm / self.mm_per_unit()
+    }
+}
+
+/// Find the CadHeader node (Ecad/CadHeader)
+fn find_cad_header(root: &XmlNode) -> Option<&XmlNode> {
+    root.children
+        .iter()
+        .find(|n| n.name == "Ecad")?
+        .children
+        .iter()
+        .find(|n| n.name == "CadHeader")
+}
+
+/// Read the unit declared by `CadHeader units`, defaulting to millimetres
+pub fn parse_cad_units(root: &XmlNode) -> LengthUnit {
+    find_cad_header(root)
+        .and_then(|header| header.attributes.get("units"))
+        .and_then(|units| {
+            let unit = LengthUnit::from_ipc(units);
+            if unit.is_none() {
+                eprintln!("[Units] Unknown CadHeader units '{}', assuming MILLIMETER", units);
+            }
+            unit
+        })
+        .unwrap_or_default()
+}
+
+/// Convert every length attribute in the document to millimetres
+/// Returns the unit the document was written in. The CadHeader is rewritten to
+/// MILLIMETER afterwards so normalizing the same tree twice is a no-op.
+pub fn normalize_units(root: &mut XmlNode) -> LengthUnit {
+    let unit = parse_cad_units(root);
+    if unit == LengthUnit::Millimeter {
+        return unit;
+    }
+
+    let mut converted = 0usize;
+    scale_length_attributes(root, unit.mm_per_unit(), &mut converted);
+
+    if let Some(header) = root
+        .children
+        .iter_mut()
+        .find(|n| n.name == "Ecad")
+        .and_then(|ecad| ecad.children.iter_mut().find(|n| n.name == "CadHeader"))
+    {
+        header.attributes.insert("units".to_string(), LengthUnit::Millimeter.as_ipc_str().to_string());
+    }
+
+    eprintln!("[Units] Converted {} length attributes from {} to MILLIMETER", converted, unit.as_ipc_str());
+    unit
+}
+
+/// Convert the length attributes of a subtree written in `unit` to millimetres
+/// For elements handled apart from their document, which [`normalize_units`] never sees.
+pub fn normalize_subtree(node: &mut XmlNode, unit: LengthUnit) {
+    if unit != LengthUnit::Millimeter {
+        scale_length_attributes(node, unit.mm_per_unit(), &mut 0);
+    }
+}
+
+fn scale_length_attributes(node: &mut XmlNode, factor: f32, converted: &mut usize) {
+    let lengths: Vec<String> = node.attributes
+        .keys()
+        .filter(|name| is_length_attribute(node, name))
+        .cloned()
+        .collect();
+    for name in lengths {
+        let value = node.attributes.get_mut(&name).expect("attribute collected above");
+        if let Ok(v) = value.parse::<f32>() {
+            *value = format_length(v * factor);
+            *converted += 1;
+        }
+    }
+
+    for child in &mut node.children {
+        scale_length_attributes(child, factor, converted);
+    }
+}
+
+/// Format a converted length without trailing zeros
+fn format_length(value: f32) -> String {
+    let formatted = format!("{:.6}", value);
+    let trimmed = formatted.trim_end_matches('0').trim_end_matches('.');
+    if trimmed == "-0" { "0".to_string() } else { trimmed.to_string() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inch_board() -> XmlNode {
+        XmlNode::new("IPC-2581", &[], vec![XmlNode::new("Ecad", &[], vec![
+            XmlNode::new("CadHeader", &[("units", "INCH")], vec![]),
+            XmlNode::new("CadData", &[], vec![
+                XmlNode::new("Line", &[("startX", "1"), ("startY", "0.5"), ("endX", "2"), ("endY", "0")], vec![
+                    XmlNode::new("LineDesc", &[("lineWidth", "0.01"), ("lineEnd", "ROUND")], vec![]),
+                ]),
+                XmlNode::new("Xform", &[("rotation", "90"), ("scale", "1")], vec![]),
+            ]),
+        ])])
+    }
+
+    #[test]
+    fn test_normalize_inch_document() {
+        let mut root = inch_board();
+        assert_eq!(normalize_units(&mut root), LengthUnit::Inch);
+
+        let cad_data = &root.children[0].children[1];
+        let line = &cad_data.children[0];
+        assert_eq!(line.attributes["startX"], "25.4");
+        assert_eq!(line.attributes["startY"], "12.7");
+        assert_eq!(line.children[0].attributes["lineWidth"], "0.254");
+        assert_eq!(line.children[0].attributes["lineEnd"], "ROUND");
+
+        // Angles and scale factors are untouched
+        assert_eq!(cad_data.children[1].attributes["rotation"], "90");
+        assert_eq!(cad_data.children[1].attributes["scale"], "1");
+
+        // Header now says MILLIMETER, so a second pass changes nothing
+        assert_eq!(parse_cad_units(&root), LengthUnit::Millimeter);
+        assert_eq!(normalize_units(&mut root), LengthUnit::Millimeter);
+        assert_eq!(root.children[0].children[1].children[0].attributes["startX"], "25.4");
+    }
+
+    #[test]
+    fn test_normalize_scopes_tolerances_and_font_size() {
+        let mut root = XmlNode::new("IPC-2581", &[], vec![XmlNode::new("Ecad", &[], vec![
+            XmlNode::new("CadHeader", &[("units", "INCH")], vec![]),
+            XmlNode::new("CadData", &[], vec![
+                XmlNode::new("Stackup", &[("overallThickness", "0.062"), ("tolPlus", "0.005"), ("tolMinus", "0.005")], vec![
+                    XmlNode::new("StackupLayer", &[("thickness", "0.01"), ("tolPlus", "10"), ("tolMinus", "5"), ("tolPercent", "1")], vec![]),
+                ]),
+                XmlNode::new("Impedance", &[("value", "50"), ("tolPlus", "5"), ("tolMinus", "5")], vec![]),
+                XmlNode::new("Text", &[("textString", "U1"), ("fontSize", "12")], vec![]),
+            ]),
+        ])]);
+        normalize_units(&mut root);
+
+        let cad_data = &root.children[0].children[1];
+        let stackup = &cad_data.children[0];
+        assert_eq!(stackup.attributes["tolPlus"], "0.127");
+        assert_eq!(stackup.attributes["overallThickness"], "1.5748");
+
+        // Percentage tolerances keep their value; the layer thickness is still a length
+        let layer = &stackup.children[0];
+        assert_eq!(layer.attributes["tolPlus"], "10");
+        assert_eq!(layer.attributes["tolMinus"], "5");
+        assert_eq!(layer.attributes["thickness"], "0.254");
+
+        // Impedance tolerances are ohms and fontSize is an integer, neither is a length
+        assert_eq!(cad_data.children[1].attributes["tolPlus"], "5");
+        assert_eq!(cad_data.children[2].attributes["fontSize"], "12");
+    }
+
+    #[test]
+    fn test_unit_round_trip() {
+        for unit in [LengthUnit::Millimeter, LengthUnit::Micron, LengthUnit::Inch, LengthUnit::Mils] {
+            assert_eq!(LengthUnit::from_ipc(unit.as_ipc_str()), Some(unit));
+            assert!((unit.from_mm(unit.to_mm(3.5)) - 3.5).abs() < 1e-4);
+        }
+        assert_eq!(LengthUnit::from_ipc("mil"), Some(LengthUnit::Mils));
+        assert!((LengthUnit::Mils.to_mm(10.0) - 0.254).abs() < 1e-6);
+    }
+}
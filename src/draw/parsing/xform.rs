@@ -0,0 +1,51 @@
+//! Xform and Location parsing
+//!
+//! Handles reading IPC-2581 Xform elements and building the placement
+//! transform of Features nodes (Location + Xform).
+
+use crate::draw::geometry::*;
+use crate::parse_xml::XmlNode;
+
+/// Parse an Xform element, falling back to the identity for missing attributes
+pub fn parse_xform(node: &XmlNode) -> Xform {
+    let attr = |name: &str| node.attributes.get(name).and_then(|v| v.parse::<f32>().ok());
+
+    Xform {
+        x_offset: attr("xOffset").unwrap_or(0.0),
+        y_offset: attr("yOffset").unwrap_or(0.0),
+        rotation: attr("rotation").unwrap_or(0.0),
+        mirror: node
+            .attributes
+            .get("mirror")
+            .map(|v| v.eq_ignore_ascii_case("true"))
+            .unwrap_or(false),
+        scale: attr("scale").unwrap_or(1.0),
+    }
+}
+
+/// Parse the Xform child of a node (identity if there is none)
+pub fn parse_child_xform(node: &XmlNode) -> Xform {
+    node.children
+        .iter()
+        .find(|c| c.name == "Xform")
+        .map(parse_xform)
+        .unwrap_or_default()
+}
+
+/// Parse the Location child of a node (origin if there is none)
+pub fn parse_child_location(node: &XmlNode) -> Point {
+    let location = node.children.iter().find(|c| c.name == "Location");
+    let coord = |name: &str| {
+        location
+            .and_then(|l| l.attributes.get(name))
+            .and_then(|v| v.parse::<f32>().ok())
+            .unwrap_or(0.0)
+    };
+    Point { x: coord("x"), y: coord("y") }
+}
+
+/// Placement transform of a Features node: its Xform, then translation to its Location
+pub fn features_transform(node: &XmlNode) -> Transform2D {
+    let location = parse_child_location(node);
+    Transform2D::translation(location.x, location.y).then_apply(&parse_child_xform(node).to_transform())
+}
@@ -0,0 +1,189 @@
+//! Cancellation and progress of long-running work
+//!
+//! Load, Save and DRC take seconds on large boards. They take a [`Progress`], which
+//! carries the request's [`CancellationToken`] into their rayon loops and forwards
+//! phase updates to whoever started them (`$/progress` notifications in the LSP server).
+//! Cancelled work stops at its next check and fails with [`Cancelled`].
+
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Shared flag set when the work's requester gives up on it
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Error of work stopped by its [`CancellationToken`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl std::fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "operation cancelled")
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+/// Whether an error (possibly wrapped in anyhow) is a cancellation
+pub fn is_cancelled(error: &anyhow::Error) -> bool {
+    error.downcast_ref::<Cancelled>().is_some()
+}
+
+/// A progress update, shaped like LSP work done progress
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ProgressEvent<'a> {
+    Begin { title: &'a str },
+    Report { message: &'a str, percentage: u32 },
+    End,
+}
+
+/// Receives the progress updates of one operation, from any thread
+pub type ProgressReporter = Arc<dyn Fn(ProgressEvent) + Send + Sync>;
+
+/// Cancellation token and progress reporter of one operation
+/// The default neither reports nor is ever cancelled. Phases map their own 0-100% into a
+/// sub-range of the operation ([`Progress::phase`]).
+#[derive(Clone)]
+pub struct Progress {
+    cancel: CancellationToken,
+    reporter: Option<ProgressReporter>,
+    start: u32,
+    end: u32,
+}
+
+impl Default for Progress {
+    fn default() -> Self {
+        Self::new(CancellationToken::default(), None)
+    }
+}
+
+impl Progress {
+    pub fn new(cancel: CancellationToken, reporter: Option<ProgressReporter>) -> Self {
+        Self { cancel, reporter, start: 0, end: 100 }
+    }
+
+    /// The same operation, with 0-100% of the phase mapped to `start..end` of this range
+    pub fn phase(&self, start: u32, end: u32) -> Progress {
+        let span = self.end - self.start;
+        Progress {
+            cancel: self.cancel.clone(),
+            reporter: self.reporter.clone(),
+            start: self.start + span * start / 100,
+            end: self.start + span * end / 100,
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.is_cancelled()
+    }
+
+    /// Err once the operation is cancelled; call between units of work
+    pub fn check(&self) -> Result<(), Cancelled> {
+        if self.is_cancelled() {
+            Err(Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn begin(&self, title: &str) {
+        self.emit(ProgressEvent::Begin { title });
+    }
+
+    /// Report `percent` (0-100) of this phase done
+    pub fn report(&self, message: &str, percent: u32) {
+        let percentage = self.start + (self.end - self.start) * percent.min(100) / 100;
+        self.emit(ProgressEvent::Report { message, percentage });
+    }
+
+    pub fn end(&self) {
+        self.emit(ProgressEvent::End);
+    }
+
+    /// Counter of `total` units of this phase, reporting each whole percent as units complete
+    pub fn counter(&self, message: &str, total: usize) -> ProgressCounter<'_> {
+        self.report(message, 0);
+        ProgressCounter {
+            progress: self,
+            message: message.to_string(),
+            total: total.max(1),
+            done: AtomicUsize::new(0),
+            reported: AtomicU32::new(0),
+        }
+    }
+
+    fn emit(&self, event: ProgressEvent) {
+        if let Some(reporter) = &self.reporter {
+            reporter(event);
+        }
+    }
+}
+
+/// Completed units of a phase, shared by its rayon workers
+pub struct ProgressCounter<'a> {
+    progress: &'a Progress,
+    message: String,
+    total: usize,
+    done: AtomicUsize,
+    reported: AtomicU32,
+}
+
+impl ProgressCounter<'_> {
+    /// Count one unit done
+    pub fn tick(&self) {
+        if self.progress.reporter.is_none() {
+            return;
+        }
+        let done = self.done.fetch_add(1, Ordering::Relaxed) + 1;
+        let percent = (done.min(self.total) * 100 / self.total) as u32;
+        // Only the worker that moves the percentage on reports it
+        if self.reported.fetch_max(percent, Ordering::Relaxed) < percent {
+            self.progress.report(&self.message, percent);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_phases_and_counter() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = events.clone();
+        let reporter: ProgressReporter = Arc::new(move |event| {
+            if let ProgressEvent::Report { percentage, .. } = event {
+                sink.lock().unwrap().push(percentage);
+            }
+        });
+        let cancel = CancellationToken::new();
+        let progress = Progress::new(cancel.clone(), Some(reporter));
+
+        let phase = progress.phase(40, 80);
+        let counter = phase.counter("Tessellating", 4);
+        for _ in 0..4 {
+            counter.tick();
+        }
+        assert_eq!(*events.lock().unwrap(), vec![40, 50, 60, 70, 80]);
+
+        assert_eq!(phase.check(), Ok(()));
+        cancel.cancel();
+        assert_eq!(phase.check(), Err(Cancelled));
+        assert!(is_cancelled(&anyhow::Error::new(Cancelled)));
+    }
+}
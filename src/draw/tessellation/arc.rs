@@ -0,0 +1,182 @@
+//! Circular arc flattening
+//!
+//! This module converts IPC-2581 arcs (`PolyStepCurve`, `Arc`) into point
+//! sequences. The number of chords is derived from a maximum sagitta (chord
+//! tolerance), so large arcs get more segments than tiny ones and coarser LODs
+//! get fewer segments than LOD0.
+
+use crate::draw::geometry::{Point, PathStep, CurvedPath};
+use std::f32::consts::PI;
+
+/// Maximum chord deviation (sagitta) in mm used when flattening arcs at each LOD
+/// LOD0 is tight enough that a 0.25mm via ring still looks round at full zoom,
+/// coarser LODs follow the same zoom ranges as MIN_VISIBLE_WIDTH_LOD
+pub const ARC_CHORD_TOLERANCE_LOD: [f32; 5] = [
+    0.002,  // LOD0: full detail
+    0.005,  // LOD1
+    0.010,  // LOD2
+    0.025,  // LOD3
+    0.100,  // LOD4
+];
+
+/// Upper bound on segments generated for a single full circle
+const MAX_ARC_SEGMENTS: u32 = 256;
+
+/// Start and end points closer than this are treated as a full circle
+const FULL_CIRCLE_EPSILON: f32 = 1e-5;
+
+/// Number of chords needed so that no chord deviates from the arc by more than `tolerance`
+pub fn arc_segment_count(radius: f32, sweep: f32, tolerance: f32) -> u32 {
+    let sweep = sweep.abs();
+    if radius <= 0.0 || sweep <= 0.0 {
+        return 1;
+    }
+
+    // Angle subtended by a chord whose sagitta equals the tolerance
+    let step = if tolerance >= radius {
+        PI / 2.0
+    } else {
+        2.0 * (1.0 - tolerance / radius).acos()
+    };
+
+    let max_segments = ((sweep / (2.0 * PI)) * MAX_ARC_SEGMENTS as f32).ceil().max(1.0) as u32;
+    ((sweep / step).ceil() as u32).clamp(1, max_segments)
+}
+
+/// Flatten a circular arc into points
+/// Returns the points after `start` up to and including `end`
+/// Identical start and end points describe a full circle
+pub fn tessellate_arc(start: Point, end: Point, center: Point, clockwise: bool, tolerance: f32) -> Vec<Point> {
+    let start_radius = ((start.x - center.x).powi(2) + (start.y - center.y).powi(2)).sqrt();
+    let end_radius = ((end.x - center.x).powi(2) + (end.y - center.y).powi(2)).sqrt();
+
+    if start_radius < 1e-9 {
+        return vec![end];
+    }
+
+    let start_angle = (start.y - center.y).atan2(start.x - center.x);
+    let end_angle = (end.y - center.y).atan2(end.x - center.x);
+
+    let is_full_circle = (start.x - end.x).abs() < FULL_CIRCLE_EPSILON
+        && (start.y - end.y).abs() < FULL_CIRCLE_EPSILON;
+
+    // Sweep is positive for counter-clockwise arcs and negative for clockwise arcs
+    let sweep = if is_full_circle {
+        2.0 * PI
+    } else {
+        let mut ccw = end_angle - start_angle;
+        while ccw <= 0.0 {
+            ccw += 2.0 * PI;
+        }
+        ccw
+    };
+    let sweep = if clockwise {
+        if is_full_circle { -sweep } else { sweep - 2.0 * PI }
+    } else {
+        sweep
+    };
+
+    let segments = arc_segment_count(start_radius.max(end_radius), sweep, tolerance);
+    let mut points = Vec::with_capacity(segments as usize);
+
+    for i in 1..segments {
+        let t = i as f32 / segments as f32;
+        let angle = start_angle + sweep * t;
+        // Interpolate the radius so slightly inconsistent CAD exports still meet the end point
+        let radius = start_radius + (end_radius - start_radius) * t;
+        points.push(Point {
+            x: center.x + angle.cos() * radius,
+            y: center.y + angle.sin() * radius,
+        });
+    }
+    points.push(end);
+
+    points
+}
+
+/// Flatten a path made of straight and curved steps into a point list
+pub fn flatten_path(path: &CurvedPath, tolerance: f32) -> Vec<Point> {
+    let mut points = vec![path.start];
+    let mut current = path.start;
+
+    for step in &path.steps {
+        match *step {
+            PathStep::Segment(end) => {
+                points.push(end);
+                current = end;
+            }
+            PathStep::Curve { end, center, clockwise } => {
+                points.extend(tessellate_arc(current, end, center, clockwise, tolerance));
+                current = end;
+            }
+        }
+    }
+
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn max_deviation(points: &[Point], center: Point, radius: f32) -> f32 {
+        points.windows(2)
+            .map(|w| {
+                let mid = Point { x: (w[0].x + w[1].x) / 2.0, y: (w[0].y + w[1].y) / 2.0 };
+                radius - ((mid.x - center.x).powi(2) + (mid.y - center.y).powi(2)).sqrt()
+            })
+            .fold(0.0, f32::max)
+    }
+
+    #[test]
+    fn test_quarter_arc_direction() {
+        let center = Point { x: 0.0, y: 0.0 };
+        let start = Point { x: 1.0, y: 0.0 };
+        let end = Point { x: 0.0, y: 1.0 };
+
+        // Counter-clockwise quarter: all points stay in the first quadrant
+        let ccw = tessellate_arc(start, end, center, false, 0.001);
+        assert!(ccw.iter().all(|p| p.x >= -1e-4 && p.y >= -1e-4));
+
+        // Clockwise goes the long way round through the other three quadrants
+        let cw = tessellate_arc(start, end, center, true, 0.001);
+        assert!(cw.iter().any(|p| p.x < -0.5));
+        assert!(cw.iter().any(|p| p.y < -0.5));
+        assert!(cw.len() > ccw.len());
+    }
+
+    #[test]
+    fn test_full_circle_and_tolerance() {
+        let center = Point { x: 2.0, y: 3.0 };
+        let start = Point { x: 3.0, y: 3.0 };
+        let points = tessellate_arc(start, start, center, false, 0.01);
+
+        let mut all = vec![start];
+        all.extend(points.iter().copied());
+        assert!(max_deviation(&all, center, 1.0) <= 0.0101);
+        assert!(all.iter().any(|p| p.x < 1.5)); // Went all the way round
+
+        // Coarser tolerance produces fewer segments
+        let coarse = tessellate_arc(start, start, center, false, 0.1);
+        assert!(coarse.len() < points.len());
+    }
+
+    #[test]
+    fn test_flatten_path_mixed_steps() {
+        let path = CurvedPath {
+            start: Point { x: 0.0, y: 0.0 },
+            steps: vec![
+                PathStep::Segment(Point { x: 1.0, y: 0.0 }),
+                PathStep::Curve {
+                    end: Point { x: 2.0, y: 1.0 },
+                    center: Point { x: 1.0, y: 1.0 },
+                    clockwise: false,
+                },
+            ],
+        };
+        let points = flatten_path(&path, ARC_CHORD_TOLERANCE_LOD[0]);
+        assert!(points.len() > 3);
+        let last = points[points.len() - 1];
+        assert_eq!((last.x, last.y), (2.0, 1.0));
+    }
+}
@@ -0,0 +1,82 @@
+//! Polygon boolean helpers on top of i_overlay
+//!
+//! Shapes are passed to i_overlay as contour lists filled with the non-zero
+//! rule: outer rings counter-clockwise, holes clockwise, and stroked lines as
+//! counter-clockwise triangles, so overlapping pieces union instead of cancelling.
+
+use super::polyline::tessellate_polyline;
+use crate::draw::geometry::{LineEnd, Point, Polygon};
+use i_overlay::i_float::float::compatible::FloatPointCompatible;
+
+impl FloatPointCompatible<f32> for Point {
+    fn from_xy(x: f32, y: f32) -> Self {
+        Point { x, y }
+    }
+
+    fn x(&self) -> f32 {
+        self.x
+    }
+
+    fn y(&self) -> f32 {
+        self.y
+    }
+}
+
+/// Outer ring counter-clockwise and holes clockwise, so the non-zero rule fills the polygon
+pub fn polygon_contours(polygon: &Polygon) -> Vec<Vec<Point>> {
+    if polygon.outer_ring.len() < 3 {
+        return Vec::new();
+    }
+    let mut contours = vec![oriented(&polygon.outer_ring, true)];
+    contours.extend(polygon.holes.iter().filter(|h| h.len() >= 3).map(|h| oriented(h, false)));
+    contours
+}
+
+/// Stroke outline of a line as counter-clockwise triangles (caps and joins included)
+pub fn stroke_contours(points: &[Point], width: f32, line_end: LineEnd) -> Vec<Vec<Point>> {
+    let (verts, indices) = tessellate_polyline(points, width, line_end);
+    let vertex = |i: u32| Point { x: verts[i as usize * 2], y: verts[i as usize * 2 + 1] };
+    indices.chunks_exact(3)
+        .map(|tri| vec![vertex(tri[0]), vertex(tri[1]), vertex(tri[2])])
+        .filter(|tri| signed_area(tri).abs() > f32::EPSILON)
+        .map(|tri| oriented(&tri, true))
+        .collect()
+}
+
+/// Copy of `ring` wound in the requested direction
+pub fn oriented(ring: &[Point], counter_clockwise: bool) -> Vec<Point> {
+    let mut ring = ring.to_vec();
+    if (signed_area(&ring) > 0.0) != counter_clockwise {
+        ring.reverse();
+    }
+    ring
+}
+
+/// [min_x, min_y, max_x, max_y] of all contour points, or None when there are none
+pub fn contours_bounds(contours: &[Vec<Point>]) -> Option<[f32; 4]> {
+    let mut points = contours.iter().flatten().peekable();
+    points.peek()?;
+    Some(points.fold([f32::MAX, f32::MAX, f32::MIN, f32::MIN], |b, p| {
+        [b[0].min(p.x), b[1].min(p.y), b[2].max(p.x), b[3].max(p.y)]
+    }))
+}
+
+/// Shoelace area, positive for counter-clockwise rings
+pub fn signed_area(ring: &[Point]) -> f32 {
+    ring.iter()
+        .zip(ring.iter().cycle().skip(1))
+        .map(|(a, b)| a.x * b.y - b.x * a.y)
+        .sum::<f32>() / 2.0
+}
+
+pub fn ring_area(ring: &[Point]) -> f32 {
+    signed_area(ring).abs()
+}
+
+/// Filled area of overlay shapes (outer contours minus holes)
+pub fn shapes_area(shapes: &[Vec<Vec<Point>>]) -> f32 {
+    shapes.iter()
+        .flat_map(|shape| shape.iter().enumerate())
+        .map(|(i, ring)| if i == 0 { ring_area(ring) } else { -ring_area(ring) })
+        .sum()
+}
@@ -0,0 +1,194 @@
+//! Non-solid polygon fills
+//!
+//! HOLLOW, HATCH and MESH fills (FillDesc) are built as copper shapes: the
+//! boundary is stroked, hatch lines are clipped to the polygon area, and the
+//! union is triangulated with earcut like any solid polygon. The lattice is
+//! aligned to the board origin so neighbouring hatched areas line up.
+
+use super::clip::{oriented, polygon_contours, stroke_contours, contours_bounds};
+use super::polygon::tessellate_polygon;
+use crate::draw::geometry::*;
+use i_overlay::core::fill_rule::FillRule;
+use i_overlay::core::overlay_rule::OverlayRule;
+use i_overlay::float::single::SingleFloatOverlay;
+
+/// Line width when neither the FillDesc nor a LineDesc gives one (LineDesc default)
+const DEFAULT_LINE_WIDTH: f32 = 0.1;
+const DEFAULT_ANGLE1: f32 = 45.0;
+const DEFAULT_ANGLE2: f32 = 135.0;
+/// Default pitch as a multiple of the hatch line width (IPC-2581 FillDesc)
+const PITCH_PER_LINE_WIDTH: f32 = 4.0;
+/// Hatch lines per direction above which the area is drawn solid instead
+const MAX_HATCH_LINES: usize = 5000;
+
+/// Tessellate a polygon according to its fill: solid, boundary only, hatched or void
+pub fn tessellate_polygon_fill(polygon: &Polygon) -> (Vec<f32>, Vec<u32>) {
+    match polygon.fill.fill_property {
+        FillProperty::Fill => tessellate_polygon(polygon, 0.0),
+        FillProperty::Void => (Vec::new(), Vec::new()),
+        FillProperty::Hollow | FillProperty::Hatch | FillProperty::Mesh => tessellate_shapes(&fill_pattern(polygon)),
+    }
+}
+
+/// Copper shapes (outer contour then holes) of a HOLLOW, HATCH or MESH fill
+pub fn fill_pattern(polygon: &Polygon) -> Vec<Vec<Vec<Point>>> {
+    let fill = &polygon.fill;
+    let hatch_width = fill.line_width.filter(|w| *w > 0.0).unwrap_or(DEFAULT_LINE_WIDTH);
+    let border_width = polygon.line_width.filter(|w| *w > 0.0).unwrap_or(hatch_width);
+    let area = polygon_contours(polygon);
+    let Some(bounds) = contours_bounds(&area) else { return Vec::new() };
+
+    let pitch1 = fill.pitch1.filter(|p| *p > 0.0).unwrap_or(hatch_width * PITCH_PER_LINE_WIDTH);
+    let pitch2 = fill.pitch2.filter(|p| *p > 0.0).unwrap_or(hatch_width * PITCH_PER_LINE_WIDTH);
+    let angle1 = fill.angle1.unwrap_or(DEFAULT_ANGLE1);
+    let angle2 = fill.angle2.unwrap_or(DEFAULT_ANGLE2);
+
+    let line_sets = match fill.fill_property {
+        FillProperty::Hatch => vec![(angle1, pitch1)],
+        FillProperty::Mesh => vec![(angle1, pitch1), (angle2, pitch2)],
+        _ => Vec::new(),
+    };
+    let mut lines = Vec::new();
+    for (angle, pitch) in line_sets {
+        match hatch_lines(bounds, angle, pitch, hatch_width) {
+            Some(set) => lines.extend(set),
+            None => return vec![area],  // Lines would overlap or be too many to draw: solid
+        }
+    }
+
+    // Boundary of the outer ring and every hole
+    let mut copper: Vec<Vec<Point>> = std::iter::once(&polygon.outer_ring)
+        .chain(polygon.holes.iter())
+        .filter(|ring| ring.len() >= 3)
+        .flat_map(|ring| {
+            let mut closed = ring.clone();
+            closed.push(ring[0]);
+            stroke_contours(&closed, border_width, LineEnd::Round)
+        })
+        .collect();
+
+    if !lines.is_empty() {
+        let lattice = lines.overlay(&area, OverlayRule::Intersect, FillRule::NonZero);
+        copper.extend(lattice.into_iter().flatten());
+    }
+    copper.overlay(&Vec::<Vec<Point>>::new(), OverlayRule::Union, FillRule::NonZero)
+}
+
+/// Parallel lines at `angle` degrees every `pitch` covering `bounds`, as counter-clockwise rectangles
+/// None when the lines would merge into a solid area or exceed MAX_HATCH_LINES.
+fn hatch_lines(bounds: [f32; 4], angle: f32, pitch: f32, width: f32) -> Option<Vec<Vec<Point>>> {
+    if pitch <= width {
+        return None;
+    }
+    let (sin, cos) = angle.to_radians().sin_cos();
+    let corners = [(bounds[0], bounds[1]), (bounds[2], bounds[1]), (bounds[2], bounds[3]), (bounds[0], bounds[3])];
+    let along = |(x, y): (f32, f32)| x * cos + y * sin;
+    let across = |(x, y): (f32, f32)| -x * sin + y * cos;
+    let range = |f: &dyn Fn((f32, f32)) -> f32| corners.iter()
+        .map(|&c| f(c))
+        .fold((f32::MAX, f32::MIN), |(lo, hi), v| (lo.min(v), hi.max(v)));
+    let (start, end) = range(&along);
+    let (first, last) = range(&across);
+
+    let first_line = (first / pitch).floor() as i64;
+    let last_line = (last / pitch).ceil() as i64;
+    if (last_line - first_line) as usize > MAX_HATCH_LINES {
+        return None;
+    }
+
+    // Board point for a position along and across the hatch direction
+    let point = |u: f32, v: f32| Point { x: u * cos - v * sin, y: u * sin + v * cos };
+    let half = width / 2.0;
+    Some((first_line..=last_line)
+        .map(|i| {
+            let v = i as f32 * pitch;
+            oriented(&[point(start, v - half), point(end, v - half), point(end, v + half), point(start, v + half)], true)
+        })
+        .collect())
+}
+
+/// Triangulate shapes into one vertex/index buffer
+fn tessellate_shapes(shapes: &[Vec<Vec<Point>>]) -> (Vec<f32>, Vec<u32>) {
+    let mut verts = Vec::new();
+    let mut indices = Vec::new();
+    for shape in shapes {
+        let Some((outer_ring, holes)) = shape.split_first() else { continue };
+        let piece = Polygon {
+            outer_ring: outer_ring.clone(),
+            holes: holes.to_vec(),
+            curved_rings: None,
+            fill_color: [0.0; 4],
+            net_name: None,
+            component_ref: None,
+            polarity: Polarity::Positive,
+            fill: FillDescriptor::default(),
+            line_width: None,
+        };
+        let (piece_verts, piece_indices) = tessellate_polygon(&piece, 0.0);
+        let offset = (verts.len() / 2) as u32;
+        verts.extend(piece_verts);
+        indices.extend(piece_indices.iter().map(|i| i + offset));
+    }
+    (verts, indices)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::draw::tessellation::shapes_area;
+
+    fn square(size: f32, fill: FillDescriptor) -> Polygon {
+        Polygon {
+            outer_ring: vec![
+                Point { x: 0.0, y: 0.0 },
+                Point { x: size, y: 0.0 },
+                Point { x: size, y: size },
+                Point { x: 0.0, y: size },
+            ],
+            holes: Vec::new(),
+            curved_rings: None,
+            fill_color: [1.0; 4],
+            net_name: None,
+            component_ref: None,
+            polarity: Polarity::Positive,
+            fill,
+            line_width: None,
+        }
+    }
+
+    fn mesh_area((verts, indices): &(Vec<f32>, Vec<u32>)) -> f32 {
+        indices.chunks_exact(3)
+            .map(|t| {
+                let p = |i: u32| (verts[i as usize * 2], verts[i as usize * 2 + 1]);
+                let (a, b, c) = (p(t[0]), p(t[1]), p(t[2]));
+                ((b.0 - a.0) * (c.1 - a.1) - (c.0 - a.0) * (b.1 - a.1)).abs() / 2.0
+            })
+            .sum()
+    }
+
+    #[test]
+    fn test_hollow_and_hatch_fills() {
+        let hollow = FillDescriptor { fill_property: FillProperty::Hollow, line_width: Some(0.2), ..Default::default() };
+        // Border only: 4 sides of 10mm at 0.2mm (round corners add a little outside)
+        let border = shapes_area(&fill_pattern(&square(10.0, hollow)));
+        assert!((border - 8.0).abs() < 0.05, "hollow area {}", border);
+
+        // Horizontal lines every 1mm: 9 interior lines of 9.8mm inside the border, edge lines fall in the border
+        let hatch = FillDescriptor {
+            fill_property: FillProperty::Hatch,
+            line_width: Some(0.2),
+            pitch1: Some(1.0),
+            angle1: Some(0.0),
+            ..Default::default()
+        };
+        let polygon = square(10.0, hatch);
+        let expected = border + 9.0 * 9.8 * 0.2;
+        let shapes = fill_pattern(&polygon);
+        assert!((shapes_area(&shapes) - expected).abs() < 0.05, "hatch area {}", shapes_area(&shapes));
+        assert!((mesh_area(&tessellate_polygon_fill(&polygon)) - expected).abs() < 0.05);
+
+        // A pitch no wider than the line is solid
+        let dense = FillDescriptor { pitch1: Some(0.1), ..hatch };
+        assert!((shapes_area(&fill_pattern(&square(10.0, dense))) - 100.0).abs() < 1e-3);
+    }
+}
@@ -7,12 +7,22 @@
 //! - `simplify` - Douglas-Peucker simplification and LOD generation
 //! - `polyline` - Polyline stroking with line caps and joins
 //! - `polygon` - Polygon triangulation using earcut
-//! - `shapes` - Standard shape tessellation (circles, rectangles, etc.)
+//! - `shapes` - Standard primitive tessellation (circles, rectangles, thermals, etc.)
+//! - `arc` - Arc flattening with LOD-dependent chord tolerance
+//! - `clip` - Polygon boolean helpers (contour orientation, stroke outlines, areas)
+//! - `fill` - HOLLOW, HATCH and MESH polygon fills
 
 mod simplify;
 mod polyline;
 mod polygon;
 mod shapes;
+mod arc;
+mod clip;
+mod fill;
+
+/// Version of the tessellated output, part of the Load cache key
+/// Bump it with any change to tessellation or layer generation that alters what a file loads as.
+pub const TESSELLATOR_VERSION: u32 = 3;
 
 // Re-export all public functions for backward compatibility
 pub use simplify::{
@@ -28,6 +38,7 @@ pub use polyline::{
 
 pub use polygon::{
     tessellate_polygon,
+    polygon_with_arc_tolerance,
     tessellate_padstack_holes,
     tessellate_custom_polygon,
 };
@@ -39,5 +50,33 @@ pub use shapes::{
     tessellate_rectangular_ring,
     tessellate_oval,
     tessellate_roundrect,
+    tessellate_donut,
+    tessellate_thermal,
+    tessellate_butterfly,
+    tessellate_moire,
+    tessellate_outline_with_holes,
+    primitive_outline,
     tessellate_primitive,
 };
+
+pub use arc::{
+    ARC_CHORD_TOLERANCE_LOD,
+    arc_segment_count,
+    tessellate_arc,
+    flatten_path,
+};
+
+pub use clip::{
+    polygon_contours,
+    stroke_contours,
+    oriented,
+    contours_bounds,
+    signed_area,
+    ring_area,
+    shapes_area,
+};
+
+pub use fill::{
+    tessellate_polygon_fill,
+    fill_pattern,
+};
@@ -4,7 +4,9 @@
 //! suitable for GPU rendering.
 
 use crate::draw::geometry::{Point, Polygon, PadStackHole};
+use super::arc::flatten_path;
 use super::simplify::douglas_peucker;
+use std::borrow::Cow;
 use std::f32::consts::PI;
 
 /// Tessellate a filled polygon using earcut triangulation
@@ -60,6 +62,20 @@ pub fn tessellate_polygon(polygon: &Polygon, tolerance: f32) -> (Vec<f32>, Vec<u
     (vertices, indices_u32)
 }
 
+/// The polygon with its true arcs re-flattened at chord `tolerance`
+/// Polygons without arcs are returned as they are.
+pub fn polygon_with_arc_tolerance(polygon: &Polygon, tolerance: f32) -> Cow<'_, Polygon> {
+    let Some(paths) = &polygon.curved_rings else {
+        return Cow::Borrowed(polygon);
+    };
+    let mut rings = paths.iter().map(|path| flatten_path(path, tolerance));
+    Cow::Owned(Polygon {
+        outer_ring: rings.next().unwrap_or_default(),
+        holes: rings.collect(),
+        ..polygon.clone()
+    })
+}
+
 /// Tessellate pad stack holes with optional annular rings
 /// Groups holes by size for LOD optimization (matching PadStackHoleBatch.js)
 /// Returns separate geometry for rings and holes
@@ -1,12 +1,16 @@
 //! Standard shape tessellation
 //!
 //! This module provides tessellation functions for standard PCB shapes:
-//! circles, rectangles, ovals, rounded rectangles, and annular rings.
+//! circles, rectangles, ovals, rounded rectangles, and annular rings, plus
+//! the rest of the IPC-2581 StandardPrimitive group (donuts, thermals, moires, ...).
 
-use crate::draw::geometry::StandardPrimitive;
+use crate::draw::geometry::{Point, RingShape, StandardPrimitive, Xform};
 use std::f32::consts::PI;
 use super::polygon::tessellate_custom_polygon;
 
+/// Segments used for a full turn of a round ring outline (matches tessellate_circle)
+const RING_SEGMENTS: f32 = 32.0;
+
 /// Tessellate a circle into triangle fan
 pub fn tessellate_circle(radius: f32) -> (Vec<f32>, Vec<u32>) {
     let segments = 32;
@@ -248,6 +252,252 @@ pub fn tessellate_roundrect(width: f32, height: f32, corner_radius: f32) -> (Vec
     (vertices, indices)
 }
 
+/// Outline of a single-contour primitive, counter-clockwise
+/// Returns None for primitives that are not one simple polygon (rings, thermals, moires, ...)
+pub fn primitive_outline(primitive: &StandardPrimitive) -> Option<Vec<Point>> {
+    let pt = |x: f32, y: f32| Point { x, y };
+    let outline = match primitive {
+        StandardPrimitive::Diamond { width, height } => {
+            let (hw, hh) = (width / 2.0, height / 2.0);
+            vec![pt(hw, 0.0), pt(0.0, hh), pt(-hw, 0.0), pt(0.0, -hh)]
+        }
+        StandardPrimitive::Hexagon { length } => regular_polygon(6, length / 2.0, 0.0),
+        StandardPrimitive::Octagon { length } => {
+            // length is flat-to-flat, so the circumradius is the apothem / cos(22.5°)
+            regular_polygon(8, (length / 2.0) / (PI / 8.0).cos(), PI / 8.0)
+        }
+        StandardPrimitive::Triangle { base, height } => {
+            let (hb, hh) = (base / 2.0, height / 2.0);
+            vec![pt(-hb, -hh), pt(hb, -hh), pt(0.0, hh)]
+        }
+        StandardPrimitive::RectCham { width, height, chamfer, corners } => {
+            let (hw, hh) = (width / 2.0, height / 2.0);
+            let c = chamfer.min(hw).min(hh).max(0.0);
+            let [upper_right, upper_left, lower_left, lower_right] = *corners;
+            let mut points = Vec::with_capacity(8);
+            if lower_right { points.extend([pt(hw - c, -hh), pt(hw, -hh + c)]); } else { points.push(pt(hw, -hh)); }
+            if upper_right { points.extend([pt(hw, hh - c), pt(hw - c, hh)]); } else { points.push(pt(hw, hh)); }
+            if upper_left { points.extend([pt(-hw + c, hh), pt(-hw, hh - c)]); } else { points.push(pt(-hw, hh)); }
+            if lower_left { points.extend([pt(-hw, -hh + c), pt(-hw + c, -hh)]); } else { points.push(pt(-hw, -hh)); }
+            points
+        }
+        StandardPrimitive::RectCorner { lower_left, upper_right } => vec![
+            *lower_left,
+            pt(upper_right.x, lower_left.y),
+            *upper_right,
+            pt(lower_left.x, upper_right.y),
+        ],
+        // Sampled like tessellate_oval, so a cut-out outline matches the solid shape
+        StandardPrimitive::Oval { width, height } | StandardPrimitive::Ellipse { width, height } => {
+            let (rx, ry) = (width / 2.0, height / 2.0);
+            (0..RING_SEGMENTS as u32)
+                .map(|i| {
+                    let angle = i as f32 / RING_SEGMENTS * 2.0 * PI;
+                    pt(angle.cos() * rx, angle.sin() * ry)
+                })
+                .collect()
+        }
+        _ => return None,
+    };
+    Some(outline)
+}
+
+/// Regular polygon with `sides` vertices on a circle of `radius`, first vertex at `start_angle`
+fn regular_polygon(sides: u32, radius: f32, start_angle: f32) -> Vec<Point> {
+    (0..sides)
+        .map(|i| {
+            let angle = start_angle + i as f32 * 2.0 * PI / sides as f32;
+            Point { x: angle.cos() * radius, y: angle.sin() * radius }
+        })
+        .collect()
+}
+
+/// Number of sides, apothem and first face-normal angle of a polygonal ring outline
+fn ring_polygon(shape: RingShape, diameter: f32) -> Option<(u32, f32, f32)> {
+    match shape {
+        RingShape::Round => None,
+        RingShape::Square => Some((4, diameter / 2.0, 0.0)),
+        RingShape::Octagon => Some((8, diameter / 2.0, 0.0)),
+        // Point-to-point diameter with vertices on the X axis: faces point at 30°, 90°, ...
+        RingShape::Hexagon => Some((6, (diameter / 2.0) * (PI / 6.0).cos(), PI / 6.0)),
+    }
+}
+
+/// Distance from the centre to a ring outline in direction `angle`
+fn ring_radius(shape: RingShape, diameter: f32, angle: f32) -> f32 {
+    match ring_polygon(shape, diameter) {
+        None => diameter / 2.0,
+        Some((sides, apothem, first_normal)) => {
+            let step = 2.0 * PI / sides as f32;
+            let normal = first_normal + ((angle - first_normal) / step).round() * step;
+            apothem / (angle - normal).cos()
+        }
+    }
+}
+
+/// Points along a ring outline from `from` to `to` (radians, counter-clockwise, `to` > `from`)
+/// Polygonal outlines get their corners; round outlines are sampled like tessellate_circle
+fn ring_boundary(shape: RingShape, diameter: f32, from: f32, to: f32) -> Vec<Point> {
+    let mut angles = vec![from];
+    match ring_polygon(shape, diameter) {
+        None => {
+            let segments = ((to - from) / (2.0 * PI) * RING_SEGMENTS).ceil().max(1.0) as u32;
+            angles.extend((1..segments).map(|i| from + (to - from) * i as f32 / segments as f32));
+        }
+        Some((sides, _, first_normal)) => {
+            let step = 2.0 * PI / sides as f32;
+            let first_corner = first_normal + step / 2.0;
+            let mut corner = first_corner + ((from - first_corner) / step).floor() * step;
+            while corner <= from {
+                corner += step;
+            }
+            while corner < to {
+                angles.push(corner);
+                corner += step;
+            }
+        }
+    }
+    angles.push(to);
+
+    angles
+        .into_iter()
+        .map(|angle| {
+            let r = ring_radius(shape, diameter, angle);
+            Point { x: angle.cos() * r, y: angle.sin() * r }
+        })
+        .collect()
+}
+
+/// Closed ring outline (no repeated end point)
+fn ring_contour(shape: RingShape, diameter: f32) -> Vec<Point> {
+    let mut points = ring_boundary(shape, diameter, 0.0, 2.0 * PI);
+    points.pop();
+    points
+}
+
+/// Triangulate an outline with optional holes using earcut
+pub fn tessellate_outline_with_holes(outer: &[Point], holes: &[Vec<Point>]) -> (Vec<f32>, Vec<u32>) {
+    let mut vertices = Vec::new();
+    let mut hole_indices = Vec::with_capacity(holes.len());
+    for p in outer {
+        vertices.push(p.x);
+        vertices.push(p.y);
+    }
+    for hole in holes.iter().filter(|h| h.len() >= 3) {
+        hole_indices.push(vertices.len() / 2);
+        for p in hole {
+            vertices.push(p.x);
+            vertices.push(p.y);
+        }
+    }
+
+    let indices = earcutr::earcut(&vertices, &hole_indices, 2).unwrap_or_default();
+    (vertices, indices.into_iter().map(|i| i as u32).collect())
+}
+
+/// Append one mesh to another, offsetting its indices
+fn append_mesh(mesh: &mut (Vec<f32>, Vec<u32>), part: (Vec<f32>, Vec<u32>)) {
+    let base = (mesh.0.len() / 2) as u32;
+    mesh.0.extend(part.0);
+    mesh.1.extend(part.1.into_iter().map(|i| i + base));
+}
+
+/// Tessellate a donut (ring) with a round, square, hexagonal or octagonal outline
+pub fn tessellate_donut(shape: RingShape, outer_diameter: f32, inner_diameter: f32) -> (Vec<f32>, Vec<u32>) {
+    if shape == RingShape::Round && inner_diameter > 0.0 {
+        return tessellate_annular_ring(outer_diameter / 2.0, inner_diameter / 2.0);
+    }
+    let holes = if inner_diameter > 0.0 { vec![ring_contour(shape, inner_diameter)] } else { Vec::new() };
+    tessellate_outline_with_holes(&ring_contour(shape, outer_diameter), &holes)
+}
+
+/// Tessellate a thermal relief: a donut broken by `spoke_count` gaps of `spoke_width`
+pub fn tessellate_thermal(
+    shape: RingShape,
+    outer_diameter: f32,
+    inner_diameter: f32,
+    spoke_count: u32,
+    spoke_width: f32,
+    spoke_start_angle: f32,
+) -> (Vec<f32>, Vec<u32>) {
+    if spoke_count == 0 || spoke_width <= 0.0 {
+        return tessellate_donut(shape, outer_diameter, inner_diameter);
+    }
+
+    let step = 2.0 * PI / spoke_count as f32;
+    let start = spoke_start_angle.to_radians();
+    // Half-angle of the gap where a spoke of the given width crosses each outline
+    let half_gap = |diameter: f32| {
+        let r = diameter / 2.0;
+        if r <= 0.0 { step / 2.0 } else { ((spoke_width / 2.0) / r).min(1.0).asin().min(step / 2.0) }
+    };
+    let (outer_gap, inner_gap) = (half_gap(outer_diameter), half_gap(inner_diameter));
+
+    let mut mesh = (Vec::new(), Vec::new());
+    for k in 0..spoke_count {
+        let spoke = start + k as f32 * step;
+        let (outer_from, outer_to) = (spoke + outer_gap, spoke + step - outer_gap);
+        if outer_to <= outer_from {
+            continue;
+        }
+
+        let mut sector = ring_boundary(shape, outer_diameter, outer_from, outer_to);
+        if inner_diameter > 0.0 && inner_gap < step / 2.0 {
+            let mut inner = ring_boundary(shape, inner_diameter, spoke + inner_gap, spoke + step - inner_gap);
+            inner.reverse();
+            sector.extend(inner);
+        } else {
+            // Spokes wider than the inner outline: the sector closes at the centre
+            sector.push(Point { x: 0.0, y: 0.0 });
+        }
+        append_mesh(&mut mesh, tessellate_outline_with_holes(&sector, &[]));
+    }
+    mesh
+}
+
+/// Tessellate a butterfly: the first and third quadrants of a circle or square
+pub fn tessellate_butterfly(shape: RingShape, size: f32) -> (Vec<f32>, Vec<u32>) {
+    let mut mesh = (Vec::new(), Vec::new());
+    for from in [0.0, PI] {
+        let mut quadrant = vec![Point { x: 0.0, y: 0.0 }];
+        quadrant.extend(ring_boundary(shape, size, from, from + PI / 2.0));
+        append_mesh(&mut mesh, tessellate_outline_with_holes(&quadrant, &[]));
+    }
+    mesh
+}
+
+/// Tessellate a moire: concentric rings plus a crosshair of two lines
+pub fn tessellate_moire(
+    diameter: f32,
+    ring_width: f32,
+    ring_gap: f32,
+    ring_number: u32,
+    line_width: f32,
+    line_length: f32,
+    line_angle: f32,
+) -> (Vec<f32>, Vec<u32>) {
+    let mut mesh = (Vec::new(), Vec::new());
+
+    for i in 0..ring_number {
+        let outer = diameter - 2.0 * i as f32 * (ring_width + ring_gap);
+        if outer <= 0.0 || ring_width <= 0.0 {
+            break;
+        }
+        let inner = (outer - 2.0 * ring_width).max(0.0);
+        append_mesh(&mut mesh, tessellate_donut(RingShape::Round, outer, inner));
+    }
+
+    if line_width > 0.0 && line_length > 0.0 {
+        for angle in [line_angle, line_angle + 90.0] {
+            let (mut verts, indices) = tessellate_rectangle(line_length, line_width);
+            Xform { rotation: angle, ..Xform::default() }.to_transform().apply_to_vertices(&mut verts);
+            append_mesh(&mut mesh, (verts, indices));
+        }
+    }
+
+    mesh
+}
+
 /// Tessellate a standard primitive shape
 pub fn tessellate_primitive(primitive: &StandardPrimitive) -> (Vec<f32>, Vec<u32>) {
     match primitive {
@@ -266,5 +516,100 @@ pub fn tessellate_primitive(primitive: &StandardPrimitive) -> (Vec<f32>, Vec<u32
         StandardPrimitive::CustomPolygon { points } => {
             tessellate_custom_polygon(points)
         }
+        StandardPrimitive::Ellipse { width, height } => {
+            tessellate_oval(*width, *height)
+        }
+        StandardPrimitive::Diamond { .. }
+        | StandardPrimitive::Hexagon { .. }
+        | StandardPrimitive::Octagon { .. }
+        | StandardPrimitive::Triangle { .. }
+        | StandardPrimitive::RectCham { .. }
+        | StandardPrimitive::RectCorner { .. } => {
+            let outline = primitive_outline(primitive).unwrap_or_default();
+            tessellate_custom_polygon(&outline)
+        }
+        StandardPrimitive::Donut { shape, outer_diameter, inner_diameter } => {
+            tessellate_donut(*shape, *outer_diameter, *inner_diameter)
+        }
+        StandardPrimitive::Thermal { shape, outer_diameter, inner_diameter, spoke_count, spoke_width, spoke_start_angle } => {
+            tessellate_thermal(*shape, *outer_diameter, *inner_diameter, *spoke_count, *spoke_width, *spoke_start_angle)
+        }
+        StandardPrimitive::Butterfly { shape, size } => {
+            tessellate_butterfly(*shape, *size)
+        }
+        StandardPrimitive::Moire { diameter, ring_width, ring_gap, ring_number, line_width, line_length, line_angle } => {
+            tessellate_moire(*diameter, *ring_width, *ring_gap, *ring_number, *line_width, *line_length, *line_angle)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Total triangle area of an indexed mesh
+    fn mesh_area((verts, indices): &(Vec<f32>, Vec<u32>)) -> f32 {
+        indices
+            .chunks_exact(3)
+            .map(|t| {
+                let p = |i: u32| (verts[i as usize * 2], verts[i as usize * 2 + 1]);
+                let ((x0, y0), (x1, y1), (x2, y2)) = (p(t[0]), p(t[1]), p(t[2]));
+                ((x1 - x0) * (y2 - y0) - (x2 - x0) * (y1 - y0)).abs() / 2.0
+            })
+            .sum()
+    }
+
+    #[test]
+    fn test_polygonal_primitive_areas() {
+        let diamond = tessellate_primitive(&StandardPrimitive::Diamond { width: 2.0, height: 4.0 });
+        assert!((mesh_area(&diamond) - 4.0).abs() < 1e-4);
+
+        let triangle = tessellate_primitive(&StandardPrimitive::Triangle { base: 2.0, height: 3.0 });
+        assert!((mesh_area(&triangle) - 3.0).abs() < 1e-4);
+
+        // Octagon measured flat to flat: area = 2 * (sqrt(2) - 1) * length^2
+        let octagon = tessellate_primitive(&StandardPrimitive::Octagon { length: 2.0 });
+        assert!((mesh_area(&octagon) - 8.0 * (2.0f32.sqrt() - 1.0)).abs() < 1e-3);
+
+        // Two chamfered corners remove 2 * c^2 / 2
+        let cham = tessellate_primitive(&StandardPrimitive::RectCham {
+            width: 4.0,
+            height: 2.0,
+            chamfer: 0.5,
+            corners: [true, false, true, false],
+        });
+        assert!((mesh_area(&cham) - (8.0 - 0.25)).abs() < 1e-4);
+
+        let corner = tessellate_primitive(&StandardPrimitive::RectCorner {
+            lower_left: Point { x: 1.0, y: 1.0 },
+            upper_right: Point { x: 3.0, y: 2.0 },
+        });
+        assert!((mesh_area(&corner) - 2.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_square_donut_and_thermal() {
+        // Square donut: 4x4 outline with a 2x2 hole
+        let donut = tessellate_donut(RingShape::Square, 4.0, 2.0);
+        assert!((mesh_area(&donut) - 12.0).abs() < 1e-3);
+
+        // Thermal removes the spoke gaps from the ring
+        let thermal = tessellate_thermal(RingShape::Round, 4.0, 2.0, 4, 0.5, 45.0);
+        let ring = tessellate_donut(RingShape::Round, 4.0, 2.0);
+        let (thermal_area, ring_area) = (mesh_area(&thermal), mesh_area(&ring));
+        assert!(thermal_area < ring_area);
+        // Four gaps of roughly spoke_width x ring_width
+        assert!((ring_area - thermal_area - 4.0 * 0.5 * 1.0).abs() < 0.15);
+    }
+
+    #[test]
+    fn test_butterfly_and_moire() {
+        let butterfly = tessellate_butterfly(RingShape::Square, 2.0);
+        assert!((mesh_area(&butterfly) - 2.0).abs() < 1e-4);
+
+        let moire = tessellate_moire(4.0, 0.2, 0.3, 3, 0.1, 5.0, 0.0);
+        assert!(!moire.1.is_empty());
+        let max_extent = moire.0.iter().fold(0.0f32, |m, v| m.max(v.abs()));
+        assert!((max_extent - 2.5).abs() < 1e-4); // crosshair reaches past the outer ring
     }
 }
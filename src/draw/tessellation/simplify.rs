@@ -4,6 +4,7 @@
 //! and LOD (Level of Detail) generation for polylines.
 
 use crate::draw::geometry::{Point, Polyline};
+use super::arc::{flatten_path, ARC_CHORD_TOLERANCE_LOD};
 
 /// Douglas-Peucker polyline simplification
 /// Reduces number of points while maintaining shape within tolerance
@@ -55,11 +56,19 @@ fn point_line_distance(p: Point, a: Point, b: Point) -> f32 {
 }
 
 /// Generate 5 LOD levels for a single polyline using Douglas-Peucker
+/// Polylines with true arcs are re-flattened per LOD using the arc chord tolerance instead
 pub fn generate_polyline_lods(polyline: &Polyline) -> Vec<Vec<Point>> {
     if polyline.points.len() < 2 {
         return vec![vec![]];
     }
 
+    if let Some(path) = &polyline.curved_path {
+        return ARC_CHORD_TOLERANCE_LOD
+            .iter()
+            .map(|&tolerance| flatten_path(path, tolerance))
+            .collect();
+    }
+
     let mut lods = vec![polyline.points.clone()]; // LOD0: exact
 
     // Calculate bounding box for tolerance scaling
@@ -123,7 +132,7 @@ pub fn generate_polyline_lods(polyline: &Polyline) -> Vec<Vec<Point>> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::draw::geometry::LineEnd;
+    use crate::draw::geometry::{LineEnd, Polarity};
 
     #[test]
     fn test_douglas_peucker() {
@@ -156,6 +165,8 @@ mod tests {
             line_end: LineEnd::Round,
             net_name: None,
             component_ref: None,
+            curved_path: None,
+            polarity: Polarity::Positive,
         };
 
         let lods = generate_polyline_lods(&polyline);
@@ -165,4 +176,36 @@ mod tests {
             assert!(lods[i].len() <= lods[i - 1].len()); // Each LOD has fewer or equal points
         }
     }
+
+    #[test]
+    fn test_curved_path_lods_reflatten_arcs() {
+        use crate::draw::geometry::{CurvedPath, PathStep};
+
+        let path = CurvedPath {
+            start: Point { x: 5.0, y: 0.0 },
+            steps: vec![PathStep::Curve {
+                end: Point { x: 5.0, y: 0.0 },
+                center: Point { x: 0.0, y: 0.0 },
+                clockwise: false,
+            }],
+        };
+        let polyline = Polyline {
+            points: flatten_path(&path, ARC_CHORD_TOLERANCE_LOD[0]),
+            width: 0.1,
+            color: [1.0, 0.0, 0.0, 1.0],
+            line_end: LineEnd::Round,
+            net_name: None,
+            component_ref: None,
+            curved_path: Some(path),
+            polarity: Polarity::Positive,
+        };
+
+        let lods = generate_polyline_lods(&polyline);
+        assert_eq!(lods.len(), 5);
+        assert_eq!(lods[0].len(), polyline.points.len());
+        for pair in lods.windows(2) {
+            assert!(pair[1].len() <= pair[0].len());
+        }
+        assert!(lods[4].len() < lods[0].len());
+    }
 }
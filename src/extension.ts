@@ -137,7 +137,7 @@ function startLspServer(context: vscode.ExtensionContext) {
     
     console.log('[Extension] Starting LSP server:', serverPath);
 
-    lspServer = spawn(serverPath, [], {
+    lspServer = spawn(serverPath, ['--lines'], {
         stdio: ['pipe', 'pipe', 'pipe'],
         env: { ...process.env, LSP_EXTENSION_MODE: '1' }
     });
@@ -5,10 +5,12 @@ pub mod serialize_xml;
 pub mod xml_draw;
 pub mod draw;
 pub mod lsp;
+pub mod schema;
 
 // Re-export commonly used types and functions
-pub use parse_xml::{XmlNode, parse_xml_file};
-pub use serialize_xml::{xml_node_to_file, xml_node_to_string, xml_node_to_compact_string};
+pub use parse_xml::{XmlNode, parse_xml_file, parse_xml_file_parallel, parse_xml_str, parse_xml_bytes, parse_xml_bytes_parallel};
+pub use serialize_xml::{xml_node_to_file, xml_node_to_string, xml_node_to_compact_string, xml_node_to_file_lossless, xml_node_to_lossless_bytes};
+pub use xml_to_sqlite::{xml_file_to_sqlite, export_design, SqliteExport};
 pub use xml_draw::extract_and_generate_layers;
 pub use draw::geometry::{LayerJSON, LayerBinary};
 
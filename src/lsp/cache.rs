@@ -0,0 +1,204 @@
+//! On-disk Load cache
+//!
+//! Nearly all of Load is parsing and tessellation. Its result is written to
+//! `.<file name>.cache` next to the file and read back when the same bytes are
+//! loaded again, skipping both. An entry is keyed by the xxh3 hash of the file
+//! content, [`TESSELLATOR_VERSION`], [`MODEL_VERSION`], the crate version and the
+//! requested step; any other key is a miss, and the entry is replaced. `IPC2581_CACHE_DIR` moves cache files into
+//! one directory, for sources in read-only or version-controlled folders.
+//!
+//! File layout: `IPC2581C` magic, then the [`CacheKey`] and the [`LoadedDesign`]
+//! as MessagePack maps.
+
+use crate::draw::geometry::{LayerJSON, ObjectRange, PadStackDef};
+use crate::draw::parsing::{BoardOutline, BomItem, Component, LengthUnit, LoadDiagnostic, Net, Stackup, StepInfo};
+use crate::draw::tessellation::TESSELLATOR_VERSION;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use xxhash_rust::xxh3::{xxh3_64, Xxh3};
+
+const MAGIC: &[u8; 8] = b"IPC2581C";
+
+/// Directory for cache files instead of the source file's directory
+pub const CACHE_DIR_ENV: &str = "IPC2581_CACHE_DIR";
+
+/// Version of the parsed models in [`LoadedDesign`], part of the cache key
+/// Bump it with any change to parsing that alters the components, BOM, nets, stackup,
+/// outline, steps or diagnostics a file loads as. Releases miss old entries regardless.
+pub const MODEL_VERSION: u32 = 5;
+
+/// Everything Load derives from a file, whether parsed or read from the cache
+#[derive(Serialize, Deserialize)]
+pub struct LoadedDesign {
+    pub source_unit: LengthUnit,
+    pub layers: Vec<LayerJSON>,  // DictionaryColor already applied
+    pub object_ranges: Vec<ObjectRange>,  // With component polar coordinates
+    pub padstack_defs: IndexMap<String, PadStackDef>,
+    pub layer_pairs: HashMap<String, String>,
+    pub layer_colors: HashMap<String, [f32; 4]>,
+    pub components: IndexMap<String, Component>,
+    pub bom: Vec<BomItem>,
+    pub nets: IndexMap<String, Net>,
+    pub stackup: Option<Stackup>,
+    pub board_outline: Option<BoardOutline>,
+    pub steps: Vec<StepInfo>,
+    pub active_step: Option<String>,
+    pub load_diagnostics: Vec<LoadDiagnostic>,
+    pub conductor_clearance_mm: Option<f32>,  // Dfx clearance rule, if the file has one
+}
+
+/// What a cache entry was built from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CacheKey {
+    pub content_hash: String,  // xxh3-128 of the file bytes, hex
+    pub tessellator_version: u32,
+    pub model_version: u32,
+    pub crate_version: String,  // CARGO_PKG_VERSION of the build that wrote the entry
+    pub step: Option<String>,  // Step requested by Load (None for the default step)
+}
+
+impl CacheKey {
+    /// Key for loading `file_path` with the requested step; reads the whole file once
+    pub fn for_file(file_path: &Path, step: Option<&str>) -> std::io::Result<Self> {
+        let mut reader = std::fs::File::open(file_path)?;
+        let mut hasher = Xxh3::new();
+        let mut buffer = vec![0u8; 1 << 20];
+        loop {
+            let read = reader.read(&mut buffer)?;
+            if read == 0 {
+                break;
+            }
+            hasher.update(&buffer[..read]);
+        }
+        Ok(Self {
+            content_hash: format!("{:032x}", hasher.digest128()),
+            tessellator_version: TESSELLATOR_VERSION,
+            model_version: MODEL_VERSION,
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            step: step.map(str::to_string),
+        })
+    }
+}
+
+/// Cache file for a source file: next to it, or in `IPC2581_CACHE_DIR` under a name unique to its path
+pub fn cache_path(file_path: &Path) -> PathBuf {
+    let file_name = file_path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    match std::env::var_os(CACHE_DIR_ENV) {
+        Some(dir) => {
+            let absolute = std::fs::canonicalize(file_path).unwrap_or_else(|_| file_path.to_path_buf());
+            let path_hash = xxh3_64(absolute.to_string_lossy().as_bytes());
+            PathBuf::from(dir).join(format!("{}-{:016x}.cache", file_name, path_hash))
+        }
+        None => file_path.with_file_name(format!(".{}.cache", file_name)),
+    }
+}
+
+/// The cached design for `key`, or None when the entry is missing, stale or unreadable
+pub fn read_cache(cache_path: &Path, key: &CacheKey) -> Option<LoadedDesign> {
+    let file = std::fs::File::open(cache_path).ok()?;
+    let mut reader = BufReader::new(file);
+    let mut magic = [0u8; 8];
+    reader.read_exact(&mut magic).ok()?;
+    if &magic != MAGIC {
+        return None;
+    }
+    let cached_key: CacheKey = rmp_serde::from_read(&mut reader).ok()?;
+    if &cached_key != key {
+        return None;
+    }
+    match rmp_serde::from_read(&mut reader) {
+        Ok(design) => Some(design),
+        Err(e) => {
+            eprintln!("[Cache] Ignoring unreadable cache {}: {}", cache_path.display(), e);
+            None
+        }
+    }
+}
+
+/// Write the design for `key`, replacing any previous entry
+/// The entry is written to a temporary file first, so a failed write never leaves a truncated cache.
+pub fn write_cache(cache_path: &Path, key: &CacheKey, design: &LoadedDesign) -> Result<(), anyhow::Error> {
+    if let Some(dir) = cache_path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
+    let temp_path = cache_path.with_extension("cache.tmp");
+    let result = (|| -> Result<(), anyhow::Error> {
+        let mut writer = BufWriter::new(std::fs::File::create(&temp_path)?);
+        writer.write_all(MAGIC)?;
+        rmp_serde::encode::write_named(&mut writer, key)?;
+        rmp_serde::encode::write_named(&mut writer, design)?;
+        writer.flush()?;
+        Ok(())
+    })();
+    match result {
+        Ok(()) => Ok(std::fs::rename(&temp_path, cache_path)?),
+        Err(e) => {
+            let _ = std::fs::remove_file(&temp_path);
+            Err(e)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::draw::progress::Progress;
+    use crate::lsp::handlers::handle_load;
+    use crate::lsp::state::{DocumentState, ServerState};
+
+    fn json<T: Serialize>(value: &T) -> String {
+        serde_json::to_string(value).unwrap()
+    }
+
+    fn load(path: &Path) -> (DocumentState, serde_json::Value) {
+        let mut state = ServerState::new(std::sync::mpsc::channel().0);
+        let response = handle_load(&mut state, None, Some(serde_json::json!({ "file_path": path })), &Progress::default());
+        let response = serde_json::to_value(&response).unwrap();
+        (state.documents.pop().unwrap().1, response["result"].clone())
+    }
+
+    #[test]
+    fn test_cached_load_matches_parsed_load() {
+        let dir = std::env::temp_dir().join(format!("ipc2581_cache_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("board.xml");
+        std::fs::copy("tests/pic_programmerC.xml", &path).unwrap();
+        let _ = std::fs::remove_file(cache_path(&path));
+
+        let (parsed, result) = load(&path);
+        assert_eq!(result["cached"], false);
+        assert!(cache_path(&path).exists());
+
+        let (cached, result) = load(&path);
+        assert_eq!(result["cached"], true);
+        assert_eq!(json(&parsed.layers), json(&cached.layers));
+        assert_eq!(json(&parsed.all_object_ranges), json(&cached.all_object_ranges));
+        assert_eq!(json(&parsed.components), json(&cached.components));
+        assert_eq!(json(&parsed.nets), json(&cached.nets));
+        assert_eq!(json(&parsed.bom), json(&cached.bom));
+        assert_eq!(json(&parsed.board_outline), json(&cached.board_outline));
+        assert_eq!(json(&parsed.load_diagnostics), json(&cached.load_diagnostics));
+        assert_eq!(parsed.padstack_defs.len(), cached.padstack_defs.len());
+        assert_eq!(parsed.spatial_index.as_ref().unwrap().size(), cached.spatial_index.as_ref().unwrap().size());
+
+        // Entries written by another model format or release are a miss
+        let key = CacheKey::for_file(&path, None).unwrap();
+        assert!(read_cache(&cache_path(&path), &key).is_some());
+        let older_model = CacheKey { model_version: MODEL_VERSION - 1, ..key.clone() };
+        assert!(read_cache(&cache_path(&path), &older_model).is_none());
+        let other_release = CacheKey { crate_version: "0.0.0".to_string(), ..key };
+        assert!(read_cache(&cache_path(&path), &other_release).is_none());
+
+        // Any change to the bytes is a miss
+        let mut source = std::fs::read(&path).unwrap();
+        source.extend_from_slice(b"\n");
+        std::fs::write(&path, source).unwrap();
+        let (_, result) = load(&path);
+        assert_eq!(result["cached"], false);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
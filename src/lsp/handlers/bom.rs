@@ -0,0 +1,55 @@
+//! BOM handlers: GetBom, GetBomItem
+
+use crate::lsp::protocol::{Response, error_codes};
+use crate::lsp::state::DocumentState;
+use crate::lsp::util::{parse_params, require_file_loaded};
+use serde::Deserialize;
+
+/// Handle GetBom request - returns all BOM items with refdes lists and approved vendors
+pub fn handle_get_bom(state: &DocumentState, id: Option<serde_json::Value>) -> Response {
+    if let Err(e) = require_file_loaded(state, id.clone()) {
+        return e;
+    }
+
+    Response::success(id, serde_json::json!({
+        "count": state.bom.len(),
+        "items": state.bom
+    }))
+}
+
+/// Handle GetBomItem request - looks up one BOM item by part number or by one of its refdes
+pub fn handle_get_bom_item(
+    state: &DocumentState,
+    id: Option<serde_json::Value>,
+    params: Option<serde_json::Value>
+) -> Response {
+    #[derive(Deserialize)]
+    struct Params {
+        part_number: Option<String>,
+        refdes: Option<String>,
+    }
+
+    let p: Params = match parse_params(id.clone(), params, "{part_number: string} or {refdes: string}") {
+        Ok(p) => p,
+        Err(e) => return e,
+    };
+
+    if let Err(e) = require_file_loaded(state, id.clone()) {
+        return e;
+    }
+
+    let item = match (&p.part_number, &p.refdes) {
+        (Some(part_number), _) => state.bom.iter().find(|item| &item.part_number == part_number),
+        (None, Some(refdes)) => state.bom.iter().find(|item| item.refdes.iter().any(|r| &r.name == refdes)),
+        (None, None) => {
+            return Response::error(id, error_codes::INVALID_PARAMS,
+                "Invalid params: expected {part_number: string} or {refdes: string}".to_string());
+        }
+    };
+
+    match item {
+        Some(item) => Response::success(id, serde_json::json!(item)),
+        None => Response::error(id, error_codes::BOM_ITEM_NOT_FOUND,
+            format!("BOM item not found: {}", p.part_number.or(p.refdes).unwrap_or_default())),
+    }
+}
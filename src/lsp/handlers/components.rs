@@ -0,0 +1,76 @@
+//! Component handlers: GetComponents, GetComponent
+
+use crate::lsp::protocol::{Response, error_codes};
+use crate::lsp::state::DocumentState;
+use crate::lsp::util::{parse_params, require_file_loaded};
+use serde::Deserialize;
+
+/// Handle GetComponents request - lists all placed components (without pins)
+/// Optional `side` param ("TOP" or "BOTTOM") filters by placement side.
+pub fn handle_get_components(
+    state: &DocumentState,
+    id: Option<serde_json::Value>,
+    params: Option<serde_json::Value>
+) -> Response {
+    #[derive(Deserialize, Default)]
+    struct Params { side: Option<String> }
+
+    if let Err(e) = require_file_loaded(state, id.clone()) {
+        return e;
+    }
+
+    let p: Params = match params {
+        Some(_) => match parse_params(id.clone(), params, "{side?: 'TOP' | 'BOTTOM'}") {
+            Ok(p) => p,
+            Err(e) => return e,
+        },
+        None => Params::default(),
+    };
+
+    let components: Vec<serde_json::Value> = state.components.values()
+        .filter(|c| p.side.as_ref().is_none_or(|side| c.side.eq_ignore_ascii_case(side)))
+        .map(|c| serde_json::json!({
+            "refdes": c.refdes,
+            "package_ref": c.package_ref,
+            "part": c.part,
+            "layer_ref": c.layer_ref,
+            "side": c.side,
+            "mount_type": c.mount_type,
+            "x": c.x,
+            "y": c.y,
+            "rotation": c.rotation,
+            "bounds": c.bounds,
+            "pin_count": c.pins.len()
+        }))
+        .collect();
+
+    Response::success(id, serde_json::json!({
+        "count": components.len(),
+        "components": components
+    }))
+}
+
+/// Handle GetComponent request - returns one component by refdes, including its pins
+pub fn handle_get_component(
+    state: &DocumentState,
+    id: Option<serde_json::Value>,
+    params: Option<serde_json::Value>
+) -> Response {
+    #[derive(Deserialize)]
+    struct Params { refdes: String }
+
+    let p: Params = match parse_params(id.clone(), params, "{refdes: string}") {
+        Ok(p) => p,
+        Err(e) => return e,
+    };
+
+    if let Err(e) = require_file_loaded(state, id.clone()) {
+        return e;
+    }
+
+    match state.components.get(&p.refdes) {
+        Some(component) => Response::success(id, serde_json::json!(component)),
+        None => Response::error(id, error_codes::COMPONENT_NOT_FOUND,
+            format!("Component not found: {}", p.refdes)),
+    }
+}
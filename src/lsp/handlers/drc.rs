@@ -1,11 +1,13 @@
 //! DRC handlers: RunDRC, GetDRCViolations, RunDRCWithRegions, GetDRCRegions
 
 use crate::lsp::protocol::{Response, error_codes};
-use crate::lsp::state::{ServerState, DrcAsyncResult};
+use crate::lsp::state::{DocumentState, DrcAsyncResult};
 use crate::draw::drc::{
     DesignRules, ModifiedRegionInfo,
     run_full_drc, run_full_drc_with_regions, run_incremental_drc_with_regions,
 };
+use crate::draw::progress::Progress;
+use crate::lsp::util::request_cancelled;
 use serde::Deserialize;
 use std::collections::HashSet;
 use std::sync::mpsc::Sender;
@@ -13,10 +15,12 @@ use std::thread;
 use std::time::Instant;
 
 /// Handle RunDRC request - runs Design Rule Check on all copper layers
+/// Cancelling `progress` keeps the previous violations.
 pub fn handle_run_drc(
-    state: &mut ServerState, 
+    state: &mut DocumentState, 
     id: Option<serde_json::Value>, 
-    params: Option<serde_json::Value>
+    params: Option<serde_json::Value>,
+    progress: &Progress,
 ) -> Response {
     #[derive(Deserialize)]
     struct RunDRCParams {
@@ -41,11 +45,17 @@ pub fn handle_run_drc(
         state.design_rules.conductor_clearance_mm);
     
     let start = Instant::now();
+    progress.begin("DRC");
     
     let violations = if let Some(ref spatial_index) = state.spatial_index {
-        run_full_drc(&state.layers, spatial_index, &state.design_rules)
+        run_full_drc(&state.layers, spatial_index, &state.design_rules, progress)
     } else {
-        vec![]
+        Ok(vec![])
+    };
+    progress.end();
+    let Ok(violations) = violations else {
+        eprintln!("[LSP Server] DRC cancelled after {:.2?}", start.elapsed());
+        return request_cancelled(id);
     };
     
     let elapsed = start.elapsed();
@@ -65,18 +75,21 @@ pub fn handle_run_drc(
 
 /// Handle GetDRCViolations request - returns cached DRC violations
 pub fn handle_get_drc_violations(
-    state: &ServerState, 
+    state: &DocumentState, 
     id: Option<serde_json::Value>
 ) -> Response {
     Response::success(id, serde_json::to_value(&state.drc_violations).unwrap())
 }
 
 /// Handle RunDRCWithRegions request asynchronously
+/// The check keeps running after the response: `progress` reports it until the `drcComplete`
+/// notification, and cancelling it (by the request id) ends the check without new regions.
 pub fn handle_run_drc_with_regions_async(
-    state: &mut ServerState, 
+    state: &mut DocumentState, 
     id: Option<serde_json::Value>, 
     params: Option<serde_json::Value>,
-    tx: Option<Sender<DrcAsyncResult>>
+    tx: Sender<DrcAsyncResult>,
+    progress: Progress,
 ) -> String {
     #[derive(Deserialize)]
     struct RunDRCParams {
@@ -96,14 +109,6 @@ pub fn handle_run_drc_with_regions_async(
         return serde_json::to_string(&response).unwrap();
     }
 
-    let tx = match tx {
-        Some(tx) => tx,
-        None => {
-            let response = Response::error(id, 3, "DRC channel not available".to_string());
-            return serde_json::to_string(&response).unwrap();
-        }
-    };
-
     let clearance = params.clearance_mm.unwrap_or(state.design_rules.conductor_clearance_mm);
     
     // Clone data for background thread
@@ -112,6 +117,8 @@ pub fn handle_run_drc_with_regions_async(
     let design_rules = DesignRules { conductor_clearance_mm: clearance };
     
     let deleted_ids: HashSet<u64> = state.deleted_objects.keys().copied().collect();
+    let document = state.handle.clone();
+    let request_id = id.clone();
     
     // Check for incremental DRC
     let modified_regions: Vec<ModifiedRegionInfo> = state.modified_regions
@@ -141,22 +148,24 @@ pub fn handle_run_drc_with_regions_async(
     // Spawn DRC in background
     thread::spawn(move || {
         let start = Instant::now();
+        progress.begin("DRC");
         
         let regions = if let Some(ref index) = spatial_index {
             if use_incremental {
                 run_incremental_drc_with_regions(
                     &layers, index, &design_rules, &deleted_ids, 
-                    &modified_regions, &existing_regions
+                    &modified_regions, &existing_regions, &progress
                 )
             } else {
-                run_full_drc_with_regions(&layers, index, &design_rules, &deleted_ids)
+                run_full_drc_with_regions(&layers, index, &design_rules, &deleted_ids, &progress)
             }
         } else {
-            vec![]
+            Ok(vec![])
         };
+        progress.end();
         
         let elapsed_ms = start.elapsed().as_secs_f64() * 1000.0;
-        let _ = tx.send(DrcAsyncResult { regions, elapsed_ms });
+        let _ = tx.send(DrcAsyncResult { document, request_id, regions: regions.ok(), elapsed_ms });
     });
 
     let response = Response::success(id, serde_json::json!({
@@ -172,7 +181,7 @@ pub fn handle_run_drc_with_regions_async(
 
 /// Handle GetDRCRegions request - returns cached DRC regions
 pub fn handle_get_drc_regions(
-    state: &ServerState, 
+    state: &DocumentState, 
     id: Option<serde_json::Value>
 ) -> Response {
     Response::success(id, serde_json::to_value(&state.drc_regions).unwrap())
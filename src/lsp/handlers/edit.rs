@@ -1,14 +1,14 @@
 //! Edit handlers: Delete, Undo, Redo, MoveObjects
 
 use crate::lsp::protocol::{Response, error_codes};
-use crate::lsp::state::{ServerState, ObjectMove, ObjectRotation};
+use crate::lsp::state::{DocumentState, ObjectMove, ObjectRotation};
 use crate::lsp::util::parse_params;
 use crate::draw::geometry::ObjectRange;
 use serde::Deserialize;
 
 /// Handle Delete request - marks an object as deleted
 pub fn handle_delete(
-    state: &mut ServerState, 
+    state: &mut DocumentState, 
     id: Option<serde_json::Value>, 
     params: Option<serde_json::Value>
 ) -> Response {
@@ -75,7 +75,7 @@ pub fn handle_delete(
 
 /// Handle Undo request - restores a deleted object
 pub fn handle_undo(
-    state: &mut ServerState, 
+    state: &mut DocumentState, 
     id: Option<serde_json::Value>, 
     params: Option<serde_json::Value>
 ) -> Response {
@@ -107,7 +107,7 @@ pub fn handle_undo(
 
 /// Handle Redo request - re-deletes an undone object
 pub fn handle_redo(
-    state: &mut ServerState, 
+    state: &mut DocumentState, 
     id: Option<serde_json::Value>, 
     params: Option<serde_json::Value>
 ) -> Response {
@@ -139,7 +139,7 @@ pub fn handle_redo(
 
 /// Handle MoveObjects request - records a move operation for multiple objects
 pub fn handle_move_objects(
-    state: &mut ServerState,
+    state: &mut DocumentState,
     id: Option<serde_json::Value>,
     params: Option<serde_json::Value>
 ) -> Response {
@@ -204,7 +204,7 @@ pub fn handle_move_objects(
 }
 
 /// Rebuild the spatial index from all_object_ranges
-pub fn rebuild_spatial_index(state: &mut ServerState) {
+pub fn rebuild_spatial_index(state: &mut DocumentState) {
     use crate::draw::geometry::SelectableObject;
     use rstar::RTree;
     
@@ -221,7 +221,7 @@ pub fn rebuild_spatial_index(state: &mut ServerState) {
 
 /// Handle RotateObjects request - records a rotation operation for multiple objects
 pub fn handle_rotate_objects(
-    state: &mut ServerState,
+    state: &mut DocumentState,
     id: Option<serde_json::Value>,
     params: Option<serde_json::Value>
 ) -> Response {
@@ -323,7 +323,7 @@ pub fn handle_rotate_objects(
 
 /// Handle UndoRotate request - reverses a rotation operation for objects
 pub fn handle_undo_rotate(
-    state: &mut ServerState,
+    state: &mut DocumentState,
     id: Option<serde_json::Value>,
     params: Option<serde_json::Value>
 ) -> Response {
@@ -367,7 +367,7 @@ pub fn handle_undo_rotate(
 
 /// Handle RedoRotate request - re-applies a rotation operation for objects
 pub fn handle_redo_rotate(
-    state: &mut ServerState,
+    state: &mut DocumentState,
     id: Option<serde_json::Value>,
     params: Option<serde_json::Value>
 ) -> Response {
@@ -417,7 +417,7 @@ pub fn handle_redo_rotate(
 
 /// Handle UndoMove request - reverses a move operation for objects
 pub fn handle_undo_move(
-    state: &mut ServerState,
+    state: &mut DocumentState,
     id: Option<serde_json::Value>,
     params: Option<serde_json::Value>
 ) -> Response {
@@ -475,7 +475,7 @@ pub fn handle_undo_move(
 
 /// Handle RedoMove request - re-applies a move operation for objects
 pub fn handle_redo_move(
-    state: &mut ServerState,
+    state: &mut DocumentState,
     id: Option<serde_json::Value>,
     params: Option<serde_json::Value>
 ) -> Response {
@@ -534,7 +534,7 @@ pub fn handle_redo_move(
 /// Handle FlipObjects request - records a flip operation for component objects
 /// Flips geometry around component center and swaps TOP↔BOTTOM layers
 pub fn handle_flip_objects(
-    state: &mut ServerState,
+    state: &mut DocumentState,
     id: Option<serde_json::Value>,
     params: Option<serde_json::Value>
 ) -> Response {
@@ -0,0 +1,69 @@
+//! Design export: ExportSqlite
+
+use crate::draw::parsing::{normalize_units, select_step};
+use crate::lsp::protocol::{Response, error_codes};
+use crate::lsp::state::DocumentState;
+use crate::parse_xml::parse_xml_file_parallel;
+use crate::xml_to_sqlite::export_design;
+use serde::Deserialize;
+use std::time::Instant;
+
+/// Handle ExportSqlite request - writes the design database of a file for ad-hoc SQL
+/// Exports `file_path` when given (no Load needed), otherwise the `document`'s file as it is on disk
+/// and its active step. The database goes next to the file (`<stem>.sqlite`) unless `output_path` is set.
+pub fn handle_export_sqlite(
+    document: Option<&DocumentState>,
+    id: Option<serde_json::Value>,
+    params: Option<serde_json::Value>
+) -> Response {
+    #[derive(Deserialize)]
+    struct ExportParams {
+        #[serde(default)]
+        file_path: Option<String>,
+        #[serde(default)]
+        output_path: Option<String>,
+        #[serde(default)]
+        step: Option<String>,
+    }
+
+    let params: ExportParams = params
+        .and_then(|p| serde_json::from_value(p).ok())
+        .unwrap_or(ExportParams { file_path: None, output_path: None, step: None });
+
+    // The loaded DOM is not kept, so read the file itself
+    let (file_path, step) = match params.file_path {
+        Some(path) => (path, params.step),
+        None => match document.and_then(|d| d.xml_file_path.as_ref().map(|path| (path, &d.active_step))) {
+            Some((path, active_step)) => (path.clone(), params.step.or_else(|| active_step.clone())),
+            None => return Response::error(id, error_codes::NO_FILE_LOADED,
+                "No file loaded. Call Load first or pass file_path.".to_string()),
+        },
+    };
+    let output_path = params.output_path.unwrap_or_else(|| {
+        std::path::Path::new(&file_path).with_extension("sqlite").to_string_lossy().to_string()
+    });
+
+    let start = Instant::now();
+    let mut root = match parse_xml_file_parallel(&file_path, "LayerFeature") {
+        Ok(root) => root,
+        Err(e) => return Response::error(id, error_codes::PARSE_FAILED, format!("Failed to parse XML: {}", e)),
+    };
+    let source_unit = normalize_units(&mut root);
+    if let Err(e) = select_step(&root, step.as_deref()) {
+        return Response::error(id, error_codes::STEP_NOT_FOUND, e.to_string());
+    }
+
+    match export_design(&root, &file_path, source_unit, step.as_deref(), &output_path) {
+        Ok(export) => {
+            eprintln!("[LSP Server] Exported {} to {} in {:.2?}: {} components, {} features",
+                file_path, output_path, start.elapsed(), export.components, export.features);
+            Response::success(id, serde_json::json!({
+                "status": "ok",
+                "file_path": file_path,
+                "output_path": output_path,
+                "rows": export
+            }))
+        }
+        Err(e) => Response::error(id, error_codes::EXPORT_FAILED, format!("Failed to export SQLite: {:#}", e)),
+    }
+}
@@ -1,28 +1,47 @@
-//! File operations: Load, Save, Close
+//! File operations: Load, GetDocuments, GetLoadDiagnostics, Save, Close
 
+use crate::lsp::cache::{cache_path, read_cache, write_cache, CacheKey, LoadedDesign};
 use crate::lsp::protocol::{Response, error_codes};
-use crate::lsp::state::ServerState;
-use crate::lsp::util::get_process_memory_bytes;
+use crate::lsp::state::{DocumentState, ServerState, document_handle};
+use crate::lsp::util::{get_process_memory_bytes, request_cancelled};
 use crate::lsp::xml_helpers::{parse_dictionary_colors, update_dictionary_colors, remove_deleted_objects_from_xml, apply_moved_objects_to_xml, parse_dfx_clearance_rule};
-use crate::parse_xml::parse_xml_file;
+use crate::parse_xml::{parse_xml_bytes_parallel, XmlParseError};
 use crate::draw::geometry::SelectableObject;
-use crate::draw::parsing::{extract_and_generate_layers, parse_padstack_definitions, parse_layer_metadata, build_layer_pairs};
-use crate::serialize_xml::xml_node_to_file;
+use crate::draw::progress::{is_cancelled, Progress};
+use crate::draw::parsing::{stream_document, parse_document_parallel, parse_padstack_definitions, parse_layer_metadata, build_layer_pairs, parse_components, parse_stackup, parse_bom, parse_step_outline, parse_nets, parse_steps, find_step, select_step, step_name};
+use crate::lsp::util::require_file_loaded;
+use crate::schema::Severity;
+use crate::serialize_xml::{xml_node_to_file, xml_node_to_file_lossless};
 use rstar::RTree;
 use serde::Deserialize;
 use std::time::Instant;
 
-/// Handle Load request - loads and parses an IPC-2581 XML file
+#[derive(Deserialize)]
+struct LoadParams {
+    file_path: String,
+    #[serde(default)]
+    document: Option<String>,  // Handle for later requests (default: file_path); loading it again replaces it
+    #[serde(default)]
+    step: Option<String>,  // Step to draw (default: the first step no StepRepeat places)
+    #[serde(default)]
+    parallel: bool,  // Parse LayerFeatures on all cores into the full DOM instead of streaming them
+    #[serde(default = "default_cache")]
+    cache: bool,  // Read and write the on-disk Load cache
+}
+
+fn default_cache() -> bool {
+    true
+}
+
+/// Handle Load request - loads and parses an IPC-2581 XML file into a document
+/// Other open documents are kept; a document with the same handle is replaced once the new one has loaded.
+/// Progress is reported for parsing, tessellation (per layer), the design model and the spatial index.
 pub fn handle_load(
-    state: &mut ServerState, 
+    server: &mut ServerState, 
     id: Option<serde_json::Value>, 
-    params: Option<serde_json::Value>
+    params: Option<serde_json::Value>,
+    progress: &Progress,
 ) -> Response {
-    #[derive(Deserialize)]
-    struct LoadParams {
-        file_path: String,
-    }
-
     let params: LoadParams = match params.and_then(|p| serde_json::from_value(p).ok()) {
         Some(p) => p,
         None => {
@@ -31,28 +50,198 @@ pub fn handle_load(
         }
     };
 
-    eprintln!("[LSP Server] Loading file: {}", params.file_path);
+    progress.begin(&format!("Loading {}", params.file_path));
+    let response = load_document(server, id, params, progress);
+    progress.end();
+    response
+}
+
+fn load_document(server: &mut ServerState, id: Option<serde_json::Value>, params: LoadParams, progress: &Progress) -> Response {
+    let handle = params.document.clone().unwrap_or_else(|| params.file_path.clone());
+    eprintln!("[LSP Server] Loading file: {} (document {})", params.file_path, handle);
 
     let start_total = Instant::now();
 
-    // Parse XML file
+    // A cache entry for the same bytes, tessellator and step replaces parsing and tessellation
+    let cache = if params.cache {
+        let path = std::path::Path::new(&params.file_path);
+        match CacheKey::for_file(path, params.step.as_deref()) {
+            Ok(key) => Some((cache_path(path), key)),
+            Err(e) => {
+                eprintln!("[LSP Server] Cache disabled, cannot hash file: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+    if cache.is_some() {
+        progress.report("Reading cache", 0);
+    }
+    let cached = cache.as_ref().and_then(|(path, key)| read_cache(path, key));
+    let from_cache = cached.is_some();
+
+    let design = match cached {
+        Some(design) => {
+            eprintln!("[LSP Server] Loaded from cache {} in {:.2?}", cache.as_ref().unwrap().0.display(), start_total.elapsed());
+            design
+        }
+        None => {
+            let design = match parse_design(&params, id.clone(), &progress.phase(0, 90)) {
+                Ok(design) => design,
+                Err(response) => return response,
+            };
+            if let Some((path, key)) = &cache {
+                let start_write = Instant::now();
+                match write_cache(path, key, &design) {
+                    Ok(()) => eprintln!("[LSP Server] Wrote cache {} in {:.2?}", path.display(), start_write.elapsed()),
+                    Err(e) => eprintln!("[LSP Server] Failed to write cache {}: {}", path.display(), e),
+                }
+            }
+            design
+        }
+    };
+
+    // Build spatial index
+    if progress.is_cancelled() {
+        return request_cancelled(id);
+    }
+    progress.report("Building spatial index", 90);
+    let start_index = Instant::now();
+    let selectable_objects: Vec<SelectableObject> = design.object_ranges.iter()
+        .cloned()
+        .map(SelectableObject::new)
+        .collect();
+    let spatial_index = RTree::bulk_load(selectable_objects);
+    eprintln!("[LSP Server] Spatial Index build time: {:.2?}", start_index.elapsed());
+
+    let mut state = DocumentState::new(handle.clone());
+    match design.conductor_clearance_mm {
+        Some(clearance_mm) => {
+            state.design_rules.conductor_clearance_mm = clearance_mm;
+            eprintln!("[LSP Server] Using DFM clearance from file: {:.4}mm", clearance_mm);
+        }
+        None => eprintln!("[LSP Server] No DFM clearance rule found, using default: {:.4}mm", 
+            state.design_rules.conductor_clearance_mm),
+    }
+
+    // Update state
+    let layer_pairs = design.layer_pairs;
+    state.xml_file_path = Some(params.file_path.clone());
+    state.xml_root = None; // Don't store to save memory
+    state.source_unit = design.source_unit;
+    state.layers = design.layers;
+    state.layer_colors = design.layer_colors;
+    state.spatial_index = Some(spatial_index);
+    state.padstack_defs = design.padstack_defs;
+    state.components = design.components;
+    state.bom = design.bom;
+    state.nets = design.nets;
+    state.stackup = design.stackup;
+    state.board_outline = design.board_outline;
+    state.steps = design.steps;
+    state.active_step = design.active_step;
+    state.load_diagnostics = design.load_diagnostics;
+    state.layer_pairs = layer_pairs.clone();
+    state.all_object_ranges = design.object_ranges;
+
+    eprintln!("[LSP Server] Total Load time: {:.2?}", start_total.elapsed());
+    eprintln!("[LSP Server] File loaded successfully (xml_root dropped to save memory)");
+
+    let result = serde_json::json!({
+        "status": "ok",
+        "document": handle,
+        "file_path": params.file_path,
+        "source_unit": state.source_unit,
+        "active_step": state.active_step,
+        "load_diagnostic_count": state.load_diagnostics.len(),
+        "layer_pairs": layer_pairs,
+        "cached": from_cache
+    });
+
+    // Re-inserting moves a reloaded document to the end, making it the default target
+    server.documents.shift_remove(&handle);
+    server.documents.insert(handle, state);
+    eprintln!("[LSP Server] Open documents: {}", server.documents.len());
+
+    Response::success(id, result)
+}
+
+/// Handle GetDocuments request - lists the open documents, the default target last
+pub fn handle_get_documents(server: &ServerState, id: Option<serde_json::Value>) -> Response {
+    let documents: Vec<serde_json::Value> = server.documents.values()
+        .map(|document| serde_json::json!({
+            "document": document.handle,
+            "file_path": document.xml_file_path,
+            "active_step": document.active_step,
+            "object_count": document.all_object_ranges.len()
+        }))
+        .collect();
+    Response::success(id, serde_json::json!(documents))
+}
+
+/// Parse and tessellate the file for Load; errors are the Load response
+fn parse_design(params: &LoadParams, id: Option<serde_json::Value>, progress: &Progress) -> Result<LoadedDesign, Response> {
+    // Parse XML file; streamed LayerFeatures are collected into geometry as they are read and not kept
     let start_parse = Instant::now();
-    let root = match parse_xml_file(&params.file_path) {
+    progress.report("Parsing XML", 0);
+    let parsed = if params.parallel {
+        parse_document_parallel(&params.file_path)
+    } else {
+        stream_document(&params.file_path, progress)
+    };
+    let mut document = match parsed {
         Ok(doc) => doc,
+        Err(e) if is_cancelled(&e) => return Err(request_cancelled(id)),
         Err(e) => {
-            return Response::error(id, 1, format!("Failed to parse XML: {}", e));
+            // Syntax errors carry their location so the editor can jump to it
+            let message = format!("Failed to parse XML: {}", e);
+            return Err(match e.downcast_ref::<XmlParseError>() {
+                Some(error) => Response::error_with_data(id, 1, message, serde_json::json!({
+                    "file_path": params.file_path,
+                    "reason": error.message,
+                    "offset": error.position.offset,
+                    "line": error.position.line,
+                    "column": error.position.column,
+                })),
+                None => Response::error(id, 1, message),
+            });
         }
     };
     eprintln!("[LSP Server] XML Parse time: {:.2?}", start_parse.elapsed());
+    if progress.is_cancelled() {
+        return Err(request_cancelled(id));
+    }
 
-    // Extract and generate layer geometries
+    // All lengths are in millimetres now; Save converts edits back to this unit
+    let source_unit = document.source_unit;
+    eprintln!("[LSP Server] CadHeader units: {}", source_unit.as_ipc_str());
+
+    // Dangling references are skipped by the parsers below; record them so missing objects can be explained
+    let load_diagnostics = std::mem::take(&mut document.load_diagnostics);
+    eprintln!("[LSP Server] Reference check: {} diagnostics", load_diagnostics.len());
+
+    // Choose the Step to draw; files with several steps (panels, coupons) show one at a time
+    let steps = parse_steps(&document.root);
+    let active_step_name = match select_step(&document.root, params.step.as_deref()) {
+        Ok(step) => step.map(|s| step_name(s).to_string()),
+        Err(e) => return Err(Response::error(id, error_codes::STEP_NOT_FOUND, e.to_string())),
+    };
+    if let Some(name) = &active_step_name {
+        eprintln!("[LSP Server] Active step: {} ({} steps)", name, steps.len());
+    }
+
+    // Generate layer geometries from the collected features
     let start_gen = Instant::now();
-    let (layers, mut object_ranges) = match extract_and_generate_layers(&root) {
+    let (layers, mut object_ranges) = match document.step_layers(active_step_name.as_deref(), &progress.phase(45, 90)) {
         Ok((layers, ranges)) => (layers, ranges),
+        Err(e) if is_cancelled(&e) => return Err(request_cancelled(id)),
         Err(e) => {
-            return Response::error(id, 1, format!("Failed to generate layers: {}", e));
+            return Err(Response::error(id, 1, format!("Failed to generate layers: {}", e)));
         }
     };
+    let root = document.root;
+    let active_step = active_step_name.as_deref().and_then(|name| find_step(&root, name));
     eprintln!("[LSP Server] Layer Generation (Tessellation) time: {:.2?}", start_gen.elapsed());
     
     // Debug stats
@@ -64,20 +253,10 @@ pub fn handle_load(
         object_ranges.len(), pads, vias, objects_with_net, objects_with_component);
     
     // Calculate component polar coordinates for rotation support
+    progress.report("Building design model", 90);
     use crate::draw::geometry::calculate_component_polar_coords;
     calculate_component_polar_coords(&mut object_ranges);
     
-    // Keep a copy of object_ranges for DRC
-    let all_object_ranges = object_ranges.clone();
-    
-    // Build spatial index
-    let start_index = Instant::now();
-    let selectable_objects: Vec<SelectableObject> = object_ranges.into_iter()
-        .map(SelectableObject::new)
-        .collect();
-    let spatial_index = RTree::bulk_load(selectable_objects);
-    eprintln!("[LSP Server] Spatial Index build time: {:.2?}", start_index.elapsed());
-    
     // Parse padstack definitions
     let padstack_defs = parse_padstack_definitions(&root);
     eprintln!("[LSP Server] Parsed {} padstack definitions", padstack_defs.len());
@@ -86,8 +265,28 @@ pub fn handle_load(
     let layer_meta = parse_layer_metadata(&root);
     let layer_pairs = build_layer_pairs(&layer_meta);
     eprintln!("[LSP Server] Built {} layer pairs for flip operations", layer_pairs.len() / 2);
+
+    // Build the component model (placement, package and board-space pins)
+    let components = parse_components(&root, &layer_meta);
+    eprintln!("[LSP Server] Parsed {} components", components.len());
+
+    // BOM/AVL, linked to the placed components by refdes
+    let bom = parse_bom(&root, |refdes| components.contains_key(refdes));
+    eprintln!("[LSP Server] Parsed {} BOM items", bom.len());
+
+    // Netlist from LogicalNet/PhyNetGroup, with pin locations from the components
+    let nets = parse_nets(&root, &components);
+    eprintln!("[LSP Server] Parsed {} nets", nets.len());
+
+    // Stackup model (layers are already ordered by its sequence)
+    let stackup = parse_stackup(&root, &layer_meta);
+
+    // Board outline of the active step (also emitted as the synthetic outline layer)
+    let board_outline = active_step.and_then(parse_step_outline);
+    if let Some(outline) = &board_outline {
+        eprintln!("[LSP Server] Board outline: {} cutouts, area {:.2}mm²", outline.cutouts.len(), outline.area);
+    }
     
-    eprintln!("[LSP Server] Total Load time: {:.2?}", start_total.elapsed());
     eprintln!("[LSP Server] Generated {} layers", layers.len());
 
     // Parse DictionaryColor from XML
@@ -95,13 +294,7 @@ pub fn handle_load(
     eprintln!("[LSP Server] Parsed {} layer colors from DictionaryColor", layer_colors.len());
 
     // Parse DFM design rules from Dfx elements
-    if let Some(clearance_mm) = parse_dfx_clearance_rule(&root) {
-        state.design_rules.conductor_clearance_mm = clearance_mm;
-        eprintln!("[LSP Server] Using DFM clearance from file: {:.4}mm", clearance_mm);
-    } else {
-        eprintln!("[LSP Server] No DFM clearance rule found, using default: {:.4}mm", 
-            state.design_rules.conductor_clearance_mm);
-    }
+    let conductor_clearance_mm = parse_dfx_clearance_rule(&root, source_unit);
 
     // Apply colors to layers
     let mut layers = layers;
@@ -114,46 +307,74 @@ pub fn handle_load(
         }
     }
 
-    // Update state
-    state.xml_file_path = Some(params.file_path.clone());
-    state.xml_root = None; // Don't store to save memory
-    state.layers = layers;
-    state.layer_colors = layer_colors;
-    state.spatial_index = Some(spatial_index);
-    state.padstack_defs = padstack_defs;
-    state.layer_pairs = layer_pairs.clone();
-    state.all_object_ranges = all_object_ranges;
-    state.drc_violations.clear();
-    state.drc_regions.clear();
-    state.deleted_objects.clear();
-    state.moved_objects.clear();
-    state.flipped_objects.clear();
-    state.modified_regions.clear();
+    Ok(LoadedDesign {
+        source_unit,
+        layers,
+        object_ranges,
+        padstack_defs,
+        layer_pairs,
+        layer_colors,
+        components,
+        bom,
+        nets,
+        stackup,
+        board_outline,
+        steps,
+        active_step: active_step_name,
+        load_diagnostics,
+        conductor_clearance_mm,
+    })
+}
 
-    eprintln!("[LSP Server] File loaded successfully (xml_root dropped to save memory)");
+/// Handle GetLoadDiagnostics request - returns the reference problems found during Load
+pub fn handle_get_load_diagnostics(state: &DocumentState, id: Option<serde_json::Value>) -> Response {
+    if let Err(e) = require_file_loaded(state, id.clone()) {
+        return e;
+    }
 
+    let errors = state.load_diagnostics.iter().filter(|d| d.severity == Severity::Error).count();
     Response::success(id, serde_json::json!({
-        "status": "ok",
-        "file_path": params.file_path,
-        "layer_pairs": layer_pairs
+        "count": state.load_diagnostics.len(),
+        "error_count": errors,
+        "warning_count": state.load_diagnostics.len() - errors,
+        "diagnostics": state.load_diagnostics
     }))
 }
 
 /// Handle Save request - serializes XML with modifications to disk
+/// Cancellation is honoured until the output file is written.
 pub fn handle_save(
-    state: &mut ServerState,
+    state: &mut DocumentState,
+    id: Option<serde_json::Value>,
+    params: Option<serde_json::Value>,
+    progress: &Progress,
+) -> Response {
+    progress.begin("Saving");
+    let response = save_document(state, id, params, progress);
+    progress.end();
+    response
+}
+
+fn save_document(
+    state: &mut DocumentState,
     id: Option<serde_json::Value>,
     params: Option<serde_json::Value>,
+    progress: &Progress,
 ) -> Response {
     #[derive(Deserialize)]
     struct SaveParams {
         #[serde(default)]
         file_path: Option<String>,
+        #[serde(default = "default_lossless")]
+        lossless: bool,  // Patch the source text instead of re-serializing the whole tree
+    }
+    fn default_lossless() -> bool {
+        true
     }
 
     let params: SaveParams = params
         .and_then(|p| serde_json::from_value(p).ok())
-        .unwrap_or(SaveParams { file_path: None });
+        .unwrap_or(SaveParams { file_path: None, lossless: true });
 
     if !state.is_file_loaded() {
         return Response::error(id, error_codes::NO_FILE_LOADED, 
@@ -185,9 +406,17 @@ pub fn handle_save(
         eprintln!("[LSP Server]   Moved: id={}, delta=({:.3}, {:.3})", obj_id, mov.delta_x, mov.delta_y);
     }
 
-    // Re-parse the original XML file
+    // Re-parse the original XML file, keeping its text for the lossless writer
+    progress.report("Parsing XML", 0);
     let start_parse = std::time::Instant::now();
-    let mut root = match parse_xml_file(original_path) {
+    let source = match std::fs::read(original_path) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            return Response::error(id, error_codes::PARSE_FAILED,
+                format!("Failed to read XML for save: {}", e));
+        }
+    };
+    let mut root = match parse_xml_bytes_parallel(&source, "LayerFeature") {
         Ok(r) => r,
         Err(e) => {
             return Response::error(id, error_codes::PARSE_FAILED, 
@@ -195,6 +424,10 @@ pub fn handle_save(
         }
     };
     eprintln!("[LSP Server] Re-parsed XML in {:.2?}", start_parse.elapsed());
+    if progress.is_cancelled() {
+        return request_cancelled(id);
+    }
+    progress.report("Applying edits", 60);
     
     // Update colors if modified
     if !state.modified_colors.is_empty() {
@@ -205,19 +438,29 @@ pub fn handle_save(
     // Apply moved objects
     if !state.moved_objects.is_empty() {
         let moved_count = apply_moved_objects_to_xml(
-            &mut root, &state.moved_objects, &state.all_object_ranges, &state.padstack_defs);
+            &mut root, &state.moved_objects, &state.all_object_ranges, &state.padstack_defs, state.source_unit,
+            state.active_step.as_deref());
         eprintln!("[LSP Server] Applied moves to {} objects in XML", moved_count);
     }
     
     // Remove deleted objects
     if !state.deleted_objects.is_empty() {
         let removed_count = remove_deleted_objects_from_xml(
-            &mut root, &state.deleted_objects, &state.layers, &state.padstack_defs);
+            &mut root, &state.deleted_objects, &state.layers, &state.padstack_defs, state.active_step.as_deref());
         eprintln!("[LSP Server] Removed {} objects from XML", removed_count);
     }
 
-    // Serialize to file
-    match xml_node_to_file(&root, &output_path) {
+    // Serialize to file; the lossless writer leaves everything but the edits as in the source
+    if progress.is_cancelled() {
+        return request_cancelled(id);
+    }
+    progress.report("Writing file", 80);
+    let result = if params.lossless {
+        xml_node_to_file_lossless(&root, &source, &output_path)
+    } else {
+        xml_node_to_file(&root, &output_path)
+    };
+    match result {
         Ok(_) => {
             let deleted_count = state.deleted_objects.len();
             let moved_count = state.moved_objects.len();
@@ -225,6 +468,7 @@ pub fn handle_save(
             Response::success(id, serde_json::json!({
                 "status": "ok",
                 "file_path": output_path,
+                "lossless": params.lossless,
                 "deleted_objects_count": deleted_count,
                 "moved_objects_count": moved_count
             }))
@@ -236,42 +480,78 @@ pub fn handle_save(
     }
 }
 
-/// Handle Close request - clears all state to free memory
-pub fn handle_close(state: &mut ServerState, id: Option<serde_json::Value>) -> Response {
+/// Handle Close request - closes the `document` (default: the most recently loaded one) to free its memory
+pub fn handle_close(
+    server: &mut ServerState,
+    id: Option<serde_json::Value>,
+    params: Option<serde_json::Value>,
+) -> Response {
+    let handle = match document_handle(params.as_ref()) {
+        Some(handle) if !server.documents.contains_key(handle) => {
+            return Response::error(id, error_codes::DOCUMENT_NOT_FOUND,
+                format!("Document not found: {}", handle));
+        }
+        Some(handle) => Some(handle.to_string()),
+        None => server.documents.last().map(|(handle, _)| handle.clone()),
+    };
+
     let old_memory = get_process_memory_bytes().unwrap_or(0);
-    
-    // Clear all state
-    state.xml_file_path = None;
-    state.xml_root = None;
-    state.layers.clear();
-    state.layer_colors.clear();
-    state.modified_colors.clear();
-    state.spatial_index = None;
-    state.padstack_defs.clear();
-    state.deleted_objects.clear();
-    state.moved_objects.clear();
-    state.hidden_layers.clear();
-    state.all_object_ranges.clear();
-    state.drc_violations.clear();
-    state.drc_regions.clear();
-    state.modified_regions.clear();
-    
-    // Shrink capacity
-    state.layers.shrink_to_fit();
-    state.layer_colors.shrink_to_fit();
-    state.modified_colors.shrink_to_fit();
-    state.padstack_defs.shrink_to_fit();
-    state.deleted_objects.shrink_to_fit();
-    state.moved_objects.shrink_to_fit();
-    state.all_object_ranges.shrink_to_fit();
-    state.drc_violations.shrink_to_fit();
-    state.drc_regions.shrink_to_fit();
-    
+    if let Some(handle) = &handle {
+        // Dropping the document frees all of its state
+        server.documents.shift_remove(handle);
+    }
     let new_memory = get_process_memory_bytes().unwrap_or(0);
-    eprintln!("[LSP Server] Close: freed {} MB", 
-        (old_memory as i64 - new_memory as i64) / 1024 / 1024);
+    eprintln!("[LSP Server] Close {}: freed {} MB, {} documents open",
+        handle.as_deref().unwrap_or("(none)"), (old_memory as i64 - new_memory as i64) / 1024 / 1024,
+        server.documents.len());
     
     Response::success(id, serde_json::json!({
+        "document": handle,
         "freed_bytes": old_memory.saturating_sub(new_memory)
     }))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(response: Response) -> serde_json::Value {
+        serde_json::to_value(&response).unwrap()
+    }
+
+    fn load(server: &mut ServerState, file_path: &str, document: Option<&str>) -> serde_json::Value {
+        call(handle_load(server, None, Some(serde_json::json!({
+            "file_path": file_path, "document": document, "cache": false
+        })), &Progress::default()))
+    }
+
+    #[test]
+    fn test_documents_are_independent() {
+        let mut server = ServerState::new(std::sync::mpsc::channel().0);
+        assert_eq!(load(&mut server, "tests/pic_programmerB.xml", None)["result"]["document"], "tests/pic_programmerB.xml");
+        assert_eq!(load(&mut server, "tests/pic_programmerC.xml", Some("rev-c"))["result"]["document"], "rev-c");
+        assert_eq!(server.documents.len(), 2);
+
+        // No handle targets the most recently loaded document
+        let rev_b = server.document(Some("tests/pic_programmerB.xml")).unwrap();
+        assert_eq!(rev_b.xml_file_path.as_deref(), Some("tests/pic_programmerB.xml"));
+        assert_eq!(server.document(None).unwrap().handle, "rev-c");
+        let listed = call(handle_get_documents(&server, None));
+        assert_eq!(listed["result"][1]["document"], "rev-c");
+
+        // Loading a handle again replaces that document and makes it the default
+        load(&mut server, "tests/pic_programmerB.xml", None);
+        assert_eq!(server.documents.len(), 2);
+        assert_eq!(server.document(None).unwrap().handle, "tests/pic_programmerB.xml");
+
+        let closed = call(handle_close(&mut server, None, Some(serde_json::json!({ "document": "rev-c" }))));
+        assert_eq!(closed["result"]["document"], "rev-c");
+        assert_eq!(server.documents.len(), 1);
+        assert!(server.document(Some("tests/pic_programmerB.xml")).unwrap().spatial_index.is_some());
+
+        let unknown = call(handle_close(&mut server, None, Some(serde_json::json!({ "document": "rev-c" }))));
+        assert_eq!(unknown["error"]["code"], error_codes::DOCUMENT_NOT_FOUND);
+        call(handle_close(&mut server, None, None));
+        assert!(server.documents.is_empty());
+    }
+}
@@ -1,7 +1,7 @@
 //! Highlight handlers: HighlightSelectedNets, HighlightSelectedComponents
 
 use crate::lsp::protocol::{Response, error_codes};
-use crate::lsp::state::ServerState;
+use crate::lsp::state::DocumentState;
 use crate::lsp::util::log_to_file;
 use crate::draw::geometry::ObjectRange;
 use serde::Deserialize;
@@ -9,7 +9,7 @@ use std::collections::HashSet;
 
 /// Handle HighlightSelectedNets request - finds all shapes with matching net names
 pub fn handle_highlight_selected_nets(
-    state: &ServerState, 
+    state: &DocumentState, 
     id: Option<serde_json::Value>, 
     params: Option<serde_json::Value>
 ) -> Response {
@@ -178,7 +178,7 @@ pub fn handle_highlight_selected_nets(
 
 /// Handle HighlightSelectedComponents request - finds all shapes with matching component refs
 pub fn handle_highlight_selected_components(
-    state: &ServerState, 
+    state: &DocumentState, 
     id: Option<serde_json::Value>, 
     params: Option<serde_json::Value>
 ) -> Response {
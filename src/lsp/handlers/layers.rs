@@ -1,11 +1,12 @@
-//! Layer operations: GetLayers, UpdateLayerColor, SetLayerVisibility
+//! Layer operations: GetLayers, GetStackup, GetBoardOutline, GetSteps, UpdateLayerColor, SetLayerVisibility
 
+use crate::draw::parsing::OUTLINE_LAYER_ID;
 use crate::lsp::protocol::{Response, error_codes};
-use crate::lsp::state::ServerState;
+use crate::lsp::state::DocumentState;
 use serde::Deserialize;
 
 /// Handle GetLayers request - returns list of layer IDs
-pub fn handle_get_layers(state: &ServerState, id: Option<serde_json::Value>) -> Response {
+pub fn handle_get_layers(state: &DocumentState, id: Option<serde_json::Value>) -> Response {
     if !state.is_file_loaded() {
         return Response::error(id, error_codes::NO_FILE_LOADED, 
             "No file loaded. Call Load first.".to_string());
@@ -18,9 +19,65 @@ pub fn handle_get_layers(state: &ServerState, id: Option<serde_json::Value>) ->
     Response::success(id, serde_json::to_value(layer_ids).unwrap())
 }
 
+/// Handle GetStackup request - returns the physical stackup model
+/// `stackup` is null when the file has no Stackup; `layer_order` is the rendered layer order.
+pub fn handle_get_stackup(state: &DocumentState, id: Option<serde_json::Value>) -> Response {
+    if !state.is_file_loaded() {
+        return Response::error(id, error_codes::NO_FILE_LOADED, 
+            "No file loaded. Call Load first.".to_string());
+    }
+
+    let layer_order: Vec<&str> = state.layers.iter()
+        .map(|l| l.layer_id.as_str())
+        .collect();
+
+    Response::success(id, serde_json::json!({
+        "stackup": state.stackup,
+        "layer_order": layer_order
+    }))
+}
+
+/// Handle GetBoardOutline request - returns the Profile outline with its bounds and area
+/// `outline` is null when the file has no Profile; `layer_id` names the synthetic outline layer.
+pub fn handle_get_board_outline(state: &DocumentState, id: Option<serde_json::Value>) -> Response {
+    if !state.is_file_loaded() {
+        return Response::error(id, error_codes::NO_FILE_LOADED, 
+            "No file loaded. Call Load first.".to_string());
+    }
+
+    let Some(outline) = &state.board_outline else {
+        return Response::success(id, serde_json::json!({ "outline": null }));
+    };
+
+    let [min_x, min_y, max_x, max_y] = outline.bounds;
+    Response::success(id, serde_json::json!({
+        "layer_id": OUTLINE_LAYER_ID,
+        "bounds": outline.bounds,
+        "width": max_x - min_x,
+        "height": max_y - min_y,
+        "area": outline.area,
+        "outline": outline.outline,
+        "cutouts": outline.cutouts
+    }))
+}
+
+/// Handle GetSteps request - lists every Step with its layers and StepRepeat panel instances
+/// `active_step` is the step drawn; Load with `step` to switch to another one.
+pub fn handle_get_steps(state: &DocumentState, id: Option<serde_json::Value>) -> Response {
+    if !state.is_file_loaded() {
+        return Response::error(id, error_codes::NO_FILE_LOADED, 
+            "No file loaded. Call Load first.".to_string());
+    }
+
+    Response::success(id, serde_json::json!({
+        "active_step": state.active_step,
+        "steps": state.steps
+    }))
+}
+
 /// Handle UpdateLayerColor request - updates layer color in memory
 pub fn handle_update_layer_color(
-    state: &mut ServerState,
+    state: &mut DocumentState,
     id: Option<serde_json::Value>,
     params: Option<serde_json::Value>,
 ) -> Response {
@@ -68,7 +125,7 @@ pub fn handle_update_layer_color(
 
 /// Handle SetLayerVisibility request - updates layer visibility state
 pub fn handle_set_layer_visibility(
-    state: &mut ServerState,
+    state: &mut DocumentState,
     id: Option<serde_json::Value>,
     params: Option<serde_json::Value>,
 ) -> Response {
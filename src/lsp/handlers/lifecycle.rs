@@ -0,0 +1,73 @@
+//! LSP lifecycle: initialize, shutdown
+
+use crate::lsp::protocol::{Response, error_codes};
+use crate::lsp::state::{Lifecycle, ServerState};
+
+/// Name reported in `serverInfo`
+pub const SERVER_NAME: &str = "ipc2581-lsp";
+
+/// Handle initialize request - records the client capabilities and returns the server's
+/// The IPC-2581 methods are not part of LSP: they are listed under `experimental.ipc2581.methods`
+/// and can also be called through `workspace/executeCommand` with the params as the only argument.
+pub fn handle_initialize(
+    state: &mut ServerState,
+    id: Option<serde_json::Value>,
+    params: Option<serde_json::Value>,
+    methods: &[&str],
+) -> Response {
+    if state.lifecycle != Lifecycle::Uninitialized {
+        return Response::error(id, error_codes::INVALID_REQUEST,
+            "initialize may only be sent once".to_string());
+    }
+
+    let params = params.unwrap_or(serde_json::Value::Null);
+    if let Some(client) = params.get("clientInfo") {
+        eprintln!("[LSP Server] Client: {} {}",
+            client["name"].as_str().unwrap_or("?"), client["version"].as_str().unwrap_or(""));
+    }
+    state.client_capabilities = params.get("capabilities").cloned().unwrap_or(serde_json::Value::Null);
+    state.lifecycle = Lifecycle::Initialized;
+
+    Response::success(id, serde_json::json!({
+        "capabilities": {
+            "textDocumentSync": 0,  // None: designs are loaded by path, not synced as text
+            "executeCommandProvider": { "commands": methods },
+            "experimental": { "ipc2581": { "methods": methods } }
+        },
+        "serverInfo": {
+            "name": SERVER_NAME,
+            "version": env!("CARGO_PKG_VERSION")
+        }
+    }))
+}
+
+/// Handle shutdown request - closes every document; only `exit` is accepted afterwards
+pub fn handle_shutdown(state: &mut ServerState, id: Option<serde_json::Value>) -> Response {
+    state.documents.clear();
+    state.lifecycle = Lifecycle::ShutDown;
+    Response::success(id, serde_json::Value::Null)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_initialize_and_shutdown() {
+        let mut state = ServerState::new(std::sync::mpsc::channel().0);
+        let params = serde_json::json!({ "capabilities": { "general": { "positionEncodings": ["utf-16"] } } });
+        let response = serde_json::to_value(handle_initialize(&mut state, Some(1.into()), Some(params), &["Load"])).unwrap();
+        assert_eq!(response["jsonrpc"], "2.0");
+        assert_eq!(response["result"]["capabilities"]["experimental"]["ipc2581"]["methods"][0], "Load");
+        assert_eq!(response["result"]["serverInfo"]["name"], SERVER_NAME);
+        assert_eq!(state.lifecycle, Lifecycle::Initialized);
+        assert!(state.client_capabilities["general"].is_object());
+
+        let again = serde_json::to_value(handle_initialize(&mut state, Some(2.into()), None, &[])).unwrap();
+        assert_eq!(again["error"]["code"], error_codes::INVALID_REQUEST);
+
+        let response = serde_json::to_value(handle_shutdown(&mut state, Some(3.into()))).unwrap();
+        assert!(response["result"].is_null());
+        assert_eq!(state.lifecycle, Lifecycle::ShutDown);
+    }
+}
@@ -1,22 +1,34 @@
 //! Handler module declarations and re-exports
 
+pub mod bom;
+pub mod components;
 pub mod drc;
 pub mod edit;
+pub mod export;
 pub mod file;
 pub mod highlight;
 pub mod layers;
+pub mod lifecycle;
+pub mod nets;
 pub mod query;
 pub mod selection;
 pub mod tessellation;
 pub mod transform;
+pub mod validation;
 
 // Re-export all handlers for convenient access
+pub use bom::*;
+pub use components::*;
 pub use drc::*;
 pub use edit::*;
+pub use export::*;
 pub use file::*;
 pub use highlight::*;
 pub use layers::*;
+pub use lifecycle::*;
+pub use nets::*;
 pub use query::*;
 pub use selection::*;
 pub use tessellation::*;
 pub use transform::*;
+pub use validation::*;
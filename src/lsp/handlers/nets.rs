@@ -0,0 +1,140 @@
+//! Netlist handlers: GetNets, GetNet
+
+use crate::draw::geometry::ObjectRange;
+use crate::draw::parsing::Net;
+use crate::lsp::protocol::{Response, error_codes};
+use crate::lsp::state::DocumentState;
+use crate::lsp::util::{parse_params, require_file_loaded};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Copper objects tagged with a net, by object type
+#[derive(Default, Serialize)]
+struct NetObjectCounts {
+    total: usize,
+    polylines: usize,
+    polygons: usize,
+    vias: usize,
+    pads: usize,
+}
+
+/// Object counts and layers of the geometry tagged with each net
+/// Deleted objects are left out so counts follow edits.
+fn copper_by_net(state: &DocumentState) -> HashMap<&str, (NetObjectCounts, HashSet<&str>)> {
+    let mut by_net: HashMap<&str, (NetObjectCounts, HashSet<&str>)> = HashMap::new();
+    let live = state.all_object_ranges.iter()
+        .filter(|r| !state.deleted_objects.contains_key(&r.id));
+
+    for range in live {
+        let Some(net) = tagged_net(range) else { continue };
+        let (counts, layers) = by_net.entry(net).or_default();
+        counts.total += 1;
+        match range.obj_type {
+            0 => counts.polylines += 1,
+            1 => counts.polygons += 1,
+            2 => counts.vias += 1,
+            3 => counts.pads += 1,
+            _ => {}
+        }
+        layers.insert(range.layer_id.as_str());
+    }
+
+    by_net
+}
+
+fn tagged_net(range: &ObjectRange) -> Option<&str> {
+    range.net_name.as_deref().filter(|n| !n.is_empty() && *n != "No Net")
+}
+
+/// Layers used by a net (net points and tagged copper), in rendered layer order
+fn net_layers<'a>(state: &'a DocumentState, net: &'a Net, copper_layers: Option<&HashSet<&'a str>>) -> Vec<&'a str> {
+    let mut used: Vec<&str> = net.point_layers();
+    used.extend(copper_layers.into_iter().flatten().copied());
+
+    let mut layers: Vec<&str> = state.layers.iter()
+        .map(|l| l.layer_id.as_str())
+        .filter(|id| used.contains(id))
+        .collect();
+    // Net points may reference layers that have no rendered geometry
+    for layer in used {
+        if !layers.contains(&layer) {
+            layers.push(layer);
+        }
+    }
+    layers
+}
+
+/// Handle GetNets request - lists the netlist with pin, layer and object counts
+/// `untracked_nets` names nets found on copper that the netlist does not declare.
+pub fn handle_get_nets(state: &DocumentState, id: Option<serde_json::Value>) -> Response {
+    if let Err(e) = require_file_loaded(state, id.clone()) {
+        return e;
+    }
+
+    let copper = copper_by_net(state);
+
+    let nets: Vec<serde_json::Value> = state.nets.values()
+        .map(|net| {
+            let (counts, copper_layers) = copper.get(net.name.as_str())
+                .map(|(c, l)| (c.total, Some(l)))
+                .unwrap_or((0, None));
+            serde_json::json!({
+                "name": net.name,
+                "net_class": net.net_class,
+                "logical": net.logical,
+                "pin_count": net.pins.len(),
+                "point_count": net.points.len(),
+                "layers": net_layers(state, net, copper_layers),
+                "object_count": counts
+            })
+        })
+        .collect();
+
+    let mut untracked_nets: Vec<&str> = copper.keys()
+        .filter(|name| !state.nets.contains_key(**name))
+        .copied()
+        .collect();
+    untracked_nets.sort_unstable();
+
+    Response::success(id, serde_json::json!({
+        "count": nets.len(),
+        "nets": nets,
+        "untracked_nets": untracked_nets
+    }))
+}
+
+/// Handle GetNet request - returns one net by name with its pins, net points, layers and object counts
+pub fn handle_get_net(
+    state: &DocumentState,
+    id: Option<serde_json::Value>,
+    params: Option<serde_json::Value>
+) -> Response {
+    #[derive(Deserialize)]
+    struct Params { name: String }
+
+    let p: Params = match parse_params(id.clone(), params, "{name: string}") {
+        Ok(p) => p,
+        Err(e) => return e,
+    };
+
+    if let Err(e) = require_file_loaded(state, id.clone()) {
+        return e;
+    }
+
+    let Some(net) = state.nets.get(&p.name) else {
+        return Response::error(id, error_codes::NET_NOT_FOUND,
+            format!("Net not found: {}", p.name));
+    };
+
+    let mut copper = copper_by_net(state);
+    let (counts, copper_layers) = match copper.remove(net.name.as_str()) {
+        Some((counts, layers)) => (counts, Some(layers)),
+        None => (NetObjectCounts::default(), None),
+    };
+
+    Response::success(id, serde_json::json!({
+        "net": net,
+        "layers": net_layers(state, net, copper_layers.as_ref()),
+        "objects": counts
+    }))
+}
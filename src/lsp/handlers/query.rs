@@ -2,7 +2,7 @@
 
 use crate::draw::drc::is_copper_layer;
 use crate::lsp::protocol::Response;
-use crate::lsp::state::ServerState;
+use crate::lsp::state::DocumentState;
 use crate::lsp::util::{get_process_memory_bytes, parse_params, require_file_loaded, log_to_file};
 use crate::lsp::handlers::selection::find_objects_at_point;
 use serde::Deserialize;
@@ -11,7 +11,7 @@ use serde::Deserialize;
 /// Returns info for the same object that Select would return (topmost by layer order).
 /// If that object is a non-copper pad, looks for the corresponding copper pad's net info.
 pub fn handle_query_net_at_point(
-    state: &ServerState, 
+    state: &DocumentState, 
     id: Option<serde_json::Value>, 
     params: Option<serde_json::Value>
 ) -> Response {
@@ -134,7 +134,7 @@ pub fn handle_get_memory(id: Option<serde_json::Value>) -> Response {
 /// Handle GetObjectBounds request - returns the current bounds for specified object IDs
 /// Used for debugging to compare LSP bounds vs. WebView-calculated bounds after transforms
 pub fn handle_get_object_bounds(
-    state: &ServerState, 
+    state: &DocumentState, 
     id: Option<serde_json::Value>, 
     params: Option<serde_json::Value>
 ) -> Response {
@@ -1,13 +1,14 @@
 //! Selection handlers: Select, BoxSelect, point_hits_object
 
 use crate::lsp::protocol::Response;
-use crate::lsp::state::ServerState;
+use crate::lsp::state::DocumentState;
 use crate::lsp::util::{point_in_triangle, parse_params};
 use crate::draw::geometry::{LayerJSON, ObjectRange};
 use serde::Deserialize;
 
 /// Check if a line segment intersects with an AABB
 #[inline]
+#[allow(clippy::too_many_arguments)]
 fn segment_intersects_aabb(
     x0: f32, y0: f32, x1: f32, y1: f32,
     min_x: f32, min_y: f32, max_x: f32, max_y: f32,
@@ -58,6 +59,7 @@ fn segment_intersects_aabb(
 }
 
 /// Check if a triangle intersects with an AABB (selection box)
+#[allow(clippy::too_many_arguments)]
 fn triangle_intersects_aabb(
     x0: f32, y0: f32, x1: f32, y1: f32, x2: f32, y2: f32,
     min_x: f32, min_y: f32, max_x: f32, max_y: f32,
@@ -97,6 +99,7 @@ fn triangle_intersects_aabb(
 /// `move_delta` is the (dx, dy) to apply if this object was moved
 /// `rotation_delta` is the rotation angle in radians to apply if this object was rotated
 /// `flip_info` is the (center_x, center_y, is_flipped, original_layer_id) to apply if this object was flipped
+#[allow(clippy::too_many_arguments)]
 pub fn box_intersects_object(
     min_x: f32, min_y: f32, max_x: f32, max_y: f32,
     range: &ObjectRange, 
@@ -331,9 +334,9 @@ pub fn point_hits_object(px: f32, py: f32, range: &ObjectRange, layers: &[LayerJ
     
     // Handle instanced geometry (vias, pads)
     if range.obj_type == 2 || range.obj_type == 3 {
-        if debug && range.pin_ref.is_some() {
+        if let (true, Some(pin_ref)) = (debug, range.pin_ref.as_ref()) {
             eprintln!("[DEBUG_SELECT] Checking {} obj_type={} shape_idx={:?} inst_idx={:?} lods.len()={}",
-                range.pin_ref.as_ref().unwrap(), range.obj_type, range.shape_index, range.instance_index, lods.len());
+                pin_ref, range.obj_type, range.shape_index, range.instance_index, lods.len());
         }
         let shape_idx = range.shape_index.unwrap_or(0) as usize;
         if shape_idx >= lods.len() {
@@ -379,9 +382,9 @@ pub fn point_hits_object(px: f32, py: f32, range: &ObjectRange, layers: &[LayerJ
                 let cos_r = rotation.cos();
                 let sin_r = rotation.sin();
                 
-                if debug && range.pin_ref.is_some() {
+                if let (true, Some(pin_ref)) = (debug, range.pin_ref.as_ref()) {
                     eprintln!("[DEBUG_SELECT] Testing {} on {}: point=({:.2},{:.2}) inst=({:.2},{:.2}) rot={:.2}deg shape_idx={} inst_idx={} vertex_count={} has_indices={}",
-                        range.pin_ref.as_ref().unwrap(), range.layer_id,
+                        pin_ref, range.layer_id,
                         px, py, inst_x, inst_y, rotation.to_degrees(), shape_idx, inst_idx,
                         lod_entry.vertex_data.len() / 2, lod_entry.index_data.is_some());
                 }
@@ -508,7 +511,7 @@ pub fn point_hits_object(px: f32, py: f32, range: &ObjectRange, layers: &[LayerJ
 /// Find all objects at a point with triangle intersection testing.
 /// Returns objects sorted by priority (net > no net, pad > via > polygon > polyline).
 /// If `only_visible` is true, hidden layers are excluded from results.
-pub fn find_objects_at_point(state: &ServerState, x: f32, y: f32, only_visible: bool) -> Vec<ObjectRange> {
+pub fn find_objects_at_point(state: &DocumentState, x: f32, y: f32, only_visible: bool) -> Vec<ObjectRange> {
     let Some(tree) = &state.spatial_index else {
         return vec![];
     };
@@ -542,7 +545,7 @@ pub fn find_objects_at_point(state: &ServerState, x: f32, y: f32, only_visible:
 
 /// Handle Select request - performs spatial selection at a point
 pub fn handle_select(
-    state: &ServerState, 
+    state: &DocumentState, 
     id: Option<serde_json::Value>, 
     params: Option<serde_json::Value>
 ) -> Response {
@@ -561,7 +564,7 @@ pub fn handle_select(
 /// Handle BoxSelect request - performs spatial selection for a rectangle
 /// Uses AABB for initial filtering, then triangle intersection for precise matching
 pub fn handle_box_select(
-    state: &ServerState, 
+    state: &DocumentState, 
     id: Option<serde_json::Value>, 
     params: Option<serde_json::Value>
 ) -> Response {
@@ -614,7 +617,7 @@ pub fn handle_box_select(
 /// Uses full triangle intersection testing, not just AABB
 /// Returns the first hit object ID, or null if no hit
 pub fn handle_check_point_hits_selection(
-    state: &ServerState,
+    state: &DocumentState,
     id: Option<serde_json::Value>,
     params: Option<serde_json::Value>
 ) -> Response {
@@ -1,14 +1,14 @@
 //! Tessellation handlers: GetTessellation (JSON and Binary)
 
-use crate::lsp::protocol::{TypedResponse, ErrorResponse, error_codes};
-use crate::lsp::state::ServerState;
+use crate::lsp::protocol::{TypedResponse, error_codes};
+use crate::lsp::state::DocumentState;
 use crate::draw::geometry::LayerBinary;
 use serde::Deserialize;
 use std::time::Instant;
 
 /// Handle GetTessellation request - returns layer geometry as JSON
 pub fn handle_get_tessellation_json(
-    state: &mut ServerState,
+    state: &mut DocumentState,
     id: Option<serde_json::Value>,
     params: Option<serde_json::Value>,
 ) -> String {
@@ -20,27 +20,13 @@ pub fn handle_get_tessellation_json(
     let params: TessellationParams = match params.and_then(|p| serde_json::from_value(p).ok()) {
         Some(p) => p,
         None => {
-            let response = TypedResponse::<()> {
-                id,
-                result: None,
-                error: Some(ErrorResponse {
-                    code: error_codes::INVALID_PARAMS,
-                    message: "Invalid params: expected {layer_id: string}".to_string(),
-                }),
-            };
+            let response = TypedResponse::<()>::error(id, error_codes::INVALID_PARAMS, "Invalid params: expected {layer_id: string}".to_string());
             return serde_json::to_string(&response).unwrap();
         }
     };
 
     if !state.is_file_loaded() {
-        let response = TypedResponse::<()> {
-            id,
-            result: None,
-            error: Some(ErrorResponse {
-                code: error_codes::NO_FILE_LOADED,
-                message: "No file loaded. Call Load first.".to_string(),
-            }),
-        };
+        let response = TypedResponse::<()>::error(id, error_codes::NO_FILE_LOADED, "No file loaded. Call Load first.".to_string());
         return serde_json::to_string(&response).unwrap();
     }
 
@@ -52,11 +38,7 @@ pub fn handle_get_tessellation_json(
         Some(layer_json) => {
             let start_serialize = Instant::now();
             
-            let response = TypedResponse {
-                id,
-                result: Some(layer_json),
-                error: None,
-            };
+            let response = TypedResponse::success(id, layer_json);
             
             let result_string = serde_json::to_string(&response).unwrap();
             eprintln!("[LSP Server] Serialization time for layer {}: {:.2?}", 
@@ -66,14 +48,7 @@ pub fn handle_get_tessellation_json(
             result_string
         }
         None => {
-            let response = TypedResponse::<()> {
-                id,
-                result: None,
-                error: Some(ErrorResponse {
-                    code: error_codes::LAYER_NOT_FOUND,
-                    message: format!("Layer not found: {}", params.layer_id),
-                }),
-            };
+            let response = TypedResponse::<()>::error(id, error_codes::LAYER_NOT_FOUND, format!("Layer not found: {}", params.layer_id));
             serde_json::to_string(&response).unwrap()
         }
     }
@@ -81,7 +56,7 @@ pub fn handle_get_tessellation_json(
 
 /// Handle GetTessellationBinary request - returns binary-encoded geometry
 pub fn handle_get_tessellation_binary(
-    state: &mut ServerState,
+    state: &mut DocumentState,
     id: Option<serde_json::Value>,
     params: Option<serde_json::Value>,
 ) -> String {
@@ -93,27 +68,13 @@ pub fn handle_get_tessellation_binary(
     let params: TessellationParams = match params.and_then(|p| serde_json::from_value(p).ok()) {
         Some(p) => p,
         None => {
-            let response = TypedResponse::<()> {
-                id,
-                result: None,
-                error: Some(ErrorResponse {
-                    code: error_codes::INVALID_PARAMS,
-                    message: "Invalid params: expected {layer_id: string}".to_string(),
-                }),
-            };
+            let response = TypedResponse::<()>::error(id, error_codes::INVALID_PARAMS, "Invalid params: expected {layer_id: string}".to_string());
             return serde_json::to_string(&response).unwrap();
         }
     };
 
     if !state.is_file_loaded() {
-        let response = TypedResponse::<()> {
-            id,
-            result: None,
-            error: Some(ErrorResponse {
-                code: error_codes::NO_FILE_LOADED,
-                message: "No file loaded. Call Load first.".to_string(),
-            }),
-        };
+        let response = TypedResponse::<()>::error(id, error_codes::NO_FILE_LOADED, "No file loaded. Call Load first.".to_string());
         return serde_json::to_string(&response).unwrap();
     }
 
@@ -145,14 +106,7 @@ pub fn handle_get_tessellation_binary(
             format!("BINARY:{}:{}", id_str, encoded_data)
         }
         None => {
-            let response = TypedResponse::<()> {
-                id,
-                result: None,
-                error: Some(ErrorResponse {
-                    code: error_codes::LAYER_NOT_FOUND,
-                    message: format!("Layer not found: {}", params.layer_id),
-                }),
-            };
+            let response = TypedResponse::<()>::error(id, error_codes::LAYER_NOT_FOUND, format!("Layer not found: {}", params.layer_id));
             serde_json::to_string(&response).unwrap()
         }
     }
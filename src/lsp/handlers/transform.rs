@@ -7,7 +7,7 @@
 //! All geometry data is already in memory from tessellation - WebView only sends commands.
 
 use crate::lsp::protocol::{Response, error_codes};
-use crate::lsp::state::ServerState;
+use crate::lsp::state::DocumentState;
 use crate::lsp::util::parse_params;
 use crate::draw::geometry::ObjectRange;
 use serde::{Deserialize, Serialize};
@@ -82,7 +82,7 @@ struct TransformedInstance {
 
 /// Handle StartTransform - begin a transform session for selected objects
 pub fn handle_start_transform(
-    state: &mut ServerState,
+    state: &mut DocumentState,
     id: Option<serde_json::Value>,
     params: Option<serde_json::Value>,
 ) -> Response {
@@ -104,8 +104,7 @@ pub fn handle_start_transform(
             original_ranges.push(range.clone());
             
             // For instanced objects (pads/vias), look up the instance data from layers
-            if range.instance_index.is_some() && range.shape_index.is_some() {
-                let shape_idx = range.shape_index.unwrap();
+            if let (Some(instance_idx), Some(shape_idx)) = (range.instance_index, range.shape_index) {
                 
                 // Find the layer's geometry data
                 if let Some(layer_json) = state.layers.iter().find(|l| l.layer_id == range.layer_id) {
@@ -119,8 +118,7 @@ pub fn handle_start_transform(
                             if let Some(inst_data) = &lod.instance_data {
                                 // Instance data format: [x, y, packed_rot_vis, x, y, packed_rot_vis, ...]
                                 // Each instance is 3 floats
-                                let instance_idx = range.instance_index.unwrap() as usize;
-                                let offset = instance_idx * 3;
+                                let offset = instance_idx as usize * 3;
                                 
                                 if offset + 2 < inst_data.len() {
                                     let x = inst_data[offset];
@@ -133,7 +131,7 @@ pub fn handle_start_transform(
                                         packed_rot_vis,
                                         layer_id: range.layer_id.clone(),
                                         shape_idx,
-                                        instance_idx: instance_idx as u32,
+                                        instance_idx,
                                     });
                                 }
                             }
@@ -152,7 +150,7 @@ pub fn handle_start_transform(
                     packed_rot_vis: pack_rotation_vis(0.0, true, false),
                     layer_id: range.layer_id.clone(),
                     shape_idx: range.shape_index.unwrap_or(0),
-                    instance_idx: range.instance_index.unwrap_or(0) as u32,
+                    instance_idx: range.instance_index.unwrap_or(0),
                 });
             }
         }
@@ -204,7 +202,7 @@ pub fn handle_start_transform(
 
 /// Handle TransformPreview - apply incremental transform and return transformed positions
 pub fn handle_transform_preview(
-    state: &mut ServerState,
+    state: &mut DocumentState,
     id: Option<serde_json::Value>,
     params: Option<serde_json::Value>,
 ) -> Response {
@@ -326,7 +324,7 @@ pub fn handle_transform_preview(
 
 /// Handle ApplyTransform - commit the current transform to the spatial index
 pub fn handle_apply_transform(
-    state: &mut ServerState,
+    state: &mut DocumentState,
     id: Option<serde_json::Value>,
     _params: Option<serde_json::Value>,
 ) -> Response {
@@ -615,7 +613,7 @@ pub fn handle_apply_transform(
 
 /// Handle CancelTransform - discard the current transform, return original positions
 pub fn handle_cancel_transform(
-    state: &mut ServerState,
+    state: &mut DocumentState,
     id: Option<serde_json::Value>,
     _params: Option<serde_json::Value>,
 ) -> Response {
@@ -684,7 +682,7 @@ fn pack_rotation_vis(rotation: f32, visible: bool, moving: bool) -> u32 {
 
 /// Handle UndoTransform - undo the last transform operation
 pub fn handle_undo_transform(
-    state: &mut ServerState,
+    state: &mut DocumentState,
     id: Option<serde_json::Value>,
     _params: Option<serde_json::Value>,
 ) -> Response {
@@ -724,7 +722,7 @@ pub fn handle_undo_transform(
 
 /// Handle RedoTransform - redo the last undone transform
 pub fn handle_redo_transform(
-    state: &mut ServerState,
+    state: &mut DocumentState,
     id: Option<serde_json::Value>,
     _params: Option<serde_json::Value>,
 ) -> Response {
@@ -764,7 +762,7 @@ pub fn handle_redo_transform(
 
 /// Apply positions from a position map and return instances for WebView update
 fn apply_positions(
-    state: &mut ServerState,
+    state: &mut DocumentState,
     object_ids: &[u64],
     positions: &HashMap<u64, (f32, f32, u32)>,
 ) -> Vec<TransformedInstance> {
@@ -0,0 +1,51 @@
+//! Schema validation: Validate
+
+use crate::lsp::protocol::{Response, error_codes};
+use crate::lsp::state::DocumentState;
+use crate::parse_xml::parse_xml_file_parallel;
+use crate::schema::validate_document;
+use serde::Deserialize;
+use std::time::Instant;
+
+/// Diagnostics returned when the request sets no limit
+const DEFAULT_MAX_DIAGNOSTICS: usize = 1000;
+
+/// Handle Validate request - checks a file against the XSD of its revision
+/// Validates `file_path` when given (no Load needed), otherwise the `document`'s file as it is on disk.
+pub fn handle_validate(
+    document: Option<&DocumentState>,
+    id: Option<serde_json::Value>,
+    params: Option<serde_json::Value>
+) -> Response {
+    #[derive(Deserialize)]
+    struct ValidateParams {
+        #[serde(default)]
+        file_path: Option<String>,
+        #[serde(default)]
+        max_diagnostics: Option<usize>,
+    }
+
+    let params: ValidateParams = params
+        .and_then(|p| serde_json::from_value(p).ok())
+        .unwrap_or(ValidateParams { file_path: None, max_diagnostics: None });
+
+    // The loaded DOM is not kept (and is unit-normalized), so read the file itself
+    let Some(file_path) = params.file_path.or_else(|| document.and_then(|d| d.xml_file_path.clone())) else {
+        return Response::error(id, error_codes::NO_FILE_LOADED,
+            "No file loaded. Call Load first or pass file_path.".to_string());
+    };
+    let root = match parse_xml_file_parallel(&file_path, "LayerFeature") {
+        Ok(root) => root,
+        Err(e) => return Response::error(id, error_codes::PARSE_FAILED, format!("Failed to parse XML: {}", e)),
+    };
+
+    let start = Instant::now();
+    match validate_document(&root, params.max_diagnostics.unwrap_or(DEFAULT_MAX_DIAGNOSTICS)) {
+        Ok(report) => {
+            eprintln!("[LSP Server] Validated against {} in {:.2}ms: {} errors, {} warnings",
+                report.schema, start.elapsed().as_secs_f64() * 1000.0, report.error_count, report.warning_count);
+            Response::success(id, serde_json::json!(report))
+        }
+        Err(e) => Response::error(id, error_codes::INTERNAL_ERROR, format!("Schema validation failed: {}", e)),
+    }
+}
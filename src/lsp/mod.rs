@@ -4,17 +4,21 @@
 //!
 //! # Module Structure
 //! - `protocol` - JSON-RPC request/response types
-//! - `state` - Server state management
+//! - `state` - Server state management (one `DocumentState` per open document)
 //! - `util` - Utility functions (logging, memory, geometry)
 //! - `xml_helpers` - XML DOM manipulation helpers
+//! - `cache` - On-disk cache of Load results
+//! - `transport` - Content-Length and line framing of messages on stdio
 //! - `handlers` - Request handlers organized by functionality
 
+pub mod cache;
 pub mod handlers;
 pub mod protocol;
 pub mod state;
+pub mod transport;
 pub mod util;
 pub mod xml_helpers;
 
 // Re-export key types for convenience
-pub use protocol::{Request, Response, TypedResponse, ErrorResponse, error_codes};
-pub use state::{ServerState, ModifiedRegion, DrcAsyncResult};
+pub use protocol::{Request, Response, TypedResponse, ErrorResponse, Notification, error_codes};
+pub use state::{ServerState, DocumentState, Lifecycle, ModifiedRegion, DrcAsyncResult, document_handle};
@@ -1,8 +1,12 @@
 //! JSON-RPC protocol types for the LSP server
 
+use crate::draw::progress::ProgressEvent;
 use serde::{Deserialize, Serialize};
 
-/// JSON-RPC Request format
+/// Value of the `jsonrpc` member of every message
+pub const JSONRPC_VERSION: &str = "2.0";
+
+/// JSON-RPC Request format (a notification when `id` is absent)
 #[derive(Debug, Deserialize)]
 pub struct Request {
     pub id: Option<serde_json::Value>,
@@ -13,6 +17,7 @@ pub struct Request {
 /// JSON-RPC Response format
 #[derive(Debug, Serialize)]
 pub struct Response {
+    pub jsonrpc: &'static str,
     pub id: Option<serde_json::Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub result: Option<serde_json::Value>,
@@ -25,11 +30,14 @@ pub struct Response {
 pub struct ErrorResponse {
     pub code: i32,
     pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Box<serde_json::Value>>,  // Structured details, e.g. the location of a parse error
 }
 
 /// Generic typed response for handlers that return structured data
 #[derive(Debug, Serialize)]
 pub struct TypedResponse<T: Serialize> {
+    pub jsonrpc: &'static str,
     pub id: Option<serde_json::Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub result: Option<T>,
@@ -37,10 +45,39 @@ pub struct TypedResponse<T: Serialize> {
     pub error: Option<ErrorResponse>,
 }
 
+/// JSON-RPC notification sent by the server
+#[derive(Debug, Serialize)]
+pub struct Notification {
+    pub jsonrpc: &'static str,
+    pub method: String,
+    pub params: serde_json::Value,
+}
+
+impl Notification {
+    pub fn new(method: &str, params: serde_json::Value) -> Self {
+        Notification { jsonrpc: JSONRPC_VERSION, method: method.to_string(), params }
+    }
+
+    /// `$/progress` work done notification for the client's `workDoneToken`
+    pub fn progress(token: &serde_json::Value, event: ProgressEvent) -> Self {
+        let value = match event {
+            ProgressEvent::Begin { title } => serde_json::json!({
+                "kind": "begin", "title": title, "cancellable": true, "percentage": 0
+            }),
+            ProgressEvent::Report { message, percentage } => serde_json::json!({
+                "kind": "report", "message": message, "percentage": percentage
+            }),
+            ProgressEvent::End => serde_json::json!({ "kind": "end" }),
+        };
+        Self::new("$/progress", serde_json::json!({ "token": token, "value": value }))
+    }
+}
+
 impl Response {
     /// Create a success response with a JSON value
     pub fn success(id: Option<serde_json::Value>, result: serde_json::Value) -> Self {
         Response {
+            jsonrpc: JSONRPC_VERSION,
             id,
             result: Some(result),
             error: None,
@@ -50,9 +87,42 @@ impl Response {
     /// Create an error response
     pub fn error(id: Option<serde_json::Value>, code: i32, message: String) -> Self {
         Response {
+            jsonrpc: JSONRPC_VERSION,
+            id,
+            result: None,
+            error: Some(ErrorResponse { code, message, data: None }),
+        }
+    }
+
+    /// Create an error response carrying structured `data`
+    pub fn error_with_data(id: Option<serde_json::Value>, code: i32, message: String, data: serde_json::Value) -> Self {
+        Response {
+            jsonrpc: JSONRPC_VERSION,
+            id,
+            result: None,
+            error: Some(ErrorResponse { code, message, data: Some(Box::new(data)) }),
+        }
+    }
+}
+
+impl<T: Serialize> TypedResponse<T> {
+    /// Create a success response with a typed result
+    pub fn success(id: Option<serde_json::Value>, result: T) -> Self {
+        TypedResponse {
+            jsonrpc: JSONRPC_VERSION,
+            id,
+            result: Some(result),
+            error: None,
+        }
+    }
+
+    /// Create an error response
+    pub fn error(id: Option<serde_json::Value>, code: i32, message: String) -> Self {
+        TypedResponse {
+            jsonrpc: JSONRPC_VERSION,
             id,
             result: None,
-            error: Some(ErrorResponse { code, message }),
+            error: Some(ErrorResponse { code, message, data: None }),
         }
     }
 }
@@ -64,10 +134,20 @@ pub mod error_codes {
     pub const METHOD_NOT_FOUND: i32 = -32601;
     pub const INVALID_PARAMS: i32 = -32602;
     pub const INTERNAL_ERROR: i32 = -32603;
+
+    // LSP error codes
+    pub const SERVER_NOT_INITIALIZED: i32 = -32002;
+    pub const REQUEST_CANCELLED: i32 = -32800;
     
     // Custom error codes
     pub const NO_FILE_LOADED: i32 = 2;
     pub const LAYER_NOT_FOUND: i32 = 3;
     pub const SAVE_FAILED: i32 = 4;
     pub const PARSE_FAILED: i32 = 5;
+    pub const COMPONENT_NOT_FOUND: i32 = 6;
+    pub const BOM_ITEM_NOT_FOUND: i32 = 7;
+    pub const NET_NOT_FOUND: i32 = 8;
+    pub const STEP_NOT_FOUND: i32 = 9;
+    pub const EXPORT_FAILED: i32 = 10;
+    pub const DOCUMENT_NOT_FOUND: i32 = 11;
 }
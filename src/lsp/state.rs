@@ -2,11 +2,13 @@
 
 use crate::draw::geometry::{LayerJSON, ObjectRange, PadStackDef, SelectableObject};
 use crate::draw::drc::{DrcViolation, DrcRegion, DesignRules};
+use crate::draw::parsing::{BoardOutline, BomItem, Component, LengthUnit, LoadDiagnostic, Net, Stackup, StepInfo};
 use crate::parse_xml::XmlNode;
 use crate::lsp::handlers::transform::TransformSession;
 use indexmap::IndexMap;
 use rstar::RTree;
 use std::collections::{HashMap, HashSet};
+use std::sync::mpsc::Sender;
 
 /// A region that has been modified and needs DRC re-checking
 #[derive(Clone, Debug)]
@@ -59,15 +61,75 @@ pub struct TransformAction {
     pub final_positions: HashMap<u64, (f32, f32, u32)>,
 }
 
-/// In-memory state: DOM, layers, and layer colors
+/// LSP lifecycle phase: `initialize` starts the session, `shutdown` ends it before `exit`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Lifecycle {
+    #[default]
+    Uninitialized,
+    Initialized,
+    ShutDown,
+}
+
+/// Server-wide state: the LSP session and every open document
 pub struct ServerState {
+    pub lifecycle: Lifecycle,
+    pub client_capabilities: serde_json::Value,  // From `initialize` (Null until then)
+    pub documents: IndexMap<String, DocumentState>,  // Keyed by handle, in Load order (most recent last)
+    /// Where async DRC runs send their results; the server loop receives them with client messages
+    pub drc_sender: Sender<DrcAsyncResult>,
+}
+
+impl ServerState {
+    pub fn new(drc_sender: Sender<DrcAsyncResult>) -> Self {
+        Self {
+            lifecycle: Lifecycle::Uninitialized,
+            client_capabilities: serde_json::Value::Null,
+            documents: IndexMap::new(),
+            drc_sender,
+        }
+    }
+
+    /// Document named by `handle`, or the most recently loaded one when no handle is given
+    pub fn document(&self, handle: Option<&str>) -> Option<&DocumentState> {
+        match handle {
+            Some(handle) => self.documents.get(handle),
+            None => self.documents.last().map(|(_, document)| document),
+        }
+    }
+
+    /// Mutable [`ServerState::document`]
+    pub fn document_mut(&mut self, handle: Option<&str>) -> Option<&mut DocumentState> {
+        match handle {
+            Some(handle) => self.documents.get_mut(handle),
+            None => self.documents.last_mut().map(|(_, document)| document),
+        }
+    }
+}
+
+/// Handle of the document a request targets: the `document` param, if any
+pub fn document_handle(params: Option<&serde_json::Value>) -> Option<&str> {
+    params.and_then(|p| p.get("document")).and_then(|d| d.as_str())
+}
+
+/// In-memory state of one open document: DOM, layers, and layer colors
+pub struct DocumentState {
+    pub handle: String,  // Key in `ServerState::documents` (the Load `document` param, default its file_path)
     pub xml_file_path: Option<String>,
     pub xml_root: Option<XmlNode>,
+    pub source_unit: LengthUnit,  // CadHeader unit of the loaded file (in-memory geometry is mm)
     pub layers: Vec<LayerJSON>,
     pub layer_colors: HashMap<String, [f32; 4]>,
     pub modified_colors: HashMap<String, [f32; 4]>,
     pub spatial_index: Option<RTree<SelectableObject>>,
     pub padstack_defs: IndexMap<String, PadStackDef>,
+    pub components: IndexMap<String, Component>,  // Placed components keyed by refdes
+    pub bom: Vec<BomItem>,  // BOM items in document order, joined with the AVL
+    pub nets: IndexMap<String, Net>,  // Intended netlist (LogicalNet/PhyNet) keyed by net name
+    pub stackup: Option<Stackup>,  // Physical build-up (None if the file has no Stackup)
+    pub board_outline: Option<BoardOutline>,  // Profile outline (None if the file has no Profile)
+    pub steps: Vec<StepInfo>,  // Every Step in the file
+    pub active_step: Option<String>,  // Step drawn and edited (None if the file has no Step)
+    pub load_diagnostics: Vec<LoadDiagnostic>,  // Dangling references, duplicate ids, unused dictionary entries
     pub deleted_objects: HashMap<u64, ObjectRange>,
     pub moved_objects: HashMap<u64, ObjectMove>,  // Track moved objects by ID
     pub rotated_objects: HashMap<u64, ObjectRotation>,  // Track rotated objects by ID
@@ -84,16 +146,26 @@ pub struct ServerState {
     pub redo_stack: Vec<TransformAction>,  // Redo stack for transform operations
 }
 
-impl ServerState {
-    pub fn new() -> Self {
+impl DocumentState {
+    pub fn new(handle: String) -> Self {
         Self {
+            handle,
             xml_file_path: None,
             xml_root: None,
+            source_unit: LengthUnit::Millimeter,
             layers: Vec::new(),
             layer_colors: HashMap::new(),
             modified_colors: HashMap::new(),
             spatial_index: None,
             padstack_defs: IndexMap::new(),
+            components: IndexMap::new(),
+            bom: Vec::new(),
+            nets: IndexMap::new(),
+            stackup: None,
+            board_outline: None,
+            steps: Vec::new(),
+            active_step: None,
+            load_diagnostics: Vec::new(),
             deleted_objects: HashMap::new(),
             moved_objects: HashMap::new(),
             rotated_objects: HashMap::new(),
@@ -131,14 +203,10 @@ impl ServerState {
     }
 }
 
-impl Default for ServerState {
-    fn default() -> Self {
-        Self::new()
-    }
-}
-
 /// Result from async DRC computation
 pub struct DrcAsyncResult {
-    pub regions: Vec<DrcRegion>,
+    pub document: String,  // Handle of the document checked
+    pub request_id: Option<serde_json::Value>,  // RunDRCWithRegions request that started it
+    pub regions: Option<Vec<DrcRegion>>,  // None if the request was cancelled
     pub elapsed_ms: f64,
 }
@@ -0,0 +1,164 @@
+//! Message framing on stdio
+//!
+//! LSP clients frame every JSON-RPC message with a `Content-Length` header
+//! (the LSP base protocol). The VS Code extension and dev server of this
+//! repository instead write one JSON message per line; the server keeps that
+//! mode behind `--lines`.
+
+use std::io::{self, BufRead, Write};
+use std::sync::Mutex;
+
+/// How messages are delimited on the stream
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Framing {
+    /// `Content-Length: <bytes>\r\n\r\n<body>` (LSP base protocol)
+    ContentLength,
+    /// One message per line (`\n`-terminated)
+    Lines,
+}
+
+/// Read the next message body; None at the end of the stream
+/// A malformed header is an `InvalidData` error; reading can go on, resynchronizing at a later header.
+pub fn read_message<R: BufRead>(reader: &mut R, framing: Framing) -> io::Result<Option<String>> {
+    match framing {
+        Framing::Lines => read_line_message(reader),
+        Framing::ContentLength => read_framed_message(reader),
+    }
+}
+
+/// Write one message body with its framing and flush it
+pub fn write_message<W: Write>(writer: &mut W, framing: Framing, body: &str) -> io::Result<()> {
+    match framing {
+        Framing::Lines => writeln!(writer, "{}", body)?,
+        Framing::ContentLength => {
+            write!(writer, "Content-Length: {}\r\n\r\n", body.len())?;
+            writer.write_all(body.as_bytes())?;
+        }
+    }
+    writer.flush()
+}
+
+/// Writes whole framed messages from any thread (responses, progress from workers, DRC notifications)
+pub struct MessageWriter<W: Write> {
+    framing: Framing,
+    writer: Mutex<W>,
+}
+
+impl<W: Write> MessageWriter<W> {
+    pub fn new(writer: W, framing: Framing) -> Self {
+        Self { framing, writer: Mutex::new(writer) }
+    }
+
+    pub fn framing(&self) -> Framing {
+        self.framing
+    }
+
+    pub fn send(&self, body: &str) -> io::Result<()> {
+        let mut writer = self.writer.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        write_message(&mut *writer, self.framing, body)
+    }
+}
+
+fn read_line_message<R: BufRead>(reader: &mut R) -> io::Result<Option<String>> {
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let message = line.trim_end_matches(['\r', '\n']);
+        if !message.trim().is_empty() {
+            return Ok(Some(message.to_string()));
+        }
+    }
+}
+
+fn read_framed_message<R: BufRead>(reader: &mut R) -> io::Result<Option<String>> {
+    let mut content_length: Option<usize> = None;
+    let mut line = String::new();
+    let mut header_lines = 0;
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return if header_lines == 0 {
+                Ok(None)
+            } else {
+                Err(io::Error::new(io::ErrorKind::UnexpectedEof, "stream ended inside a message header"))
+            };
+        }
+        let header = line.trim_end_matches(['\r', '\n']);
+        if header.is_empty() {
+            if header_lines == 0 {
+                continue;  // Stray line break between messages
+            }
+            break;
+        }
+        header_lines += 1;
+        let (name, value) = header.split_once(':')
+            .ok_or_else(|| invalid_data(format!("malformed header line '{}'", header)))?;
+        // Content-Type is optional and always utf-8 JSON-RPC; other headers are ignored
+        if name.trim().eq_ignore_ascii_case("Content-Length") {
+            let length = value.trim().parse::<usize>()
+                .map_err(|_| invalid_data(format!("invalid Content-Length '{}'", value.trim())))?;
+            content_length = Some(length);
+        }
+    }
+
+    let length = content_length.ok_or_else(|| invalid_data("message header without Content-Length".to_string()))?;
+    let mut body = vec![0u8; length];
+    reader.read_exact(&mut body)?;
+    String::from_utf8(body)
+        .map(Some)
+        .map_err(|_| invalid_data("message body is not valid UTF-8".to_string()))
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_length_round_trip() {
+        let bodies = [r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#, r#"{"jsonrpc":"2.0","method":"x","params":{"name":"µ"}}"#];
+        let mut stream = Vec::new();
+        for body in bodies {
+            write_message(&mut stream, Framing::ContentLength, body).unwrap();
+        }
+        // The length counts bytes, not characters
+        let second = format!("Content-Length: {}\r\n\r\n{}", bodies[1].len(), bodies[1]);
+        assert!(stream.ends_with(second.as_bytes()));
+        assert_eq!(bodies[1].len(), bodies[1].chars().count() + 1);
+
+        let mut reader = io::Cursor::new(stream);
+        for body in bodies {
+            assert_eq!(read_message(&mut reader, Framing::ContentLength).unwrap().as_deref(), Some(body));
+        }
+        assert_eq!(read_message(&mut reader, Framing::ContentLength).unwrap(), None);
+    }
+
+    #[test]
+    fn test_content_length_headers() {
+        let stream = "content-length: 2\r\nContent-Type: application/vscode-jsonrpc; charset=utf-8\r\n\r\n{}Content-Length: 4\r\n\r\n{}";
+        let mut reader = io::Cursor::new(stream.as_bytes());
+        assert_eq!(read_message(&mut reader, Framing::ContentLength).unwrap().as_deref(), Some("{}"));
+        assert_eq!(read_message(&mut reader, Framing::ContentLength).unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+
+        let mut reader = io::Cursor::new("Content-Type: x\r\n\r\n{}".as_bytes());
+        assert_eq!(read_message(&mut reader, Framing::ContentLength).unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_line_messages() {
+        let mut reader = io::Cursor::new("{\"id\":1}\r\n\n  \n{\"id\":2}".as_bytes());
+        assert_eq!(read_message(&mut reader, Framing::Lines).unwrap().as_deref(), Some("{\"id\":1}"));
+        assert_eq!(read_message(&mut reader, Framing::Lines).unwrap().as_deref(), Some("{\"id\":2}"));
+        assert_eq!(read_message(&mut reader, Framing::Lines).unwrap(), None);
+
+        let mut stream = Vec::new();
+        write_message(&mut stream, Framing::Lines, "{}").unwrap();
+        assert_eq!(stream, b"{}\n");
+    }
+}
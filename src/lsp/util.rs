@@ -5,7 +5,7 @@ use std::io::Write;
 use std::sync::atomic::{AtomicBool, Ordering};
 use serde::de::DeserializeOwned;
 use crate::lsp::protocol::{Response, error_codes};
-use crate::lsp::state::ServerState;
+use crate::lsp::state::DocumentState;
 use crate::draw::geometry::ObjectRange;
 
 #[cfg(windows)]
@@ -41,7 +41,7 @@ pub fn parse_params<T: DeserializeOwned>(
 
 /// Check if a file is loaded, returning an error Response if not
 pub fn require_file_loaded(
-    state: &ServerState,
+    state: &DocumentState,
     id: Option<serde_json::Value>,
 ) -> Result<(), Response> {
     if state.is_file_loaded() {
@@ -52,6 +52,11 @@ pub fn require_file_loaded(
     }
 }
 
+/// Error Response of a request stopped by `$/cancelRequest`
+pub fn request_cancelled(id: Option<serde_json::Value>) -> Response {
+    Response::error(id, error_codes::REQUEST_CANCELLED, "Request cancelled".to_string())
+}
+
 /// Parse ObjectRange from params, handling both `{object: ...}` wrapper and direct format
 pub fn parse_object_param(params: Option<serde_json::Value>) -> Option<ObjectRange> {
     params.and_then(|p| {
@@ -1,6 +1,7 @@
 //! XML manipulation helpers for the LSP server
 
-use crate::draw::geometry::{LayerJSON, ObjectRange, PadStackDef};
+use crate::draw::geometry::{LayerJSON, ObjectRange, PadStackDef, Transform2D};
+use crate::draw::parsing::{LengthUnit, features_transform};
 use crate::parse_xml::XmlNode;
 use indexmap::IndexMap;
 use std::collections::HashMap;
@@ -51,6 +52,8 @@ pub fn update_dictionary_colors(root: &mut XmlNode, modified_colors: &HashMap<St
                 attributes: indexmap::IndexMap::new(),
                 children: Vec::new(),
                 text_content: String::new(),
+                position: None,
+                span: None,
             };
             
             for (layer_id, color) in modified_colors {
@@ -105,8 +108,28 @@ fn create_entry_color(layer_id: &str, color: &[f32; 4]) -> XmlNode {
             attributes: color_attrs,
             children: Vec::new(),
             text_content: String::new(),
+            position: None,
+            span: None,
         }],
         text_content: String::new(),
+        position: None,
+        span: None,
+    }
+}
+
+/// The Step named `step`, or the whole document when there is none, so Save counts elements in the step Load drew
+fn step_scope_mut<'a>(root: &'a mut XmlNode, step: Option<&str>) -> &'a mut XmlNode {
+    let Some(step) = step else { return root };
+    let path = root.children.iter().position(|n| n.name == "Ecad").and_then(|e| {
+        let ecad = &root.children[e];
+        let c = ecad.children.iter().position(|n| n.name == "CadData")?;
+        let s = ecad.children[c].children.iter()
+            .position(|n| n.name == "Step" && n.attributes.get("name").map(|v| v.as_str()) == Some(step))?;
+        Some((e, c, s))
+    });
+    match path {
+        Some((e, c, s)) => &mut root.children[e].children[c].children[s],
+        None => root,
     }
 }
 
@@ -117,6 +140,7 @@ pub fn remove_deleted_objects_from_xml(
     deleted_objects: &HashMap<u64, ObjectRange>,
     _layers: &[LayerJSON],
     padstack_defs: &IndexMap<String, PadStackDef>,
+    active_step: Option<&str>,
 ) -> usize {
     // Build a map of layer_id -> set of deleted object indices by type
     let mut deleted_by_layer: HashMap<String, HashMap<u8, std::collections::HashSet<usize>>> = HashMap::new();
@@ -139,7 +163,7 @@ pub fn remove_deleted_objects_from_xml(
     let mut total_removed = 0;
     let mut counters: HashMap<String, HashMap<u8, usize>> = HashMap::new();
     
-    process_node(root, &deleted_by_layer, None, false, &mut counters, &mut total_removed, padstack_defs);
+    process_node(step_scope_mut(root, active_step), &deleted_by_layer, None, false, &mut counters, &mut total_removed, padstack_defs);
     
     eprintln!("[XML Remove] Total removed: {}", total_removed);
     total_removed
@@ -201,7 +225,7 @@ fn check_should_remove(
     let layer_id = layer?;
     
     let obj_type = match child.name.as_str() {
-        "Polyline" | "Line" => Some(0u8),
+        "Polyline" | "Line" | "Arc" => Some(0u8),
         "Polygon" => Some(1u8),
         "Pad" => {
             let has_via_attr = child.attributes.get("padUsage").map(|s| s.as_str()) == Some("VIA");
@@ -255,6 +279,8 @@ pub fn apply_moved_objects_to_xml(
     moved_objects: &HashMap<u64, crate::lsp::state::ObjectMove>,
     all_object_ranges: &[ObjectRange],
     padstack_defs: &IndexMap<String, PadStackDef>,
+    source_unit: LengthUnit,
+    active_step: Option<&str>,
 ) -> usize {
     if moved_objects.is_empty() {
         return 0;
@@ -268,7 +294,8 @@ pub fn apply_moved_objects_to_xml(
         if let Some(range) = all_object_ranges.iter().find(|r| r.id == *obj_id) {
             let obj_index = (*obj_id & 0xFFFFFFFFF) as usize;
             let key = (range.layer_id.clone(), range.obj_type, obj_index);
-            move_lookup.insert(key, (mov.delta_x, mov.delta_y));
+            // Deltas are tracked in mm; the re-parsed document is in its original unit
+            move_lookup.insert(key, (source_unit.from_mm(mov.delta_x), source_unit.from_mm(mov.delta_y)));
             eprintln!("[XML Move] Marking for move: layer={}, obj_type={}, index={}, delta=({:.3}, {:.3})", 
                 range.layer_id, range.obj_type, obj_index, mov.delta_x, mov.delta_y);
         }
@@ -277,17 +304,19 @@ pub fn apply_moved_objects_to_xml(
     let mut total_modified = 0;
     let mut counters: HashMap<String, HashMap<u8, usize>> = HashMap::new();
     
-    apply_moves_to_node(root, &move_lookup, None, false, &mut counters, &mut total_modified, padstack_defs);
+    apply_moves_to_node(step_scope_mut(root, active_step), &move_lookup, None, false, &Transform2D::identity(), &mut counters, &mut total_modified, padstack_defs);
     
     eprintln!("[XML Move] Total modified: {}", total_modified);
     total_modified
 }
 
+#[allow(clippy::too_many_arguments)]
 fn apply_moves_to_node(
     node: &mut XmlNode,
     move_lookup: &HashMap<(String, u8, usize), (f32, f32)>,
     current_layer: Option<&str>,
     in_via_set: bool,
+    placement: &Transform2D,
     counters: &mut HashMap<String, HashMap<u8, usize>>,
     modified: &mut usize,
     padstack_defs: &IndexMap<String, PadStackDef>,
@@ -302,6 +331,14 @@ fn apply_moves_to_node(
         node.attributes.get("padUsage").map(|s| s.as_str()) == Some("VIA");
     let child_in_via_set = in_via_set || is_via_set;
     
+    // Geometry inside Features is in the Features' local frame (Location + Xform)
+    let placement = if node.name == "Features" {
+        placement.then_apply(&features_transform(node))
+    } else {
+        *placement
+    };
+    let to_local = placement.inverse().unwrap_or_default();
+    
     for child in &mut node.children {
         // Check if this child should be moved
         if let Some((delta_x, delta_y)) = check_should_move(
@@ -312,12 +349,13 @@ fn apply_moves_to_node(
             move_lookup,
             padstack_defs
         ) {
+            let (delta_x, delta_y) = to_local.apply_vector(delta_x, delta_y);
             apply_move_to_node(child, delta_x, delta_y);
             *modified += 1;
         }
         
         // Recurse into children
-        apply_moves_to_node(child, move_lookup, layer_ref, child_in_via_set, 
+        apply_moves_to_node(child, move_lookup, layer_ref, child_in_via_set, &placement,
             counters, modified, padstack_defs);
     }
 }
@@ -333,7 +371,7 @@ fn check_should_move(
     let layer_id = layer?;
     
     let obj_type = match child.name.as_str() {
-        "Polyline" | "Line" => Some(0u8),
+        "Polyline" | "Line" | "Arc" => Some(0u8),
         "Polygon" => Some(1u8),
         "Pad" => {
             let has_via_attr = child.attributes.get("padUsage").map(|s| s.as_str()) == Some("VIA");
@@ -399,23 +437,23 @@ fn apply_move_to_node(node: &mut XmlNode, delta_x: f32, delta_y: f32) {
             }
             eprintln!("[XML Move] Applied delta to Polyline");
         }
-        "Line" => {
-            // Line elements have startX, startY, endX, endY attributes directly
-            for attr in ["startX", "endX"] {
+        "Line" | "Arc" => {
+            // Line and Arc elements have startX, startY, endX, endY (Arc adds centerX, centerY) attributes directly
+            for attr in ["startX", "endX", "centerX"] {
                 if let Some(val_str) = node.attributes.get(attr) {
                     if let Ok(val) = val_str.parse::<f32>() {
                         node.attributes.insert(attr.to_string(), format!("{:.6}", val + delta_x));
                     }
                 }
             }
-            for attr in ["startY", "endY"] {
+            for attr in ["startY", "endY", "centerY"] {
                 if let Some(val_str) = node.attributes.get(attr) {
                     if let Ok(val) = val_str.parse::<f32>() {
                         node.attributes.insert(attr.to_string(), format!("{:.6}", val + delta_y));
                     }
                 }
             }
-            eprintln!("[XML Move] Applied delta to Line");
+            eprintln!("[XML Move] Applied delta to {}", node.name);
         }
         "Polygon" => {
             // Polygon elements directly contain PolyBegin/PolyStepSegment
@@ -455,15 +493,15 @@ fn apply_move_to_coordinate_node(node: &mut XmlNode, delta_x: f32, delta_y: f32)
             }
         }
         "PolyStepCurve" => {
-            // Curves have x, y and cx, cy (control point)
-            for attr in ["x", "cx"] {
+            // Curves have x, y (end point) and centerX, centerY (arc center)
+            for attr in ["x", "centerX"] {
                 if let Some(val_str) = node.attributes.get(attr) {
                     if let Ok(val) = val_str.parse::<f32>() {
                         node.attributes.insert(attr.to_string(), format!("{:.6}", val + delta_x));
                     }
                 }
             }
-            for attr in ["y", "cy"] {
+            for attr in ["y", "centerY"] {
                 if let Some(val_str) = node.attributes.get(attr) {
                     if let Ok(val) = val_str.parse::<f32>() {
                         node.attributes.insert(attr.to_string(), format!("{:.6}", val + delta_y));
@@ -478,7 +516,8 @@ fn apply_move_to_coordinate_node(node: &mut XmlNode, delta_x: f32, delta_y: f32)
 /// Parse DFM design rules from Dfx elements in the XML
 /// Looks for clearance rules in Step/Dfx/Criteria/Property elements
 /// Returns the conductor clearance in mm if found, otherwise None
-pub fn parse_dfx_clearance_rule(root: &XmlNode) -> Option<f32> {
+/// Property values without a unit attribute are in the file's CadHeader unit
+pub fn parse_dfx_clearance_rule(root: &XmlNode, default_unit: LengthUnit) -> Option<f32> {
     // Navigate to Ecad/CadData/Step
     let ecad = root.children.iter().find(|n| n.name == "Ecad")?;
     let cad_data = ecad.children.iter().find(|n| n.name == "CadData")?;
@@ -514,15 +553,12 @@ pub fn parse_dfx_clearance_rule(root: &XmlNode) -> Option<f32> {
                     for prop in criteria.children.iter().filter(|n| n.name == "Property") {
                         if let Some(value_str) = prop.attributes.get("value") {
                             if let Ok(value) = value_str.parse::<f32>() {
-                                // Check unit - assume MILLIMETER if not specified
-                                let unit = prop.attributes.get("unit").map(|s| s.as_str()).unwrap_or("MILLIMETER");
+                                // Check unit - fall back to the CadHeader unit if not specified
+                                let unit = prop.attributes.get("unit")
+                                    .and_then(|u| LengthUnit::from_ipc(u))
+                                    .unwrap_or(default_unit);
                                 
-                                let value_mm = match unit.to_uppercase().as_str() {
-                                    "MILLIMETER" | "MM" => value,
-                                    "INCH" | "IN" => value * 25.4,
-                                    "MIL" => value * 0.0254,
-                                    _ => value, // Assume mm
-                                };
+                                let value_mm = unit.to_mm(value);
                                 
                                 eprintln!("[LSP Server] Found DFM clearance rule '{}': {:.4}mm", dfx_name, value_mm);
                                 return Some(value_mm);
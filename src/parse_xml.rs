@@ -1,8 +1,11 @@
 use std::fs::File;
-use std::io::BufReader;
+use std::io::{BufRead, BufReader, Read};
 use quick_xml::Reader;
 use quick_xml::events::Event;
 use indexmap::IndexMap;
+use rayon::prelude::*;
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
 
 /// Represents a parsed XML node with its properties
 /// This is a simplified structure to demonstrate parsing
@@ -16,6 +19,159 @@ pub struct XmlNode {
     pub text_content: String,
     /// Child nodes
     pub children: Vec<XmlNode>,
+    /// Where the start tag begins in the source (None for nodes built in code)
+    pub position: Option<SourcePosition>,
+    /// Byte range of the element in the source, used by the lossless serializer
+    pub span: Option<SourceSpan>,
+}
+
+#[cfg(test)]
+impl XmlNode {
+    /// Element without text or source location, for building trees in tests
+    pub fn new(name: &str, attrs: &[(&str, &str)], children: Vec<XmlNode>) -> Self {
+        XmlNode {
+            name: name.to_string(),
+            attributes: attrs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+            text_content: String::new(),
+            children,
+            position: None,
+            span: None,
+        }
+    }
+}
+
+/// Location in an XML source: byte offset plus 1-based line and column
+/// Columns count bytes, which equals characters for the ASCII content of IPC-2581 files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct SourcePosition {
+    pub offset: usize,
+    pub line: u32,
+    pub column: u32,
+}
+
+/// Byte offsets of an element in its source
+/// `<Tag a="1">content</Tag>`: `start` is at `<Tag`, `content_start` just past the start tag,
+/// `content_end` at `</Tag>` and `end` just past it. Self-closing elements have empty content
+/// and `content_start == content_end == end`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourceSpan {
+    pub start: usize,
+    pub content_start: usize,
+    pub content_end: usize,
+    pub end: usize,
+}
+
+/// XML syntax or structure error with the location it was detected at
+#[derive(Debug, Clone)]
+pub struct XmlParseError {
+    pub message: String,
+    pub position: SourcePosition,
+}
+
+impl std::fmt::Display for XmlParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}, column {}: {}", self.position.line, self.position.column, self.message)
+    }
+}
+
+impl std::error::Error for XmlParseError {}
+
+/// Buffered reader that records line starts as the XML reader consumes input
+/// Lookups must not go backwards; line starts already passed are dropped, so
+/// memory stays bounded by the distance between the read position and the last lookup.
+struct LineTracker<R> {
+    inner: R,
+    start: usize,     // Source offset of the first byte of `inner`
+    consumed: usize,  // Source offset of the next byte to read
+    line: u32,
+    line_start: usize,
+    pending: VecDeque<usize>,  // Offsets just past each newline not yet looked up
+}
+
+impl<R: BufRead> LineTracker<R> {
+    fn new(inner: R) -> Self {
+        Self::starting_at(inner, SourcePosition { offset: 0, line: 1, column: 1 })
+    }
+
+    /// Tracker for input that is a fragment of a larger source, beginning at `start`
+    fn starting_at(inner: R, start: SourcePosition) -> Self {
+        LineTracker {
+            inner,
+            start: start.offset,
+            consumed: start.offset,
+            line: start.line,
+            line_start: start.offset + 1 - start.column as usize,
+            pending: VecDeque::new(),
+        }
+    }
+
+    fn position(&mut self, offset: usize) -> SourcePosition {
+        while self.pending.front().is_some_and(|&start| start <= offset) {
+            self.line_start = self.pending.pop_front().unwrap_or(self.line_start);
+            self.line += 1;
+        }
+        SourcePosition { offset, line: self.line, column: (offset.saturating_sub(self.line_start) + 1) as u32 }
+    }
+}
+
+impl<R: BufRead> Read for LineTracker<R> {
+    fn read(&mut self, out: &mut [u8]) -> std::io::Result<usize> {
+        let available = self.fill_buf()?;
+        let n = available.len().min(out.len());
+        out[..n].copy_from_slice(&available[..n]);
+        self.consume(n);
+        Ok(n)
+    }
+}
+
+impl<R: BufRead> BufRead for LineTracker<R> {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amount: usize) {
+        if let Ok(buf) = self.inner.fill_buf() {
+            let start = self.consumed;
+            self.pending.extend(buf[..amount.min(buf.len())].iter().enumerate()
+                .filter(|(_, &b)| b == b'\n')
+                .map(|(i, _)| start + i + 1));
+        }
+        self.consumed += amount;
+        self.inner.consume(amount);
+    }
+}
+
+type TrackedReader<R> = Reader<LineTracker<R>>;
+
+/// Source offset of the reader's position
+fn source_offset<R: BufRead>(reader: &TrackedReader<R>) -> usize {
+    reader.get_ref().start + reader.buffer_position()
+}
+
+/// Attach the current location to an error
+fn located<R: BufRead>(reader: &mut TrackedReader<R>, offset: usize, message: impl std::fmt::Display) -> anyhow::Error {
+    let position = reader.get_mut().position(offset);
+    anyhow::Error::new(XmlParseError { message: message.to_string(), position })
+}
+
+/// Element paths of a node's children (`path/Step[2]`), indexed (1-based) where siblings share a name
+pub fn child_paths(node: &XmlNode, path: &str) -> Vec<String> {
+    let mut totals: HashMap<&str, usize> = HashMap::new();
+    for child in &node.children {
+        *totals.entry(child.name.as_str()).or_default() += 1;
+    }
+    let mut seen: HashMap<&str, usize> = HashMap::new();
+    node.children.iter()
+        .map(|child| {
+            let index = seen.entry(child.name.as_str()).or_default();
+            *index += 1;
+            if totals[child.name.as_str()] > 1 {
+                format!("{}/{}[{}]", path, child.name, index)
+            } else {
+                format!("{}/{}", path, child.name)
+            }
+        })
+        .collect()
 }
 
 /// Parses an IPC-2581 XML file and returns the root node
@@ -24,7 +180,7 @@ pub struct XmlNode {
 /// * `path` - The file path to the XML file to parse
 ///
 /// # Returns
-/// * `Result<XmlNode>` - The parsed XML tree or an error
+/// * `Result<XmlNode>` - The parsed XML tree or an error; syntax errors downcast to [`XmlParseError`]
 ///
 /// # Example
 /// ```ignore
@@ -42,88 +198,349 @@ pub fn parse_xml_file<P: AsRef<std::path::Path>>(path: P) -> anyhow::Result<XmlN
     
     // Initialize the quick_xml Reader with the buffered reader
     // quick_xml is one of the fastest XML parsers in the Rust ecosystem
-    let mut reader = Reader::from_reader(buf_reader);
-    
-    // Configure the reader for better performance
-    reader.trim_text(true);
-    
-    // Create a buffer to store bytes read from the XML
-    let mut buf = Vec::new();
+    parse_document(Reader::from_reader(LineTracker::new(buf_reader)), None, |_, _| Ok(()))
+}
 
-    loop {
-        buf.clear();
-        let event = reader.read_event_into(&mut buf)?;
-        let maybe_root = match event {
-            Event::Start(start) => Some((start.into_owned(), false)),
-            Event::Empty(start) => Some((start.into_owned(), true)),
-            Event::Eof => {
-                anyhow::bail!("XML document is empty");
+/// Parses an XML document held in memory and returns the root node
+pub fn parse_xml_str(xml: &str) -> anyhow::Result<XmlNode> {
+    parse_xml_bytes(xml.as_bytes())
+}
+
+/// Parses raw XML bytes; node spans index into `xml`
+pub fn parse_xml_bytes(xml: &[u8]) -> anyhow::Result<XmlNode> {
+    parse_document(Reader::from_reader(LineTracker::new(xml)), None, |_, _| Ok(()))
+}
+
+/// Parses an XML file on all cores; see [`parse_xml_bytes_parallel`]
+pub fn parse_xml_file_parallel<P: AsRef<std::path::Path>>(path: P, split: &str) -> anyhow::Result<XmlNode> {
+    let xml = std::fs::read(&path)
+        .map_err(|e| anyhow::anyhow!("Failed to open file: {}", e))?;
+    parse_xml_bytes_parallel(&xml, split)
+}
+
+/// Parses raw XML bytes like [`parse_xml_bytes`], with every element named `split` parsed in parallel
+///
+/// A pre-scan finds the byte ranges of the `split` elements (LayerFeature in IPC-2581, which holds
+/// nearly all of a board's data). Their subtrees are parsed with rayon while the rest of the document
+/// is parsed with their contents blanked out, then each subtree replaces its placeholder. The result,
+/// positions and spans included, is the same as the sequential parse. Split elements must not nest;
+/// when the pre-scan meets one inside another the document is parsed sequentially.
+pub fn parse_xml_bytes_parallel(xml: &[u8], split: &str) -> anyhow::Result<XmlNode> {
+    let spans = match split_spans(xml, split) {
+        Some(spans) if !spans.is_empty() => spans,
+        _ => return parse_xml_bytes(xml),
+    };
+
+    // Blank the split contents but keep their tags and newlines, so offsets and lines stay put
+    let mut skeleton = xml.to_vec();
+    for span in &spans {
+        for byte in &mut skeleton[span.content_start..span.content_end] {
+            if *byte != b'\n' && *byte != b'\r' {
+                *byte = b' ';
             }
-            _ => None,
-        };
+        }
+    }
+    let bases = span_positions(xml, &spans);
 
-        if let Some((start, self_closing)) = maybe_root {
-            let mut node_buf = Vec::new();
-            return parse_node(&mut reader, &mut node_buf, start, self_closing);
+    let (root, subtrees) = rayon::join(
+        || parse_xml_bytes(&skeleton),
+        || spans.par_iter().zip(bases.par_iter())
+            .map(|(span, &base)| {
+                let fragment = LineTracker::starting_at(&xml[span.start..span.end], base);
+                parse_document(Reader::from_reader(fragment), None, |_, _| Ok(()))
+            })
+            .collect::<Vec<anyhow::Result<XmlNode>>>(),
+    );
+
+    let mut errors = Vec::new();
+    let root = root.map_err(|e| errors.push(e));
+    let subtrees: Vec<XmlNode> = subtrees.into_iter().filter_map(|s| s.map_err(|e| errors.push(e)).ok()).collect();
+    // Report the error nearest the start of the file, as the sequential parse would
+    if let Some(error) = errors.into_iter().min_by_key(|e| e.downcast_ref::<XmlParseError>().map_or(usize::MAX, |e| e.position.offset)) {
+        return Err(error);
+    }
+
+    let mut root = root.expect("errors returned above");
+    let mut subtrees = subtrees.into_iter();
+    let mut placeholders = 0;
+    for child in &mut root.children {
+        stitch(child, split, &mut subtrees, &mut placeholders);
+    }
+    if placeholders != spans.len() {
+        // The pre-scan disagreed with the parser (e.g. a split element as the root); trust the parser
+        return parse_xml_bytes(xml);
+    }
+    Ok(root)
+}
+
+/// Spans of every element named `name`, in document order
+/// Comments, CDATA sections, processing instructions and declarations are skipped. None when an
+/// element opens inside another or a closing tag has no matching start.
+fn split_spans(xml: &[u8], name: &str) -> Option<Vec<SourceSpan>> {
+    let name = name.as_bytes();
+    let skip_past = |from: usize, pattern: &[u8]| xml[from..].windows(pattern.len())
+        .position(|w| w == pattern)
+        .map_or(xml.len(), |i| from + i + pattern.len());
+
+    let mut spans = Vec::new();
+    let mut open: Option<(usize, usize)> = None;  // Start and content start of the element being scanned
+    let mut offset = 0;
+    while let Some(found) = xml[offset..].iter().position(|&b| b == b'<') {
+        let start = offset + found;
+        let rest = &xml[start + 1..];
+        if rest.starts_with(b"!--") {
+            offset = skip_past(start, b"-->");
+            continue;
+        }
+        if rest.starts_with(b"![CDATA[") {
+            offset = skip_past(start, b"]]>");
+            continue;
         }
+        if rest.starts_with(b"?") {
+            offset = skip_past(start, b"?>");
+            continue;
+        }
+        let tag_end = tag_end(xml, start)?;
+        let closing = rest.first() == Some(&b'/');
+        let tag_name = &rest[closing as usize..];
+        let named = tag_name.starts_with(name)
+            && tag_name.get(name.len()).is_some_and(|&b| b.is_ascii_whitespace() || b == b'/' || b == b'>');
+        if named && !rest.starts_with(b"!") {
+            match (closing, open) {
+                (false, None) if xml[tag_end - 2] == b'/' => spans.push(SourceSpan {
+                    start, content_start: tag_end, content_end: tag_end, end: tag_end,
+                }),
+                (false, None) => open = Some((start, tag_end)),
+                (true, Some((element_start, content_start))) => {
+                    spans.push(SourceSpan { start: element_start, content_start, content_end: start, end: tag_end });
+                    open = None;
+                }
+                _ => return None,
+            }
+        }
+        offset = tag_end;
     }
+    Some(spans)
 }
 
-fn parse_node(reader: &mut Reader<BufReader<File>>, buf: &mut Vec<u8>, start: quick_xml::events::BytesStart<'static>, self_closing: bool) -> anyhow::Result<XmlNode> {
-    let element_name_bytes = start.name().as_ref().to_vec();
-    let element_name = String::from_utf8_lossy(&element_name_bytes).to_string();
-    let attributes = collect_attributes(start.attributes())?;
+/// Offset just past the `>` closing the tag that starts at `start`, ignoring `>` inside attribute values
+fn tag_end(xml: &[u8], start: usize) -> Option<usize> {
+    let mut quote = None;
+    for (i, &b) in xml[start..].iter().enumerate() {
+        match quote {
+            Some(q) if b == q => quote = None,
+            Some(_) => {}
+            None if b == b'"' || b == b'\'' => quote = Some(b),
+            None if b == b'>' => return Some(start + i + 1),
+            None => {}
+        }
+    }
+    None
+}
 
-    let mut node = XmlNode {
-        name: element_name,
-        attributes,
-        text_content: String::new(),
-        children: Vec::new(),
-    };
+/// Source position of each span start, in one pass over the bytes before the last one
+fn span_positions(xml: &[u8], spans: &[SourceSpan]) -> Vec<SourcePosition> {
+    let mut line = 1u32;
+    let mut line_start = 0;
+    let mut scanned = 0;
+    spans.iter()
+        .map(|span| {
+            for (i, &b) in xml[scanned..span.start].iter().enumerate() {
+                if b == b'\n' {
+                    line += 1;
+                    line_start = scanned + i + 1;
+                }
+            }
+            scanned = span.start;
+            SourcePosition { offset: span.start, line, column: (span.start - line_start + 1) as u32 }
+        })
+        .collect()
+}
+
+/// Replace the `split` placeholders under `node` with the parsed subtrees, in document order
+fn stitch(node: &mut XmlNode, split: &str, subtrees: &mut impl Iterator<Item = XmlNode>, placeholders: &mut usize) {
+    if node.name == split {
+        *placeholders += 1;
+        if let Some(subtree) = subtrees.next() {
+            *node = subtree;
+        }
+        return;
+    }
+    for child in &mut node.children {
+        stitch(child, split, subtrees, placeholders);
+    }
+}
 
-    if self_closing {
-        return Ok(node);
+/// Parses an XML file like [`parse_xml_file`], handing every complete element named `detach` to
+/// `on_detached` instead of keeping it
+///
+/// The element stays in the returned tree as an empty placeholder (name, attributes, position and
+/// span), so memory holds one detached subtree at a time. Large files whose bulk sits in one kind
+/// of element (LayerFeature in IPC-2581) can be processed without the full DOM.
+pub fn parse_xml_file_detaching<P, F>(path: P, detach: &str, on_detached: F) -> anyhow::Result<XmlNode>
+where
+    P: AsRef<std::path::Path>,
+    F: FnMut(&mut PartialDocument, XmlNode) -> anyhow::Result<()>,
+{
+    let file = File::open(&path)
+        .map_err(|e| anyhow::anyhow!("Failed to open file: {}", e))?;
+    parse_document(Reader::from_reader(LineTracker::new(BufReader::new(file))), Some(detach), on_detached)
+}
+
+/// The tree under construction while an element is handed out by [`parse_xml_file_detaching`]
+pub struct PartialDocument {
+    /// Open elements, root first; each holds the children completed so far
+    stack: Vec<XmlNode>,
+}
+
+impl PartialDocument {
+    /// Open ancestors of the handed-out element, root first
+    pub fn ancestors(&self) -> &[XmlNode] {
+        &self.stack
     }
 
+    /// Run `f` on the document parsed so far: every completed element, with the open ones linked into their parents
+    /// Elements are only handed out from inside the root, so the root is always open here.
+    pub fn with_root<T>(&mut self, f: impl FnOnce(&XmlNode) -> T) -> T {
+        let depth = self.stack.len();
+        for _ in 1..depth {
+            let child = self.stack.pop().expect("open element");
+            self.stack.last_mut().expect("open parent").children.push(child);
+        }
+        let result = f(&self.stack[0]);
+        // Unlink the open elements again; each is the last child of its parent
+        for _ in 1..depth {
+            let child = self.stack.last_mut().and_then(|parent| parent.children.pop()).expect("linked element");
+            self.stack.push(child);
+        }
+        result
+    }
+}
+
+fn parse_document<R, F>(mut reader: TrackedReader<R>, detach: Option<&str>, mut on_detached: F) -> anyhow::Result<XmlNode>
+where
+    R: BufRead,
+    F: FnMut(&mut PartialDocument, XmlNode) -> anyhow::Result<()>,
+{
+    // Configure the reader for better performance
+    reader.trim_text(true);
+    // End names are checked here, reporting where the mismatched tag starts;
+    // untrimmed closing tags keep their length so spans end exactly at `>`
+    reader.check_end_names(false);
+    reader.trim_markup_names_in_closing_tags(false);
+    
+    // Create a buffer to store bytes read from the XML
+    let mut buf = Vec::new();
+    // Elements are built on an explicit stack, so deeply nested files cannot overflow the call stack
+    let mut document = PartialDocument { stack: Vec::new() };
+
     loop {
         buf.clear();
-        let event = reader.read_event_into(buf)?;
-        match event {
-            Event::Start(child_start) => {
-                let mut child_buf = Vec::new();
-                let child = parse_node(reader, &mut child_buf, child_start.into_owned(), false)?;
-                node.children.push(child);
+        let event = match reader.read_event_into(&mut buf) {
+            Ok(event) => event,
+            Err(e) => {
+                let offset = source_offset(&reader);
+                return Err(located(&mut reader, offset, e));
             }
-            Event::Empty(child_start) => {
-                let mut child_buf = Vec::new();
-                let child = parse_node(reader, &mut child_buf, child_start.into_owned(), true)?;
-                node.children.push(child);
+        };
+        let complete = match event {
+            Event::Start(start) => {
+                let node = open_element(&mut reader, &start, false)?;
+                document.stack.push(node);
+                None
             }
+            Event::Empty(start) => Some(open_element(&mut reader, &start, true)?),
             Event::Text(text) => {
-                let value = String::from_utf8_lossy(text.as_ref()).to_string();
-                if !value.trim().is_empty() {
-                    node.text_content.push_str(&value);
-                }
+                append_text(&mut document, text.as_ref());
+                None
             }
             Event::CData(text) => {
-                let value = String::from_utf8_lossy(text.as_ref()).to_string();
-                if !value.trim().is_empty() {
-                    node.text_content.push_str(&value);
-                }
+                append_text(&mut document, text.as_ref());
+                None
             }
             Event::End(end) => {
-                anyhow::ensure!(
-                    end.name().as_ref() == element_name_bytes.as_slice(),
-                    "unexpected closing tag '</{}>' while parsing '<{}>'",
-                    String::from_utf8_lossy(end.name().as_ref()),
-                    node.name
-                );
-                return Ok(node);
+                // Closing tags outside the root element are ignored
+                let Some(mut node) = document.stack.pop() else { continue };
+                let end_offset = source_offset(&reader);
+                let offset = end_offset.saturating_sub(end.len() + 3);
+                let end_name = end.as_ref().trim_ascii_end();
+                let position = node.position.unwrap_or(SourcePosition { offset: 0, line: 1, column: 1 });
+                if end_name != node.name.as_bytes() {
+                    return Err(located(&mut reader, offset, format_args!(
+                        "unexpected closing tag '</{}>' while parsing '<{}>' opened at line {}, column {}",
+                        String::from_utf8_lossy(end_name), node.name, position.line, position.column)));
+                }
+                if let Some(span) = &mut node.span {
+                    span.content_end = offset;
+                    span.end = end_offset;
+                }
+                Some(node)
             }
             Event::Eof => {
-                anyhow::bail!("unexpected end of file while parsing element '{}'", node.name);
+                let offset = source_offset(&reader);
+                return Err(match document.stack.last() {
+                    Some(open) => {
+                        let position = open.position.unwrap_or(SourcePosition { offset: 0, line: 1, column: 1 });
+                        let message = format!("unexpected end of file while parsing element '{}' opened at line {}, column {}",
+                            open.name, position.line, position.column);
+                        located(&mut reader, offset, message)
+                    }
+                    None => located(&mut reader, offset, "XML document is empty"),
+                });
+            }
+            _ => None,
+        };
+
+        if let Some(node) = complete {
+            if document.stack.is_empty() {
+                return Ok(node);
             }
-            _ => {}
+            let node = if detach == Some(node.name.as_str()) {
+                let placeholder = XmlNode {
+                    name: node.name.clone(),
+                    attributes: node.attributes.clone(),
+                    text_content: String::new(),
+                    children: Vec::new(),
+                    position: node.position,
+                    span: node.span,
+                };
+                on_detached(&mut document, node)?;
+                placeholder
+            } else {
+                node
+            };
+            if let Some(parent) = document.stack.last_mut() {
+                parent.children.push(node);
+            }
+        }
+    }
+}
+
+/// Element for the start tag just read, without children yet
+fn open_element<R: BufRead>(reader: &mut TrackedReader<R>, start: &quick_xml::events::BytesStart<'_>, self_closing: bool) -> anyhow::Result<XmlNode> {
+    // The reader sits just past the tag: `<` + content + `>` (or `/>`)
+    let content_start = source_offset(reader);
+    let tag_offset = content_start.saturating_sub(start.len() + if self_closing { 3 } else { 2 });
+    let position = reader.get_mut().position(tag_offset);
+    let attributes = collect_attributes(start.attributes())
+        .map_err(|e| anyhow::Error::new(XmlParseError { message: e.to_string(), position }))?;
+
+    Ok(XmlNode {
+        name: String::from_utf8_lossy(start.name().as_ref()).to_string(),
+        attributes,
+        text_content: String::new(),
+        children: Vec::new(),
+        position: Some(position),
+        span: Some(SourceSpan { start: tag_offset, content_start, content_end: content_start, end: content_start }),
+    })
+}
+
+/// Append text or CDATA to the innermost open element (text outside the root is ignored)
+fn append_text(document: &mut PartialDocument, text: &[u8]) {
+    let value = String::from_utf8_lossy(text);
+    if let Some(node) = document.stack.last_mut() {
+        if !value.trim().is_empty() {
+            node.text_content.push_str(&value);
         }
     }
 }
@@ -138,3 +555,81 @@ fn collect_attributes(attributes: quick_xml::events::attributes::Attributes<'_>)
     }
     Ok(map)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_node_positions() {
+        let xml = "<?xml version=\"1.0\"?>\n<IPC-2581 revision=\"C\">\n  <Content>\n    <Entry id=\"a\"/><Entry id=\"b\"/>\n  </Content>\n</IPC-2581>\n";
+        let root = parse_xml_str(xml).unwrap();
+        let position = |node: &XmlNode| node.position.map(|p| (p.line, p.column));
+        assert_eq!(position(&root), Some((2, 1)));
+        assert_eq!(root.position.unwrap().offset, xml.find("<IPC").unwrap());
+
+        let content = &root.children[0];
+        assert_eq!(position(content), Some((3, 3)));
+        assert_eq!(position(&content.children[0]), Some((4, 5)));
+        assert_eq!(position(&content.children[1]), Some((4, 20)));
+    }
+
+    #[test]
+    fn test_error_locations() {
+        let error = |xml: &str| parse_xml_str(xml).unwrap_err().downcast::<XmlParseError>().unwrap();
+
+        let mismatched = error("<Root>\n  <Step>\n  </Stpe>\n</Root>");
+        assert_eq!((mismatched.position.line, mismatched.position.column), (3, 3));
+        assert!(mismatched.message.contains("'</Stpe>' while parsing '<Step>' opened at line 2, column 3"));
+
+        let truncated = error("<Root>\n  <Step>\n");
+        assert_eq!(truncated.position.line, 3);
+        assert!(truncated.message.starts_with("unexpected end of file"));
+
+        let bad_attribute = error("<Root>\n\n  <Pad x=1/>\n</Root>");
+        assert_eq!(bad_attribute.position.line, 3);
+    }
+
+    fn assert_same_tree(a: &XmlNode, b: &XmlNode) {
+        assert_eq!((&a.name, &a.attributes, &a.text_content), (&b.name, &b.attributes, &b.text_content));
+        assert_eq!((a.position, a.span), (b.position, b.span), "{}", a.name);
+        assert_eq!(a.children.len(), b.children.len(), "{}", a.name);
+        for (a, b) in a.children.iter().zip(&b.children) {
+            assert_same_tree(a, b);
+        }
+    }
+
+    #[test]
+    fn test_parallel_matches_sequential() {
+        let xml = concat!(
+            "<?xml version=\"1.0\"?>\n",
+            "<IPC-2581>\n",
+            "  <!-- <LayerFeature> in a comment -->\n",
+            "  <Step name=\"a>b\">\n",
+            "    <LayerFeature layerRef=\"TOP\"><Set net=\"GND\">\n",
+            "      <Pad x=\"1\"/>\n",
+            "    </Set></LayerFeature>\n",
+            "    <LayerFeature layerRef=\"BOTTOM\"/>\n",
+            "    <LayerFeature layerRef=\"INNER\">\r\n",
+            "      <Set><![CDATA[</LayerFeature>]]></Set>\r\n",
+            "    </LayerFeature >\n",
+            "  </Step>\n",
+            "</IPC-2581>\n",
+        );
+        assert_eq!(split_spans(xml.as_bytes(), "LayerFeature").map(|spans| spans.len()), Some(3));
+        let sequential = parse_xml_str(xml).unwrap();
+        let parallel = parse_xml_bytes_parallel(xml.as_bytes(), "LayerFeature").unwrap();
+        assert_same_tree(&parallel, &sequential);
+        assert_eq!(parallel.children[0].children[0].children[0].children[0].position.map(|p| (p.line, p.column)), Some((6, 7)));
+    }
+
+    #[test]
+    fn test_parallel_error_locations() {
+        let xml = "<Root>\n  <LayerFeature>\n    <Set>\n    </Sat>\n  </LayerFeature>\n  <Other>\n</Root>";
+        let sequential = parse_xml_str(xml).unwrap_err().downcast::<XmlParseError>().unwrap();
+        let parallel = parse_xml_bytes_parallel(xml.as_bytes(), "LayerFeature").unwrap_err().downcast::<XmlParseError>().unwrap();
+        assert_eq!(parallel.position, sequential.position);
+        assert_eq!(parallel.message, sequential.message);
+        assert!(parallel.message.contains("opened at line 3, column 5"));
+    }
+}
@@ -0,0 +1,18 @@
+//! IPC-2581 schema validation
+//!
+//! Checks a parsed document against the bundled XSD of its revision
+//! (`IPC-2581B.xsd`, `IPC-2581B1.xsd`, `IPC-2581C.xsd`): element structure,
+//! child order, required and unknown attributes, enumerations and facets.
+//! Identity constraints (`xsd:key`/`xsd:keyref`) are not checked.
+//!
+//! # Submodules
+//! - `model` - XSD subset compiled into element, complex and simple type tables
+//! - `values` - Built-in type and facet checks for attribute values
+//! - `validate` - Document walk producing path-located diagnostics
+
+mod model;
+mod values;
+mod validate;
+
+pub use model::{Revision, Schema};
+pub use validate::{validate_document, DiagnosticKind, SchemaDiagnostic, Severity, ValidationReport};
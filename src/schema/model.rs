@@ -0,0 +1,270 @@
+//! Schema model compiled from the bundled IPC-2581 XSD files
+//!
+//! The IPC-2581 schemas use a small XSD subset: global element declarations
+//! (with abstract heads and substitution groups), named complex types holding
+//! one `sequence` or `choice` of element references plus attributes, and
+//! named simple types restricting a built-in type with facets.
+
+use crate::parse_xml::{parse_xml_str, XmlNode};
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+use std::sync::OnceLock;
+
+/// IPC-2581 revisions with a bundled schema
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Revision {
+    B,
+    B1,
+    C,
+}
+
+impl Revision {
+    pub const ALL: [Revision; 3] = [Revision::B, Revision::B1, Revision::C];
+
+    /// Revision declared by a document root
+    /// `revision="B"` files pointing `xsi:schemaLocation` at the B1 schema are amendment 1 files.
+    pub fn of_document(root: &XmlNode) -> Option<Revision> {
+        let revision = root.attributes.get("revision")?.trim().to_ascii_uppercase();
+        let location = root.attributes.get("xsi:schemaLocation").map(|s| s.as_str()).unwrap_or("");
+        if revision.starts_with('C') {
+            Some(Revision::C)
+        } else if revision.starts_with("B1") || (revision.starts_with('B') && location.contains("2581B1")) {
+            Some(Revision::B1)
+        } else if revision.starts_with('B') {
+            Some(Revision::B)
+        } else {
+            None
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Revision::B => "B",
+            Revision::B1 => "B1",
+            Revision::C => "C",
+        }
+    }
+
+    pub fn schema_file(self) -> &'static str {
+        match self {
+            Revision::B => "IPC-2581B.xsd",
+            Revision::B1 => "IPC-2581B1.xsd",
+            Revision::C => "IPC-2581C.xsd",
+        }
+    }
+
+    fn source(self) -> &'static str {
+        match self {
+            Revision::B => include_str!("../../IPC-2581B.xsd"),
+            Revision::B1 => include_str!("../../IPC-2581B1.xsd"),
+            Revision::C => include_str!("../../IPC-2581C.xsd"),
+        }
+    }
+
+    /// Compiled schema of this revision (compiled once, on first use)
+    pub fn schema(self) -> anyhow::Result<&'static Schema> {
+        static SCHEMAS: [OnceLock<Result<Schema, String>>; 3] = [OnceLock::new(), OnceLock::new(), OnceLock::new()];
+        let slot = &SCHEMAS[Revision::ALL.iter().position(|r| *r == self).unwrap_or(0)];
+        slot.get_or_init(|| Schema::parse(self.source()).map_err(|e| format!("{}: {}", self.schema_file(), e)))
+            .as_ref()
+            .map_err(|e| anyhow::anyhow!("{}", e))
+    }
+}
+
+/// Global element declaration
+#[derive(Debug, Clone)]
+pub struct ElementDecl {
+    pub name: String,
+    pub type_name: Option<String>,  // None: any content
+    pub is_abstract: bool,
+}
+
+/// Attribute declaration of a complex type
+#[derive(Debug, Clone)]
+pub struct AttributeDecl {
+    pub name: String,
+    pub type_name: String,
+    pub required: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compositor {
+    Sequence,
+    Choice,
+}
+
+/// Element reference inside a content model
+#[derive(Debug, Clone)]
+pub struct Particle {
+    pub element: String,
+    pub min: u32,
+    pub max: Option<u32>,  // None = unbounded
+}
+
+/// Child element structure of a complex type
+#[derive(Debug, Clone)]
+pub struct ContentModel {
+    pub compositor: Compositor,
+    pub min: u32,
+    pub max: Option<u32>,
+    pub particles: Vec<Particle>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ComplexType {
+    pub attributes: Vec<AttributeDecl>,
+    pub content: Option<ContentModel>,  // None: empty element
+}
+
+/// Named simple type: a built-in base narrowed by facets
+#[derive(Debug, Clone, Default)]
+pub struct SimpleType {
+    pub base: String,
+    pub enumerations: Vec<String>,
+    pub pattern: Option<Regex>,
+    pub min_inclusive: Option<f64>,
+    pub max_inclusive: Option<f64>,
+    pub min_exclusive: Option<f64>,
+    pub max_exclusive: Option<f64>,
+    pub total_digits: Option<usize>,
+    pub fraction_digits: Option<usize>,
+}
+
+/// Compiled schema
+#[derive(Debug, Default)]
+pub struct Schema {
+    pub elements: HashMap<String, ElementDecl>,
+    pub complex_types: HashMap<String, ComplexType>,
+    pub simple_types: HashMap<String, SimpleType>,
+    /// Concrete elements that may appear where a declared element is referenced
+    substitutes: HashMap<String, HashSet<String>>,
+}
+
+impl Schema {
+    /// Compile an XSD document
+    pub fn parse(xsd: &str) -> anyhow::Result<Schema> {
+        let root = parse_xml_str(xsd)?;
+        anyhow::ensure!(local_name(&root.name) == "schema", "root element is '{}', not 'schema'", root.name);
+
+        let mut schema = Schema::default();
+        let mut heads: Vec<(String, String)> = Vec::new();  // (member, substitutionGroup head)
+        for node in &root.children {
+            let name = node.attributes.get("name").cloned().unwrap_or_default();
+            match local_name(&node.name) {
+                "element" => {
+                    if let Some(head) = node.attributes.get("substitutionGroup") {
+                        heads.push((name.clone(), head.clone()));
+                    }
+                    schema.elements.insert(name.clone(), ElementDecl {
+                        type_name: node.attributes.get("type").cloned(),
+                        is_abstract: node.attributes.get("abstract").is_some_and(|v| v == "true"),
+                        name,
+                    });
+                }
+                "complexType" => {
+                    schema.complex_types.insert(name, parse_complex_type(node)?);
+                }
+                "simpleType" => {
+                    schema.simple_types.insert(name.clone(), parse_simple_type(node)
+                        .map_err(|e| anyhow::anyhow!("simpleType '{}': {}", name, e))?);
+                }
+                _ => {}
+            }
+        }
+
+        // Each element stands for itself (unless abstract) and for every head above it
+        for decl in schema.elements.values().filter(|d| !d.is_abstract) {
+            let mut current = Some(decl.name.as_str());
+            let mut seen = HashSet::new();
+            while let Some(name) = current.filter(|n| seen.insert(*n)) {
+                schema.substitutes.entry(name.to_string()).or_default().insert(decl.name.clone());
+                current = heads.iter().find(|(member, _)| member == name).map(|(_, head)| head.as_str());
+            }
+        }
+        Ok(schema)
+    }
+
+    /// Whether element `name` may appear where `declared` is referenced
+    pub fn substitutes(&self, declared: &str, name: &str) -> bool {
+        self.substitutes.get(declared).is_some_and(|members| members.contains(name))
+    }
+
+    /// Complex type of a declared element (None for undeclared or untyped elements)
+    pub fn element_type(&self, name: &str) -> Option<&ComplexType> {
+        let type_name = self.elements.get(name)?.type_name.as_deref()?;
+        self.complex_types.get(local_name(type_name))
+    }
+}
+
+/// Name without its namespace prefix
+pub fn local_name(name: &str) -> &str {
+    name.rsplit(':').next().unwrap_or(name)
+}
+
+fn parse_occurs(node: &XmlNode) -> anyhow::Result<(u32, Option<u32>)> {
+    let min = match node.attributes.get("minOccurs") {
+        Some(v) => v.parse()?,
+        None => 1,
+    };
+    let max = match node.attributes.get("maxOccurs").map(|v| v.as_str()) {
+        Some("unbounded") => None,
+        Some(v) => Some(v.parse()?),
+        None => Some(1),
+    };
+    Ok((min, max))
+}
+
+fn parse_complex_type(node: &XmlNode) -> anyhow::Result<ComplexType> {
+    let mut complex_type = ComplexType { attributes: Vec::new(), content: None };
+    for child in &node.children {
+        match local_name(&child.name) {
+            "attribute" => complex_type.attributes.push(AttributeDecl {
+                name: child.attributes.get("name").cloned().unwrap_or_default(),
+                type_name: child.attributes.get("type").cloned().unwrap_or_else(|| "xsd:string".to_string()),
+                required: child.attributes.get("use").is_some_and(|v| v == "required"),
+            }),
+            group @ ("sequence" | "choice") => {
+                let (min, max) = parse_occurs(child)?;
+                let particles = child.children.iter()
+                    .map(|p| {
+                        anyhow::ensure!(local_name(&p.name) == "element", "nested '{}' groups are not supported", p.name);
+                        let (min, max) = parse_occurs(p)?;
+                        let element = p.attributes.get("ref").or_else(|| p.attributes.get("name"))
+                            .ok_or_else(|| anyhow::anyhow!("element particle without ref"))?;
+                        Ok(Particle { element: local_name(element).to_string(), min, max })
+                    })
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+                let compositor = if group == "sequence" { Compositor::Sequence } else { Compositor::Choice };
+                complex_type.content = Some(ContentModel { compositor, min, max, particles });
+            }
+            _ => {}
+        }
+    }
+    Ok(complex_type)
+}
+
+fn parse_simple_type(node: &XmlNode) -> anyhow::Result<SimpleType> {
+    let restriction = node.children.iter()
+        .find(|c| local_name(&c.name) == "restriction")
+        .ok_or_else(|| anyhow::anyhow!("only restrictions are supported"))?;
+    let mut simple_type = SimpleType {
+        base: restriction.attributes.get("base").cloned().unwrap_or_else(|| "xsd:string".to_string()),
+        ..SimpleType::default()
+    };
+    for facet in &restriction.children {
+        let value = facet.attributes.get("value").map(|v| v.as_str()).unwrap_or("");
+        let number = || value.parse::<f64>();
+        match local_name(&facet.name) {
+            "enumeration" => simple_type.enumerations.push(value.to_string()),
+            "pattern" => simple_type.pattern = Some(Regex::new(&format!("^(?:{})$", value))?),
+            "minInclusive" => simple_type.min_inclusive = Some(number()?),
+            "maxInclusive" => simple_type.max_inclusive = Some(number()?),
+            "minExclusive" => simple_type.min_exclusive = Some(number()?),
+            "maxExclusive" => simple_type.max_exclusive = Some(number()?),
+            "totalDigits" => simple_type.total_digits = Some(value.parse()?),
+            "fractionDigits" => simple_type.fraction_digits = Some(value.parse()?),
+            _ => {}
+        }
+    }
+    Ok(simple_type)
+}
@@ -0,0 +1,323 @@
+//! Document validation against a compiled schema
+//!
+//! Walks the parsed tree once, checking each declared element's attributes and
+//! the order and count of its children (IPC-2581 3.1.6: elements appear in
+//! schema sequence order). Children are matched greedily, which is exact for
+//! the deterministic content models XSD requires. After a misplaced child the
+//! walk continues, so one stray element yields one diagnostic.
+
+use super::model::{local_name, Compositor, ContentModel, Particle, Revision, Schema};
+use super::values::check_value;
+use crate::parse_xml::{child_paths, XmlNode};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiagnosticKind {
+    UnknownRevision,
+    UnknownElement,     // Not declared by the schema
+    UnexpectedElement,  // Declared, but not allowed in this parent
+    OutOfOrder,
+    TooManyElements,
+    MissingElement,
+    MissingAttribute,
+    UnknownAttribute,
+    InvalidValue,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// One schema violation, located by element path (`/IPC-2581/Ecad/CadData/Step[2]`)
+#[derive(Debug, Clone, Serialize)]
+pub struct SchemaDiagnostic {
+    pub severity: Severity,
+    pub kind: DiagnosticKind,
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attribute: Option<String>,
+    pub message: String,
+}
+
+/// Result of validating one document
+#[derive(Debug, Clone, Serialize)]
+pub struct ValidationReport {
+    pub revision: &'static str,
+    pub schema: &'static str,
+    pub valid: bool,            // No errors (warnings allowed)
+    pub error_count: usize,     // Including errors beyond the diagnostics limit
+    pub warning_count: usize,
+    pub truncated: bool,        // Diagnostics stopped at the limit
+    pub diagnostics: Vec<SchemaDiagnostic>,
+}
+
+/// Validate a document against the schema of its revision
+/// Files with an unknown revision are checked against the latest schema, with a warning.
+/// At most `limit` diagnostics are kept; the counts cover all of them.
+pub fn validate_document(root: &XmlNode, limit: usize) -> anyhow::Result<ValidationReport> {
+    let detected = Revision::of_document(root);
+    let revision = detected.unwrap_or(Revision::C);
+    let mut validator = Validator {
+        schema: revision.schema()?,
+        diagnostics: Vec::new(),
+        limit,
+        error_count: 0,
+        warning_count: 0,
+    };
+
+    let path = format!("/{}", root.name);
+    if detected.is_none() {
+        let declared = root.attributes.get("revision").map(|r| r.as_str()).unwrap_or("");
+        validator.report(Severity::Warning, DiagnosticKind::UnknownRevision, &path, None,
+            format!("unknown revision '{}', validated against revision {}", declared, revision.name()));
+    }
+    if validator.schema.elements.contains_key(local_name(&root.name)) {
+        validator.validate_element(root, &path);
+    } else {
+        validator.report(Severity::Error, DiagnosticKind::UnknownElement, &path, None,
+            format!("root element '{}' is not declared by the schema", root.name));
+    }
+
+    Ok(ValidationReport {
+        revision: revision.name(),
+        schema: revision.schema_file(),
+        valid: validator.error_count == 0,
+        error_count: validator.error_count,
+        warning_count: validator.warning_count,
+        truncated: validator.error_count + validator.warning_count > validator.diagnostics.len(),
+        diagnostics: validator.diagnostics,
+    })
+}
+
+struct Validator<'s> {
+    schema: &'s Schema,
+    diagnostics: Vec<SchemaDiagnostic>,
+    limit: usize,
+    error_count: usize,
+    warning_count: usize,
+}
+
+impl Validator<'_> {
+    fn report(&mut self, severity: Severity, kind: DiagnosticKind, path: &str, attribute: Option<&str>, message: String) {
+        match severity {
+            Severity::Error => self.error_count += 1,
+            Severity::Warning => self.warning_count += 1,
+        }
+        if self.diagnostics.len() < self.limit {
+            self.diagnostics.push(SchemaDiagnostic {
+                severity,
+                kind,
+                path: path.to_string(),
+                attribute: attribute.map(|a| a.to_string()),
+                message,
+            });
+        }
+    }
+
+    fn error(&mut self, kind: DiagnosticKind, path: &str, message: String) {
+        self.report(Severity::Error, kind, path, None, message);
+    }
+
+    /// Check a declared element and recurse into its children
+    fn validate_element(&mut self, node: &XmlNode, path: &str) {
+        let schema = self.schema;
+        let name = local_name(&node.name);
+        // Untyped declarations accept any content
+        let Some(complex_type) = schema.element_type(name) else { return };
+
+        for decl in complex_type.attributes.iter().filter(|a| a.required) {
+            if !node.attributes.contains_key(&decl.name) {
+                self.report(Severity::Error, DiagnosticKind::MissingAttribute, path, Some(&decl.name),
+                    format!("'{}' is missing required attribute '{}'", name, decl.name));
+            }
+        }
+        for (attribute, value) in &node.attributes {
+            if attribute == "xmlns" || attribute.starts_with("xmlns:") || attribute.starts_with("xsi:") {
+                continue;
+            }
+            match complex_type.attributes.iter().find(|a| &a.name == attribute) {
+                Some(decl) => {
+                    if let Err(message) = check_value(schema, &decl.type_name, value) {
+                        self.report(Severity::Error, DiagnosticKind::InvalidValue, path, Some(attribute),
+                            format!("attribute '{}': {}", attribute, message));
+                    }
+                }
+                None => self.report(Severity::Error, DiagnosticKind::UnknownAttribute, path, Some(attribute),
+                    format!("'{}' has no attribute '{}'", name, attribute)),
+            }
+        }
+
+        let paths = child_paths(node, path);
+        match &complex_type.content {
+            Some(content) if content.compositor == Compositor::Sequence => self.check_sequence(node, path, content, &paths),
+            Some(content) => self.check_choice(node, path, content, &paths),
+            None => {
+                for (child, child_path) in node.children.iter().zip(&paths) {
+                    self.unexpected(name, child, child_path);
+                }
+            }
+        }
+
+        for (child, child_path) in node.children.iter().zip(&paths) {
+            if schema.elements.contains_key(local_name(&child.name)) {
+                self.validate_element(child, child_path);
+            }
+        }
+    }
+
+    fn check_sequence(&mut self, node: &XmlNode, path: &str, content: &ContentModel, paths: &[String]) {
+        let schema = self.schema;
+        let particles = &content.particles;
+        let parent = local_name(&node.name);
+        let (mut current, mut count) = (0, 0);
+
+        for (i, (child, child_path)) in node.children.iter().zip(paths).enumerate() {
+            let name = local_name(&child.name);
+            let fits = |p: &Particle| schema.substitutes(&p.element, name);
+            if particles.get(current).is_some_and(|p| fits(p) && p.max.is_none_or(|max| count < max)) {
+                count += 1;
+            } else if let Some(offset) = particles.iter().skip(current + 1).position(fits) {
+                // Skipped particles must allow zero occurrences, unless they turn up later (out of order)
+                let next = current + 1 + offset;
+                if let Some(p) = particles.get(current) {
+                    self.check_min(parent, path, p, count);
+                }
+                let later = &node.children[i + 1..];
+                for p in &particles[current + 1..next] {
+                    if !later.iter().any(|c| schema.substitutes(&p.element, local_name(&c.name))) {
+                        self.check_min(parent, path, p, 0);
+                    }
+                }
+                (current, count) = (next, 1);
+            } else if particles.get(current).is_some_and(fits) {
+                self.error(DiagnosticKind::TooManyElements, child_path,
+                    format!("'{}' allows at most {} '{}'", parent, count, particles[current].element));
+            } else if particles[..current.min(particles.len())].iter().any(fits) {
+                self.error(DiagnosticKind::OutOfOrder, child_path,
+                    format!("'{}' is out of order: it must come before '{}'", name, particles[current].element));
+            } else {
+                self.unexpected(parent, child, child_path);
+            }
+        }
+
+        if let Some(p) = particles.get(current) {
+            self.check_min(parent, path, p, count);
+        }
+        for p in particles.iter().skip(current + 1) {
+            self.check_min(parent, path, p, 0);
+        }
+    }
+
+    fn check_choice(&mut self, node: &XmlNode, path: &str, content: &ContentModel, paths: &[String]) {
+        let parent = local_name(&node.name);
+        let mut runs: Vec<(&Particle, u32)> = Vec::new();  // Consecutive children of one alternative
+
+        for (child, child_path) in node.children.iter().zip(paths) {
+            let name = local_name(&child.name);
+            match content.particles.iter().find(|p| self.schema.substitutes(&p.element, name)) {
+                Some(p) => match runs.last_mut() {
+                    Some((last, count)) if std::ptr::eq(*last, p) && p.max.is_none_or(|max| *count < max) => *count += 1,
+                    _ => runs.push((p, 1)),
+                },
+                None => self.unexpected(parent, child, child_path),
+            }
+        }
+
+        if (runs.len() as u32) < content.min {
+            let alternatives: Vec<&str> = content.particles.iter().map(|p| p.element.as_str()).collect();
+            self.error(DiagnosticKind::MissingElement, path,
+                format!("'{}' requires one of: {}", parent, alternatives.join(", ")));
+        }
+        if content.max.is_some_and(|max| runs.len() as u32 > max) {
+            self.error(DiagnosticKind::TooManyElements, path,
+                format!("'{}' allows at most {} of its alternatives", parent, content.max.unwrap_or(0)));
+        }
+        for (p, count) in runs {
+            self.check_min(parent, path, p, count);
+        }
+    }
+
+    fn check_min(&mut self, parent: &str, path: &str, particle: &Particle, count: u32) {
+        if count >= particle.min {
+            return;
+        }
+        let message = if particle.min == 1 {
+            format!("'{}' is missing required element '{}'", parent, particle.element)
+        } else {
+            format!("'{}' requires at least {} '{}', found {}", parent, particle.min, particle.element, count)
+        };
+        self.error(DiagnosticKind::MissingElement, path, message);
+    }
+
+    fn unexpected(&mut self, parent: &str, child: &XmlNode, child_path: &str) {
+        let name = local_name(&child.name);
+        if self.schema.elements.contains_key(name) {
+            self.error(DiagnosticKind::UnexpectedElement, child_path,
+                format!("'{}' is not allowed in '{}'", name, parent));
+        } else {
+            self.error(DiagnosticKind::UnknownElement, child_path,
+                format!("'{}' is not declared by the schema", name));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn has(report: &ValidationReport, kind: DiagnosticKind, path: &str) -> bool {
+        report.diagnostics.iter().any(|d| d.kind == kind && d.path == path)
+    }
+
+    #[test]
+    fn test_structure_attributes_and_order() {
+        let root = XmlNode::new("IPC-2581", &[("revision", "C"), ("xmlns", "http://webstds.ipc.org/2581")], vec![
+            XmlNode::new("Content", &[("roleRef", "Owner")], vec![
+                XmlNode::new("FunctionMode", &[("mode", "FABRICATION")], vec![]),
+                XmlNode::new("LayerRef", &[("name", "TOP")], vec![]),
+                XmlNode::new("LayerRef", &[("name", "BOTTOM")], vec![]),
+                XmlNode::new("Widget", &[], vec![]),
+            ]),
+            XmlNode::new("Ecad", &[], vec![XmlNode::new("CadHeader", &[("units", "MILLIMETER"), ("scale", "2")], vec![])]),
+            XmlNode::new("HistoryRecord", &[("number", "1.0"), ("origination", "2024-03-01T10:00:00"),
+                ("software", "cad"), ("lastChange", "yesterday")], vec![XmlNode::new("FileRevision", &[("fileRevisionId", "1"), ("comment", ""), ("label", "")], vec![])]),
+        ]);
+        let report = validate_document(&root, 100).unwrap();
+        assert_eq!(report.revision, "C");
+        assert!(!report.valid);
+
+        // Ecad skips LogisticHeader (absent) and HistoryRecord (present, but after Ecad)
+        assert!(has(&report, DiagnosticKind::MissingElement, "/IPC-2581"));
+        assert!(has(&report, DiagnosticKind::OutOfOrder, "/IPC-2581/HistoryRecord"));
+        assert_eq!(report.diagnostics.iter().filter(|d| d.kind == DiagnosticKind::MissingElement && d.path == "/IPC-2581").count(), 1);
+
+        assert!(has(&report, DiagnosticKind::UnknownElement, "/IPC-2581/Content/Widget"));
+        assert!(has(&report, DiagnosticKind::MissingAttribute, "/IPC-2581/Ecad"));
+        assert!(has(&report, DiagnosticKind::UnknownAttribute, "/IPC-2581/Ecad/CadHeader"));
+        let bad_date = report.diagnostics.iter().find(|d| d.kind == DiagnosticKind::InvalidValue).unwrap();
+        assert_eq!((bad_date.path.as_str(), bad_date.attribute.as_deref()), ("/IPC-2581/HistoryRecord", Some("lastChange")));
+        assert_eq!(report.error_count, 7);  // FileRevision also lacks its SoftwarePackage
+
+        // Enumerations, and the diagnostics limit
+        let mut root = root;
+        root.children[0].children[0].attributes.insert("mode".to_string(), "DRAWING".to_string());
+        let report = validate_document(&root, 2).unwrap();
+        assert_eq!((report.error_count, report.diagnostics.len(), report.truncated), (8, 2, true));
+    }
+
+    #[test]
+    fn test_revision_selection() {
+        let b1 = XmlNode::new("IPC-2581", &[("revision", "B"), ("xsi:schemaLocation", "http://webstds.ipc.org/2581 IPC-2581B1.xsd")], vec![]);
+        assert_eq!(Revision::of_document(&b1), Some(Revision::B1));
+        let unknown = XmlNode::new("IPC-2581", &[("revision", "Z")], vec![]);
+        let report = validate_document(&unknown, 100).unwrap();
+        assert_eq!((report.revision, report.warning_count), ("C", 1));
+        for revision in Revision::ALL {
+            assert!(revision.schema().unwrap().substitutes("Feature", "Circle"));
+        }
+    }
+}
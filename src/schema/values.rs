@@ -0,0 +1,114 @@
+//! Attribute value checks against built-in XSD types and simple type facets
+
+use super::model::{local_name, Schema, SimpleType};
+
+/// Check `value` against a built-in (`xsd:*`) or named simple type
+/// Err holds a description of the first violated constraint.
+pub fn check_value(schema: &Schema, type_name: &str, value: &str) -> Result<(), String> {
+    match schema.simple_types.get(local_name(type_name)) {
+        Some(simple_type) => check_simple_type(schema, simple_type, value),
+        None => check_builtin(local_name(type_name), value),
+    }
+}
+
+fn check_simple_type(schema: &Schema, simple_type: &SimpleType, value: &str) -> Result<(), String> {
+    check_value(schema, &simple_type.base, value)?;
+
+    if !simple_type.enumerations.is_empty() && !simple_type.enumerations.iter().any(|e| e == value) {
+        let allowed = if simple_type.enumerations.len() > 12 {
+            format!("{}, ... ({} values)", simple_type.enumerations[..12].join(", "), simple_type.enumerations.len())
+        } else {
+            simple_type.enumerations.join(", ")
+        };
+        return Err(format!("'{}' is not one of: {}", value, allowed));
+    }
+    if let Some(pattern) = &simple_type.pattern {
+        if !pattern.is_match(value) {
+            return Err(format!("'{}' does not match pattern {}", value, pattern.as_str()));
+        }
+    }
+
+    let has_bounds = simple_type.min_inclusive.is_some() || simple_type.max_inclusive.is_some()
+        || simple_type.min_exclusive.is_some() || simple_type.max_exclusive.is_some();
+    if has_bounds {
+        let number: f64 = value.trim().parse().map_err(|_| format!("'{}' is not a number", value))?;
+        let out_of_range = simple_type.min_inclusive.is_some_and(|min| number < min)
+            || simple_type.max_inclusive.is_some_and(|max| number > max)
+            || simple_type.min_exclusive.is_some_and(|min| number <= min)
+            || simple_type.max_exclusive.is_some_and(|max| number >= max);
+        if out_of_range {
+            return Err(format!("{} is out of range{}{}{}{}", value.trim(),
+                bound(" >= ", simple_type.min_inclusive), bound(" > ", simple_type.min_exclusive),
+                bound(" <= ", simple_type.max_inclusive), bound(" < ", simple_type.max_exclusive)));
+        }
+    }
+
+    if simple_type.total_digits.is_some() || simple_type.fraction_digits.is_some() {
+        let digits = value.trim().trim_start_matches(['+', '-']);
+        let (integer, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+        let integer = integer.trim_start_matches('0');
+        let fraction = fraction.trim_end_matches('0');
+        if simple_type.fraction_digits.is_some_and(|max| fraction.len() > max) {
+            return Err(format!("'{}' has more than {} fraction digits", value, simple_type.fraction_digits.unwrap_or(0)));
+        }
+        if simple_type.total_digits.is_some_and(|max| integer.len() + fraction.len() > max) {
+            return Err(format!("'{}' has more than {} digits", value, simple_type.total_digits.unwrap_or(0)));
+        }
+    }
+    Ok(())
+}
+
+fn bound(op: &str, value: Option<f64>) -> String {
+    value.map(|v| format!("{}{}", op, v)).unwrap_or_default()
+}
+
+/// Built-in XSD types used by the IPC-2581 schemas (others are accepted as strings)
+fn check_builtin(type_name: &str, value: &str) -> Result<(), String> {
+    // Every built-in type except string collapses whitespace
+    let collapsed = value.trim();
+    let valid = match type_name {
+        "double" | "float" => matches!(collapsed, "INF" | "-INF" | "NaN")
+            || (collapsed.chars().all(|c| c.is_ascii_digit() || matches!(c, '+' | '-' | '.' | 'e' | 'E'))
+                && collapsed.parse::<f64>().is_ok()),
+        "decimal" => collapsed.chars().all(|c| c.is_ascii_digit() || matches!(c, '+' | '-' | '.'))
+            && collapsed.parse::<f64>().is_ok(),
+        "integer" | "int" | "long" | "short" => collapsed.parse::<i64>().is_ok(),
+        "nonNegativeInteger" => collapsed.trim_start_matches('+').parse::<u64>().is_ok(),
+        "positiveInteger" => collapsed.trim_start_matches('+').parse::<u64>().is_ok_and(|n| n > 0),
+        "boolean" => matches!(collapsed, "true" | "false" | "1" | "0"),
+        "dateTime" => is_date_time(collapsed),
+        "hexBinary" => collapsed.len().is_multiple_of(2) && collapsed.chars().all(|c| c.is_ascii_hexdigit()),
+        "base64Binary" => value.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '/' | '=') || c.is_whitespace()),
+        _ => true,
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("'{}' is not a valid xsd:{}", value, type_name))
+    }
+}
+
+/// `[-]YYYY-MM-DDThh:mm:ss[.fff][Z|(+|-)hh:mm]`
+fn is_date_time(value: &str) -> bool {
+    let value = value.strip_prefix('-').unwrap_or(value);
+    let Some((date, time)) = value.split_once('T') else { return false };
+    let digits = |s: &str, n: usize| s.len() == n && s.chars().all(|c| c.is_ascii_digit());
+
+    let date: Vec<&str> = date.split('-').collect();
+    let date_ok = date.len() == 3 && date[0].len() >= 4 && digits(date[0], date[0].len())
+        && digits(date[1], 2) && digits(date[2], 2);
+
+    let (clock, zone) = match time.find(['Z', '+', '-']) {
+        Some(i) => time.split_at(i),
+        None => (time, ""),
+    };
+    let (clock, fraction) = clock.split_once('.').unwrap_or((clock, "0"));
+    let clock: Vec<&str> = clock.split(':').collect();
+    let clock_ok = clock.len() == 3 && clock.iter().all(|part| digits(part, 2))
+        && !fraction.is_empty() && fraction.chars().all(|c| c.is_ascii_digit());
+    let zone_ok = match zone {
+        "" | "Z" => true,
+        _ => zone[1..].split_once(':').is_some_and(|(h, m)| digits(h, 2) && digits(m, 2)),
+    };
+    date_ok && clock_ok && zone_ok
+}
@@ -2,11 +2,15 @@
 ///
 /// This module provides fast serialization of parsed XML trees back to file format,
 /// useful for validating parse fidelity, roundtrip testing, and performance benchmarking.
+/// The lossless writers patch the original source instead, so unchanged parts of a file
+/// (declaration, comments, processing instructions, CDATA, formatting) are kept byte for byte.
 use crate::parse_xml::XmlNode;
 use anyhow::{Result, Context};
 use std::fs::File;
 use std::io::{self, BufWriter, Write};
 use std::path::Path;
+use quick_xml::events::Event;
+use quick_xml::Reader;
 use rayon::prelude::*;
 
 /// Line ending to use for serialization (CRLF on Windows, LF elsewhere)
@@ -67,6 +71,292 @@ pub fn xml_node_to_compact_string(node: &XmlNode) -> String {
     String::from_utf8(buffer).expect("serialized XML was not valid UTF-8")
 }
 
+/// Serializes an XmlNode tree parsed from `source` (see [`parse_xml_bytes`](crate::parse_xml::parse_xml_bytes))
+/// back to bytes, copying the source text everywhere the tree was not changed
+///
+/// Start tags are rewritten only when their attributes changed (unchanged attributes keep
+/// their quoting and spacing), elements removed from the tree are dropped together with their
+/// line, and nodes added in code are pretty-printed at the indentation of their siblings.
+/// A tree without source spans is pretty-printed like [`xml_node_to_string`].
+pub fn xml_node_to_lossless_bytes(node: &XmlNode, source: &[u8]) -> Result<Vec<u8>> {
+    let mut buffer = Vec::with_capacity(source.len() + 1024);
+    write_document_lossless(node, source, &mut buffer).context("Failed to serialize XML")?;
+    Ok(buffer)
+}
+
+/// Writes an XmlNode tree parsed from `source` to a file, keeping the source text where unchanged
+pub fn xml_node_to_file_lossless<P: AsRef<Path>>(node: &XmlNode, source: &[u8], file_path: P) -> Result<()> {
+    let file = File::create(&file_path).context("Failed to create XML file")?;
+    let mut writer = BufWriter::with_capacity(1024 * 1024, file);
+    write_document_lossless(node, source, &mut writer).context("Failed to serialize XML")?;
+    writer.flush().context("Failed to flush XML writer")?;
+    Ok(())
+}
+
+fn write_document_lossless<W: Write>(node: &XmlNode, source: &[u8], writer: &mut W) -> io::Result<()> {
+    let Some(span) = node.span else {
+        writer.write_all(XML_DECLARATION)?;
+        writer.write_all(LINE_ENDING)?;
+        return write_node_pretty(node, writer, 0);
+    };
+    if span.end > source.len() || !source[span.start..].starts_with(b"<") {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "node spans do not match the source"));
+    }
+
+    // Prolog (declaration, DOCTYPE, comments) and epilog are kept as they are
+    let line_ending: &[u8] = match source.iter().position(|&b| b == b'\n') {
+        Some(i) if i > 0 && source[i - 1] == b'\r' => b"\r\n",
+        _ => b"\n",
+    };
+    writer.write_all(&source[..span.start])?;
+    write_node_lossless(node, &Lossless { source, line_ending }, writer)?;
+    writer.write_all(&source[span.end..])
+}
+
+/// Source and formatting shared by the lossless writers
+struct Lossless<'a> {
+    source: &'a [u8],
+    line_ending: &'a [u8],
+}
+
+/// Attribute of a source start tag: `start` includes the whitespace before the name
+struct RawAttribute<'a> {
+    key: &'a [u8],
+    value: &'a [u8],
+    quote: u8,
+    start: usize,
+    end: usize,
+}
+
+/// Split a source start tag into name, attributes and tail (whitespace plus `>` or `/>`)
+fn split_start_tag(tag: &[u8]) -> Option<(&[u8], Vec<RawAttribute<'_>>, &[u8])> {
+    let is_space = |b: u8| b.is_ascii_whitespace();
+    let name_end = tag.iter().position(|&b| is_space(b) || b == b'/' || b == b'>')?;
+    let mut attributes = Vec::new();
+    let mut i = name_end;
+    loop {
+        let start = i;
+        while i < tag.len() && is_space(tag[i]) {
+            i += 1;
+        }
+        if i >= tag.len() || tag[i] == b'/' || tag[i] == b'>' {
+            return Some((&tag[1..name_end], attributes, &tag[start..]));
+        }
+        let key_start = i;
+        while i < tag.len() && tag[i] != b'=' && !is_space(tag[i]) {
+            i += 1;
+        }
+        let key = &tag[key_start..i];
+        while i < tag.len() && (is_space(tag[i]) || tag[i] == b'=') {
+            i += 1;
+        }
+        let quote = *tag.get(i)?;
+        let value_start = i + 1;
+        let value_len = tag.get(value_start..)?.iter().position(|&b| b == quote)?;
+        i = value_start + value_len + 1;
+        attributes.push(RawAttribute { key, value: &tag[value_start..i - 1], quote, start, end: i });
+    }
+}
+
+/// Whitespace before `offset` on its line, when nothing else precedes it there
+fn line_indent(source: &[u8], offset: usize) -> Option<&[u8]> {
+    let line_start = source[..offset].iter().rposition(|&b| b == b'\n').map_or(0, |i| i + 1);
+    let indent = &source[line_start..offset];
+    indent.iter().all(|&b| b == b' ' || b == b'\t').then_some(indent)
+}
+
+fn write_node_lossless<W: Write>(node: &XmlNode, ctx: &Lossless<'_>, writer: &mut W) -> io::Result<()> {
+    let Some(span) = node.span else {
+        return write_node_pretty(node, writer, 0);
+    };
+    let source = ctx.source;
+    let was_empty = span.content_start == span.end;
+    let is_empty = node.children.is_empty() && node.text_content.trim().is_empty();
+    let has_content = !was_empty || !is_empty;
+
+    write_start_tag_lossless(node, &source[span.start..span.content_start], was_empty && !has_content, writer)?;
+    if !has_content {
+        return Ok(());
+    }
+
+    let parent_indent = line_indent(source, span.start).unwrap_or(b"");
+    let content = span.content_start..span.content_end;
+    let is_in_content = |child: &XmlNode| child.span.is_some_and(|c| c.start >= content.start && c.end <= content.end);
+    let child_indent: Vec<u8> = match node.children.iter().find(|c| is_in_content(c)) {
+        Some(child) => line_indent(source, child.span.map_or(0, |c| c.start)).unwrap_or(b"").to_vec(),
+        None => [parent_indent, b"  "].concat(),
+    };
+
+    if was_empty {
+        // A self-closing element that gained content
+        let text = node.text_content.trim();
+        write_escaped_text(writer, text)?;
+        for child in &node.children {
+            write_new_child(child, &child_indent, ctx, writer)?;
+        }
+        if !node.children.is_empty() {
+            writer.write_all(ctx.line_ending)?;
+            writer.write_all(parent_indent)?;
+        }
+        writer.write_all(b"</")?;
+        writer.write_all(node.name.as_bytes())?;
+        return writer.write_all(b">");
+    }
+
+    // Leaf text edited in the tree replaces the source text, keeping the whitespace around it
+    let region = &source[content.clone()];
+    if node.children.is_empty() && !region.contains(&b'<') && region.trim_ascii() != node.text_content.as_bytes() {
+        let leading = region.len() - region.trim_ascii_start().len();
+        let trailing = region.len() - region.trim_ascii_end().len();
+        writer.write_all(&region[..leading])?;
+        write_escaped_text(writer, node.text_content.trim())?;
+        writer.write_all(&region[region.len() - trailing..])?;
+    } else {
+        let mut cursor = content.start;
+        for child in &node.children {
+            match child.span {
+                Some(child_span) if is_in_content(child) && child_span.start >= cursor => {
+                    write_gap_lossless(&source[cursor..child_span.start], writer)?;
+                    write_node_lossless(child, ctx, writer)?;
+                    cursor = child_span.end;
+                }
+                _ => write_new_child(child, &child_indent, ctx, writer)?,
+            }
+        }
+        write_gap_lossless(&source[cursor..content.end], writer)?;
+    }
+
+    let end_tag = &source[span.content_end..span.end];
+    if end_tag.get(2..).is_some_and(|name| name.trim_ascii_end().strip_suffix(b">").unwrap_or(name).trim_ascii_end() == node.name.as_bytes()) {
+        writer.write_all(end_tag)
+    } else {
+        writer.write_all(b"</")?;
+        writer.write_all(node.name.as_bytes())?;
+        writer.write_all(b">")
+    }
+}
+
+/// Copy the source start tag, patching only attributes that differ from the node
+fn write_start_tag_lossless<W: Write>(node: &XmlNode, tag: &[u8], self_closing: bool, writer: &mut W) -> io::Result<()> {
+    let Some((name, attributes, tail)) = split_start_tag(tag) else {
+        // Not expected for tags the parser accepted; write the tag from the node
+        writer.write_all(b"<")?;
+        writer.write_all(node.name.as_bytes())?;
+        write_attributes(node.attributes.iter().map(|(k, v)| (k.as_str(), v.as_str())), writer)?;
+        return writer.write_all(if self_closing { b" />" } else { b">" });
+    };
+    let was_self_closing = tail.trim_ascii_start().starts_with(b"/");
+    let unchanged = name == node.name.as_bytes()
+        && was_self_closing == self_closing
+        && attributes.len() == node.attributes.len()
+        && attributes.iter().all(|a| {
+            std::str::from_utf8(a.key).ok().and_then(|k| node.attributes.get(k)).is_some_and(|v| v.as_bytes() == a.value)
+        });
+    if unchanged {
+        return writer.write_all(tag);
+    }
+
+    writer.write_all(b"<")?;
+    writer.write_all(node.name.as_bytes())?;
+    for attribute in &attributes {
+        let Some(value) = std::str::from_utf8(attribute.key).ok().and_then(|k| node.attributes.get(k)) else {
+            continue;  // Removed
+        };
+        if value.as_bytes() == attribute.value {
+            writer.write_all(&tag[attribute.start..attribute.end])?;
+        } else {
+            // Keep the spacing around `=` and the quote style, up to and including the opening quote
+            writer.write_all(&tag[attribute.start..attribute.end - attribute.value.len() - 1])?;
+            write_escaped_attr(writer, value)?;
+            writer.write_all(&[attribute.quote])?;
+        }
+    }
+    let added = node.attributes.iter().filter(|(k, _)| !attributes.iter().any(|a| a.key == k.as_bytes()));
+    write_attributes(added.map(|(k, v)| (k.as_str(), v.as_str())), writer)?;
+
+    match (was_self_closing, self_closing) {
+        (true, false) => {
+            let tail = tail.trim_ascii_start();
+            writer.write_all(tail.strip_prefix(b"/").unwrap_or(tail))
+        }
+        (false, true) => writer.write_all(b" />"),
+        _ => writer.write_all(tail),
+    }
+}
+
+fn write_attributes<'a, W: Write>(attributes: impl Iterator<Item = (&'a str, &'a str)>, writer: &mut W) -> io::Result<()> {
+    for (key, value) in attributes {
+        writer.write_all(b" ")?;
+        writer.write_all(key.as_bytes())?;
+        writer.write_all(b"=\"")?;
+        write_escaped_attr(writer, value)?;
+        writer.write_all(b"\"")?;
+    }
+    Ok(())
+}
+
+/// Pretty-print a node added in code on its own line at `indent`
+fn write_new_child<W: Write>(node: &XmlNode, indent: &[u8], ctx: &Lossless<'_>, writer: &mut W) -> io::Result<()> {
+    let mut buffer = Vec::with_capacity(256);
+    write_node_pretty(node, &mut buffer, 0)?;
+    for line in buffer.split(|&b| b == b'\n').filter(|line| !line.is_empty()) {
+        writer.write_all(ctx.line_ending)?;
+        writer.write_all(indent)?;
+        writer.write_all(line.strip_suffix(b"\r").unwrap_or(line))?;
+    }
+    Ok(())
+}
+
+/// Copy source text between kept siblings, dropping elements (removed from the tree) and their indentation
+fn write_gap_lossless<W: Write>(gap: &[u8], writer: &mut W) -> io::Result<()> {
+    let has_element = gap.windows(2).any(|w| w[0] == b'<' && !matches!(w[1], b'!' | b'?' | b'/'));
+    if !has_element {
+        return writer.write_all(gap);
+    }
+
+    let mut output = Vec::with_capacity(gap.len());
+    let mut reader = Reader::from_reader(gap);
+    reader.check_end_names(false);
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        let before = reader.buffer_position();
+        let removed = match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(start)) => {
+                let name = start.name().as_ref().to_vec();
+                let mut skipped = Vec::new();
+                reader.read_to_end_into(quick_xml::name::QName(&name), &mut skipped)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+                true
+            }
+            Ok(Event::Empty(_)) => true,
+            Ok(Event::Eof) => {
+                // Trailing text is reported one byte short of the end
+                output.extend_from_slice(&gap[before.min(gap.len())..]);
+                break;
+            }
+            Ok(_) => false,
+            Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidData, e)),
+        };
+        if removed {
+            // Drop the indentation and line break of an element that stood on its own line
+            let kept = output.len() - output.iter().rev().take_while(|&&b| b == b' ' || b == b'\t').count();
+            if kept == 0 || output[kept - 1] == b'\n' {
+                output.truncate(kept);
+                if output.last() == Some(&b'\n') {
+                    output.pop();
+                    if output.last() == Some(&b'\r') {
+                        output.pop();
+                    }
+                }
+            }
+        } else {
+            output.extend_from_slice(&gap[before..reader.buffer_position()]);
+        }
+    }
+    writer.write_all(&output)
+}
+
 /// Internal recursive function to serialize a node with formatting
 /// Uses parallel processing for nodes with many children (e.g. CadData)
 fn write_node_pretty<W: Write>(node: &XmlNode, writer: &mut W, indent_level: usize) -> io::Result<()> {
@@ -246,6 +536,8 @@ mod tests {
             attributes: child_attrs,
             text_content: "Hello World".to_string(),
             children: vec![],
+            position: None,
+            span: None,
         };
         
         XmlNode {
@@ -253,6 +545,8 @@ mod tests {
             attributes: attrs,
             text_content: String::new(),
             children: vec![child],
+            position: None,
+            span: None,
         }
     }
 
@@ -292,6 +586,8 @@ mod tests {
             },
             text_content: "text<with>special&chars".to_string(),
             children: vec![],
+            position: None,
+            span: None,
         };
         
         let xml = xml_node_to_string(&node, 0);
@@ -308,9 +604,60 @@ mod tests {
             attributes: IndexMap::new(),
             text_content: String::new(),
             children: vec![],
+            position: None,
+            span: None,
         };
         
         let xml = xml_node_to_string(&node, 0);
         assert!(xml.contains(" />"));
     }
+
+    const LOSSLESS_SOURCE: &str = concat!(
+        "<?xml version='1.0' encoding='UTF-8' standalone='yes'?>\r\n",
+        "<!DOCTYPE IPC-2581>\r\n",
+        "<!-- exported by CAD -->\r\n",
+        "<IPC-2581 revision='C' xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\">\r\n",
+        "  <?cad-tool keep?>\r\n",
+        "  <Content  roleRef = \"Owner\">\r\n",
+        "    <FunctionMode mode=\"USERDEF\"/>\r\n",
+        "    <Spec name=\"A\"><![CDATA[ a < b ]]></Spec>\r\n",
+        "    <DictionaryColor/>\r\n",
+        "    <Note>  R&amp;D  </Note>\r\n",
+        "  </Content >\r\n",
+        "</IPC-2581>\r\n",
+        "<!-- trailer -->\r\n",
+    );
+
+    fn lossless(root: &XmlNode) -> String {
+        String::from_utf8(xml_node_to_lossless_bytes(root, LOSSLESS_SOURCE.as_bytes()).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_lossless_round_trip_is_identical() {
+        let root = crate::parse_xml::parse_xml_str(LOSSLESS_SOURCE).unwrap();
+        assert_eq!(lossless(&root), LOSSLESS_SOURCE);
+    }
+
+    #[test]
+    fn test_lossless_patches_only_edits() {
+        let mut root = crate::parse_xml::parse_xml_str(LOSSLESS_SOURCE).unwrap();
+        let content = &mut root.children[0];
+        content.attributes.insert("roleRef".to_string(), "Engineer".to_string());
+        content.children.retain(|c| c.name != "FunctionMode");
+        let colors = content.children.iter_mut().find(|c| c.name == "DictionaryColor").unwrap();
+        colors.children.push(XmlNode {
+            name: "EntryColor".to_string(),
+            attributes: IndexMap::from([("id".to_string(), "TOP".to_string())]),
+            text_content: String::new(),
+            children: vec![],
+            position: None,
+            span: None,
+        });
+
+        let expected = LOSSLESS_SOURCE
+            .replace("roleRef = \"Owner\"", "roleRef = \"Engineer\"")
+            .replace("\r\n    <FunctionMode mode=\"USERDEF\"/>", "")
+            .replace("<DictionaryColor/>", "<DictionaryColor>\r\n      <EntryColor id=\"TOP\" />\r\n    </DictionaryColor>");
+        assert_eq!(lossless(&root), expected);
+    }
 }
@@ -2,5 +2,568 @@
 //!
 //! This module handles conversion of parsed IPC-2581 XML documents
 //! into SQLite database records for efficient querying and storage.
+//!
+//! The database holds the parsed design rather than the raw XML: layers, nets, components and
+//! their pins, padstacks, drawn features with their bounds, drills and the BOM, linked by
+//! integer foreign keys. Lengths are in millimetres. `feature_bounds` is an R*Tree over the
+//! feature bounds for window queries:
+//!
+//! ```sql
+//! SELECT f.kind, n.name FROM feature_bounds b
+//! JOIN features f ON f.id = b.id LEFT JOIN nets n ON n.id = f.net_id
+//! WHERE b.min_x <= 12.0 AND b.max_x >= 10.0 AND b.min_y <= 6.0 AND b.max_y >= 4.0;
+//! ```
+
+use crate::draw::geometry::ObjectRange;
+use crate::draw::parsing::{
+    extract_and_generate_step_layers, normalize_units, parse_bom, parse_components, parse_drills, parse_layer_metadata, parse_nets,
+    parse_padstack_definitions, parse_stackup, parse_standard_primitives, physical_layer_order, select_step, step_name,
+    BomItem, Component, Drill, HoleShape, LayerMeta, LengthUnit, Net, Stackup, StackupLayer,
+};
+use crate::parse_xml::{parse_xml_file_parallel, XmlNode};
+use anyhow::{Context, Result};
+use indexmap::IndexMap;
+use rusqlite::{params, Connection, Transaction};
+use serde::Serialize;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::path::Path;
+
+const SCHEMA: &str = "
+CREATE TABLE design (
+    key TEXT PRIMARY KEY,
+    value TEXT
+);
+CREATE TABLE layers (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE,
+    function TEXT,
+    side TEXT,
+    polarity TEXT,
+    sequence INTEGER,          -- Physical order, top first; NULL outside the stack
+    thickness REAL,
+    material TEXT
+);
+CREATE TABLE nets (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE,
+    net_class TEXT,
+    net_pair TEXT,
+    logical INTEGER NOT NULL,
+    phy_net_group TEXT
+);
+CREATE TABLE net_points (
+    id INTEGER PRIMARY KEY,
+    net_id INTEGER NOT NULL REFERENCES nets(id),
+    layer_id INTEGER REFERENCES layers(id),
+    secondary_layer_id INTEGER REFERENCES layers(id),
+    x REAL NOT NULL,
+    y REAL NOT NULL,
+    net_node TEXT,
+    exposure TEXT,
+    via INTEGER NOT NULL,
+    test INTEGER NOT NULL
+);
+CREATE TABLE components (
+    id INTEGER PRIMARY KEY,
+    refdes TEXT NOT NULL UNIQUE,
+    package TEXT,
+    part TEXT,
+    layer_id INTEGER REFERENCES layers(id),
+    side TEXT,
+    mount_type TEXT,
+    x REAL NOT NULL,
+    y REAL NOT NULL,
+    rotation REAL NOT NULL,
+    mirror INTEGER NOT NULL,
+    height REAL,
+    min_x REAL, min_y REAL, max_x REAL, max_y REAL
+);
+CREATE TABLE pins (
+    id INTEGER PRIMARY KEY,
+    component_id INTEGER NOT NULL REFERENCES components(id),
+    number TEXT NOT NULL,
+    name TEXT,
+    pin_type TEXT,
+    electrical_type TEXT,
+    mount_type TEXT,
+    x REAL NOT NULL,
+    y REAL NOT NULL,
+    net_id INTEGER REFERENCES nets(id)
+);
+CREATE TABLE padstacks (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE,
+    hole_diameter REAL,
+    outer_diameter REAL,
+    shape TEXT,                -- StandardPrimitive variant
+    shape_json TEXT            -- Its dimensions
+);
+CREATE TABLE features (
+    id INTEGER PRIMARY KEY,    -- Object id used by the viewer (Select, Delete, ...)
+    layer_id INTEGER NOT NULL REFERENCES layers(id),
+    kind TEXT NOT NULL,        -- polyline, polygon, via, pad
+    net_id INTEGER REFERENCES nets(id),
+    component_id INTEGER REFERENCES components(id),
+    pin TEXT,
+    min_x REAL NOT NULL, min_y REAL NOT NULL, max_x REAL NOT NULL, max_y REAL NOT NULL
+);
+CREATE VIRTUAL TABLE feature_bounds USING rtree(id, min_x, max_x, min_y, max_y);
+CREATE TABLE drills (
+    id INTEGER PRIMARY KEY,
+    name TEXT,
+    layer_id INTEGER REFERENCES layers(id),
+    shape TEXT NOT NULL,       -- Circle, Square, Slot
+    diameter REAL NOT NULL,
+    slot_width REAL,
+    slot_length REAL,
+    plating TEXT NOT NULL,
+    plus_tol REAL,
+    minus_tol REAL,
+    x REAL NOT NULL,
+    y REAL NOT NULL,
+    net_id INTEGER REFERENCES nets(id),
+    padstack_id INTEGER REFERENCES padstacks(id),
+    span_kind TEXT,            -- Through, Blind, Buried, Backdrilled
+    from_layer_id INTEGER REFERENCES layers(id),
+    to_layer_id INTEGER REFERENCES layers(id)
+);
+CREATE TABLE bom_items (
+    id INTEGER PRIMARY KEY,
+    part_number TEXT NOT NULL,
+    internal_part_number TEXT,
+    description TEXT,
+    category TEXT,
+    quantity INTEGER NOT NULL,
+    pin_count INTEGER
+);
+CREATE TABLE bom_refdes (
+    id INTEGER PRIMARY KEY,
+    bom_item_id INTEGER NOT NULL REFERENCES bom_items(id),
+    refdes TEXT NOT NULL,
+    component_id INTEGER REFERENCES components(id),
+    package TEXT,
+    layer_id INTEGER REFERENCES layers(id),
+    populate INTEGER NOT NULL
+);
+CREATE TABLE bom_characteristics (
+    id INTEGER PRIMARY KEY,
+    bom_item_id INTEGER NOT NULL REFERENCES bom_items(id),
+    name TEXT NOT NULL,
+    value TEXT,
+    unit TEXT
+);
+CREATE TABLE approved_vendors (
+    id INTEGER PRIMARY KEY,
+    bom_item_id INTEGER NOT NULL REFERENCES bom_items(id),
+    vendor TEXT,
+    mpn TEXT,
+    rank INTEGER,
+    qualified INTEGER NOT NULL,
+    chosen INTEGER NOT NULL
+);
+CREATE INDEX pins_component ON pins(component_id);
+CREATE INDEX pins_net ON pins(net_id);
+CREATE INDEX features_layer ON features(layer_id);
+CREATE INDEX features_net ON features(net_id);
+CREATE INDEX features_component ON features(component_id);
+CREATE INDEX drills_net ON drills(net_id);
+CREATE INDEX net_points_net ON net_points(net_id);
+";
+
+/// Rows written per table
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SqliteExport {
+    pub step: Option<String>,
+    pub layers: usize,
+    pub nets: usize,
+    pub components: usize,
+    pub pins: usize,
+    pub padstacks: usize,
+    pub features: usize,
+    pub drills: usize,
+    pub bom_items: usize,
+}
+
+/// Row ids of named rows, for foreign keys
+#[derive(Default)]
+struct Ids {
+    layers: HashMap<String, i64>,
+    nets: HashMap<String, i64>,
+    components: HashMap<String, i64>,
+    padstacks: HashMap<String, i64>,
+}
+
+impl Ids {
+    fn layer(&self, name: Option<&str>) -> Option<i64> {
+        name.and_then(|n| self.layers.get(n).copied())
+    }
+
+    fn net(&self, name: Option<&str>) -> Option<i64> {
+        name.and_then(|n| self.nets.get(n).copied())
+    }
+}
+
+/// Parse an IPC-2581 file and write its design database to `db_path`
+/// `step` selects the Step whose features are exported (default: the top-level step).
+pub fn xml_file_to_sqlite<P: AsRef<Path>, Q: AsRef<Path>>(xml_path: P, db_path: Q, step: Option<&str>) -> Result<SqliteExport> {
+    let mut root = parse_xml_file_parallel(&xml_path, "LayerFeature")?;
+    let source_unit = normalize_units(&mut root);
+    let source = xml_path.as_ref().to_string_lossy();
+    export_design(&root, &source, source_unit, step, db_path)
+}
+
+/// Write the design database of a parsed document to `db_path`, replacing any file there
+/// `root` must be normalized to millimetres (see `normalize_units`); `source_unit` is recorded
+/// in the `design` table along with `source`, the file the document was read from.
+pub fn export_design<P: AsRef<Path>>(
+    root: &XmlNode,
+    source: &str,
+    source_unit: LengthUnit,
+    step: Option<&str>,
+    db_path: P,
+) -> Result<SqliteExport> {
+    let step = select_step(root, step)?.map(|s| step_name(s).to_string());
+    let (_, object_ranges) = extract_and_generate_step_layers(root, step.as_deref())
+        .context("Failed to generate layers")?;
+    let step_root = step_document(root, step.as_deref());
+
+    // A fresh database each time; rows of an earlier export would mix two designs
+    let db_path = db_path.as_ref();
+    if db_path.exists() {
+        std::fs::remove_file(db_path).with_context(|| format!("Failed to replace {}", db_path.display()))?;
+    }
+    let mut connection = Connection::open(db_path)
+        .with_context(|| format!("Failed to create {}", db_path.display()))?;
+    connection.execute_batch(SCHEMA)?;
+
+    let transaction = connection.transaction()?;
+    let mut export = SqliteExport { step: step.clone(), ..Default::default() };
+    let mut ids = Ids::default();
+    {
+        let mut design = transaction.prepare("INSERT INTO design (key, value) VALUES (?1, ?2)")?;
+        design.execute(params!["source", source])?;
+        design.execute(params!["source_unit", source_unit.as_ipc_str()])?;
+        design.execute(params!["unit", LengthUnit::Millimeter.as_ipc_str()])?;
+        design.execute(params!["step", step])?;
+    }
+    let layer_meta = parse_layer_metadata(root);
+    let stackup = parse_stackup(root, &layer_meta);
+    let stack_order = physical_layer_order(root, stackup.as_ref());
+    export.layers = insert_layers(&transaction, &layer_meta, stackup.as_ref(), &stack_order, &mut ids)?;
+    export.padstacks = insert_padstacks(&transaction, &step_root, &mut ids)?;
+
+    let components = parse_components(&step_root, &layer_meta);
+    let nets = parse_nets(&step_root, &components);
+    let drills = parse_drills(&step_root, &layer_meta, &stack_order, &parse_standard_primitives(root));
+
+    // Files without a netlist still name nets on their features and drills
+    let referenced_nets = object_ranges.iter().filter_map(|r| r.net_name.as_deref())
+        .chain(drills.drills.iter().filter_map(|d| d.net_name.as_deref()));
+    export.nets = insert_nets(&transaction, &nets, referenced_nets, &mut ids)?;
+    (export.components, export.pins) = insert_components(&transaction, &components, &nets, &object_ranges, &mut ids)?;
+    export.features = insert_features(&transaction, &object_ranges, &ids)?;
+    export.drills = insert_drills(&transaction, &drills.drills, &ids)?;
+
+    let bom = parse_bom(root, |refdes| components.contains_key(refdes));
+    export.bom_items = insert_bom(&transaction, &bom, &ids)?;
+    transaction.commit()?;
+
+    Ok(export)
+}
+
+/// `root` without the Steps other than `step`, for the parsers that read every Step
+/// Features are exported for one step only; components, nets, padstacks and drills follow it.
+fn step_document<'a>(root: &'a XmlNode, step: Option<&str>) -> Cow<'a, XmlNode> {
+    let Some(step) = step else { return Cow::Borrowed(root) };
+    let other_step = |node: &XmlNode| node.name == "Step" && step_name(node) != step;
+    let cad_data = |node: &XmlNode| node.name == "CadData" && node.children.iter().any(other_step);
+    if !root.children.iter().any(|ecad| ecad.name == "Ecad" && ecad.children.iter().any(cad_data)) {
+        return Cow::Borrowed(root);
+    }
+
+    let mut document = root.clone();
+    for ecad in document.children.iter_mut().filter(|n| n.name == "Ecad") {
+        for cad_data in ecad.children.iter_mut().filter(|n| n.name == "CadData") {
+            cad_data.children.retain(|n| !other_step(n));
+        }
+    }
+    Cow::Owned(document)
+}
+
+fn insert_layers(
+    transaction: &Transaction,
+    layer_meta: &HashMap<String, LayerMeta>,
+    stackup: Option<&Stackup>,
+    order: &[String],
+    ids: &mut Ids,
+) -> Result<usize> {
+    let stack_layers: HashMap<&str, &StackupLayer> = stackup.iter()
+        .flat_map(|s| s.layers_in_sequence())
+        .map(|l| (l.layer_ref.as_str(), l))
+        .collect();
+
+    // Stack order first, then the layers outside the stack by name
+    let mut names: Vec<&String> = order.iter().filter(|n| layer_meta.contains_key(*n)).collect();
+    let mut rest: Vec<&String> = layer_meta.keys().filter(|n| !order.contains(*n)).collect();
+    rest.sort();
+    names.extend(rest);
+
+    let mut insert = transaction.prepare(
+        "INSERT INTO layers (name, function, side, polarity, sequence, thickness, material) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)")?;
+    for name in names {
+        let meta = &layer_meta[name];
+        let sequence = order.iter().position(|n| n == name);
+        let stack_layer = stack_layers.get(name.as_str());
+        insert.execute(params![
+            name, meta.function, meta.side, variant_name(&meta.polarity), sequence,
+            stack_layer.map(|l| l.thickness), stack_layer.and_then(|l| l.material.clone()),
+        ])?;
+        ids.layers.insert(name.clone(), transaction.last_insert_rowid());
+    }
+    Ok(ids.layers.len())
+}
+
+fn insert_padstacks(transaction: &Transaction, root: &XmlNode, ids: &mut Ids) -> Result<usize> {
+    let mut insert = transaction.prepare(
+        "INSERT INTO padstacks (name, hole_diameter, outer_diameter, shape, shape_json) VALUES (?1, ?2, ?3, ?4, ?5)")?;
+    for (name, padstack) in parse_padstack_definitions(root) {
+        let shape = serde_json::to_value(&padstack.shape)?;
+        insert.execute(params![name, padstack.hole_diameter, padstack.outer_diameter, variant_name(&padstack.shape), shape.to_string()])?;
+        ids.padstacks.insert(name, transaction.last_insert_rowid());
+    }
+    Ok(ids.padstacks.len())
+}
+
+/// Netlist entries, then nets only named by features and drills (`logical` 0, no points)
+fn insert_nets<'a>(
+    transaction: &Transaction,
+    nets: &IndexMap<String, Net>,
+    referenced: impl Iterator<Item = &'a str>,
+    ids: &mut Ids,
+) -> Result<usize> {
+    let mut insert = transaction.prepare(
+        "INSERT INTO nets (name, net_class, net_pair, logical, phy_net_group) VALUES (?1, ?2, ?3, ?4, ?5)")?;
+    let mut insert_point = transaction.prepare(
+        "INSERT INTO net_points (net_id, layer_id, secondary_layer_id, x, y, net_node, exposure, via, test)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)")?;
+    for net in nets.values() {
+        insert.execute(params![net.name, net.net_class, net.net_pair, net.logical, net.phy_net_group])?;
+        let net_id = transaction.last_insert_rowid();
+        ids.nets.insert(net.name.clone(), net_id);
+        for point in &net.points {
+            insert_point.execute(params![
+                net_id, ids.layer(Some(&point.layer_ref)), ids.layer(point.secondary_layer_ref.as_deref()),
+                point.x, point.y, point.net_node, point.exposure, point.via, point.test,
+            ])?;
+        }
+    }
+    for name in referenced {
+        if !ids.nets.contains_key(name) {
+            insert.execute(params![name, None::<String>, None::<String>, false, None::<String>])?;
+            ids.nets.insert(name.to_string(), transaction.last_insert_rowid());
+        }
+    }
+    Ok(ids.nets.len())
+}
+
+/// Components and their pins
+/// A pin's net comes from the netlist entry naming its refdes and number, else from the pads placed for it.
+fn insert_components(
+    transaction: &Transaction,
+    components: &IndexMap<String, Component>,
+    nets: &IndexMap<String, Net>,
+    object_ranges: &[ObjectRange],
+    ids: &mut Ids,
+) -> Result<(usize, usize)> {
+    let pad_nets = object_ranges.iter().filter_map(|r| {
+        Some(((r.component_ref.as_deref()?, r.pin_ref.as_deref()?), r.net_name.as_deref()?))
+    });
+    let netlist_nets = nets.values().flat_map(|net| net.pins.iter().filter_map(move |pin| {
+        pin.refdes.as_deref().map(|refdes| ((refdes, pin.pin.as_str()), net.name.as_str()))
+    }));
+    // Netlist entries are collected last and win
+    let pin_nets: HashMap<(&str, &str), &str> = pad_nets.chain(netlist_nets).collect();
+
+    let mut insert = transaction.prepare(
+        "INSERT INTO components (refdes, package, part, layer_id, side, mount_type, x, y, rotation, mirror, height, min_x, min_y, max_x, max_y)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)")?;
+    let mut insert_pin = transaction.prepare(
+        "INSERT INTO pins (component_id, number, name, pin_type, electrical_type, mount_type, x, y, net_id)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)")?;
+    let mut pins = 0;
+    for component in components.values() {
+        let [min_x, min_y, max_x, max_y] = component.bounds;
+        insert.execute(params![
+            component.refdes, component.package_ref, component.part, ids.layer(Some(&component.layer_ref)),
+            component.side, component.mount_type, component.x, component.y, component.rotation, component.mirror,
+            component.height, min_x, min_y, max_x, max_y,
+        ])?;
+        let component_id = transaction.last_insert_rowid();
+        ids.components.insert(component.refdes.clone(), component_id);
+        for pin in &component.pins {
+            let net = pin_nets.get(&(component.refdes.as_str(), pin.number.as_str())).copied();
+            insert_pin.execute(params![
+                component_id, pin.number, pin.name, pin.pin_type, pin.electrical_type, pin.mount_type,
+                pin.x, pin.y, ids.net(net),
+            ])?;
+            pins += 1;
+        }
+    }
+    Ok((ids.components.len(), pins))
+}
+
+fn insert_features(transaction: &Transaction, object_ranges: &[ObjectRange], ids: &Ids) -> Result<usize> {
+    let mut insert = transaction.prepare(
+        "INSERT INTO features (id, layer_id, kind, net_id, component_id, pin, min_x, min_y, max_x, max_y)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)")?;
+    let mut insert_bounds = transaction.prepare(
+        "INSERT INTO feature_bounds (id, min_x, max_x, min_y, max_y) VALUES (?1, ?2, ?3, ?4, ?5)")?;
+    let mut features = 0;
+    for range in object_ranges {
+        // The synthetic outline layer and other generated layers have no Layer row
+        let Some(layer_id) = ids.layer(Some(&range.layer_id)).or_else(|| ids.layer(range.layer_id.strip_prefix("LAYER:"))) else {
+            continue;
+        };
+        let kind = match range.obj_type {
+            0 => "polyline",
+            1 => "polygon",
+            2 => "via",
+            _ => "pad",
+        };
+        let [min_x, min_y, max_x, max_y] = range.bounds;
+        // Object ids use the full u64 range; SQLite integers are signed
+        let id = range.id as i64;
+        let component = range.component_ref.as_deref().and_then(|c| ids.components.get(c).copied());
+        insert.execute(params![
+            id, layer_id, kind, ids.net(range.net_name.as_deref()), component, range.pin_ref, min_x, min_y, max_x, max_y,
+        ])?;
+        insert_bounds.execute(params![id, min_x, max_x, min_y, max_y])?;
+        features += 1;
+    }
+    Ok(features)
+}
+
+fn insert_drills(transaction: &Transaction, drills: &[Drill], ids: &Ids) -> Result<usize> {
+    let mut insert = transaction.prepare(
+        "INSERT INTO drills (name, layer_id, shape, diameter, slot_width, slot_length, plating, plus_tol, minus_tol, x, y,
+                             net_id, padstack_id, span_kind, from_layer_id, to_layer_id)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)")?;
+    for drill in drills {
+        let (slot_width, slot_length) = match drill.shape {
+            HoleShape::Slot { width, length } => (Some(width), Some(length)),
+            _ => (None, None),
+        };
+        let span = drill.span.as_ref();
+        insert.execute(params![
+            drill.name, ids.layer(drill.layer_ref.as_deref()), variant_name(&drill.shape), drill.diameter,
+            slot_width, slot_length, variant_name(&drill.plating), drill.plus_tol, drill.minus_tol, drill.x, drill.y,
+            ids.net(drill.net_name.as_deref()),
+            drill.padstack_ref.as_deref().and_then(|p| ids.padstacks.get(p).copied()),
+            span.map(|s| variant_name(&s.kind)),
+            ids.layer(span.map(|s| s.from_layer.as_str())), ids.layer(span.map(|s| s.to_layer.as_str())),
+        ])?;
+    }
+    Ok(drills.len())
+}
+
+fn insert_bom(transaction: &Transaction, bom: &[BomItem], ids: &Ids) -> Result<usize> {
+    let mut insert = transaction.prepare(
+        "INSERT INTO bom_items (part_number, internal_part_number, description, category, quantity, pin_count)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)")?;
+    let mut insert_refdes = transaction.prepare(
+        "INSERT INTO bom_refdes (bom_item_id, refdes, component_id, package, layer_id, populate) VALUES (?1, ?2, ?3, ?4, ?5, ?6)")?;
+    let mut insert_characteristic = transaction.prepare(
+        "INSERT INTO bom_characteristics (bom_item_id, name, value, unit) VALUES (?1, ?2, ?3, ?4)")?;
+    let mut insert_vendor = transaction.prepare(
+        "INSERT INTO approved_vendors (bom_item_id, vendor, mpn, rank, qualified, chosen) VALUES (?1, ?2, ?3, ?4, ?5, ?6)")?;
+    for item in bom {
+        insert.execute(params![
+            item.part_number, item.internal_part_number, item.description, item.category, item.quantity, item.pin_count,
+        ])?;
+        let item_id = transaction.last_insert_rowid();
+        for refdes in &item.refdes {
+            insert_refdes.execute(params![
+                item_id, refdes.name, ids.components.get(&refdes.name), refdes.package_ref,
+                ids.layer(refdes.layer_ref.as_deref()), refdes.populate,
+            ])?;
+        }
+        for characteristic in &item.characteristics {
+            insert_characteristic.execute(params![item_id, characteristic.name, characteristic.value, characteristic.unit])?;
+        }
+        for vendor in &item.approved_vendors {
+            insert_vendor.execute(params![item_id, vendor.vendor, vendor.mpn, vendor.rank, vendor.qualified, vendor.chosen])?;
+        }
+    }
+    Ok(bom.len())
+}
+
+/// Name of an enum variant as serde writes it (`Slot` for `HoleShape::Slot { .. }`)
+fn variant_name<T: Serialize>(value: &T) -> Option<String> {
+    match serde_json::to_value(value).ok()? {
+        serde_json::Value::String(name) => Some(name),
+        serde_json::Value::Object(map) => map.keys().next().cloned(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(name: &str, refdes: &str, x: &str) -> XmlNode {
+        XmlNode::new("Step", &[("name", name)], vec![
+            XmlNode::new("PadStackDef", &[("name", &format!("{}_VIA", name))], vec![
+                XmlNode::new("PadstackHoleDef", &[("name", "H"), ("diameter", "0.3"), ("platingStatus", "PLATED"),
+                    ("plusTol", "0"), ("minusTol", "0"), ("x", "0"), ("y", "0")], vec![]),
+                XmlNode::new("PadstackPadDef", &[("layerRef", "TOP"), ("padUse", "REGULAR")], vec![
+                    XmlNode::new("StandardPrimitiveRef", &[("id", "ROUND")], vec![]),
+                ]),
+            ]),
+            XmlNode::new("Package", &[("name", "R0603")], vec![
+                XmlNode::new("Pin", &[("number", "1")], vec![XmlNode::new("Location", &[("x", "0"), ("y", "0")], vec![])]),
+            ]),
+            XmlNode::new("Component", &[("refDes", refdes), ("packageRef", "R0603"), ("layerRef", "TOP")], vec![
+                XmlNode::new("Location", &[("x", x), ("y", "0")], vec![]),
+            ]),
+            XmlNode::new("LayerFeature", &[("layerRef", "TOP")], vec![
+                XmlNode::new("Set", &[], vec![XmlNode::new("Hole", &[("name", "H1"), ("diameter", "0.3"),
+                    ("platingStatus", "PLATED"), ("plusTol", "0"), ("minusTol", "0"), ("x", x), ("y", "0")], vec![])]),
+                XmlNode::new("Set", &[], vec![XmlNode::new("Line", &[("startX", x), ("startY", "0"), ("endX", "1"), ("endY", "1")], vec![
+                    XmlNode::new("LineDesc", &[("lineWidth", "0.2")], vec![]),
+                ])]),
+            ]),
+        ])
+    }
+
+    #[test]
+    fn test_export_only_the_selected_step() {
+        let root = XmlNode::new("IPC-2581", &[], vec![
+            XmlNode::new("Content", &[], vec![XmlNode::new("DictionaryStandard", &[], vec![
+                XmlNode::new("EntryStandard", &[("id", "ROUND")], vec![XmlNode::new("Circle", &[("diameter", "0.6")], vec![])]),
+            ])]),
+            XmlNode::new("Ecad", &[], vec![
+                XmlNode::new("CadHeader", &[("units", "MILLIMETER")], vec![]),
+                XmlNode::new("CadData", &[], vec![
+                    XmlNode::new("Layer", &[("name", "TOP"), ("layerFunction", "SIGNAL"), ("side", "TOP"), ("polarity", "POSITIVE")], vec![]),
+                    step("board", "R1", "0"),
+                    step("coupon", "R2", "5"),
+                ]),
+            ]),
+        ]);
+        let db_path = std::env::temp_dir().join(format!("export_step_test_{}.sqlite", std::process::id()));
+
+        let export = export_design(&root, "test.xml", LengthUnit::Millimeter, Some("coupon"), &db_path).unwrap();
+        assert_eq!(export.step.as_deref(), Some("coupon"));
+        assert_eq!((export.components, export.pins, export.padstacks, export.drills), (1, 1, 1, 1));
+
+        let db = Connection::open(&db_path).unwrap();
+        let text = |sql: &str| db.query_row(sql, [], |row| row.get::<_, String>(0)).expect(sql);
+        assert_eq!(text("SELECT refdes FROM components"), "R2");
+        assert_eq!(text("SELECT name FROM padstacks"), "coupon_VIA");
+        assert_eq!(db.query_row("SELECT x FROM drills", [], |row| row.get::<_, f64>(0)).unwrap(), 5.0);
 
-// TODO: Implement XML to SQLite conversion logic
+        drop(db);
+        std::fs::remove_file(&db_path).ok();
+    }
+}
@@ -1,5 +1,7 @@
 // Import the library functions
-use rust_extension::{parse_xml_file, print_xml_tree, xml_node_to_file};
+use rust_extension::{parse_xml_bytes, parse_xml_bytes_parallel, parse_xml_file, print_xml_tree, xml_node_to_file, xml_node_to_lossless_bytes};
+use rust_extension::schema::validate_document;
+use rust_extension::xml_file_to_sqlite;
 use std::time::Instant;
 
 #[cfg(test)]
@@ -50,4 +52,86 @@ mod tests {
         println!("Output file size: {} bytes", file_size);
         assert!(file_size > 0, "Output file is empty");
     }
+
+    #[test]
+    fn test_pic_programmer_c_schema_validation() {
+        let root = parse_xml_file("tests/pic_programmerC.xml").expect("Failed to parse XML");
+
+        let start = Instant::now();
+        let report = validate_document(&root, 20).expect("Failed to load schema");
+        println!("Validated against {} in {:.3}ms", report.schema, start.elapsed().as_secs_f64() * 1000.0);
+
+        assert_eq!(report.revision, "C");
+        assert!(report.valid, "Schema errors: {:#?}", report.diagnostics);
+    }
+
+    #[test]
+    fn test_pic_programmer_c_lossless_round_trip() {
+        let source = std::fs::read("tests/pic_programmerC.xml").expect("Failed to read XML");
+        let mut root = parse_xml_bytes(&source).expect("Failed to parse XML");
+
+        let start = Instant::now();
+        let output = xml_node_to_lossless_bytes(&root, &source).expect("Failed to serialize XML");
+        println!("Lossless serialization time: {:.3}ms", start.elapsed().as_secs_f64() * 1000.0);
+        assert!(output == source, "Unmodified tree must serialize to the source bytes");
+
+        // Editing one attribute changes one line
+        let header = root.children.iter_mut().find(|c| c.name == "Ecad").unwrap()
+            .children.iter_mut().find(|c| c.name == "CadHeader").unwrap();
+        header.attributes.insert("units".to_string(), "MICRON".to_string());
+        let output = xml_node_to_lossless_bytes(&root, &source).expect("Failed to serialize XML");
+        let (before, after) = (String::from_utf8_lossy(&source), String::from_utf8_lossy(&output));
+        let changed: Vec<_> = before.lines().zip(after.lines()).filter(|(a, b)| a != b).collect();
+        assert_eq!(before.lines().count(), after.lines().count());
+        assert_eq!(changed.len(), 1, "{:?}", changed);
+        assert!(changed[0].1.contains("units=\"MICRON\""));
+    }
+
+    #[test]
+    fn test_pic_programmer_c_parallel_parsing() {
+        let source = std::fs::read("tests/pic_programmerC.xml").expect("Failed to read XML");
+
+        let start = Instant::now();
+        let sequential = parse_xml_bytes(&source).expect("Failed to parse XML");
+        println!("Sequential parse time: {:.3}ms", start.elapsed().as_secs_f64() * 1000.0);
+        let start = Instant::now();
+        let parallel = parse_xml_bytes_parallel(&source, "LayerFeature").expect("Failed to parse XML");
+        println!("Parallel parse time: {:.3}ms", start.elapsed().as_secs_f64() * 1000.0);
+
+        // Same tree, positions and spans included, so lossless Save works on either
+        fn count(node: &rust_extension::XmlNode) -> usize {
+            1 + node.children.iter().map(count).sum::<usize>()
+        }
+        assert_eq!(count(&parallel), count(&sequential));
+        let output = xml_node_to_lossless_bytes(&parallel, &source).expect("Failed to serialize XML");
+        assert!(output == source, "Unmodified tree must serialize to the source bytes");
+        assert_eq!(
+            format!("{:?}", parallel.children.last().map(|n| (n.position, n.span))),
+            format!("{:?}", sequential.children.last().map(|n| (n.position, n.span))),
+        );
+    }
+
+    #[test]
+    fn test_pic_programmer_c_sqlite_export() {
+        let db_path = std::env::temp_dir().join("pic_programmer_c_test.sqlite");
+
+        let start = Instant::now();
+        let export = xml_file_to_sqlite("tests/pic_programmerC.xml", &db_path, None).expect("Failed to export");
+        println!("SQLite export time: {:.3}ms, {:?}", start.elapsed().as_secs_f64() * 1000.0, export);
+        assert_eq!(export.components, 63);
+        assert!(export.features > 0 && export.drills > 0 && export.bom_items > 0);
+
+        let db = rusqlite::Connection::open(&db_path).expect("Failed to open database");
+        let count = |sql: &str| db.query_row(sql, [], |row| row.get::<_, i64>(0)).expect(sql);
+        assert_eq!(count("SELECT COUNT(*) FROM components"), 63);
+        assert_eq!(count("SELECT COUNT(*) FROM features"), export.features as i64);
+        // No netlist in this file: nets come from the features, and pins get them from their pads
+        assert!(count("SELECT COUNT(*) FROM nets WHERE logical = 0") > 0);
+        assert!(count("SELECT COUNT(*) FROM pins WHERE net_id IS NOT NULL") > 0);
+        assert_eq!(count("SELECT COUNT(*) FROM features f LEFT JOIN layers l ON l.id = f.layer_id WHERE l.id IS NULL"), 0);
+        assert_eq!(count("SELECT COUNT(*) FROM feature_bounds"), export.features as i64);
+        assert!(count("SELECT COUNT(*) FROM bom_refdes WHERE component_id IS NOT NULL") > 0);
+
+        std::fs::remove_file(&db_path).ok();
+    }
 }
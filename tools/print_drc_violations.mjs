@@ -13,7 +13,7 @@ const __dirname = path.dirname(__filename);
 const xmlFile = process.argv[2] || 'tests/tinytapeout-demo.xml';
 const lspServerPath = path.join(__dirname, '..', 'target', 'release', 'lsp_server.exe');
 
-const lsp = spawn(lspServerPath, [], { stdio: ['pipe', 'pipe', 'pipe'] });
+const lsp = spawn(lspServerPath, ['--lines'], { stdio: ['pipe', 'pipe', 'pipe'] });
 lsp.stderr.pipe(process.stderr);
 
 let id = 1;
@@ -30,7 +30,7 @@ function makeRequest(method, params = {}) {
 
 async function main() {
     // Start the LSP server
-    const lsp = spawn(lspServerPath, [], {
+    const lsp = spawn(lspServerPath, ['--lines'], {
         stdio: ['pipe', 'pipe', 'pipe']
     });
 